@@ -0,0 +1,53 @@
+//! Deserializing an array of numbers element by element against
+//! [`helpers::numbers`](simdjson_rust::serde::helpers::numbers).
+//!
+//! ```text
+//! cargo bench --bench serde_numbers --features serde_impl
+//! ```
+
+use std::{hint::black_box, time::Instant};
+
+use serde::Deserialize;
+use simdjson_rust::{dom::Parser, prelude::*, serde::de::from_element};
+
+#[derive(Deserialize)]
+struct PerElement {
+    samples: Vec<f64>,
+}
+
+#[derive(Deserialize)]
+struct OnePass {
+    #[serde(with = "simdjson_rust::serde::helpers::numbers")]
+    samples: Vec<f64>,
+}
+
+fn time<T: for<'a> Deserialize<'a>>(
+    name: &str,
+    parser: &mut Parser,
+    json: &str,
+    samples: fn(&T) -> &[f64],
+) {
+    const ROUNDS: u32 = 200;
+    let element = parser.parse(json).unwrap();
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        black_box(samples(&from_element::<T>(&element).unwrap()).len());
+    }
+    println!("{name:<12} {:?} per document", start.elapsed() / ROUNDS);
+}
+
+fn main() {
+    let samples: Vec<String> = (0..100_000)
+        .map(|i| match i % 3 {
+            0 => i.to_string(),
+            1 => format!("-{i}"),
+            _ => format!("{i}.25"),
+        })
+        .collect();
+    let json = format!(r#"{{"samples": [{}]}}"#, samples.join(",")).into_padded_string();
+    let mut parser = Parser::default();
+    time("per element", &mut parser, &json, |v: &PerElement| {
+        &v.samples
+    });
+    time("one pass", &mut parser, &json, |v: &OnePass| &v.samples);
+}
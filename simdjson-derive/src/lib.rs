@@ -0,0 +1,216 @@
+//! Derive macros for `simdjson-rust`. Use them through the crate's `derive`
+//! feature rather than directly.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{
+    Data, DeriveInput, Error, Field, Fields, GenericParam, Lifetime, LitStr, Result, Type,
+    parse_macro_input, punctuated::Punctuated, token::Comma,
+};
+
+/// Implements `simdjson_rust::dom::FromJsonPointers` for a struct with named
+/// fields. Each field is read from the JSON pointer given with
+/// `#[simdjson(pointer = "/meta/request_id")]`, or from `/<field name>`
+/// without one, and converted with `FromElement`. `Option` fields are `None`
+/// when the pointer does not resolve.
+#[proc_macro_derive(FromJsonPointers, attributes(simdjson))]
+pub fn derive_from_json_pointers(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Implements `simdjson_rust::builder::ToJsonBuilder` for a struct with
+/// named fields, writing an object with one key per field, in declaration
+/// order. Keys are the field names, or the name given with
+/// `#[simdjson(rename = "user-id")]`, escaped at compile time; values are
+/// written with their own `ToJsonBuilder` impls.
+#[proc_macro_derive(ToJsonBuilder, attributes(simdjson))]
+pub fn derive_to_json_builder(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_to_json_builder(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn named_fields(input: &DeriveInput) -> Result<&Punctuated<Field, Comma>> {
+    if let Data::Struct(data) = &input.data {
+        if let Fields::Named(fields) = &data.fields {
+            return Ok(&fields.named);
+        }
+    }
+    Err(Error::new_spanned(
+        &input.ident,
+        "expected a struct with named fields",
+    ))
+}
+
+fn expand(input: DeriveInput) -> Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let fields = named_fields(&input)?;
+
+    // The document lifetime: the struct's own if it borrows from the
+    // document, a fresh one otherwise.
+    let mut lifetimes = Vec::new();
+    for param in &input.generics.params {
+        match param {
+            GenericParam::Lifetime(param) => lifetimes.push(param.lifetime.clone()),
+            _ => {
+                return Err(Error::new_spanned(
+                    param,
+                    "only a lifetime parameter is supported",
+                ));
+            }
+        }
+    }
+    let json = match lifetimes.as_slice() {
+        [] => Lifetime::new("'__json", Span::call_site()),
+        [json] => json.clone(),
+        _ => {
+            return Err(Error::new_spanned(
+                &input.generics,
+                "only one lifetime parameter is supported",
+            ));
+        }
+    };
+
+    let mut inits = Vec::new();
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let pointer = match attrs(field)?.pointer {
+            Some(pointer) => pointer,
+            None => LitStr::new(
+                &format!("/{}", ident.to_string().trim_start_matches("r#")),
+                ident.span(),
+            ),
+        };
+        let value = pointer.value();
+        if !value.is_empty() && !value.starts_with('/') {
+            return Err(Error::new_spanned(
+                &pointer,
+                "a JSON pointer must be empty or start with `/`",
+            ));
+        }
+        let helper = if is_option(&field.ty) {
+            quote!(optional_field)
+        } else {
+            quote!(field)
+        };
+        inits.push(quote! {
+            #ident: ::simdjson_rust::dom::__private::#helper(root, #pointer)?
+        });
+    }
+
+    let ty_generics = input.generics.split_for_impl().1;
+    Ok(quote! {
+        impl<#json> ::simdjson_rust::dom::FromJsonPointers<#json> for #name #ty_generics {
+            fn from_pointers(
+                root: &::simdjson_rust::dom::Element<#json>,
+            ) -> ::simdjson_rust::Result<Self> {
+                ::std::result::Result::Ok(Self {
+                    #(#inits,)*
+                })
+            }
+        }
+    })
+}
+
+fn expand_to_json_builder(input: DeriveInput) -> Result<proc_macro2::TokenStream> {
+    let name = &input.ident;
+    let fields = named_fields(&input)?;
+
+    let mut writes = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let ident = field.ident.as_ref().expect("named field");
+        let key = match attrs(field)?.rename {
+            Some(rename) => rename.value(),
+            None => ident.to_string().trim_start_matches("r#").to_owned(),
+        };
+        let first = i == 0;
+        let header = format!("{}\"{}\":", if first { "" } else { "," }, escape(&key));
+        writes.push(quote! {
+            ::simdjson_rust::builder::__private::field_header(builder, #header, #key, #first);
+            ::simdjson_rust::builder::ToJsonBuilder::write_json(&self.#ident, builder);
+        });
+    }
+
+    let mut generics = input.generics.clone();
+    for param in generics.type_params_mut() {
+        param
+            .bounds
+            .push(syn::parse_quote!(::simdjson_rust::builder::ToJsonBuilder));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::simdjson_rust::builder::ToJsonBuilder for #name #ty_generics #where_clause {
+            fn write_json(&self, builder: &mut ::simdjson_rust::builder::StringBuilder) {
+                builder.start_object();
+                #(#writes)*
+                builder.end_object();
+            }
+        }
+    })
+}
+
+/// The `#[simdjson(...)]` attributes of a field.
+#[derive(Default)]
+struct FieldAttrs {
+    pointer: Option<LitStr>,
+    rename: Option<LitStr>,
+}
+
+fn attrs(field: &Field) -> Result<FieldAttrs> {
+    let mut attrs = FieldAttrs::default();
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("simdjson"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("pointer") {
+                attrs.pointer = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("rename") {
+                attrs.rename = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown simdjson attribute, expected `pointer` or `rename`"))
+            }
+        })?;
+    }
+    Ok(attrs)
+}
+
+/// `key` as the contents of a JSON string.
+fn escape(key: &str) -> String {
+    let mut out = String::with_capacity(key.len());
+    for c in key.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Whether the field is declared as `Option<..>`, however the path is
+/// spelled.
+fn is_option(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Option"),
+        _ => false,
+    }
+}
@@ -1,11 +1,51 @@
 use std::{env, path::PathBuf};
 
+/// Kernels that need SIMD instructions. They are compiled out when only the
+/// portable fallback kernel is built.
+const SIMD_IMPLEMENTATIONS: &[&str] = &[
+    "ICELAKE", "HASWELL", "WESTMERE", "ARM64", "PPC64", "LSX", "LASX",
+];
+
 fn main() {
-    cc::Build::new()
+    let target_arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap();
+    let target_family = env::var("CARGO_CFG_TARGET_FAMILY").unwrap_or_default();
+    let is_wasm = target_family.split(',').any(|family| family == "wasm");
+    let fallback_only = is_wasm || env::var_os("CARGO_FEATURE_FALLBACK").is_some();
+
+    let mut build = cc::Build::new();
+    build
         .cpp(true)
         .flag_if_supported("-std=c++20")
-        .flag_if_supported("/std:c++20") // error C7555: use of designated initializers requires at least '/std:c++20'
-        .flag_if_supported("-pthread")
+        .flag_if_supported("/std:c++20"); // error C7555: use of designated initializers requires at least '/std:c++20'
+
+    if fallback_only {
+        for implementation in SIMD_IMPLEMENTATIONS {
+            build.define(&format!("SIMDJSON_IMPLEMENTATION_{implementation}"), "0");
+        }
+        build.define("SIMDJSON_IMPLEMENTATION_FALLBACK", "1");
+    }
+    for feature in ["DOM", "ONDEMAND", "BUILDER"] {
+        let enabled = env::var_os(format!("CARGO_FEATURE_{feature}")).is_some();
+        build.define(
+            &format!("SJ_ENABLE_{feature}"),
+            if enabled { "1" } else { "0" },
+        );
+    }
+    if target_arch == "wasm32" || target_arch == "x86" || target_arch == "arm" {
+        // simdjson warns about 32-bit targets; the fallback kernel is still correct.
+        build.define("SIMDJSON_NO_PORTABILITY_WARNING", None);
+    }
+    if is_wasm {
+        // wasm has neither threads nor (by default) C++ exceptions. The C API
+        // only uses error codes, so nothing is lost.
+        build
+            .define("SIMDJSON_EXCEPTIONS", "0")
+            .flag_if_supported("-fno-exceptions");
+    } else {
+        build.flag_if_supported("-pthread");
+    }
+
+    build
         .flag_if_supported("-O3")
         .flag_if_supported("/O2")
         .flag_if_supported("-DNDEBUG")
@@ -7,3 +7,4 @@ include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 pub const SIMDJSON_PADDING: usize = 64;
 pub const SIMDJSON_MAXSIZE_BYTES: usize = 0xFFFFFFFF;
 pub const DEFAULT_BATCH_SIZE: usize = 1000000;
+pub const DEFAULT_MAX_DEPTH: usize = 1024;
@@ -1,9 +1,27 @@
 #include "simdjson.h"
 #include "simdjson_c_api.h"
+#include <algorithm>
 #include <cstdint>
+#include <cstring>
+#include <memory>
+#include <mutex>
+#include <set>
+#include <string>
 #include <string_view>
 #include <utility>
 
+// The parts of the API to compile. build.rs sets these from the crate's
+// features; a build without them gets everything.
+#ifndef SJ_ENABLE_ONDEMAND
+#define SJ_ENABLE_ONDEMAND 1
+#endif
+#ifndef SJ_ENABLE_DOM
+#define SJ_ENABLE_DOM 1
+#endif
+#ifndef SJ_ENABLE_BUILDER
+#define SJ_ENABLE_BUILDER 1
+#endif
+
 using namespace simdjson;
 
 namespace {
@@ -12,6 +30,59 @@ template <typename U, typename T> inline U object_to_pointer(T &&t) {
   return reinterpret_cast<U>(new T(std::move(t)));
 }
 
+// Buffer sizes simdjson allocates for a given capacity. These mirror
+// document::allocate, dom_parser_implementation::set_capacity/set_max_depth
+// and ondemand::parser::allocate, which do not expose them.
+inline size_t string_buf_bytes(size_t capacity) {
+  return SIMDJSON_ROUNDUP_N(5 * capacity / 3 + SIMDJSON_PADDING, 64);
+}
+inline size_t tape_bytes(size_t capacity) {
+  return SIMDJSON_ROUNDUP_N(capacity + 3, 64) * sizeof(uint64_t);
+}
+inline size_t stage1_bytes(size_t capacity, size_t max_depth) {
+  return (SIMDJSON_ROUNDUP_N(capacity, 64) + 2 + 7) * sizeof(uint32_t) +
+         max_depth * (2 * sizeof(uint32_t) + sizeof(bool));
+}
+inline size_t document_bytes(const dom::document &doc) {
+  const size_t capacity = doc.capacity();
+  return capacity == 0 ? 0 : tape_bytes(capacity) + string_buf_bytes(capacity);
+}
+
+// Copies as much of the tape and string buffer of `src` as a document parsed
+// from `capacity` bytes of input can use.
+inline error_code copy_document(const dom::document &src, size_t capacity,
+                                dom::document &dst) {
+  if (capacity == 0) {
+    return SUCCESS;
+  }
+  const error_code error = dst.allocate(capacity);
+  if (error) {
+    return error;
+  }
+  std::memcpy(dst.tape.get(), src.tape.get(), tape_bytes(capacity));
+  std::memcpy(dst.string_buf.get(), src.string_buf.get(),
+              string_buf_bytes(capacity));
+  return SUCCESS;
+}
+
+// Converts every element of a DOM or ondemand array to T, writing at most
+// `capacity` values to `out`. Stops at the first element of another type.
+template <typename T, typename Array>
+SJ_size_t_result copy_numbers(Array &array, T *out, size_t capacity) {
+  size_t len = 0;
+  for (auto element : array) {
+    if (len == capacity) {
+      return {static_cast<int>(CAPACITY), len};
+    }
+    const error_code error = element.template get<T>().get(out[len]);
+    if (error) {
+      return {static_cast<int>(error), len};
+    }
+    ++len;
+  }
+  return {static_cast<int>(SUCCESS), len};
+}
+
 // template <typename U, typename T>
 // auto simdjson_result_to_struct(simdjson_result<T> &&sr) {
 //   T value;
@@ -64,6 +135,7 @@ template <typename U, typename T> inline U object_to_pointer(T &&t) {
     return object_to_pointer<SJ_OD_value_result *>(std::move(result));         \
   }
 
+#if SJ_ENABLE_ONDEMAND
 // IMPL_CLASS(SJ_padded_string, padded_string)
 // IMPL_RESULT(SJ_padded_string, padded_string)
 IMPL_CLASS(SJ_OD_parser, ondemand::parser)
@@ -113,6 +185,27 @@ IMPL_PRIMITIVE_RESULT(int)
 SJ_OD_parser *SJ_OD_parser_new(size_t max_capacity) {
   return object_to_pointer<SJ_OD_parser *>(ondemand::parser(max_capacity));
 }
+size_t SJ_OD_parser_capacity(SJ_OD_parser *parser) {
+  return reinterpret_cast<ondemand::parser *>(parser)->capacity();
+}
+size_t SJ_OD_parser_allocated_bytes(SJ_OD_parser *parser) {
+  auto p = reinterpret_cast<ondemand::parser *>(parser);
+  if (p->capacity() == 0) {
+    return 0;
+  }
+  return stage1_bytes(p->capacity(), p->max_depth()) +
+         string_buf_bytes(p->capacity());
+}
+int SJ_OD_parser_allocate(SJ_OD_parser *parser, size_t capacity) {
+  auto p = reinterpret_cast<ondemand::parser *>(parser);
+  return p->allocate(capacity, p->max_depth());
+}
+size_t SJ_OD_parser_max_capacity(SJ_OD_parser *parser) {
+  return reinterpret_cast<ondemand::parser *>(parser)->max_capacity();
+}
+void SJ_OD_parser_set_max_capacity(SJ_OD_parser *parser, size_t max_capacity) {
+  reinterpret_cast<ondemand::parser *>(parser)->set_max_capacity(max_capacity);
+}
 
 SJ_OD_document_result *
 SJ_OD_parser_iterate_padded_string(SJ_OD_parser *parser,
@@ -132,10 +225,59 @@ SJ_OD_parser_iterate_padded_string_view(SJ_OD_parser *parser, const char *json,
   return object_to_pointer<SJ_OD_document_result *>(std::move(doc));
 }
 
+SJ_string_view SJ_OD_value_raw_json_token(SJ_OD_value *value) {
+  std::string_view token =
+      reinterpret_cast<ondemand::value *>(value)->raw_json_token();
+  return {token.data(), token.size()};
+}
+
+SJ_string_view_result SJ_OD_document_raw_json_token(SJ_OD_document *doc) {
+  std::string_view token;
+  const error_code error =
+      reinterpret_cast<ondemand::document *>(doc)->raw_json_token().get(token);
+  return {static_cast<int>(error), {token.data(), token.size()}};
+}
+
+SJ_string_view_result SJ_OD_value_raw_json(SJ_OD_value *value) {
+  std::string_view json;
+  const error_code error =
+      reinterpret_cast<ondemand::value *>(value)->raw_json().get(json);
+  return {static_cast<int>(error), {json.data(), json.size()}};
+}
+
 SJ_OD_value_result *SJ_OD_document_get_value(SJ_OD_document *doc) {
   auto value = reinterpret_cast<ondemand::document *>(doc)->get_value();
   return object_to_pointer<SJ_OD_value_result *>(std::move(value));
 }
+void SJ_OD_document_rewind(SJ_OD_document *doc) {
+  reinterpret_cast<ondemand::document *>(doc)->rewind();
+}
+SJ_OD_value_result *SJ_OD_document_at_path(SJ_OD_document *doc,
+                                           const SJ_path_token *tokens,
+                                           size_t len) {
+  auto document = reinterpret_cast<ondemand::document *>(doc);
+  // Like at_pointer, every lookup starts from the beginning of the document.
+  document->rewind();
+  simdjson_result<ondemand::value> current = document->get_value();
+  for (size_t i = 0; i < len && !current.error(); ++i) {
+    const SJ_path_token &token = tokens[i];
+    ondemand::json_type type;
+    const error_code error = current.type().get(type);
+    if (error) {
+      current = simdjson_result<ondemand::value>(error);
+    } else if (type == ondemand::json_type::object) {
+      current = current.find_field_unordered(
+          std::string_view(token.key, token.key_len));
+    } else if (type == ondemand::json_type::array) {
+      current = token.index == SIZE_MAX
+                    ? simdjson_result<ondemand::value>(INCORRECT_TYPE)
+                    : current.get_array().at(token.index);
+    } else {
+      current = simdjson_result<ondemand::value>(INVALID_JSON_POINTER);
+    }
+  }
+  return object_to_pointer<SJ_OD_value_result *>(std::move(current));
+}
 
 // self, self's real name, output value, how to get output value
 #define IMPL_GET(self, real_name, value, method)                               \
@@ -210,6 +352,16 @@ SJ_OD_value_result *SJ_OD_array_at(SJ_OD_array *array, size_t index) {
   auto result = reinterpret_cast<ondemand::array *>(array)->at(index);
   return object_to_pointer<SJ_OD_value_result *>(std::move(result));
 }
+SJ_size_t_result SJ_OD_array_copy_f64(SJ_OD_array *array, double *out,
+                                      size_t capacity) {
+  return copy_numbers(*reinterpret_cast<ondemand::array *>(array), out,
+                      capacity);
+}
+SJ_size_t_result SJ_OD_array_copy_i64(SJ_OD_array *array, int64_t *out,
+                                      size_t capacity) {
+  return copy_numbers(*reinterpret_cast<ondemand::array *>(array), out,
+                      capacity);
+}
 
 // ondemand::array_iterator
 SJ_OD_value_result *SJ_OD_array_iterator_get(SJ_OD_array_iterator *self) {
@@ -297,6 +449,8 @@ int SJ_OD_number_get_number_type(SJ_OD_number *self) {
       reinterpret_cast<ondemand::number *>(self)->get_number_type());
 }
 
+#endif // SJ_ENABLE_ONDEMAND
+
 // New macros for dom
 #define IMPL_HANDLE(name, type)                                                \
   void name##_free(name *r) { delete reinterpret_cast<type *>(r); }            \
@@ -305,6 +459,7 @@ int SJ_OD_number_get_number_type(SJ_OD_number *self) {
     return object_to_pointer<name *>(std::move(r));                            \
   }
 
+#if SJ_ENABLE_DOM
 IMPL_HANDLE(SJ_DOM_parser, dom::parser)
 IMPL_HANDLE(SJ_DOM_array, dom::array)
 IMPL_HANDLE(SJ_DOM_element, dom::element)
@@ -319,6 +474,29 @@ IMPL_HANDLE(SJ_DOM_document_stream_iterator, dom::document_stream::iterator)
 SJ_DOM_parser *SJ_DOM_parser_new(size_t max_capacity) {
   return object_to_pointer<SJ_DOM_parser *>(dom::parser(max_capacity));
 }
+SJ_DOM_parser *SJ_DOM_parser_new_with_max_depth(size_t max_capacity,
+                                                size_t max_depth) {
+  dom::parser parser(max_capacity);
+  // The depth only sticks once the parser has buffers; later growth keeps it.
+  // If this allocation fails the parser keeps the default depth, and the
+  // first parse allocates again and reports any failure.
+  const error_code error =
+      parser.allocate(dom::MINIMAL_DOCUMENT_CAPACITY, max_depth);
+  static_cast<void>(error);
+  return object_to_pointer<SJ_DOM_parser *>(std::move(parser));
+}
+size_t SJ_DOM_parser_capacity(SJ_DOM_parser *parser) {
+  return reinterpret_cast<dom::parser *>(parser)->capacity();
+}
+size_t SJ_DOM_parser_allocated_bytes(SJ_DOM_parser *parser) {
+  auto p = reinterpret_cast<dom::parser *>(parser);
+  size_t bytes = document_bytes(p->doc);
+  if (p->implementation) {
+    bytes += stage1_bytes(p->implementation->capacity(),
+                          p->implementation->max_depth());
+  }
+  return bytes;
+}
 
 SJ_DOM_element_result SJ_DOM_parser_parse(SJ_DOM_parser *parser,
                                           const char *json, size_t len) {
@@ -328,6 +506,48 @@ SJ_DOM_element_result SJ_DOM_parser_parse(SJ_DOM_parser *parser,
                          .get(value); // The string is padded, so false.
   return {static_cast<int>(error), move_to_handle(std::move(value))};
 }
+namespace {
+SJ_structural_indexes_result stage1(SJ_DOM_parser *parser, const char *json,
+                                    size_t len, stage1_mode mode) {
+  auto p = reinterpret_cast<dom::parser *>(parser);
+  error_code error = SUCCESS;
+  if (!p->implementation || p->capacity() < len) {
+    error = p->allocate(len, p->max_depth());
+  }
+  if (!error) {
+    error = p->implementation->stage1(reinterpret_cast<const uint8_t *>(json),
+                                      len, mode);
+  }
+  if (error) {
+    return {static_cast<int>(error), nullptr, 0};
+  }
+  return {static_cast<int>(error), p->implementation->structural_indexes.get(),
+          p->implementation->n_structural_indexes};
+}
+} // namespace
+SJ_structural_indexes_result SJ_DOM_parser_stage1(SJ_DOM_parser *parser,
+                                                  const char *json, size_t len) {
+  return stage1(parser, json, len, stage1_mode::regular);
+}
+SJ_structural_indexes_result
+SJ_DOM_parser_stage1_partial(SJ_DOM_parser *parser, const char *json,
+                             size_t len) {
+  return stage1(parser, json, len, stage1_mode::streaming_partial);
+}
+SJ_size_t_result SJ_DOM_parser_unescape_string(SJ_DOM_parser *parser,
+                                               const char *src, char *dst) {
+  auto p = reinterpret_cast<dom::parser *>(parser);
+  if (!p->implementation) {
+    return {static_cast<int>(UNINITIALIZED), 0};
+  }
+  uint8_t *begin = reinterpret_cast<uint8_t *>(dst);
+  uint8_t *end = p->implementation->parse_string(
+      reinterpret_cast<const uint8_t *>(src), begin, false);
+  if (!end) {
+    return {static_cast<int>(STRING_ERROR), 0};
+  }
+  return {static_cast<int>(SUCCESS), static_cast<size_t>(end - begin)};
+}
 SJ_DOM_element_result SJ_DOM_parser_parse_into_document(SJ_DOM_parser *parser,
                                                         SJ_DOM_document *doc,
                                                         const char *json,
@@ -339,6 +559,15 @@ SJ_DOM_element_result SJ_DOM_parser_parse_into_document(SJ_DOM_parser *parser,
                          .get(value);
   return {static_cast<int>(error), move_to_handle(std::move(value))};
 }
+SJ_DOM_document_result SJ_DOM_parser_copy_document(SJ_DOM_parser *parser,
+                                                  size_t len) {
+  const dom::document &doc = cast_to_type(parser)->doc;
+  dom::document copy;
+  const error_code error =
+      copy_document(doc, std::min(len, doc.capacity()), copy);
+  return {static_cast<int>(error),
+          error ? nullptr : move_to_handle(std::move(copy))};
+}
 SJ_DOM_document_stream_result SJ_DOM_parser_parse_many(SJ_DOM_parser *parser,
                                                        const char *json,
                                                        size_t len,
@@ -398,6 +627,31 @@ SJ_DOM_element_result SJ_DOM_element_at_pointer(SJ_DOM_element *self,
       cast_to_type(self)->at_pointer(std::string_view(json, len)).get(res);
   return {static_cast<int>(error), move_to_handle(std::move(res))};
 }
+SJ_DOM_element_result SJ_DOM_element_at_path(SJ_DOM_element *self,
+                                             const SJ_path_token *tokens,
+                                             size_t len) {
+  dom::element res = *cast_to_type(self);
+  error_code error = SUCCESS;
+  for (size_t i = 0; i < len && !error; ++i) {
+    const SJ_path_token &token = tokens[i];
+    switch (res.type()) {
+    case dom::element_type::OBJECT:
+      error = res.get_object()
+                  .value_unsafe()
+                  .at_key(std::string_view(token.key, token.key_len))
+                  .get(res);
+      break;
+    case dom::element_type::ARRAY:
+      error = token.index == SIZE_MAX
+                  ? INCORRECT_TYPE
+                  : res.get_array().value_unsafe().at(token.index).get(res);
+      break;
+    default:
+      error = INVALID_JSON_POINTER;
+    }
+  }
+  return {static_cast<int>(error), move_to_handle(std::move(res))};
+}
 
 // dom::array
 SJ_DOM_array_iterator *SJ_DOM_array_begin(SJ_DOM_array *self) {
@@ -424,6 +678,43 @@ SJ_DOM_element_result SJ_DOM_array_at_pointer(SJ_DOM_array *self,
       cast_to_type(self)->at_pointer(std::string_view(json, len)).get(res);
   return {static_cast<int>(error), move_to_handle(std::move(res))};
 }
+SJ_size_t_result SJ_DOM_array_copy_f64(SJ_DOM_array *self, double *out,
+                                       size_t capacity) {
+  return copy_numbers(*cast_to_type(self), out, capacity);
+}
+SJ_size_t_result SJ_DOM_array_copy_i64(SJ_DOM_array *self, int64_t *out,
+                                       size_t capacity) {
+  return copy_numbers(*cast_to_type(self), out, capacity);
+}
+SJ_size_t_result SJ_DOM_array_copy_numbers(SJ_DOM_array *self,
+                                           SJ_DOM_number *out,
+                                           size_t capacity) {
+  size_t len = 0;
+  for (dom::element element : *cast_to_type(self)) {
+    if (len == capacity) {
+      return {static_cast<int>(CAPACITY), len};
+    }
+    SJ_DOM_number &number = out[len];
+    number.element_type = static_cast<int>(element.type());
+    switch (element.type()) {
+    case dom::element_type::INT64:
+      number.bits = static_cast<uint64_t>(element.get_int64().value_unsafe());
+      break;
+    case dom::element_type::UINT64:
+      number.bits = element.get_uint64().value_unsafe();
+      break;
+    case dom::element_type::DOUBLE: {
+      const double value = element.get_double().value_unsafe();
+      std::memcpy(&number.bits, &value, sizeof(double));
+      break;
+    }
+    default:
+      return {static_cast<int>(INCORRECT_TYPE), len};
+    }
+    ++len;
+  }
+  return {static_cast<int>(SUCCESS), len};
+}
 
 // dom::array::iterator
 SJ_DOM_element *SJ_DOM_array_iterator_get(SJ_DOM_array_iterator *self) {
@@ -436,6 +727,16 @@ bool SJ_DOM_array_iterator_not_equal(SJ_DOM_array_iterator *lhs,
 void SJ_DOM_array_iterator_step(SJ_DOM_array_iterator *self) {
   ++(*cast_to_type(self));
 }
+size_t SJ_DOM_array_iterator_advance(SJ_DOM_array_iterator *self,
+                                     SJ_DOM_array_iterator *end, size_t n) {
+  auto &it = *cast_to_type(self);
+  const auto &stop = *cast_to_type(end);
+  size_t stepped = 0;
+  for (; stepped < n && it != stop; ++stepped) {
+    ++it;
+  }
+  return stepped;
+}
 
 // dom::object
 SJ_DOM_object_iterator *SJ_DOM_object_begin(SJ_DOM_object *self) {
@@ -485,12 +786,57 @@ bool SJ_DOM_object_iterator_not_equal(SJ_DOM_object_iterator *lhs,
 void SJ_DOM_object_iterator_step(SJ_DOM_object_iterator *self) {
   ++(*cast_to_type(self));
 }
+size_t SJ_DOM_object_iterator_advance(SJ_DOM_object_iterator *self,
+                                      SJ_DOM_object_iterator *end, size_t n) {
+  auto &it = *cast_to_type(self);
+  const auto &stop = *cast_to_type(end);
+  size_t stepped = 0;
+  for (; stepped < n && it != stop; ++stepped) {
+    ++it;
+  }
+  return stepped;
+}
+
+// The string buffer holds each string as a 32-bit length followed by the
+// text and a NUL; the tape points at the length. The buffer is owned by the
+// document and only const through the views of it.
+static void truncate_string(std::string_view s, size_t len) {
+  if (len >= s.size()) {
+    return;
+  }
+  auto *text = const_cast<char *>(s.data());
+  const auto new_len = static_cast<uint32_t>(len);
+  std::memcpy(text - sizeof(uint32_t), &new_len, sizeof(new_len));
+  text[len] = '\0';
+}
+void SJ_DOM_element_truncate_string(SJ_DOM_element *element, size_t len) {
+  std::string_view s;
+  if (!cast_to_type(element)->get_string().get(s)) {
+    truncate_string(s, len);
+  }
+}
+void SJ_DOM_object_iterator_truncate_key(SJ_DOM_object_iterator *iterator,
+                                         size_t len) {
+  truncate_string((**cast_to_type(iterator)).key, len);
+}
 
 // dom::document
 SJ_DOM_document *SJ_DOM_document_new() {
   return object_to_pointer<SJ_DOM_document *>(dom::document());
 }
 
+size_t SJ_DOM_document_allocated_bytes(SJ_DOM_document *self) {
+  return document_bytes(*cast_to_type(self));
+}
+
+SJ_DOM_document_result SJ_DOM_document_clone(SJ_DOM_document *self) {
+  const dom::document &doc = *cast_to_type(self);
+  dom::document copy;
+  const error_code error = copy_document(doc, doc.capacity(), copy);
+  return {static_cast<int>(error),
+          error ? nullptr : move_to_handle(std::move(copy))};
+}
+
 SJ_DOM_element *SJ_DOM_document_root(SJ_DOM_document *self) {
   return move_to_handle(cast_to_type(self)->root());
 }
@@ -518,6 +864,9 @@ bool SJ_DOM_document_stream_iterator_not_equal(
   return *cast_to_type(lhs) != *cast_to_type(rhs);
 }
 
+#endif // SJ_ENABLE_DOM
+
+#if SJ_ENABLE_BUILDER
 // builder::string_builder
 IMPL_HANDLE(SJ_string_builder, simdjson::builder::string_builder)
 
@@ -560,6 +909,11 @@ void SJ_string_builder_escape_and_append_with_quotes(SJ_string_builder *sb,
   cast_to_type(sb)->escape_and_append_with_quotes(std::string_view(str, len));
 }
 
+void SJ_string_builder_escape_and_append(SJ_string_builder *sb,
+                                         const char *str, size_t len) {
+  cast_to_type(sb)->escape_and_append(std::string_view(str, len));
+}
+
 void SJ_string_builder_start_object(SJ_string_builder *sb) {
   cast_to_type(sb)->start_object();
 }
@@ -584,6 +938,16 @@ void SJ_string_builder_append_colon(SJ_string_builder *sb) {
   cast_to_type(sb)->append_colon();
 }
 
+void SJ_string_builder_append_field_header(SJ_string_builder *sb, bool comma,
+                                           const char *key, size_t len) {
+  auto builder = cast_to_type(sb);
+  if (comma) {
+    builder->append_comma();
+  }
+  builder->escape_and_append_with_quotes(std::string_view(key, len));
+  builder->append_colon();
+}
+
 void SJ_string_builder_append_raw(SJ_string_builder *sb, const char *str,
                                   size_t len) {
   cast_to_type(sb)->append_raw(str, len);
@@ -603,3 +967,195 @@ bool SJ_string_builder_validate_unicode(const SJ_string_builder *sb) {
 size_t SJ_string_builder_size(const SJ_string_builder *sb) {
   return cast_to_type(const_cast<SJ_string_builder *>(sb))->size();
 }
+
+#endif // SJ_ENABLE_BUILDER
+
+// minify
+SJ_size_t_result SJ_minify(const char *buf, size_t len, char *dst) {
+  size_t dst_len = 0;
+  const error_code error = simdjson::minify(buf, len, dst, dst_len);
+  return {static_cast<int>(error), dst_len};
+}
+
+// unicode
+bool SJ_validate_utf8(const char *buf, size_t len) {
+  return simdjson::validate_utf8(buf, len);
+}
+
+// validation
+namespace {
+
+// Reads every value of `value` so that the ondemand parser checks the
+// grammar, each string and each number on the way. The ondemand parser only
+// tracks depth in development builds, so it is counted here: `depth` starts
+// at max_depth and, as in the DOM parser, the document itself takes a level.
+template <typename V> error_code visit(V &value, size_t depth) {
+  ondemand::json_type type;
+  SIMDJSON_TRY(value.type().get(type));
+  switch (type) {
+  case ondemand::json_type::array: {
+    if (depth <= 1) {
+      return DEPTH_ERROR;
+    }
+    ondemand::array array;
+    SIMDJSON_TRY(value.get_array().get(array));
+    for (auto child : array) {
+      ondemand::value element;
+      SIMDJSON_TRY(child.get(element));
+      SIMDJSON_TRY(visit(element, depth - 1));
+    }
+    return SUCCESS;
+  }
+  case ondemand::json_type::object: {
+    if (depth <= 1) {
+      return DEPTH_ERROR;
+    }
+    ondemand::object object;
+    SIMDJSON_TRY(value.get_object().get(object));
+    for (auto child : object) {
+      ondemand::field field;
+      SIMDJSON_TRY(std::move(child).get(field));
+      std::string_view key;
+      SIMDJSON_TRY(field.unescaped_key().get(key));
+      SIMDJSON_TRY(visit(field.value(), depth - 1));
+    }
+    return SUCCESS;
+  }
+  case ondemand::json_type::number: {
+    ondemand::number number;
+    return value.get_number().get(number);
+  }
+  case ondemand::json_type::string: {
+    std::string_view string;
+    return value.get_string().get(string);
+  }
+  case ondemand::json_type::boolean: {
+    bool boolean;
+    return value.get_bool().get(boolean);
+  }
+  case ondemand::json_type::null: {
+    bool is_null;
+    SIMDJSON_TRY(value.is_null().get(is_null));
+    return is_null ? SUCCESS : N_ATOM_ERROR;
+  }
+  }
+  return TAPE_ERROR;
+}
+
+struct validator_state {
+  ondemand::parser parser;
+  size_t max_depth;
+};
+
+} // namespace
+
+SJ_validator *SJ_validator_new(size_t max_capacity, size_t max_depth) {
+  return reinterpret_cast<SJ_validator *>(
+      new validator_state{ondemand::parser(max_capacity), max_depth});
+}
+
+void SJ_validator_free(SJ_validator *validator) {
+  delete reinterpret_cast<validator_state *>(validator);
+}
+
+int SJ_validator_validate(SJ_validator *validator, const char *json,
+                          size_t len) {
+  auto v = reinterpret_cast<validator_state *>(validator);
+  if (v->parser.capacity() < len) {
+    SIMDJSON_TRY(v->parser.allocate(len, v->max_depth));
+  }
+  ondemand::document doc;
+  SIMDJSON_TRY(v->parser
+                   .iterate(padded_string_view(json, len,
+                                               len + SIMDJSON_PADDING))
+                   .get(doc));
+  SIMDJSON_TRY(visit(doc, v->max_depth));
+  return doc.at_end() ? SUCCESS : TRAILING_CONTENT;
+}
+
+// number parsing
+namespace {
+
+// The number parsers may read a few bytes past the end of the number, so
+// they run on a padded copy: on the stack for anything number-sized.
+template <typename Result, typename Parse>
+Result parse_number(const char *buf, size_t len, Parse parse) {
+  constexpr size_t stack_len = 64;
+  uint8_t stack[stack_len + SIMDJSON_PADDING] = {};
+  std::unique_ptr<uint8_t[]> heap;
+  uint8_t *padded = stack;
+  if (len > stack_len) {
+    heap.reset(new uint8_t[len + SIMDJSON_PADDING]());
+    padded = heap.get();
+  }
+  std::memcpy(padded, buf, len);
+  auto result = parse(padded, padded + len);
+  Result out{static_cast<int>(result.error()), {}};
+  if (!result.error()) {
+    out.value = result.value_unsafe();
+  }
+  return out;
+}
+
+} // namespace
+
+SJ_double_result SJ_parse_double(const char *buf, size_t len) {
+  return parse_number<SJ_double_result>(buf, len, [](auto begin, auto end) {
+    return builtin::numberparsing::parse_double(begin, end);
+  });
+}
+
+SJ_int64_t_result SJ_parse_int64(const char *buf, size_t len) {
+  return parse_number<SJ_int64_t_result>(buf, len, [](auto begin, auto end) {
+    return builtin::numberparsing::parse_integer(begin, end);
+  });
+}
+
+SJ_uint64_t_result SJ_parse_uint64(const char *buf, size_t len) {
+  return parse_number<SJ_uint64_t_result>(buf, len, [](auto begin, auto end) {
+    return builtin::numberparsing::parse_unsigned(begin, end);
+  });
+}
+
+// implementation selection
+namespace {
+// implementation::name() and description() return a fresh std::string, so
+// keep one copy of each for the views handed to Rust to point into.
+SJ_string_view intern(std::string s) {
+  static std::mutex lock;
+  static std::set<std::string> strings;
+  std::lock_guard<std::mutex> guard(lock);
+  const std::string &kept = *strings.insert(std::move(s)).first;
+  return {kept.data(), kept.size()};
+}
+} // namespace
+
+SJ_string_view SJ_active_implementation_name(void) {
+  return intern(simdjson::get_active_implementation()->name());
+}
+
+SJ_string_view SJ_active_implementation_description(void) {
+  return intern(simdjson::get_active_implementation()->description());
+}
+
+int SJ_set_active_implementation(const char *name, size_t len) {
+  const implementation *impl =
+      simdjson::get_available_implementations()[std::string_view(name, len)];
+  if (!impl || !impl->supported_by_runtime_system()) {
+    return static_cast<int>(UNSUPPORTED_ARCHITECTURE);
+  }
+  simdjson::get_active_implementation() = impl;
+  return static_cast<int>(SUCCESS);
+}
+
+size_t SJ_available_implementations_size(void) {
+  return simdjson::get_available_implementations().size();
+}
+
+SJ_implementation_info SJ_available_implementation(size_t index) {
+  const implementation *impl =
+      simdjson::get_available_implementations().begin()[index];
+  return {intern(impl->name()), intern(impl->description()),
+          impl->supported_by_runtime_system(),
+          impl->required_instruction_sets()};
+}
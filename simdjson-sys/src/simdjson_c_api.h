@@ -74,6 +74,14 @@ DEFINE_PRIMITIVE_RESULT(int)
 
 // ondemand::parser
 SJ_OD_parser *SJ_OD_parser_new(size_t max_capacity);
+size_t SJ_OD_parser_capacity(SJ_OD_parser *parser);
+// Approximate size of the parser's internal buffers.
+size_t SJ_OD_parser_allocated_bytes(SJ_OD_parser *parser);
+// Resizes the buffers, string buffer included, for documents of up to
+// `capacity` bytes. Returns a simdjson error code.
+int SJ_OD_parser_allocate(SJ_OD_parser *parser, size_t capacity);
+size_t SJ_OD_parser_max_capacity(SJ_OD_parser *parser);
+void SJ_OD_parser_set_max_capacity(SJ_OD_parser *parser, size_t max_capacity);
 SJ_OD_document_result *
 SJ_OD_parser_iterate_padded_string(SJ_OD_parser *parser,
                                    const SJ_padded_string *s);
@@ -97,6 +105,7 @@ DEFINE_AT_POINTER(SJ_OD_value)
 
 // ondemand::document
 SJ_OD_value_result *SJ_OD_document_get_value(SJ_OD_document *doc);
+void SJ_OD_document_rewind(SJ_OD_document *doc);
 DEFINE_GET(SJ_OD_document, uint64_t, get_uint64)
 DEFINE_GET(SJ_OD_document, int64_t, get_int64)
 DEFINE_GET(SJ_OD_document, double, get_double)
@@ -213,6 +222,12 @@ DEFINE_PRIMITIVE_RESULT_V2(bool)
 DEFINE_PRIMITIVE_RESULT_V2(size_t)
 DEFINE_PRIMITIVE_RESULT_V2(int)
 
+// ondemand::array bulk conversions, declared here for SJ_size_t_result.
+SJ_size_t_result SJ_OD_array_copy_f64(SJ_OD_array *array, double *out,
+                                      size_t capacity);
+SJ_size_t_result SJ_OD_array_copy_i64(SJ_OD_array *array, int64_t *out,
+                                      size_t capacity);
+
 typedef struct SJ_string_view {
   const char *data;
   size_t len;
@@ -223,6 +238,41 @@ typedef struct SJ_string_view_result {
   SJ_string_view value;
 } SJ_string_view_result;
 
+// ondemand::value raw token, declared here for SJ_string_view: the text of a
+// scalar as it appears in the document, up to the next structural character.
+// Does not consume the value.
+SJ_string_view SJ_OD_value_raw_json_token(SJ_OD_value *value);
+// The same for a document, whose root may be a scalar.
+SJ_string_view_result SJ_OD_document_raw_json_token(SJ_OD_document *doc);
+// The text of any value, arrays and objects included, which are consumed.
+SJ_string_view_result SJ_OD_value_raw_json(SJ_OD_value *value);
+
+typedef struct SJ_structural_indexes_result {
+  int error;
+  const uint32_t *data;
+  size_t len;
+} SJ_structural_indexes_result;
+
+// One reference token of a pre-parsed JSON pointer: the unescaped key, and
+// the array index it denotes or SIZE_MAX if it is not a valid index.
+typedef struct SJ_path_token {
+  const char *key;
+  size_t key_len;
+  size_t index;
+} SJ_path_token;
+
+// ondemand::document path lookup, declared here for SJ_path_token.
+SJ_OD_value_result *SJ_OD_document_at_path(SJ_OD_document *doc,
+                                           const SJ_path_token *tokens,
+                                           size_t len);
+
+// A number from a DOM array. `element_type` is the dom::element_type ('l',
+// 'u' or 'd') and `bits` holds the int64_t, uint64_t or double bit pattern.
+typedef struct SJ_DOM_number {
+  int element_type;
+  uint64_t bits;
+} SJ_DOM_number;
+
 typedef struct SJ_DOM_key_value_pair {
   SJ_string_view key;
   SJ_DOM_element *value;
@@ -230,16 +280,40 @@ typedef struct SJ_DOM_key_value_pair {
 
 // dom::parser
 SJ_DOM_parser *SJ_DOM_parser_new(size_t max_capacity);
+SJ_DOM_parser *SJ_DOM_parser_new_with_max_depth(size_t max_capacity,
+                                                size_t max_depth);
+size_t SJ_DOM_parser_capacity(SJ_DOM_parser *parser);
+// Approximate size of the parser's internal buffers and document.
+size_t SJ_DOM_parser_allocated_bytes(SJ_DOM_parser *parser);
 SJ_DOM_element_result SJ_DOM_parser_parse(SJ_DOM_parser *parser,
                                           const char *json, size_t len);
 SJ_DOM_element_result SJ_DOM_parser_parse_into_document(SJ_DOM_parser *parser,
                                                         SJ_DOM_document *doc,
                                                         const char *json,
                                                         size_t len);
+// Copies the parser's document, sized for an input of `len` bytes: the length
+// of the last document parsed.
+SJ_DOM_document_result SJ_DOM_parser_copy_document(SJ_DOM_parser *parser,
+                                                  size_t len);
 SJ_DOM_document_stream_result SJ_DOM_parser_parse_many(SJ_DOM_parser *parser,
                                                        const char *json,
                                                        size_t len,
                                                        size_t batch_size);
+// Runs only stage 1. The indexes stay valid until the parser is used again.
+SJ_structural_indexes_result SJ_DOM_parser_stage1(SJ_DOM_parser *parser,
+                                                  const char *json, size_t len);
+// Runs stage 1 over a buffer that may end partway through a document, as
+// parse_many does for each batch: only the complete documents are indexed.
+SJ_structural_indexes_result
+SJ_DOM_parser_stage1_partial(SJ_DOM_parser *parser, const char *json,
+                             size_t len);
+// Unescapes a string with the parser's kernel, which stage 1 or a parse must
+// have set up. `src` points just past the opening quote of a string whose
+// closing quote is followed by SIMDJSON_PADDING readable bytes; `dst` must
+// have room for the string plus SIMDJSON_PADDING bytes. Returns the length
+// written, or STRING_ERROR for a malformed escape.
+SJ_size_t_result SJ_DOM_parser_unescape_string(SJ_DOM_parser *parser,
+                                               const char *src, char *dst);
 
 // dom::element
 DEFINE_GET_V2(SJ_DOM_element, int, type)
@@ -252,6 +326,15 @@ DEFINE_GET_V2(SJ_DOM_element, SJ_double_result, get_double)
 DEFINE_GET_V2(SJ_DOM_element, SJ_bool_result, get_bool)
 SJ_DOM_element_result SJ_DOM_element_at_pointer(SJ_DOM_element *element,
                                                 const char *s, size_t len);
+SJ_DOM_element_result SJ_DOM_element_at_path(SJ_DOM_element *element,
+                                             const SJ_path_token *tokens,
+                                             size_t len);
+// Shorten a string of a parsed document in place to `len` bytes: the string
+// `element` is, or the key of the field `iterator` is at. Nothing happens if
+// there is no such string or it is not longer than `len`.
+void SJ_DOM_element_truncate_string(SJ_DOM_element *element, size_t len);
+void SJ_DOM_object_iterator_truncate_key(SJ_DOM_object_iterator *iterator,
+                                         size_t len);
 
 // dom::array
 DEFINE_GET_V2(SJ_DOM_array, SJ_DOM_array_iterator *, begin)
@@ -262,6 +345,13 @@ DEFINE_GET_V2(SJ_DOM_array, size_t, number_of_slots)
 SJ_DOM_element_result SJ_DOM_array_at(SJ_DOM_array *array, size_t index);
 SJ_DOM_element_result SJ_DOM_array_at_pointer(SJ_DOM_array *array,
                                               const char *s, size_t len);
+SJ_size_t_result SJ_DOM_array_copy_f64(SJ_DOM_array *array, double *out,
+                                       size_t capacity);
+SJ_size_t_result SJ_DOM_array_copy_i64(SJ_DOM_array *array, int64_t *out,
+                                       size_t capacity);
+SJ_size_t_result SJ_DOM_array_copy_numbers(SJ_DOM_array *array,
+                                           SJ_DOM_number *out,
+                                           size_t capacity);
 
 // dom::object
 DEFINE_GET_V2(SJ_DOM_object, SJ_DOM_object_iterator *, begin)
@@ -281,14 +371,22 @@ DEFINE_GET_V2(SJ_DOM_array_iterator, SJ_DOM_element *, get)
 DEFINE_GET_V2(SJ_DOM_array_iterator, void, step)
 bool SJ_DOM_array_iterator_not_equal(SJ_DOM_array_iterator *lhs,
                                      SJ_DOM_array_iterator *rhs);
+// Steps over up to `n` elements, stopping at `end`, and returns how many
+// were stepped over. Each step is a single jump over the element's subtree.
+size_t SJ_DOM_array_iterator_advance(SJ_DOM_array_iterator *self,
+                                     SJ_DOM_array_iterator *end, size_t n);
 
 DEFINE_GET_V2(SJ_DOM_object_iterator, SJ_DOM_key_value_pair, get)
 DEFINE_GET_V2(SJ_DOM_object_iterator, void, step)
 bool SJ_DOM_object_iterator_not_equal(SJ_DOM_object_iterator *lhs,
                                       SJ_DOM_object_iterator *rhs);
+size_t SJ_DOM_object_iterator_advance(SJ_DOM_object_iterator *self,
+                                      SJ_DOM_object_iterator *end, size_t n);
 
 // dom::document
 SJ_DOM_document *SJ_DOM_document_new();
+size_t SJ_DOM_document_allocated_bytes(SJ_DOM_document *self);
+SJ_DOM_document_result SJ_DOM_document_clone(SJ_DOM_document *self);
 DEFINE_GET_V2(SJ_DOM_document, SJ_DOM_element *, root)
 DEFINE_GET_V2(SJ_DOM_document_stream, SJ_DOM_document_stream_iterator *, begin)
 DEFINE_GET_V2(SJ_DOM_document_stream, SJ_DOM_document_stream_iterator *, end)
@@ -311,18 +409,62 @@ void SJ_string_builder_append_char(SJ_string_builder *sb, char c);
 void SJ_string_builder_escape_and_append_with_quotes(SJ_string_builder *sb,
                                                      const char *str,
                                                      size_t len);
+void SJ_string_builder_escape_and_append(SJ_string_builder *sb,
+                                         const char *str, size_t len);
 void SJ_string_builder_start_object(SJ_string_builder *sb);
 void SJ_string_builder_end_object(SJ_string_builder *sb);
 void SJ_string_builder_start_array(SJ_string_builder *sb);
 void SJ_string_builder_end_array(SJ_string_builder *sb);
 void SJ_string_builder_append_comma(SJ_string_builder *sb);
 void SJ_string_builder_append_colon(SJ_string_builder *sb);
+// An optional comma, the quoted and escaped key, then a colon.
+void SJ_string_builder_append_field_header(SJ_string_builder *sb, bool comma,
+                                           const char *key, size_t len);
 void SJ_string_builder_append_raw(SJ_string_builder *sb, const char *str,
                                   size_t len);
 SJ_string_view_result SJ_string_builder_view(const SJ_string_builder *sb);
 bool SJ_string_builder_validate_unicode(const SJ_string_builder *sb);
 size_t SJ_string_builder_size(const SJ_string_builder *sb);
 
+// minify
+// `dst` must have at least `len` bytes of capacity.
+SJ_size_t_result SJ_minify(const char *buf, size_t len, char *dst);
+
+// unicode
+bool SJ_validate_utf8(const char *buf, size_t len);
+
+// validation. Backed by an ondemand parser whichever parts of the API are
+// compiled: every value is visited, and nothing is built.
+typedef struct SJ_validator SJ_validator;
+SJ_validator *SJ_validator_new(size_t max_capacity, size_t max_depth);
+void SJ_validator_free(SJ_validator *validator);
+// `json` must be followed by SIMDJSON_PADDING readable bytes.
+int SJ_validator_validate(SJ_validator *validator, const char *json,
+                          size_t len);
+
+// number parsing, for text that is a single JSON number
+SJ_double_result SJ_parse_double(const char *buf, size_t len);
+SJ_int64_t_result SJ_parse_int64(const char *buf, size_t len);
+SJ_uint64_t_result SJ_parse_uint64(const char *buf, size_t len);
+
+// implementation selection
+SJ_string_view SJ_active_implementation_name(void);
+SJ_string_view SJ_active_implementation_description(void);
+// Returns UNSUPPORTED_ARCHITECTURE for unknown names and for kernels the CPU
+// cannot run.
+int SJ_set_active_implementation(const char *name, size_t len);
+
+typedef struct SJ_implementation_info {
+  SJ_string_view name;
+  SJ_string_view description;
+  bool supported;
+  uint32_t required_instruction_sets;
+} SJ_implementation_info;
+
+size_t SJ_available_implementations_size(void);
+// `index` must be below SJ_available_implementations_size().
+SJ_implementation_info SJ_available_implementation(size_t index);
+
 #ifdef __cplusplus
 }
 #endif
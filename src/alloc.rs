@@ -0,0 +1,256 @@
+//! Padded input buffers in memory from a user-supplied allocator, with the
+//! `allocator` feature, so documents can live in huge pages or a
+//! pre-reserved arena.
+//!
+//! A [`PaddedBuffer`] derefs to `&str` and keeps [`SIMDJSON_PADDING`] bytes
+//! after its contents, so it goes straight to `dom::Parser::parse`,
+//! `ondemand::Parser::iterate_buffer` or anything else taking a padded
+//! string. `StringBuilder::to_buffer_in` copies finished output into one.
+//! The builder's own working buffer is allocated by simdjson and cannot be
+//! placed.
+
+use std::{alloc::Layout, fmt, ops::Deref, ptr::NonNull};
+
+use simdjson_sys::SIMDJSON_PADDING;
+
+use crate::{Result, SimdJsonError};
+
+/// Buffers are aligned to a cache line.
+const ALIGN: usize = 64;
+
+/// A source of memory for [`PaddedBuffer`]s.
+///
+/// # Safety
+///
+/// `allocate` must return null or memory valid for reads and writes of
+/// `layout` that stays valid until it is passed to `deallocate` with the
+/// same layout.
+pub unsafe trait BufferAllocator {
+    fn allocate(&self, layout: Layout) -> *mut u8;
+
+    /// # Safety
+    ///
+    /// `ptr` must come from `allocate` on this allocator with `layout`.
+    unsafe fn deallocate(&self, ptr: *mut u8, layout: Layout);
+}
+
+/// The global allocator.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Global;
+
+unsafe impl BufferAllocator for Global {
+    fn allocate(&self, layout: Layout) -> *mut u8 {
+        unsafe { std::alloc::alloc(layout) }
+    }
+
+    unsafe fn deallocate(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { std::alloc::dealloc(ptr, layout) }
+    }
+}
+
+unsafe impl<A: BufferAllocator + ?Sized> BufferAllocator for &A {
+    fn allocate(&self, layout: Layout) -> *mut u8 {
+        (**self).allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { (**self).deallocate(ptr, layout) }
+    }
+}
+
+/// A UTF-8 buffer followed by at least [`SIMDJSON_PADDING`] readable bytes,
+/// in memory from `A`. It grows like a `String`, by moving to a larger
+/// allocation.
+pub struct PaddedBuffer<A: BufferAllocator = Global> {
+    ptr: NonNull<u8>,
+    len: usize,
+    /// Bytes available for contents, padding excluded.
+    capacity: usize,
+    alloc: A,
+}
+
+// The buffer owns its bytes, like a `String`.
+unsafe impl<A: BufferAllocator + Send> Send for PaddedBuffer<A> {}
+unsafe impl<A: BufferAllocator + Sync> Sync for PaddedBuffer<A> {}
+
+impl<A: BufferAllocator> PaddedBuffer<A> {
+    /// An empty buffer with room for `capacity` bytes of contents. Fails with
+    /// [`SimdJsonError::MemAlloc`] if `alloc` returns null.
+    pub fn with_capacity_in(capacity: usize, alloc: A) -> Result<Self> {
+        let ptr = allocate(&alloc, capacity)?;
+        Ok(Self {
+            ptr,
+            len: 0,
+            capacity,
+            alloc,
+        })
+    }
+
+    /// A copy of `s`.
+    pub fn from_str_in(s: &str, alloc: A) -> Result<Self> {
+        let mut buffer = Self::with_capacity_in(s.len(), alloc)?;
+        buffer.push_str(s)?;
+        Ok(buffer)
+    }
+
+    /// Appends `s`, moving to a larger allocation if it does not fit.
+    pub fn push_str(&mut self, s: &str) -> Result<()> {
+        let len = self.len + s.len();
+        if len > self.capacity {
+            self.grow(len.max(self.capacity * 2))?;
+        }
+        unsafe {
+            let end = self.ptr.as_ptr().add(self.len);
+            end.copy_from_nonoverlapping(s.as_ptr(), s.len());
+        }
+        self.len = len;
+        Ok(())
+    }
+
+    /// Empties the buffer, keeping its allocation.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only whole `&str`s are copied in.
+        unsafe {
+            std::str::from_utf8_unchecked(std::slice::from_raw_parts(self.ptr.as_ptr(), self.len))
+        }
+    }
+
+    /// Bytes available for contents, padding excluded.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    fn grow(&mut self, capacity: usize) -> Result<()> {
+        let ptr = allocate(&self.alloc, capacity)?;
+        unsafe {
+            ptr.as_ptr()
+                .copy_from_nonoverlapping(self.ptr.as_ptr(), self.len);
+            self.alloc
+                .deallocate(self.ptr.as_ptr(), layout(self.capacity)?);
+        }
+        self.ptr = ptr;
+        self.capacity = capacity;
+        Ok(())
+    }
+}
+
+fn layout(capacity: usize) -> Result<Layout> {
+    capacity
+        .checked_add(SIMDJSON_PADDING)
+        .and_then(|size| Layout::from_size_align(size, ALIGN).ok())
+        .ok_or(SimdJsonError::MemAlloc)
+}
+
+/// Allocates room for `capacity` bytes and zeroes everything, so the padding
+/// never holds uninitialized memory.
+fn allocate<A: BufferAllocator>(alloc: &A, capacity: usize) -> Result<NonNull<u8>> {
+    let layout = layout(capacity)?;
+    let ptr = NonNull::new(alloc.allocate(layout)).ok_or(SimdJsonError::MemAlloc)?;
+    unsafe { ptr.as_ptr().write_bytes(0, layout.size()) };
+    Ok(ptr)
+}
+
+impl<A: BufferAllocator> Deref for PaddedBuffer<A> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<A: BufferAllocator> Drop for PaddedBuffer<A> {
+    fn drop(&mut self) {
+        // The layout was valid when the buffer was allocated.
+        if let Ok(layout) = layout(self.capacity) {
+            unsafe { self.alloc.deallocate(self.ptr.as_ptr(), layout) }
+        }
+    }
+}
+
+impl<A: BufferAllocator> fmt::Debug for PaddedBuffer<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    /// Hands out slices of one leaked block and never reuses them.
+    struct Arena {
+        block: *mut u8,
+        len: usize,
+        used: Cell<usize>,
+        freed: Cell<usize>,
+    }
+
+    unsafe impl BufferAllocator for Arena {
+        fn allocate(&self, layout: Layout) -> *mut u8 {
+            let base = self.block as usize;
+            let start = (base + self.used.get()).next_multiple_of(layout.align()) - base;
+            if start + layout.size() > self.len {
+                return std::ptr::null_mut();
+            }
+            self.used.set(start + layout.size());
+            unsafe { self.block.add(start) }
+        }
+
+        unsafe fn deallocate(&self, _ptr: *mut u8, _layout: Layout) {
+            self.freed.set(self.freed.get() + 1);
+        }
+    }
+
+    #[test]
+    fn arena_buffer() {
+        let arena = Arena {
+            block: vec![0xaa; 1024].leak().as_mut_ptr(),
+            len: 1024,
+            used: Cell::new(0),
+            freed: Cell::new(0),
+        };
+        {
+            let mut buffer = PaddedBuffer::from_str_in("[1, 2", &arena).unwrap();
+            buffer.push_str(", 3]").unwrap();
+            assert_eq!(&*buffer, "[1, 2, 3]");
+            assert!(buffer.capacity() >= 9);
+            let padding = unsafe {
+                std::slice::from_raw_parts(buffer.as_ptr().add(buffer.len()), SIMDJSON_PADDING)
+            };
+            assert!(padding.iter().all(|&b| b == 0));
+            assert_eq!(buffer.as_ptr() as usize % ALIGN, 0);
+            assert_eq!(arena.freed.get(), 1);
+        }
+        assert_eq!(arena.freed.get(), 2);
+
+        assert!(matches!(
+            PaddedBuffer::with_capacity_in(4096, &arena),
+            Err(SimdJsonError::MemAlloc)
+        ));
+        let mut buffer = PaddedBuffer::from_str_in("", Global).unwrap();
+        buffer.push_str("{}").unwrap();
+        assert_eq!(buffer.as_str(), "{}");
+    }
+
+    #[cfg(all(feature = "dom", feature = "ondemand"))]
+    #[test]
+    fn parse_buffer() {
+        let buffer = PaddedBuffer::from_str_in(r#"{"a": [1, 2]}"#, Global).unwrap();
+        let mut parser = crate::dom::Parser::default();
+        let root = parser.parse(&buffer).unwrap();
+        assert_eq!(root.at_pointer("/a/1").unwrap().get_uint64().unwrap(), 2);
+        let mut parser = crate::ondemand::Parser::default();
+        let mut doc = parser.iterate_buffer(&buffer).unwrap();
+        assert_eq!(doc.at_pointer("/a/0").unwrap().get_uint64().unwrap(), 1);
+    }
+}
@@ -0,0 +1,201 @@
+//! Conversion of arrays of objects into Arrow [`RecordBatch`]es, without an
+//! intermediate value tree.
+//!
+//! Each field of the schema becomes one column, filled by
+//! [`ondemand::Columns`](crate::ondemand::Columns) in one pass over the
+//! document. Missing fields and `null` become Arrow nulls.
+//!
+//! ```ignore
+//! let mut doc = parser.iterate(&json)?;
+//! let batch = simdjson_rust::arrow::to_record_batch(&mut doc, None)?;
+//! ```
+
+use std::sync::Arc;
+
+use arrow_array::{
+    ArrayRef, BooleanArray, Float64Array, Int64Array, RecordBatch, StringArray, UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+
+use crate::{
+    Result, SimdJsonError,
+    ondemand::{Column, ColumnData, ColumnType, Columns, Document, JsonType, NumberType},
+};
+
+/// Converts the array at the root of `doc` into a record batch.
+///
+/// With a `schema`, its fields are extracted and others skipped; the
+/// supported types are `Boolean`, `Int64`, `UInt64`, `Float64` and `Utf8`.
+/// Without one, it is inferred by [`infer_schema`] and the document read a
+/// second time.
+pub fn to_record_batch(
+    doc: &mut Document<'_, '_>,
+    schema: Option<SchemaRef>,
+) -> Result<RecordBatch> {
+    let schema = match schema {
+        Some(schema) => schema,
+        None => {
+            let schema = infer_schema(doc)?;
+            doc.rewind();
+            Arc::new(schema)
+        }
+    };
+    let mut columns = Columns::new();
+    for field in schema.fields() {
+        let ty = match field.data_type() {
+            DataType::Boolean => ColumnType::Bool,
+            DataType::Int64 => ColumnType::I64,
+            DataType::UInt64 => ColumnType::U64,
+            DataType::Float64 => ColumnType::F64,
+            DataType::Utf8 => ColumnType::String,
+            _ => return Err(SimdJsonError::IncorrectType),
+        };
+        columns = columns.column(field.name(), ty);
+    }
+    let arrays = columns.extract(doc)?.into_iter().map(into_array).collect();
+    RecordBatch::try_new(schema, arrays).map_err(|e| SimdJsonError::Arrow(e.to_string()))
+}
+
+/// Infers a schema from the array at the root of `doc`, with the fields in
+/// the order they first appear. Booleans become `Boolean` and strings
+/// `Utf8`; integers become `Int64`, or `UInt64` if all of them are beyond
+/// `i64`, and `Float64` when mixed with each other or with floats. Fields
+/// that are only ever `null` become `Utf8`. Every field is nullable.
+///
+/// A field holding arrays, objects or values of different kinds fails with
+/// [`SimdJsonError::IncorrectType`].
+pub fn infer_schema(doc: &mut Document<'_, '_>) -> Result<Schema> {
+    let mut fields: Vec<(String, Option<DataType>)> = Vec::new();
+    for element in doc.get_array()?.iter()? {
+        for field in element?.get_object()?.iter()? {
+            let mut field = field?;
+            let key = field.unescaped_key(false)?;
+            let mut value = field.take_value();
+            let seen = match value.json_type()? {
+                JsonType::Null => None,
+                JsonType::Boolean => Some(DataType::Boolean),
+                JsonType::String => Some(DataType::Utf8),
+                JsonType::Number => Some(match value.get_number()?.get_number_type() {
+                    NumberType::SignedInteger => DataType::Int64,
+                    NumberType::UnsignedInteger => DataType::UInt64,
+                    NumberType::FloatingPointNumber => DataType::Float64,
+                }),
+                JsonType::Array | JsonType::Object => return Err(SimdJsonError::IncorrectType),
+            };
+            let slot = match fields.iter().position(|(name, _)| name == key) {
+                Some(index) => &mut fields[index].1,
+                None => {
+                    fields.push((key.to_owned(), None));
+                    &mut fields.last_mut().unwrap().1
+                }
+            };
+            *slot = match (slot.take(), seen) {
+                (known, None) => known,
+                (None, seen) => seen,
+                (Some(known), Some(seen)) if known == seen => Some(known),
+                (Some(known), Some(seen)) if is_number(&known) && is_number(&seen) => {
+                    Some(DataType::Float64)
+                }
+                _ => return Err(SimdJsonError::IncorrectType),
+            };
+        }
+    }
+    Ok(Schema::new(
+        fields
+            .into_iter()
+            .map(|(name, ty)| Field::new(name, ty.unwrap_or(DataType::Utf8), true))
+            .collect::<Vec<_>>(),
+    ))
+}
+
+fn is_number(ty: &DataType) -> bool {
+    matches!(ty, DataType::Int64 | DataType::UInt64 | DataType::Float64)
+}
+
+fn into_array(column: Column) -> ArrayRef {
+    let valid = &column.valid;
+    match column.data {
+        ColumnData::Bool(values) => Arc::new(BooleanArray::from_iter(with_nulls(values, valid))),
+        ColumnData::I64(values) => Arc::new(Int64Array::from_iter(with_nulls(values, valid))),
+        ColumnData::U64(values) => Arc::new(UInt64Array::from_iter(with_nulls(values, valid))),
+        ColumnData::F64(values) => Arc::new(Float64Array::from_iter(with_nulls(values, valid))),
+        ColumnData::String(values) => Arc::new(StringArray::from_iter(with_nulls(values, valid))),
+    }
+}
+
+fn with_nulls<'v, T: 'v>(
+    values: Vec<T>,
+    valid: &'v [bool],
+) -> impl Iterator<Item = Option<T>> + 'v {
+    values.into_iter().zip(valid).map(|(v, ok)| ok.then_some(v))
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::Array;
+
+    use super::*;
+    use crate::{ondemand::Parser, prelude::*};
+
+    #[test]
+    fn record_batch() {
+        let json = r#"[
+            {"id": 1, "name": "a", "price": 1, "ok": true},
+            {"id": 2, "price": 2.5, "big": 18446744073709551615},
+            {"id": 3, "name": null, "price": 3, "ok": false}
+        ]"#
+        .to_padded_string();
+        let mut parser = Parser::default();
+        let mut doc = parser.iterate(&json).unwrap();
+        let batch = to_record_batch(&mut doc, None).unwrap();
+
+        let schema = batch.schema();
+        let types: Vec<_> = schema
+            .fields()
+            .iter()
+            .map(|field| (field.name().as_str(), field.data_type().clone()))
+            .collect();
+        assert_eq!(
+            types,
+            [
+                ("id", DataType::Int64),
+                ("name", DataType::Utf8),
+                ("price", DataType::Float64),
+                ("ok", DataType::Boolean),
+                ("big", DataType::UInt64),
+            ]
+        );
+        assert_eq!(batch.num_rows(), 3);
+        let prices = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(prices.values(), &[1.0, 2.5, 3.0]);
+        let names = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(names.null_count(), 2);
+        assert_eq!(names.value(0), "a");
+
+        // A supplied schema picks and types the columns.
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "price",
+            DataType::Float64,
+            true,
+        )]));
+        doc.rewind();
+        let batch = to_record_batch(&mut doc, Some(schema)).unwrap();
+        assert_eq!(batch.num_columns(), 1);
+
+        let json = r#"[{"a": 1}, {"a": "x"}]"#.to_padded_string();
+        drop(doc);
+        let mut doc = parser.iterate(&json).unwrap();
+        assert!(matches!(
+            infer_schema(&mut doc),
+            Err(SimdJsonError::IncorrectType)
+        ));
+    }
+}
@@ -0,0 +1,285 @@
+//! Parsing framed JSON documents from tokio readers and byte streams.
+//!
+//! [`AsyncParser`] does the buffering and framing; every frame is then parsed
+//! with a reused DOM parser, so documents are handed out exactly like
+//! [`dom::Parser::parse`] does.
+
+use std::io;
+
+use bytes::Buf;
+use futures_core::Stream;
+use simdjson_sys as ffi;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader};
+use tokio_util::io::StreamReader;
+
+use crate::{ParseLimits, Result, SimdJsonError, dom, utils::validate_utf8};
+
+const DEFAULT_MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// How documents are delimited in the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Framing {
+    /// One document per line; blank lines are skipped.
+    #[default]
+    Ndjson,
+    /// Each document is preceded by its length as a big-endian `u32`.
+    LengthPrefixed,
+}
+
+/// Reads framed documents from an async source and parses them one at a time.
+pub struct AsyncParser<R> {
+    reader: BufReader<R>,
+    framing: Framing,
+    max_frame_len: usize,
+    parser: dom::Parser,
+    frame: Vec<u8>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncParser<R> {
+    pub fn new(reader: R, framing: Framing) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            framing,
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+            parser: dom::Parser::default(),
+            frame: Vec::new(),
+        }
+    }
+
+    /// Sets the largest accepted document in bytes. Larger frames are
+    /// reported as [`SimdJsonError::Capacity`] and skipped.
+    pub fn max_frame_len(mut self, max_frame_len: usize) -> Self {
+        self.max_frame_len = max_frame_len;
+        self
+    }
+
+    /// Parses every frame with [`dom::Parser::with_limits`].
+    pub fn limits(mut self, limits: ParseLimits) -> Self {
+        self.parser = dom::Parser::with_limits(limits);
+        self
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+
+    /// Parses the next document. Returns `None` once the input is exhausted.
+    /// An error only affects the current frame, so reading may continue
+    /// afterwards, except after I/O errors.
+    pub async fn next_document(&mut self) -> Option<Result<dom::Element<'_>>> {
+        match self.next_frame().await? {
+            Ok(()) => Some(self.parser.parse(padded_frame(&self.frame))),
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    /// Deserializes the next document into `T`.
+    #[cfg(feature = "serde_impl")]
+    pub async fn next_record<T>(&mut self) -> Option<Result<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let element = self.next_document().await?;
+        Some(element.and_then(|element| crate::serde::de::from_element(&element)))
+    }
+
+    /// Reads the next frame into `self.frame`, leaving `SIMDJSON_PADDING`
+    /// bytes of spare capacity.
+    async fn next_frame(&mut self) -> Option<Result<()>> {
+        self.frame.clear();
+        let frame = match self.framing {
+            Framing::Ndjson => self.next_line().await?,
+            Framing::LengthPrefixed => self.next_prefixed().await?,
+        };
+        Some(frame.and_then(|()| {
+            if !validate_utf8(&self.frame) {
+                return Err(SimdJsonError::Utf8Error);
+            }
+            self.frame.reserve(ffi::SIMDJSON_PADDING);
+            Ok(())
+        }))
+    }
+
+    async fn next_line(&mut self) -> Option<Result<()>> {
+        loop {
+            self.frame.clear();
+            let limit = (self.max_frame_len as u64).saturating_add(1);
+            match (&mut self.reader)
+                .take(limit)
+                .read_until(b'\n', &mut self.frame)
+                .await
+            {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) => return Some(Err(e.into())),
+            }
+            if self.frame.last() == Some(&b'\n') {
+                self.frame.pop();
+            } else if self.frame.len() > self.max_frame_len {
+                if let Err(e) = self.discard_line().await {
+                    return Some(Err(e.into()));
+                }
+                return Some(Err(SimdJsonError::Capacity));
+            }
+            if self.frame.last() == Some(&b'\r') {
+                self.frame.pop();
+            }
+            if !self.frame.iter().all(u8::is_ascii_whitespace) {
+                return Some(Ok(()));
+            }
+        }
+    }
+
+    async fn next_prefixed(&mut self) -> Option<Result<()>> {
+        let len = match self.reader.read_u32().await {
+            Ok(len) => len as usize,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return None,
+            Err(e) => return Some(Err(e.into())),
+        };
+        if len > self.max_frame_len {
+            let skipped = tokio::io::copy(
+                &mut (&mut self.reader).take(len as u64),
+                &mut tokio::io::sink(),
+            )
+            .await;
+            return Some(match skipped {
+                Ok(n) if n == len as u64 => Err(SimdJsonError::Capacity),
+                Ok(_) => Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+                Err(e) => Err(e.into()),
+            });
+        }
+        self.frame.resize(len, 0);
+        Some(
+            self.reader
+                .read_exact(&mut self.frame)
+                .await
+                .map(drop)
+                .map_err(Into::into),
+        )
+    }
+
+    /// Consumes the rest of an over-long line without buffering it.
+    async fn discard_line(&mut self) -> io::Result<()> {
+        loop {
+            let buf = self.reader.fill_buf().await?;
+            if buf.is_empty() {
+                return Ok(());
+            }
+            let (used, done) = match memchr::memchr(b'\n', buf) {
+                Some(i) => (i + 1, true),
+                None => (buf.len(), false),
+            };
+            self.reader.consume(used);
+            if done {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl<S, B, E> AsyncParser<StreamReader<S, B>>
+where
+    S: Stream<Item = std::result::Result<B, E>> + Unpin,
+    B: Buf,
+    E: Into<io::Error>,
+{
+    /// Reads documents from a stream of byte chunks, such as an HTTP body.
+    /// Documents may span chunk boundaries.
+    pub fn from_stream(stream: S, framing: Framing) -> Self {
+        Self::new(StreamReader::new(stream), framing)
+    }
+}
+
+fn padded_frame(frame: &[u8]) -> &str {
+    // `next_frame` has checked the bytes with `validate_utf8` and reserved
+    // `SIMDJSON_PADDING` bytes of spare capacity.
+    unsafe { std::str::from_utf8_unchecked(frame) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn ndjson() {
+        let input: &[u8] = b"1\n\n[2]\r\n{\n3";
+        let mut parser = AsyncParser::new(input, Framing::Ndjson);
+        assert_eq!(
+            parser
+                .next_document()
+                .await
+                .unwrap()
+                .unwrap()
+                .get_uint64()
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            parser.next_document().await.unwrap().unwrap().get_type(),
+            dom::ElementType::Array
+        );
+        assert!(parser.next_document().await.unwrap().is_err());
+        assert_eq!(
+            parser
+                .next_document()
+                .await
+                .unwrap()
+                .unwrap()
+                .get_uint64()
+                .unwrap(),
+            3
+        );
+        assert!(parser.next_document().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn length_prefixed() {
+        let mut input = Vec::new();
+        for doc in ["[1,2]", "\"too long\"", "true"] {
+            input.extend_from_slice(&(doc.len() as u32).to_be_bytes());
+            input.extend_from_slice(doc.as_bytes());
+        }
+        let mut parser =
+            AsyncParser::new(input.as_slice(), Framing::LengthPrefixed).max_frame_len(8);
+        assert!(parser.next_document().await.unwrap().is_ok());
+        assert!(matches!(
+            parser.next_document().await.unwrap(),
+            Err(SimdJsonError::Capacity)
+        ));
+        assert!(
+            parser
+                .next_document()
+                .await
+                .unwrap()
+                .unwrap()
+                .get_bool()
+                .unwrap()
+        );
+        assert!(parser.next_document().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn from_stream() {
+        struct Chunks(Vec<&'static [u8]>);
+
+        impl Stream for Chunks {
+            type Item = io::Result<&'static [u8]>;
+
+            fn poll_next(
+                mut self: std::pin::Pin<&mut Self>,
+                _cx: &mut std::task::Context<'_>,
+            ) -> std::task::Poll<Option<Self::Item>> {
+                let next = (!self.0.is_empty()).then(|| Ok(self.0.remove(0)));
+                std::task::Poll::Ready(next)
+            }
+        }
+
+        let stream = Chunks(vec![b"{\"a\":", b" 1}\n[", b"]\n"]);
+        let mut parser = AsyncParser::from_stream(stream, Framing::Ndjson);
+        let a = parser.next_document().await.unwrap().unwrap();
+        assert_eq!(a.at_pointer("/a").unwrap().get_uint64().unwrap(), 1);
+        drop(a);
+        assert!(parser.next_document().await.unwrap().is_ok());
+        assert!(parser.next_document().await.is_none());
+    }
+}
@@ -0,0 +1,173 @@
+//! Standard base64 (RFC 4648, `+` and `/`, padded with `=`) for binary data
+//! carried in JSON strings, with the `base64` feature.
+//!
+//! This is a portable scalar coder with no dependency, not SIMD: whole 3-byte
+//! groups are converted a word at a time through lookup tables, and decoding
+//! checks each group of four characters at once. The builder writes encoded
+//! bytes with
+//! [`StringBuilder::append_base64`](crate::builder::StringBuilder::append_base64),
+//! and with `serde_impl`, `#[serde(with = "simdjson_rust::serde::helpers::base64")]`
+//! reads and writes `Vec<u8>` fields.
+
+use crate::{Result, SimdJsonError};
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Marks bytes outside the alphabet in [`DECODE`].
+const INVALID: u8 = 0xff;
+
+const DECODE: [u8; 256] = {
+    let mut table = [INVALID; 256];
+    let mut i = 0;
+    while i < 64 {
+        table[ALPHABET[i] as usize] = i as u8;
+        i += 1;
+    }
+    table
+};
+
+/// Length of the encoding of `len` bytes, padding included.
+pub fn encoded_len(len: usize) -> usize {
+    len.div_ceil(3) * 4
+}
+
+/// Encodes `input`.
+pub fn encode(input: &[u8]) -> String {
+    let mut out = String::with_capacity(encoded_len(input.len()));
+    encode_to(input, &mut out);
+    out
+}
+
+/// Appends the encoding of `input` to `out`.
+pub fn encode_to(input: &[u8], out: &mut String) {
+    out.reserve(encoded_len(input.len()));
+    let mut buf = [0u8; 1024];
+    for chunk in input.chunks(768) {
+        let len = encode_into(chunk, &mut buf);
+        out.push_str(std::str::from_utf8(&buf[..len]).expect("the alphabet and padding are ASCII"));
+    }
+}
+
+/// Writes the encoding of `input` to the start of `out`, which must hold at
+/// least [`encoded_len`] bytes, and returns its length.
+pub(crate) fn encode_into(input: &[u8], out: &mut [u8]) -> usize {
+    let groups = input.chunks_exact(3);
+    let rest = groups.remainder();
+    let mut len = 0;
+    for (group, quad) in groups.zip(out.chunks_exact_mut(4)) {
+        let n = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+        quad[0] = ALPHABET[(n >> 18) as usize & 63];
+        quad[1] = ALPHABET[(n >> 12) as usize & 63];
+        quad[2] = ALPHABET[(n >> 6) as usize & 63];
+        quad[3] = ALPHABET[n as usize & 63];
+        len += 4;
+    }
+    if !rest.is_empty() {
+        let n = u32::from_be_bytes([0, rest[0], rest.get(1).copied().unwrap_or(0), 0]);
+        let quad = &mut out[len..len + 4];
+        quad[0] = ALPHABET[(n >> 18) as usize & 63];
+        quad[1] = ALPHABET[(n >> 12) as usize & 63];
+        quad[2] = if rest.len() == 2 {
+            ALPHABET[(n >> 6) as usize & 63]
+        } else {
+            b'='
+        };
+        quad[3] = b'=';
+        len += 4;
+    }
+    len
+}
+
+/// Decodes `input`, which may leave out its padding. Fails with
+/// [`SimdJsonError::Base64`] at the first byte that does not belong.
+pub fn decode(input: &str) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() / 4 * 3 + 2);
+    decode_to(input, &mut out)?;
+    Ok(out)
+}
+
+/// Appends the bytes `input` encodes to `out`, like [`decode`].
+pub fn decode_to(input: &str, out: &mut Vec<u8>) -> Result<()> {
+    let bytes = input.as_bytes();
+    let unpadded = match bytes {
+        [.., b'=', b'='] if bytes.len().is_multiple_of(4) => &bytes[..bytes.len() - 2],
+        [.., b'='] if bytes.len().is_multiple_of(4) => &bytes[..bytes.len() - 1],
+        _ => bytes,
+    };
+    if unpadded.len() % 4 == 1 {
+        return Err(SimdJsonError::Base64(unpadded.len() - 1));
+    }
+    let invalid = |offset: usize, quad: &[u8]| {
+        let at = quad.iter().position(|&b| DECODE[b as usize] == INVALID);
+        SimdJsonError::Base64(offset + at.unwrap_or(0))
+    };
+
+    let quads = unpadded.chunks_exact(4);
+    let rest = quads.remainder();
+    out.reserve(unpadded.len() / 4 * 3 + 2);
+    for (i, quad) in quads.enumerate() {
+        let [a, b, c, d] = [0, 1, 2, 3].map(|j| DECODE[quad[j] as usize]);
+        if (a | b | c | d) == INVALID {
+            return Err(invalid(i * 4, quad));
+        }
+        let n = (a as u32) << 18 | (b as u32) << 12 | (c as u32) << 6 | d as u32;
+        out.extend_from_slice(&n.to_be_bytes()[1..]);
+    }
+    if !rest.is_empty() {
+        let offset = unpadded.len() - rest.len();
+        let mut values = [0; 4];
+        for (value, &b) in values.iter_mut().zip(rest) {
+            *value = DECODE[b as usize];
+        }
+        if values.contains(&INVALID) {
+            return Err(invalid(offset, rest));
+        }
+        let n = values.iter().fold(0u32, |n, &v| n << 6 | v as u32);
+        let decoded = rest.len() - 1;
+        // Bits past the last byte must be zero, so every input has one
+        // encoding.
+        if n.to_be_bytes()[1 + decoded..].iter().any(|&b| b != 0) {
+            return Err(SimdJsonError::Base64(unpadded.len() - 1));
+        }
+        out.extend_from_slice(&n.to_be_bytes()[1..1 + decoded]);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for (raw, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"fooba", "Zm9vYmE="),
+            (b"foobar", "Zm9vYmFy"),
+            (&[0xfb, 0xff, 0x00], "+/8A"),
+        ] {
+            assert_eq!(encode(raw), encoded);
+            assert_eq!(decode(encoded).unwrap(), raw);
+            assert_eq!(decode(encoded.trim_end_matches('=')).unwrap(), raw);
+        }
+        let all: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        assert_eq!(decode(&encode(&all)).unwrap(), all);
+
+        for (bad, at) in [
+            ("Zm9v!mFy", 4),
+            ("Z", 0),
+            ("Zm9vY", 4),
+            ("Zh==", 1),
+            ("Zm=v", 2),
+        ] {
+            assert!(
+                matches!(decode(bad), Err(SimdJsonError::Base64(offset)) if offset == at),
+                "{bad}"
+            );
+        }
+    }
+}
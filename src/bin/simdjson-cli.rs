@@ -0,0 +1,160 @@
+//! Command line access to the simdjson parsers: validate, minify,
+//! pretty-print and query JSON files.
+
+use std::{
+    error::Error,
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand};
+use simdjson_rust::{
+    dom::{self, SerializerOptions},
+    escape_segment, minify,
+    ondemand::{self, Projection, Segment},
+    padded_string::{load_padded_string, make_padded_string, read_padded_string},
+};
+
+#[derive(Parser)]
+#[command(name = "simdjson-cli", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Check that every file is well-formed JSON.
+    Validate {
+        /// Files to check; `-` reads stdin.
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Remove all insignificant whitespace.
+    Minify {
+        /// Input file; stdin if omitted.
+        file: Option<PathBuf>,
+    },
+    /// Print the document with indentation.
+    Pretty {
+        /// Input file; stdin if omitted.
+        file: Option<PathBuf>,
+        /// Spaces per nesting level.
+        #[arg(long, default_value_t = 2)]
+        indent: usize,
+    },
+    /// Print the values selected by JSON pointers, one per line. A `*`
+    /// segment matches every key or index.
+    Query {
+        /// Input file; `-` reads stdin.
+        file: PathBuf,
+        /// JSON pointers such as `/users/*/name`.
+        #[arg(required = true)]
+        pointers: Vec<String>,
+        /// Prefix every value with its concrete pointer and a tab.
+        #[arg(long)]
+        paths: bool,
+        /// Pretty-print the selected values.
+        #[arg(long)]
+        pretty: bool,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli.command) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("simdjson-cli: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Returns `Ok(false)` when some input was rejected but has already been
+/// reported.
+fn run(command: Command) -> Result<bool, Box<dyn Error>> {
+    let mut out = BufWriter::new(io::stdout().lock());
+    match command {
+        Command::Validate { files } => {
+            let mut parser = dom::Parser::default();
+            let mut all_valid = true;
+            for file in files {
+                let result = load(Some(&file))
+                    .map_err(Box::<dyn Error>::from)
+                    .and_then(|json| Ok(parser.validate(&json)?));
+                if let Err(e) = result {
+                    eprintln!("{}: {e}", file.display());
+                    all_valid = false;
+                }
+            }
+            return Ok(all_valid);
+        }
+        Command::Minify { file } => {
+            let json = load(file.as_ref())?;
+            dom::Parser::default().validate(&json)?;
+            out.write_all(minify(&json)?.as_bytes())?;
+            out.write_all(b"\n")?;
+        }
+        Command::Pretty { file, indent } => {
+            let json = load(file.as_ref())?;
+            let mut parser = dom::Parser::default();
+            let options = SerializerOptions {
+                indent: Some(indent),
+                ..SerializerOptions::default()
+            };
+            out.write_all(parser.parse(&json)?.serialize_with(&options)?.as_bytes())?;
+            out.write_all(b"\n")?;
+        }
+        Command::Query {
+            file,
+            pointers,
+            paths,
+            pretty,
+        } => {
+            let projection = Projection::new(&pointers)?;
+            let json = load(Some(&file))?;
+            let mut parser = ondemand::Parser::default();
+            let mut doc = parser.iterate(&json)?;
+            let options = if pretty {
+                SerializerOptions::pretty()
+            } else {
+                SerializerOptions::compact()
+            };
+            // Each selected value is parsed again on its own so that it is
+            // written by the library's emitter.
+            let mut values = dom::Parser::default();
+            projection.for_each(&mut doc, |_, path, mut value| {
+                if paths {
+                    write_pointer(&mut out, path)?;
+                    out.write_all(b"\t")?;
+                }
+                let value = make_padded_string(value.raw_json()?);
+                out.write_all(values.parse(&value)?.serialize_with(&options)?.as_bytes())?;
+                out.write_all(b"\n")?;
+                Ok(())
+            })?;
+        }
+    }
+    out.flush()?;
+    Ok(true)
+}
+
+/// Loads `file` into a padded string; `None` or `-` reads stdin.
+fn load(file: Option<&PathBuf>) -> io::Result<String> {
+    match file {
+        Some(path) if path.as_os_str() != "-" => load_padded_string(path),
+        _ => read_padded_string(io::stdin().lock()),
+    }
+}
+
+fn write_pointer(out: &mut impl Write, path: &[Segment<'_>]) -> io::Result<()> {
+    let mut pointer = String::new();
+    for segment in path {
+        pointer.push('/');
+        escape_segment(&segment.to_string(), &mut pointer);
+    }
+    out.write_all(pointer.as_bytes())
+}
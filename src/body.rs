@@ -0,0 +1,131 @@
+//! Size-limited reading of JSON request bodies.
+//!
+//! Reading stops one byte past the limit, so an oversized body is never
+//! buffered whole. The body is padded in place and parsed with a per-thread
+//! DOM parser. A body over the limit fails with
+//! [`SimdJsonError::LimitExceeded`], one that is not JSON with the parser's
+//! error, and, for the typed variants, one that does not match `T` with
+//! `SimdJsonError::Serde`.
+//!
+//! ```ignore
+//! let doc = read_json_body(request.body_reader(), 1 << 20)?;
+//! ```
+
+use std::io::Read;
+
+use simdjson_sys as ffi;
+
+use crate::{Result, SimdJsonError, dom::OwnedDocument, pool, utils::validate_utf8};
+
+const BODY_LIMIT: &str = "max_body_bytes";
+
+/// Reads at most `max_bytes` from `reader` and parses them.
+pub fn read_json_body<R: Read>(reader: R, max_bytes: usize) -> Result<OwnedDocument> {
+    let body = read_limited(reader, max_bytes)?;
+    pool::with_parser(|parser| OwnedDocument::parse_with(parser, &body))
+}
+
+/// Like [`read_json_body`], deserializing the document into `T`.
+#[cfg(feature = "serde_impl")]
+pub fn read_json_body_as<T, R>(reader: R, max_bytes: usize) -> Result<T>
+where
+    T: serde::de::DeserializeOwned,
+    R: Read,
+{
+    let body = read_limited(reader, max_bytes)?;
+    pool::with_parser(|parser| crate::serde::de::from_element(&parser.parse(&body)?))
+}
+
+/// [`read_json_body`] for tokio readers.
+#[cfg(feature = "tokio")]
+pub async fn read_json_body_async<R>(reader: R, max_bytes: usize) -> Result<OwnedDocument>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    let body = read_limited_async(reader, max_bytes).await?;
+    pool::with_parser(|parser| OwnedDocument::parse_with(parser, &body))
+}
+
+/// [`read_json_body_as`] for tokio readers.
+#[cfg(all(feature = "tokio", feature = "serde_impl"))]
+pub async fn read_json_body_as_async<T, R>(reader: R, max_bytes: usize) -> Result<T>
+where
+    T: serde::de::DeserializeOwned,
+    R: tokio::io::AsyncRead + Unpin,
+{
+    let body = read_limited_async(reader, max_bytes).await?;
+    pool::with_parser(|parser| crate::serde::de::from_element(&parser.parse(&body)?))
+}
+
+fn read_limited<R: Read>(reader: R, max_bytes: usize) -> Result<String> {
+    let mut body = Vec::new();
+    reader
+        .take((max_bytes as u64).saturating_add(1))
+        .read_to_end(&mut body)?;
+    into_padded(body, max_bytes)
+}
+
+#[cfg(feature = "tokio")]
+async fn read_limited_async<R>(reader: R, max_bytes: usize) -> Result<String>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    use tokio::io::AsyncReadExt;
+
+    let mut body = Vec::new();
+    reader
+        .take((max_bytes as u64).saturating_add(1))
+        .read_to_end(&mut body)
+        .await?;
+    into_padded(body, max_bytes)
+}
+
+fn into_padded(mut body: Vec<u8>, max_bytes: usize) -> Result<String> {
+    if body.len() > max_bytes {
+        return Err(SimdJsonError::LimitExceeded(BODY_LIMIT));
+    }
+    if !validate_utf8(&body) {
+        return Err(SimdJsonError::Utf8Error);
+    }
+    body.reserve(ffi::SIMDJSON_PADDING);
+    // Checked just above.
+    Ok(unsafe { String::from_utf8_unchecked(body) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limited_body() {
+        let doc = read_json_body(&br#"{"a": [1, 2]}"#[..], 13).unwrap();
+        assert_eq!(
+            doc.root().at_pointer("/a/1").unwrap().get_uint64().unwrap(),
+            2
+        );
+
+        assert!(matches!(
+            read_json_body(&br#"{"a": [1, 2]}"#[..], 12),
+            Err(SimdJsonError::LimitExceeded(BODY_LIMIT))
+        ));
+        assert!(matches!(
+            read_json_body(&b"{\"a\": \xff}"[..], 100),
+            Err(SimdJsonError::Utf8Error)
+        ));
+        assert!(matches!(
+            read_json_body(&b"{\"a\": }"[..], 100),
+            Err(e) if e.kind() != "LimitExceeded"
+        ));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn limited_body_async() {
+        let doc = read_json_body_async(&b"[1, 2, 3]"[..], 9).await.unwrap();
+        assert_eq!(doc.root().get_array().unwrap().size(), 3);
+        assert!(matches!(
+            read_json_body_async(&b"[1, 2, 3]"[..], 8).await,
+            Err(SimdJsonError::LimitExceeded(BODY_LIMIT))
+        ));
+    }
+}
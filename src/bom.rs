@@ -0,0 +1,49 @@
+use crate::{Result, SimdJsonError};
+
+const UTF8_BOM: &str = "\u{FEFF}";
+
+/// What to do with a UTF-8 byte order mark at the start of the input, as
+/// written by many Windows tools.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BomPolicy {
+    /// Skip it and parse the rest, like simdjson does.
+    #[default]
+    Skip,
+    /// Fail with [`SimdJsonError::ByteOrderMark`].
+    Reject,
+}
+
+impl BomPolicy {
+    /// Returns `json` without its byte order mark, or the error for it. The
+    /// result ends where `json` does, so it keeps its padding.
+    pub(crate) fn apply(self, json: &str) -> Result<&str> {
+        match json.strip_prefix(UTF8_BOM) {
+            None => Ok(json),
+            Some(_) if self == BomPolicy::Reject => Err(SimdJsonError::ByteOrderMark),
+            Some(rest) => Ok(rest),
+        }
+    }
+
+    /// For the entry points where simdjson skips the byte order mark itself.
+    pub(crate) fn check(self, json: &str) -> Result<()> {
+        self.apply(json).map(drop)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply() {
+        assert_eq!(BomPolicy::Skip.apply("\u{FEFF} [1]").unwrap(), " [1]");
+        assert_eq!(BomPolicy::Skip.apply("[1]").unwrap(), "[1]");
+        assert_eq!(BomPolicy::Reject.apply("[1]").unwrap(), "[1]");
+        assert!(matches!(
+            BomPolicy::Reject.apply("\u{FEFF}[1]"),
+            Err(SimdJsonError::ByteOrderMark)
+        ));
+        // Only a leading mark is one.
+        assert!(BomPolicy::Reject.check(" \u{FEFF}").is_ok());
+    }
+}
@@ -2,15 +2,185 @@ use std::ptr::NonNull;
 
 use simdjson_sys as ffi;
 
-use crate::{Result, SimdJsonError};
+use crate::{Number, Result, SimdJsonError};
 
 const DEFAULT_INITIAL_CAPACITY: usize = 1024;
 
+/// Layout of a [`StringBuilder`] in JSONC mode, for configuration files
+/// people read and edit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsoncOptions {
+    /// Spaces per nesting level.
+    pub indent: usize,
+    /// End [`StringBuilder::into_string`]'s output with a newline.
+    pub trailing_newline: bool,
+}
+
+impl Default for JsoncOptions {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            trailing_newline: true,
+        }
+    }
+}
+
+/// Where a JSONC-mode builder is in the document.
+#[derive(Debug)]
+struct Jsonc {
+    options: JsoncOptions,
+    depth: usize,
+    // Set after `[`, `{` and `,`: the next item starts a new line.
+    needs_break: bool,
+    // Written on their own lines before the next item, or before the closing
+    // bracket if there is none.
+    comments: Vec<String>,
+}
+
+/// A call on a [`StringBuilder`], as seen by checked mode.
+#[derive(Debug, Clone, Copy)]
+enum Token {
+    /// A string, which is a key where one is expected.
+    String,
+    /// Any other scalar, or raw text.
+    Value,
+    /// `{` when `true`, `[` otherwise.
+    Start(bool),
+    /// `}` when `true`, `]` otherwise.
+    End(bool),
+    Comma,
+    Colon,
+}
+
+/// What a checked builder accepts next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Expect {
+    Value,
+    /// After `[`.
+    ValueOrEnd,
+    /// After `{`.
+    KeyOrEnd,
+    Key,
+    Colon,
+    CommaOrEnd,
+    /// The root value is complete.
+    Done,
+}
+
+/// The structure written so far by a checked builder, and its first misuse.
+#[derive(Debug)]
+struct Checker {
+    /// Open containers, `true` for objects.
+    stack: Vec<bool>,
+    expect: Expect,
+    error: Option<&'static str>,
+}
+
+impl Checker {
+    fn new() -> Self {
+        Self {
+            stack: Vec::new(),
+            expect: Expect::Value,
+            error: None,
+        }
+    }
+
+    fn token(&mut self, token: Token) {
+        if self.error.is_none() {
+            self.error = self.step(token).err();
+        }
+    }
+
+    fn step(&mut self, token: Token) -> std::result::Result<(), &'static str> {
+        match token {
+            Token::String if matches!(self.expect, Expect::Key | Expect::KeyOrEnd) => {
+                self.expect = Expect::Colon;
+            }
+            Token::String | Token::Value => self.value()?,
+            Token::Start(object) => {
+                self.value()?;
+                self.stack.push(object);
+                self.expect = if object {
+                    Expect::KeyOrEnd
+                } else {
+                    Expect::ValueOrEnd
+                };
+            }
+            Token::End(object) => {
+                if self.stack.last() != Some(&object) {
+                    return Err(if object {
+                        "end_object without an open object"
+                    } else {
+                        "end_array without an open array"
+                    });
+                }
+                match self.expect {
+                    Expect::CommaOrEnd | Expect::KeyOrEnd | Expect::ValueOrEnd => {}
+                    // Only objects expect a colon.
+                    Expect::Colon | Expect::Value if object => {
+                        return Err("object key without a value");
+                    }
+                    _ => return Err("comma before the end of a container"),
+                }
+                self.stack.pop();
+                self.expect = self.after_value();
+            }
+            Token::Comma if self.expect == Expect::CommaOrEnd => {
+                self.expect = if self.stack.last() == Some(&true) {
+                    Expect::Key
+                } else {
+                    Expect::Value
+                };
+            }
+            Token::Comma => return Err("comma where no value ends"),
+            Token::Colon if self.expect == Expect::Colon => self.expect = Expect::Value,
+            Token::Colon => return Err("colon without an object key"),
+        }
+        Ok(())
+    }
+
+    fn value(&mut self) -> std::result::Result<(), &'static str> {
+        match self.expect {
+            Expect::Value | Expect::ValueOrEnd => {
+                self.expect = self.after_value();
+                Ok(())
+            }
+            Expect::Key | Expect::KeyOrEnd => Err("value where an object key is expected"),
+            Expect::Colon => Err("missing colon after an object key"),
+            Expect::CommaOrEnd => Err("missing comma between values"),
+            Expect::Done => Err("value after the root value"),
+        }
+    }
+
+    fn after_value(&self) -> Expect {
+        if self.stack.is_empty() {
+            Expect::Done
+        } else {
+            Expect::CommaOrEnd
+        }
+    }
+
+    fn check(&self, complete: bool) -> Result<()> {
+        match self.error {
+            Some(misuse) => Err(SimdJsonError::BuilderMisuse(misuse)),
+            None if complete && self.expect != Expect::Done => {
+                Err(SimdJsonError::BuilderMisuse("unfinished document"))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
 /// A high-performance JSON string builder backed by simdjson's SIMD-accelerated
 /// string_builder. Provides efficient JSON serialization with automatic string
 /// escaping and structural token management.
 pub struct StringBuilder {
     ptr: NonNull<ffi::SJ_string_builder>,
+    initial_capacity: usize,
+    // Largest size seen before a `clear`, which keeps the buffer.
+    peak_size: usize,
+    jsonc: Option<Box<Jsonc>>,
+    checked: Option<Box<Checker>>,
 }
 
 impl StringBuilder {
@@ -22,37 +192,122 @@ impl StringBuilder {
     /// Creates a new StringBuilder with the specified initial capacity in bytes.
     pub fn with_capacity(capacity: usize) -> Self {
         let ptr = unsafe { NonNull::new_unchecked(ffi::SJ_string_builder_new(capacity)) };
-        Self { ptr }
+        Self {
+            ptr,
+            initial_capacity: capacity,
+            peak_size: 0,
+            jsonc: None,
+            checked: None,
+        }
+    }
+
+    /// Creates a builder in JSONC mode: output is indented, one item per
+    /// line, and [`append_comment`](Self::append_comment) writes `//`
+    /// comments. The result is meant for people, and most JSON parsers,
+    /// simdjson included, reject it once it has comments.
+    pub fn jsonc(options: JsoncOptions) -> Self {
+        let mut builder = Self::new();
+        builder.jsonc = Some(Box::new(Jsonc {
+            options,
+            depth: 0,
+            needs_break: false,
+            comments: Vec::new(),
+        }));
+        builder
+    }
+
+    /// Creates a builder in checked mode, which follows the structure being
+    /// written and records the first call that breaks it: a missing comma or
+    /// colon, a value where a key belongs, an unbalanced `end_object` or
+    /// `end_array`. [`view`](Self::view) and [`check`](Self::check) then fail
+    /// with [`SimdJsonError::BuilderMisuse`], as does
+    /// [`into_string`](Self::into_string), which also rejects an unfinished
+    /// document. `append_char` and `append_escaped` are not followed.
+    pub fn checked() -> Self {
+        let mut builder = Self::new();
+        builder.checked = Some(Box::new(Checker::new()));
+        builder
+    }
+
+    /// In checked mode, fails if the builder was misused or the document is
+    /// not complete; always succeeds otherwise.
+    pub fn check(&self) -> Result<()> {
+        match &self.checked {
+            Some(checker) => checker.check(true),
+            None => Ok(()),
+        }
+    }
+
+    /// Adds a `//` comment, one per line of `text`, on its own lines before
+    /// the next value or key, or before the closing bracket when nothing
+    /// follows in the container. Ignored outside JSONC mode, so the same code
+    /// can write both.
+    pub fn append_comment(&mut self, text: &str) {
+        if let Some(jsonc) = &mut self.jsonc {
+            jsonc.comments.extend(
+                text.lines()
+                    .map(|line| format!("// {line}").trim_end().to_owned()),
+            );
+        }
     }
 
     /// Clears the contents of the builder, resetting it to empty while retaining
     /// the allocated capacity.
     pub fn clear(&mut self) {
+        self.peak_size = self.peak_size.max(self.size());
+        if let Some(jsonc) = &mut self.jsonc {
+            jsonc.depth = 0;
+            jsonc.needs_break = false;
+            jsonc.comments.clear();
+        }
+        if let Some(checker) = &mut self.checked {
+            **checker = Checker::new();
+        }
         unsafe { ffi::SJ_string_builder_clear(self.ptr.as_ptr()) }
     }
 
     /// Appends a boolean value (`true` or `false`).
     pub fn append_bool(&mut self, v: bool) {
+        self.track(Token::Value);
+        self.before_item();
         unsafe { ffi::SJ_string_builder_append_bool(self.ptr.as_ptr(), v) }
     }
 
     /// Appends a signed 64-bit integer.
     pub fn append_i64(&mut self, v: i64) {
+        self.track(Token::Value);
+        self.before_item();
         unsafe { ffi::SJ_string_builder_append_int64(self.ptr.as_ptr(), v) }
     }
 
     /// Appends an unsigned 64-bit integer.
     pub fn append_u64(&mut self, v: u64) {
+        self.track(Token::Value);
+        self.before_item();
         unsafe { ffi::SJ_string_builder_append_uint64(self.ptr.as_ptr(), v) }
     }
 
     /// Appends a 64-bit floating-point number.
     pub fn append_f64(&mut self, v: f64) {
+        self.track(Token::Value);
+        self.before_item();
         unsafe { ffi::SJ_string_builder_append_double(self.ptr.as_ptr(), v) }
     }
 
+    /// Appends a [`Number`], writing [`Number::Raw`] text unchanged.
+    pub fn append_number(&mut self, v: &Number) {
+        match v {
+            Number::I64(v) => self.append_i64(*v),
+            Number::U64(v) => self.append_u64(*v),
+            Number::F64(v) => self.append_f64(*v),
+            Number::Raw(text) => self.append_raw(text.as_str()),
+        }
+    }
+
     /// Appends the JSON `null` literal.
     pub fn append_null(&mut self) {
+        self.track(Token::Value);
+        self.before_item();
         unsafe { ffi::SJ_string_builder_append_null(self.ptr.as_ptr()) }
     }
 
@@ -64,6 +319,8 @@ impl StringBuilder {
     /// Appends a string with JSON escaping and surrounding double quotes.
     /// This uses SIMD-accelerated escaping for performance.
     pub fn append_string(&mut self, s: &str) {
+        self.track(Token::String);
+        self.before_item();
         unsafe {
             ffi::SJ_string_builder_escape_and_append_with_quotes(
                 self.ptr.as_ptr(),
@@ -73,44 +330,120 @@ impl StringBuilder {
         }
     }
 
+    /// Appends a string with JSON escaping but without the surrounding
+    /// quotes, for building a string value out of several pieces.
+    pub fn append_escaped(&mut self, s: &str) {
+        unsafe {
+            ffi::SJ_string_builder_escape_and_append(self.ptr.as_ptr(), s.as_ptr().cast(), s.len())
+        }
+    }
+
+    /// Appends `bytes` as a base64 string, encoded in chunks straight into
+    /// the buffer without an intermediate `String`.
+    #[cfg(feature = "base64")]
+    pub fn append_base64(&mut self, bytes: &[u8]) {
+        self.track(Token::String);
+        self.before_item();
+        self.write("\"");
+        let mut buf = [0u8; 1024];
+        for chunk in bytes.chunks(768) {
+            let len = crate::base64::encode_into(chunk, &mut buf);
+            self.write(
+                std::str::from_utf8(&buf[..len]).expect("the alphabet and padding are ASCII"),
+            );
+        }
+        self.write("\"");
+    }
+
     /// Appends the opening brace `{` for a JSON object.
     pub fn start_object(&mut self) {
+        self.track(Token::Start(true));
+        self.before_item();
         unsafe { ffi::SJ_string_builder_start_object(self.ptr.as_ptr()) }
+        self.opened();
     }
 
     /// Appends the closing brace `}` for a JSON object.
     pub fn end_object(&mut self) {
+        self.track(Token::End(true));
+        self.before_close();
         unsafe { ffi::SJ_string_builder_end_object(self.ptr.as_ptr()) }
     }
 
     /// Appends the opening bracket `[` for a JSON array.
     pub fn start_array(&mut self) {
+        self.track(Token::Start(false));
+        self.before_item();
         unsafe { ffi::SJ_string_builder_start_array(self.ptr.as_ptr()) }
+        self.opened();
     }
 
     /// Appends the closing bracket `]` for a JSON array.
     pub fn end_array(&mut self) {
+        self.track(Token::End(false));
+        self.before_close();
         unsafe { ffi::SJ_string_builder_end_array(self.ptr.as_ptr()) }
     }
 
     /// Appends a comma `,` separator.
     pub fn append_comma(&mut self) {
+        self.track(Token::Comma);
         unsafe { ffi::SJ_string_builder_append_comma(self.ptr.as_ptr()) }
+        if let Some(jsonc) = &mut self.jsonc {
+            jsonc.needs_break = true;
+        }
     }
 
     /// Appends a colon `:` separator (for object key-value pairs).
     pub fn append_colon(&mut self) {
+        self.track(Token::Colon);
         unsafe { ffi::SJ_string_builder_append_colon(self.ptr.as_ptr()) }
+        if self.jsonc.is_some() {
+            self.write(" ");
+        }
+    }
+
+    /// Appends an object key and its colon, preceded by a comma unless
+    /// `first` is set, in a single call into simdjson. Equivalent to
+    /// `append_comma`, `append_string(key)` and `append_colon`.
+    pub fn append_field_header(&mut self, key: &str, first: bool) {
+        if self.jsonc.is_some() {
+            if !first {
+                self.append_comma();
+            }
+            self.append_string(key);
+            self.append_colon();
+            return;
+        }
+        if !first {
+            self.track(Token::Comma);
+        }
+        self.track(Token::String);
+        self.track(Token::Colon);
+        unsafe {
+            ffi::SJ_string_builder_append_field_header(
+                self.ptr.as_ptr(),
+                !first,
+                key.as_ptr().cast(),
+                key.len(),
+            )
+        }
     }
 
     /// Appends raw bytes without escaping. Use with caution — the caller must
-    /// ensure the content is valid JSON.
+    /// ensure the content is valid JSON. In JSONC mode it is laid out as one
+    /// value.
     pub fn append_raw(&mut self, s: &str) {
+        self.track(Token::Value);
+        self.before_item();
         unsafe { ffi::SJ_string_builder_append_raw(self.ptr.as_ptr(), s.as_ptr().cast(), s.len()) }
     }
 
     /// Returns a view of the written JSON buffer as a string slice.
     pub fn view(&self) -> Result<&str> {
+        if let Some(checker) = &self.checked {
+            checker.check(false)?;
+        }
         unsafe {
             let result = ffi::SJ_string_builder_view(self.ptr.as_ptr());
             if result.error != 0 {
@@ -121,6 +454,17 @@ impl StringBuilder {
         }
     }
 
+    /// Copies the JSON written so far into a padded buffer from `alloc`,
+    /// ready to be parsed or kept in an arena. The builder's working buffer
+    /// stays where simdjson allocated it.
+    #[cfg(feature = "allocator")]
+    pub fn to_buffer_in<A: crate::alloc::BufferAllocator>(
+        &self,
+        alloc: A,
+    ) -> Result<crate::alloc::PaddedBuffer<A>> {
+        crate::alloc::PaddedBuffer::from_str_in(self.view()?, alloc)
+    }
+
     /// Validates that the content is valid UTF-8.
     pub fn validate_unicode(&self) -> bool {
         unsafe { ffi::SJ_string_builder_validate_unicode(self.ptr.as_ptr()) }
@@ -131,11 +475,99 @@ impl StringBuilder {
         unsafe { ffi::SJ_string_builder_size(self.ptr.as_ptr()) }
     }
 
+    /// Lower bound of the bytes held by the builder's buffer: the initial
+    /// capacity or the largest size reached so far, whichever is larger. The
+    /// buffer may have grown beyond that.
+    pub fn allocated_bytes(&self) -> usize {
+        self.initial_capacity.max(self.peak_size).max(self.size())
+    }
+
     /// Consumes the builder and returns the JSON string.
-    pub fn into_string(self) -> Result<String> {
+    pub fn into_string(mut self) -> Result<String> {
+        self.check()?;
+        if let Some(jsonc) = &mut self.jsonc {
+            // Comments after the root value.
+            let comments = std::mem::take(&mut jsonc.comments);
+            let trailing_newline = jsonc.options.trailing_newline;
+            for comment in comments {
+                self.write("\n");
+                self.write(&comment);
+            }
+            if trailing_newline {
+                self.write("\n");
+            }
+        }
         let s = self.view()?.to_owned();
         Ok(s)
     }
+
+    fn track(&mut self, token: Token) {
+        if let Some(checker) = &mut self.checked {
+            checker.token(token);
+        }
+    }
+
+    fn write(&mut self, s: &str) {
+        unsafe { ffi::SJ_string_builder_append_raw(self.ptr.as_ptr(), s.as_ptr().cast(), s.len()) }
+    }
+
+    /// Writes the pending comments and the line break before an item in
+    /// JSONC mode.
+    fn before_item(&mut self) {
+        let Some(jsonc) = &mut self.jsonc else {
+            return;
+        };
+        let comments = std::mem::take(&mut jsonc.comments);
+        let indent = " ".repeat(jsonc.depth * jsonc.options.indent);
+        let needs_break = std::mem::take(&mut jsonc.needs_break);
+        // The root item starts the output; nothing is indented there.
+        let at_root = jsonc.depth == 0;
+        // A comment ends its line, so the item has to start a new one even
+        // right after a field header.
+        let needs_break = needs_break || (!at_root && !comments.is_empty());
+        for comment in comments {
+            if at_root {
+                self.write(&comment);
+                self.write("\n");
+            } else {
+                self.write("\n");
+                self.write(&indent);
+                self.write(&comment);
+            }
+        }
+        if needs_break {
+            self.write("\n");
+            self.write(&indent);
+        }
+    }
+
+    fn opened(&mut self) {
+        if let Some(jsonc) = &mut self.jsonc {
+            jsonc.depth += 1;
+            jsonc.needs_break = true;
+        }
+    }
+
+    /// Closes a container in JSONC mode: empty ones stay on one line.
+    fn before_close(&mut self) {
+        let Some(jsonc) = &mut self.jsonc else {
+            return;
+        };
+        let comments = std::mem::take(&mut jsonc.comments);
+        let inner = " ".repeat(jsonc.depth * jsonc.options.indent);
+        jsonc.depth = jsonc.depth.saturating_sub(1);
+        let outer = " ".repeat(jsonc.depth * jsonc.options.indent);
+        let empty = std::mem::take(&mut jsonc.needs_break);
+        for comment in &comments {
+            self.write("\n");
+            self.write(&inner);
+            self.write(comment);
+        }
+        if !empty || !comments.is_empty() {
+            self.write("\n");
+            self.write(&outer);
+        }
+    }
 }
 
 impl Default for StringBuilder {
@@ -158,3 +590,320 @@ impl std::fmt::Display for StringBuilder {
         }
     }
 }
+
+/// Types that write themselves into a [`StringBuilder`] directly, without
+/// serde. `#[derive(ToJsonBuilder)]`, with the `derive` feature, implements
+/// it for structs with named fields, with the keys escaped at compile time.
+///
+/// ```ignore
+/// #[derive(ToJsonBuilder)]
+/// struct Point {
+///     x: f64,
+///     #[simdjson(rename = "y-axis")]
+///     y: f64,
+/// }
+///
+/// let json = Point { x: 1.0, y: 2.0 }.to_json_string()?;
+/// ```
+pub trait ToJsonBuilder {
+    fn write_json(&self, builder: &mut StringBuilder);
+
+    /// Writes `self` into a new builder and returns the JSON.
+    fn to_json_string(&self) -> Result<String> {
+        let mut builder = StringBuilder::new();
+        self.write_json(&mut builder);
+        builder.into_string()
+    }
+}
+
+macro_rules! impl_to_json_builder {
+    ($($ty:ty => $append:ident as $as:ty),* $(,)?) => {
+        $(
+            impl ToJsonBuilder for $ty {
+                fn write_json(&self, builder: &mut StringBuilder) {
+                    builder.$append(*self as $as)
+                }
+            }
+        )*
+    };
+}
+
+impl_to_json_builder! {
+    i8 => append_i64 as i64,
+    i16 => append_i64 as i64,
+    i32 => append_i64 as i64,
+    i64 => append_i64 as i64,
+    isize => append_i64 as i64,
+    u8 => append_u64 as u64,
+    u16 => append_u64 as u64,
+    u32 => append_u64 as u64,
+    u64 => append_u64 as u64,
+    usize => append_u64 as u64,
+    f32 => append_f64 as f64,
+    f64 => append_f64 as f64,
+    bool => append_bool as bool,
+}
+
+impl ToJsonBuilder for str {
+    fn write_json(&self, builder: &mut StringBuilder) {
+        builder.append_string(self)
+    }
+}
+
+impl ToJsonBuilder for String {
+    fn write_json(&self, builder: &mut StringBuilder) {
+        builder.append_string(self)
+    }
+}
+
+impl ToJsonBuilder for Number {
+    fn write_json(&self, builder: &mut StringBuilder) {
+        builder.append_number(self)
+    }
+}
+
+impl<T: ToJsonBuilder> ToJsonBuilder for Option<T> {
+    fn write_json(&self, builder: &mut StringBuilder) {
+        match self {
+            Some(value) => value.write_json(builder),
+            None => builder.append_null(),
+        }
+    }
+}
+
+impl<T: ToJsonBuilder> ToJsonBuilder for [T] {
+    fn write_json(&self, builder: &mut StringBuilder) {
+        builder.start_array();
+        for (i, value) in self.iter().enumerate() {
+            if i > 0 {
+                builder.append_comma();
+            }
+            value.write_json(builder);
+        }
+        builder.end_array();
+    }
+}
+
+impl<T: ToJsonBuilder> ToJsonBuilder for Vec<T> {
+    fn write_json(&self, builder: &mut StringBuilder) {
+        self.as_slice().write_json(builder)
+    }
+}
+
+impl<T: ToJsonBuilder + ?Sized> ToJsonBuilder for &T {
+    fn write_json(&self, builder: &mut StringBuilder) {
+        (**self).write_json(builder)
+    }
+}
+
+impl<T: ToJsonBuilder + ?Sized> ToJsonBuilder for Box<T> {
+    fn write_json(&self, builder: &mut StringBuilder) {
+        (**self).write_json(builder)
+    }
+}
+
+impl<K: AsRef<str>, V: ToJsonBuilder> ToJsonBuilder for std::collections::BTreeMap<K, V> {
+    fn write_json(&self, builder: &mut StringBuilder) {
+        builder.start_object();
+        for (i, (key, value)) in self.iter().enumerate() {
+            builder.append_field_header(key.as_ref(), i == 0);
+            value.write_json(builder);
+        }
+        builder.end_object();
+    }
+}
+
+#[cfg(feature = "derive")]
+pub use simdjson_derive::ToJsonBuilder;
+
+/// Writes JSON written in JSON syntax straight into a [`StringBuilder`],
+/// without building an intermediate value, and returns it as a
+/// `Result<String>`.
+///
+/// Object keys are string literals or parenthesized expressions yielding
+/// `&str`. Values are `null`, literals, nested arrays and objects, or any
+/// other expression, which is written through its `Serialize` impl and needs
+/// the `serde_impl` feature.
+///
+/// ```ignore
+/// let id = 7;
+/// let tags = vec!["a", "b"];
+/// let json = json_builder!({
+///     "id": id,
+///     "tags": tags,
+///     "meta": {"ok": true, "score": -1.5, "none": null},
+///     (key_name()): [1, 2, 3],
+/// })?;
+/// ```
+///
+/// `json_builder!(in &mut builder; ...)` appends to an existing builder
+/// instead and returns `Result<()>`.
+#[macro_export]
+macro_rules! json_builder {
+    (in $builder:expr; $($json:tt)+) => {{
+        let builder: &mut $crate::builder::StringBuilder = $builder;
+        let mut error: ::core::option::Option<$crate::SimdJsonError> = ::core::option::Option::None;
+        $crate::__json_builder!(builder error @value $($json)+);
+        match error {
+            ::core::option::Option::Some(e) => ::core::result::Result::Err(e),
+            ::core::option::Option::None => ::core::result::Result::Ok(()),
+        }
+    }};
+    ($($json:tt)+) => {{
+        let mut builder = $crate::builder::StringBuilder::new();
+        match $crate::json_builder!(in &mut builder; $($json)+) {
+            ::core::result::Result::Ok(()) => builder.into_string(),
+            ::core::result::Result::Err(e) => ::core::result::Result::Err(e),
+        }
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __json_builder {
+    // A complete value.
+    ($b:ident $err:ident @value null) => {
+        $b.append_null()
+    };
+    ($b:ident $err:ident @value [$($elements:tt)*]) => {{
+        $b.start_array();
+        $crate::__json_builder!($b $err @array true () $($elements)*);
+        $b.end_array();
+    }};
+    ($b:ident $err:ident @value {$($members:tt)*}) => {{
+        $b.start_object();
+        $crate::__json_builder!($b $err @object true $($members)*);
+        $b.end_object();
+    }};
+    // Checked before `literal`, which would also try to parse `-x`.
+    ($b:ident $err:ident @value - $($value:tt)+) => {
+        $crate::__json_builder!($b $err @negative $($value)+)
+    };
+    ($b:ident $err:ident @value $value:literal) => {
+        $crate::builder::__private::append_literal($b, &$value)
+    };
+    ($b:ident $err:ident @value $($value:tt)+) => {
+        $crate::builder::__private::append_serialize($b, &($($value)+), &mut $err)
+    };
+    ($b:ident $err:ident @negative $value:literal) => {
+        $crate::builder::__private::append_literal($b, &-$value)
+    };
+    ($b:ident $err:ident @negative $($value:tt)+) => {
+        $crate::builder::__private::append_serialize($b, &(-$($value)+), &mut $err)
+    };
+
+    // Array elements, collected up to the next comma.
+    ($b:ident $err:ident @array $first:tt ($($value:tt)*) , $($rest:tt)*) => {
+        $crate::__json_builder!($b $err @element $first $($value)*);
+        $crate::__json_builder!($b $err @array false () $($rest)*);
+    };
+    ($b:ident $err:ident @array $first:tt ()) => {};
+    ($b:ident $err:ident @array $first:tt ($($value:tt)+)) => {
+        $crate::__json_builder!($b $err @element $first $($value)+);
+    };
+    ($b:ident $err:ident @array $first:tt ($($value:tt)*) $next:tt $($rest:tt)*) => {
+        $crate::__json_builder!($b $err @array $first ($($value)* $next) $($rest)*);
+    };
+    ($b:ident $err:ident @element $first:tt $($value:tt)+) => {
+        $crate::builder::__private::separate($b, $first);
+        $crate::__json_builder!($b $err @value $($value)+);
+    };
+
+    // Object members: a key, then the value up to the next comma.
+    ($b:ident $err:ident @object $first:tt) => {};
+    ($b:ident $err:ident @object $first:tt $key:literal : $($rest:tt)*) => {
+        $b.append_field_header($key, $first);
+        $crate::__json_builder!($b $err @member () $($rest)*);
+    };
+    ($b:ident $err:ident @object $first:tt ($key:expr) : $($rest:tt)*) => {
+        $b.append_field_header(::core::convert::AsRef::<str>::as_ref(&$key), $first);
+        $crate::__json_builder!($b $err @member () $($rest)*);
+    };
+    ($b:ident $err:ident @member ($($value:tt)*) , $($rest:tt)*) => {
+        $crate::__json_builder!($b $err @value $($value)*);
+        $crate::__json_builder!($b $err @object false $($rest)*);
+    };
+    ($b:ident $err:ident @member ($($value:tt)*)) => {
+        $crate::__json_builder!($b $err @value $($value)*);
+    };
+    ($b:ident $err:ident @member ($($value:tt)*) $next:tt $($rest:tt)*) => {
+        $crate::__json_builder!($b $err @member ($($value)* $next) $($rest)*);
+    };
+}
+
+/// Used by [`json_builder!`] and `#[derive(ToJsonBuilder)]`.
+#[doc(hidden)]
+pub mod __private {
+    use super::StringBuilder;
+    #[cfg(feature = "serde_impl")]
+    use crate::SimdJsonError;
+
+    pub fn separate(builder: &mut StringBuilder, first: bool) {
+        if !first {
+            builder.append_comma();
+        }
+    }
+
+    /// Literals, which need no serde.
+    pub trait Literal {
+        fn append_to(&self, builder: &mut StringBuilder);
+    }
+
+    macro_rules! impl_literal {
+        ($($ty:ty => $append:ident as $as:ty),* $(,)?) => {
+            $(
+                impl Literal for $ty {
+                    fn append_to(&self, builder: &mut StringBuilder) {
+                        builder.$append(*self as $as)
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_literal! {
+        i32 => append_i64 as i64,
+        i64 => append_i64 as i64,
+        u32 => append_u64 as u64,
+        u64 => append_u64 as u64,
+        f32 => append_f64 as f64,
+        f64 => append_f64 as f64,
+        bool => append_bool as bool,
+    }
+
+    impl Literal for &str {
+        fn append_to(&self, builder: &mut StringBuilder) {
+            builder.append_string(self)
+        }
+    }
+
+    pub fn append_literal<T: Literal>(builder: &mut StringBuilder, value: &T) {
+        value.append_to(builder)
+    }
+
+    /// Writes the field header `header`, `"key":` with the key escaped by
+    /// `#[derive(ToJsonBuilder)]`, straight into the buffer. JSONC and
+    /// checked builders get `key` through `append_field_header` instead.
+    pub fn field_header(builder: &mut StringBuilder, header: &'static str, key: &str, first: bool) {
+        if builder.jsonc.is_some() || builder.checked.is_some() {
+            builder.append_field_header(key, first);
+        } else {
+            builder.write(header);
+        }
+    }
+
+    #[cfg(feature = "serde_impl")]
+    pub fn append_serialize<T: serde::Serialize + ?Sized>(
+        builder: &mut StringBuilder,
+        value: &T,
+        error: &mut Option<SimdJsonError>,
+    ) {
+        if error.is_some() {
+            return;
+        }
+        let mut serializer = crate::serde::ser::BuilderSerializer::new(builder);
+        if let Err(e) = value.serialize(&mut serializer) {
+            *error = Some(e);
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Reading JSON that is not plain UTF-8, as legacy systems send it: UTF-16
+//! in either byte order, a byte order mark, or a Latin-1 body declared in
+//! the `Content-Type` header.
+//!
+//! [`to_padded_utf8`] transcodes such input into a padded UTF-8 string, and
+//! [`dom::Parser::parse_auto`](crate::dom::Parser::parse_auto) parses it
+//! straight away.
+
+use simdjson_sys as ffi;
+
+use crate::{Result, SimdJsonError, padded_string::make_padded_string, utils::validate_utf8};
+
+/// An encoding [`to_padded_utf8`] reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    /// ISO-8859-1, every byte one character.
+    Latin1,
+}
+
+/// Works out the encoding of `bytes` and the length of its byte order mark.
+///
+/// A byte order mark decides. Otherwise the charset in `hint`, a
+/// `Content-Type` value such as `application/json; charset=utf-16le` or a
+/// bare charset name, is used, and without one the encoding is sniffed from
+/// the first two bytes as RFC 4627 describes: JSON starts with an ASCII
+/// character, so a zero byte next to it gives UTF-16 away. An unknown
+/// charset fails with [`SimdJsonError::Charset`].
+pub fn detect_charset(bytes: &[u8], hint: Option<&str>) -> Result<(Charset, usize)> {
+    match bytes {
+        [0xef, 0xbb, 0xbf, ..] => return Ok((Charset::Utf8, 3)),
+        [0xff, 0xfe, ..] => return Ok((Charset::Utf16Le, 2)),
+        [0xfe, 0xff, ..] => return Ok((Charset::Utf16Be, 2)),
+        _ => {}
+    }
+    let sniffed = match bytes {
+        [0, b, ..] if *b != 0 => Some(Charset::Utf16Be),
+        [a, 0, ..] if *a != 0 => Some(Charset::Utf16Le),
+        _ => None,
+    };
+    let charset = match hint.and_then(charset_label) {
+        None => sniffed.unwrap_or(Charset::Utf8),
+        Some(label) => match label.to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" | "us-ascii" | "ascii" => Charset::Utf8,
+            "utf-16le" => Charset::Utf16Le,
+            "utf-16be" => Charset::Utf16Be,
+            // Big endian unless the content says otherwise, as RFC 2781 has it.
+            "utf-16" => sniffed.unwrap_or(Charset::Utf16Be),
+            "iso-8859-1" | "latin1" | "l1" => Charset::Latin1,
+            _ => return Err(SimdJsonError::Charset(label.to_owned())),
+        },
+    };
+    Ok((charset, 0))
+}
+
+/// The charset in a `Content-Type` value, or `hint` itself when it is a
+/// bare name.
+fn charset_label(hint: &str) -> Option<&str> {
+    if !hint.contains(['/', ';', '=']) {
+        return Some(hint.trim()).filter(|label| !label.is_empty());
+    }
+    hint.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"'))
+    })
+}
+
+/// Transcodes `bytes` into a padded UTF-8 string, without its byte order
+/// mark, after working out its encoding with [`detect_charset`].
+///
+/// Invalid UTF-8 fails with [`SimdJsonError::Utf8Error`], and an odd length
+/// or an unpaired surrogate in UTF-16 with [`SimdJsonError::Charset`].
+pub fn to_padded_utf8(bytes: &[u8], hint: Option<&str>) -> Result<String> {
+    let (charset, bom_len) = detect_charset(bytes, hint)?;
+    let bytes = &bytes[bom_len..];
+    match charset {
+        Charset::Utf8 => {
+            if !validate_utf8(bytes) {
+                return Err(SimdJsonError::Utf8Error);
+            }
+            // Just validated.
+            Ok(make_padded_string(unsafe {
+                std::str::from_utf8_unchecked(bytes)
+            }))
+        }
+        Charset::Latin1 => {
+            let mut out = String::with_capacity(bytes.len() * 2 + ffi::SIMDJSON_PADDING);
+            out.extend(bytes.iter().map(|&b| b as char));
+            Ok(out)
+        }
+        Charset::Utf16Le | Charset::Utf16Be => {
+            if !bytes.len().is_multiple_of(2) {
+                return Err(SimdJsonError::Charset("UTF-16 of odd length".to_owned()));
+            }
+            let units = bytes.chunks_exact(2).map(|pair| {
+                let pair = [pair[0], pair[1]];
+                if charset == Charset::Utf16Le {
+                    u16::from_le_bytes(pair)
+                } else {
+                    u16::from_be_bytes(pair)
+                }
+            });
+            // ASCII, the common case, takes half the bytes.
+            let mut out = String::with_capacity(bytes.len() / 2 + ffi::SIMDJSON_PADDING);
+            for c in char::decode_utf16(units) {
+                let c = c.map_err(|e| {
+                    SimdJsonError::Charset(format!(
+                        "unpaired surrogate {:#06x} in UTF-16",
+                        e.unpaired_surrogate()
+                    ))
+                })?;
+                out.push(c);
+            }
+            if out.capacity() < out.len() + ffi::SIMDJSON_PADDING {
+                out.reserve(ffi::SIMDJSON_PADDING);
+            }
+            Ok(out)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utf16(s: &str, le: bool) -> Vec<u8> {
+        s.encode_utf16()
+            .flat_map(|u| if le { u.to_le_bytes() } else { u.to_be_bytes() })
+            .collect()
+    }
+
+    #[test]
+    fn detect_and_transcode() {
+        let json = r#"{"name": "Zoë 🎉"}"#;
+        let le = utf16(json, true);
+        let be = utf16(json, false);
+        let mut le_bom = vec![0xff, 0xfe];
+        le_bom.extend(&le);
+        let mut utf8_bom = b"\xef\xbb\xbf".to_vec();
+        utf8_bom.extend(json.as_bytes());
+
+        for (bytes, hint, charset) in [
+            (json.as_bytes(), None, Charset::Utf8),
+            (
+                &utf8_bom[..],
+                Some("application/json; charset=utf-16"),
+                Charset::Utf8,
+            ),
+            (&le_bom[..], None, Charset::Utf16Le),
+            (&le[..], None, Charset::Utf16Le),
+            (
+                &be[..],
+                Some("application/json;charset=\"UTF-16\""),
+                Charset::Utf16Be,
+            ),
+            (&le[..], Some("utf-16le"), Charset::Utf16Le),
+        ] {
+            assert_eq!(detect_charset(bytes, hint).unwrap().0, charset);
+            let out = to_padded_utf8(bytes, hint).unwrap();
+            assert_eq!(out, json);
+            assert!(out.capacity() >= out.len() + ffi::SIMDJSON_PADDING);
+        }
+
+        assert_eq!(
+            to_padded_utf8(b"[\"caf\xe9\"]", Some("text/json; charset=ISO-8859-1")).unwrap(),
+            "[\"café\"]"
+        );
+        assert_eq!(
+            detect_charset(b"{}", Some("application/json")).unwrap(),
+            (Charset::Utf8, 0)
+        );
+        assert!(matches!(
+            to_padded_utf8(b"{}", Some("shift_jis")),
+            Err(SimdJsonError::Charset(_))
+        ));
+        assert!(to_padded_utf8(&le[..3], Some("utf-16le")).is_err());
+        assert!(to_padded_utf8(&[0x00, 0xd8, 0x41, 0x00], Some("utf-16le")).is_err());
+        assert!(matches!(
+            to_padded_utf8(b"[\"\xff\"]", None),
+            Err(SimdJsonError::Utf8Error)
+        ));
+    }
+}
@@ -2,12 +2,15 @@ use std::{marker::PhantomData, ptr::NonNull};
 
 use simdjson_sys as ffi;
 
-use super::{document::Document, element::Element};
+use super::{FromElement, document::Document, element::Element};
 use crate::{
-    Result,
-    macros::{impl_drop, map_ptr_result},
+    Result, SimdJsonError,
+    macros::{impl_drop, map_primitive_result, map_ptr_result},
 };
 
+/// `size` saturates at this many elements.
+pub(super) const SIZE_SATURATED: usize = 0xFF_FFFF;
+
 pub struct Array<'a> {
     ptr: NonNull<ffi::SJ_DOM_array>,
     _doc: PhantomData<&'a Document>,
@@ -21,7 +24,7 @@ impl<'a> Array<'a> {
         }
     }
 
-    pub fn iter(&self) -> ArrayIter<'_> {
+    pub fn iter(&self) -> ArrayIter<'a> {
         let begin = unsafe { NonNull::new_unchecked(ffi::SJ_DOM_array_begin(self.ptr.as_ptr())) };
         let end = unsafe { NonNull::new_unchecked(ffi::SJ_DOM_array_end(self.ptr.as_ptr())) };
         ArrayIter::new(begin, end)
@@ -47,6 +50,59 @@ impl<'a> Array<'a> {
     pub fn at(&self, index: usize) -> Result<Element<'a>> {
         map_ptr_result!(ffi::SJ_DOM_array_at(self.ptr.as_ptr(), index)).map(Element::new)
     }
+
+    /// Converts an array of numbers to `f64` in one pass over the tape.
+    /// Integers are converted; any other element is an
+    /// [`IncorrectType`](crate::SimdJsonError::IncorrectType) error.
+    pub fn to_vec_f64(&self) -> Result<Vec<f64>> {
+        self.copy_numbers(ffi::SJ_DOM_array_copy_f64)
+    }
+
+    /// Converts an array of integers to `i64` in one pass over the tape.
+    /// Floats, integers above `i64::MAX` and non-numbers are errors.
+    pub fn to_vec_i64(&self) -> Result<Vec<i64>> {
+        self.copy_numbers(ffi::SJ_DOM_array_copy_i64)
+    }
+
+    /// Converts every element with [`FromElement`] into a vector allocated
+    /// once. Stops at the first element that does not convert, reporting its
+    /// index in a [`SimdJsonError::AtIndex`].
+    pub fn as_vec_of<T: FromElement<'a>>(&self) -> Result<Vec<T>> {
+        let mut out = Vec::with_capacity(self.exact_size().unwrap_or(0));
+        for (index, element) in self.iter().enumerate() {
+            let value = T::from_element(&element).map_err(|e| SimdJsonError::AtIndex {
+                index,
+                source: Box::new(e),
+            })?;
+            out.push(value);
+        }
+        Ok(out)
+    }
+
+    /// The number of elements, or `None` for arrays too long for `size` to
+    /// report.
+    pub(crate) fn exact_size(&self) -> Option<usize> {
+        Some(self.size()).filter(|&len| len != SIZE_SATURATED)
+    }
+
+    /// Copies out every element with its type in one pass, provided they
+    /// are all numbers.
+    #[cfg(feature = "serde_impl")]
+    pub(crate) fn to_vec_numbers(&self) -> Result<Vec<ffi::SJ_DOM_number>> {
+        self.copy_numbers(ffi::SJ_DOM_array_copy_numbers)
+    }
+
+    fn copy_numbers<T>(
+        &self,
+        copy: unsafe extern "C" fn(*mut ffi::SJ_DOM_array, *mut T, usize) -> ffi::SJ_size_t_result,
+    ) -> Result<Vec<T>> {
+        let len = self.exact_size().unwrap_or_else(|| self.iter().count());
+        let mut out = Vec::with_capacity(len);
+        let written = map_primitive_result!(copy(self.ptr.as_ptr(), out.as_mut_ptr(), len))?;
+        // `copy` initialized the first `written` values.
+        unsafe { out.set_len(written) };
+        Ok(out)
+    }
 }
 
 impl_drop!(Array<'a>, ffi::SJ_DOM_array_free);
@@ -83,6 +139,26 @@ impl<'a> ArrayIter<'a> {
     pub fn not_equal(&self) -> bool {
         unsafe { ffi::SJ_DOM_array_iterator_not_equal(self.begin.as_ptr(), self.end.as_ptr()) }
     }
+
+    /// Skips up to `n` of the elements [`next`](Iterator::next) would return
+    /// and returns how many it skipped. Each one is jumped over on the tape,
+    /// subtree and all, without creating an [`Element`] for it, so
+    /// `iter.skip(10_000).take(100)` does not visit the first 10 000.
+    pub fn advance(&mut self, n: usize) -> usize {
+        if n == 0 {
+            return 0;
+        }
+        // The element returned last is still current until stepped over.
+        let current = usize::from(std::mem::take(&mut self.running));
+        let stepped = unsafe {
+            ffi::SJ_DOM_array_iterator_advance(
+                self.begin.as_ptr(),
+                self.end.as_ptr(),
+                n.saturating_add(current),
+            )
+        };
+        stepped - current
+    }
 }
 
 impl<'a> Drop for ArrayIter<'a> {
@@ -102,11 +178,107 @@ impl<'a> Iterator for ArrayIter<'a> {
             self.step();
         }
 
-        if self.not_equal() {
-            self.running = true;
-            Some(self.get())
-        } else {
-            None
-        }
+        self.running = self.not_equal();
+        self.running.then(|| self.get())
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.advance(n);
+        self.next()
+    }
+
+    fn count(mut self) -> usize {
+        self.advance(usize::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{SimdJsonError, dom::Parser, prelude::*};
+
+    #[test]
+    fn skip_elements() {
+        let mut parser = Parser::default();
+        let json = r#"[0, [1, [1]], {"a": [2]}, 3, 4, 5]"#.to_padded_string();
+        let arr = parser.parse(&json).unwrap().get_array().unwrap();
+        let mut iter = arr.iter();
+        assert_eq!(iter.nth(3).unwrap().get_uint64().unwrap(), 3);
+        assert_eq!(iter.advance(1), 1);
+        assert_eq!(iter.next().unwrap().get_uint64().unwrap(), 5);
+        assert!(iter.nth(2).is_none());
+        assert_eq!(iter.advance(2), 0);
+
+        let mut iter = arr.iter();
+        while iter.next().is_some() {}
+        assert!(iter.next().is_none());
+        assert_eq!(iter.advance(1), 0);
+        assert!(iter.nth(1).is_none());
+        assert_eq!(iter.count(), 0);
+
+        let middle: Vec<_> = arr
+            .iter()
+            .skip(3)
+            .take(2)
+            .map(|e| e.get_uint64().unwrap())
+            .collect();
+        assert_eq!(middle, [3, 4]);
+        assert_eq!(arr.iter().count(), 6);
+        let mut iter = arr.iter();
+        iter.next();
+        assert_eq!(iter.advance(usize::MAX), 5);
+
+        let mut parser = Parser::default();
+        let root = parser
+            .parse(&r#"{"a": {"x": 1}, "b": 2, "c": 3}"#.to_padded_string())
+            .unwrap();
+        let object = root.get_object().unwrap();
+        let (key, value) = object.iter().nth(1).unwrap();
+        assert_eq!((key, value.get_uint64().unwrap()), ("b", 2));
+        assert_eq!(object.iter().count(), 3);
+    }
+
+    #[test]
+    fn to_vec() {
+        let mut parser = Parser::default();
+        let json = "[1, -2.5, 3e2]".to_padded_string();
+        let arr = parser.parse(&json).unwrap().get_array().unwrap();
+        assert_eq!(arr.to_vec_f64().unwrap(), [1.0, -2.5, 300.0]);
+        assert!(matches!(
+            arr.to_vec_i64(),
+            Err(SimdJsonError::IncorrectType)
+        ));
+    }
+
+    #[test]
+    fn to_vec_i64() {
+        let mut parser = Parser::default();
+        let json = "[[1, -2, 3], [], [1, null]]".to_padded_string();
+        let arr = parser.parse(&json).unwrap().get_array().unwrap();
+        let column = |i| arr.at(i).unwrap().get_array().unwrap().to_vec_i64();
+        assert_eq!(column(0).unwrap(), [1, -2, 3]);
+        assert!(column(1).unwrap().is_empty());
+        assert!(column(2).is_err());
+    }
+
+    #[test]
+    fn as_vec_of() {
+        let mut parser = Parser::default();
+        let json = r#"[["a", "b"], [1, null, 3], ["c", 2]]"#.to_padded_string();
+        let arr = parser.parse(&json).unwrap().get_array().unwrap();
+        let column = |i| arr.at(i).unwrap().get_array().unwrap();
+        assert_eq!(column(0).as_vec_of::<&str>().unwrap(), ["a", "b"]);
+        assert_eq!(
+            column(1).as_vec_of::<Option<u64>>().unwrap(),
+            [Some(1), None, Some(3)]
+        );
+        assert!(matches!(
+            column(2).as_vec_of::<String>(),
+            Err(crate::SimdJsonError::AtIndex { index: 1, .. })
+        ));
+        // Nested arrays report the outer index; the inner error is the source.
+        assert!(matches!(
+            arr.as_vec_of::<Vec<Option<i64>>>(),
+            Err(crate::SimdJsonError::AtIndex { index: 0, .. })
+        ));
     }
 }
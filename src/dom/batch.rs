@@ -0,0 +1,157 @@
+use std::ops::Range;
+
+use simdjson_sys as ffi;
+
+use super::{DocumentStream, Parser};
+use crate::{Result, SimdJsonError, padded_string::make_padded_string, utils::validate_utf8};
+
+/// Collects many small JSON messages, such as a poll's worth of queue
+/// records, into one padded buffer so they can be parsed with a single
+/// [`Parser::parse_many`] pass instead of one parse call each.
+///
+/// Messages are separated by newlines and their byte ranges are kept, so the
+/// `n`th document of the stream can be traced back to the `n`th message. That
+/// only holds if every message is exactly one JSON document.
+///
+/// ```ignore
+/// let mut batch = BatchBuilder::new();
+/// for record in consumer.poll() {
+///     batch.push_bytes(record.payload())?;
+/// }
+/// let stream = batch.parse_many(&mut parser)?;
+/// for (doc, range) in stream.iter().zip(batch.offsets()) {
+///     handle(doc?, range);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct BatchBuilder {
+    buf: String,
+    offsets: Vec<Range<usize>>,
+}
+
+impl BatchBuilder {
+    pub fn new() -> Self {
+        Self::with_capacity(0, 0)
+    }
+
+    /// Creates a builder with room for `bytes` of messages in total and
+    /// `messages` offsets.
+    pub fn with_capacity(bytes: usize, messages: usize) -> Self {
+        Self {
+            buf: String::with_capacity(bytes + messages + ffi::SIMDJSON_PADDING),
+            offsets: Vec::with_capacity(messages),
+        }
+    }
+
+    /// Appends one message.
+    pub fn push(&mut self, message: &str) {
+        let start = self.buf.len();
+        self.buf.reserve(message.len() + 1 + ffi::SIMDJSON_PADDING);
+        self.buf.push_str(message);
+        self.offsets.push(start..self.buf.len());
+        self.buf.push('\n');
+    }
+
+    /// Appends one message that still has to be checked for valid UTF-8.
+    pub fn push_bytes(&mut self, message: &[u8]) -> Result<()> {
+        if !validate_utf8(message) {
+            return Err(SimdJsonError::Utf8Error);
+        }
+        // Checked just above.
+        self.push(unsafe { std::str::from_utf8_unchecked(message) });
+        Ok(())
+    }
+
+    /// Number of messages pushed.
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Byte range of each message in [`as_padded_str`](Self::as_padded_str),
+    /// in push order.
+    pub fn offsets(&self) -> &[Range<usize>] {
+        &self.offsets
+    }
+
+    /// The batch as a padded string, ready for any of the parsers.
+    pub fn as_padded_str(&self) -> &str {
+        &self.buf
+    }
+
+    /// Parses the whole batch. The stream borrows this builder, which cannot
+    /// be changed until the stream is dropped.
+    pub fn parse_many<'a>(&'a self, parser: &'a mut Parser) -> Result<DocumentStream<'a>> {
+        parser.parse_many(&self.buf)
+    }
+
+    /// Forgets every message, keeping the allocations for the next batch.
+    pub fn clear(&mut self) {
+        self.buf.clear();
+        self.offsets.clear();
+    }
+}
+
+// `String::clone` drops the spare capacity the padding lives in.
+impl Clone for BatchBuilder {
+    fn clone(&self) -> Self {
+        Self {
+            buf: make_padded_string(&self.buf),
+            offsets: self.offsets.clone(),
+        }
+    }
+}
+
+impl Default for BatchBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch() {
+        let mut batch = BatchBuilder::with_capacity(64, 3);
+        batch.push(r#"{"id": 1}"#);
+        batch.push_bytes(b"[2,\n 3]").unwrap();
+        batch.push("\"four\"");
+        assert!(batch.push_bytes(b"\xff").is_err());
+        assert_eq!(batch.len(), 3);
+        assert_eq!(
+            &batch.as_padded_str()[batch.offsets()[1].clone()],
+            "[2,\n 3]"
+        );
+
+        let mut parser = Parser::default();
+        let stream = batch.parse_many(&mut parser).unwrap();
+        let docs: Vec<_> = stream
+            .iter()
+            .map(|doc| crate::dom::canonical_json(&doc.unwrap()).unwrap())
+            .collect();
+        assert_eq!(docs, [r#"{"id":1}"#, "[2,3]", r#""four""#]);
+        drop(stream);
+
+        batch.clear();
+        assert!(batch.is_empty());
+    }
+
+    #[test]
+    fn clone_keeps_padding() {
+        let mut batch = BatchBuilder::new();
+        batch.push("[1]");
+        batch.push("[2]");
+        let copy = batch.clone();
+        assert!(copy.buf.capacity() >= copy.buf.len() + ffi::SIMDJSON_PADDING);
+        assert_eq!(copy.offsets(), batch.offsets());
+
+        let mut parser = Parser::default();
+        let stream = copy.parse_many(&mut parser).unwrap();
+        assert_eq!(stream.iter().filter(|doc| doc.is_ok()).count(), 2);
+    }
+}
@@ -0,0 +1,144 @@
+use std::hash::Hasher;
+
+use super::{Element, ElementType};
+use crate::{
+    Result,
+    utils::{escape_json_string, needs_escaping},
+};
+
+/// Serializes `element` into a canonical form: no whitespace, object keys
+/// sorted by code point, and numbers normalized so that an integral double
+/// such as `1.0` is written as `1`. Two documents that only differ in
+/// formatting, key order or number spelling produce the same output.
+pub fn canonical_json(element: &Element<'_>) -> Result<String> {
+    let mut out = Vec::new();
+    write_canonical(element, &mut |bytes| out.extend_from_slice(bytes))?;
+    // Only valid UTF-8 strings and ASCII punctuation are written.
+    Ok(unsafe { String::from_utf8_unchecked(out) })
+}
+
+/// Feeds the canonical form of `element` (see [`canonical_json`]) into
+/// `hasher` without materializing it.
+pub fn digest<H: Hasher>(element: &Element<'_>, hasher: &mut H) -> Result<()> {
+    write_canonical(element, &mut |bytes| hasher.write(bytes))
+}
+
+/// SHA-256 of the canonical form of `element`.
+#[cfg(feature = "sha2")]
+pub fn sha256(element: &Element<'_>) -> Result<[u8; 32]> {
+    use sha2::Digest;
+
+    let mut hasher = sha2::Sha256::new();
+    write_canonical(element, &mut |bytes| hasher.update(bytes))?;
+    Ok(hasher.finalize().into())
+}
+
+fn write_canonical(element: &Element<'_>, out: &mut impl FnMut(&[u8])) -> Result<()> {
+    match element.get_type() {
+        ElementType::NullValue => out(b"null"),
+        ElementType::Bool => out(if element.get_bool()? {
+            b"true"
+        } else {
+            b"false"
+        }),
+        ElementType::Int64 => out(element.get_int64()?.to_string().as_bytes()),
+        ElementType::UInt64 => out(element.get_uint64()?.to_string().as_bytes()),
+        ElementType::Double => out(normalize_double(element.get_double()?).as_bytes()),
+        ElementType::String => write_string(element.get_string()?, out)?,
+        ElementType::Array => {
+            out(b"[");
+            for (i, child) in element.get_array()?.iter().enumerate() {
+                if i > 0 {
+                    out(b",");
+                }
+                write_canonical(&child, out)?;
+            }
+            out(b"]");
+        }
+        ElementType::Object => {
+            let fields = element.get_object_sorted()?;
+            out(b"{");
+            for (i, (key, child)) in fields.iter().enumerate() {
+                if i > 0 {
+                    out(b",");
+                }
+                write_string(key, out)?;
+                out(b":");
+                write_canonical(child, out)?;
+            }
+            out(b"}");
+        }
+    }
+    Ok(())
+}
+
+/// Integral doubles that fit a 64-bit integer are written as that integer.
+/// Everything else uses the shortest representation that round-trips, in
+/// exponent notation for very large or very small magnitudes.
+pub(crate) fn normalize_double(value: f64) -> String {
+    const TWO_POW_63: f64 = 9_223_372_036_854_775_808.0;
+    if value.fract() == 0.0 {
+        if (-TWO_POW_63..TWO_POW_63).contains(&value) {
+            return (value as i64).to_string();
+        }
+        if (0.0..2.0 * TWO_POW_63).contains(&value) {
+            return (value as u64).to_string();
+        }
+    }
+    if value.fract() == 0.0 || value.abs() < 1e-6 {
+        format!("{value:e}")
+    } else {
+        format!("{value}")
+    }
+}
+
+fn write_string(s: &str, out: &mut impl FnMut(&[u8])) -> Result<()> {
+    out(b"\"");
+    if needs_escaping(s) {
+        let mut escaped = String::with_capacity(s.len() + 8);
+        escape_json_string(s, &mut escaped)?;
+        out(escaped.as_bytes());
+    } else {
+        out(s.as_bytes());
+    }
+    out(b"\"");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::DefaultHasher;
+
+    use super::*;
+    use crate::{dom::Parser, prelude::*};
+
+    fn canonical(json: &str) -> String {
+        let mut parser = Parser::default();
+        let element = parser.parse(&json.to_padded_string()).unwrap();
+        canonical_json(&element).unwrap()
+    }
+
+    #[test]
+    fn canonical_form() {
+        assert_eq!(
+            canonical(r#" { "b" : [1.0, -0.0, 2.5, 1e300, 1E-7], "a" : "x\ty\u0001" } "#),
+            r#"{"a":"x\ty\u0001","b":[1,0,2.5,1e300,1e-7]}"#
+        );
+    }
+
+    #[test]
+    fn digest_ignores_formatting() {
+        let hash = |json: &str| {
+            let mut parser = Parser::default();
+            let element = parser.parse(&json.to_padded_string()).unwrap();
+            let mut hasher = DefaultHasher::new();
+            digest(&element, &mut hasher).unwrap();
+            hasher.finish()
+        };
+        assert_eq!(
+            hash(r#"{"a":1,"b":[true,null]}"#),
+            hash(r#"{ "b": [true, null], "a": 1.0 }"#)
+        );
+        assert_ne!(hash(r#"{"a":1}"#), hash(r#"{"a":"1"}"#));
+    }
+}
@@ -1,9 +1,14 @@
-use std::ptr::NonNull;
+use std::{path::Path, ptr::NonNull, sync::Arc};
 
 use simdjson_sys as ffi;
 
-use super::Element;
-use crate::macros::impl_drop;
+use super::{Element, Parser};
+use crate::{
+    Result,
+    macros::{impl_drop, map_ptr_result},
+    padded_string::load_padded_string,
+    pool,
+};
 
 pub struct Document {
     ptr: NonNull<ffi::SJ_DOM_document>,
@@ -28,9 +33,249 @@ impl Document {
         })
     }
 
+    /// Approximate number of bytes held by the document's tape and string
+    /// buffer.
+    pub fn allocated_bytes(&self) -> usize {
+        unsafe { ffi::SJ_DOM_document_allocated_bytes(self.ptr.as_ptr()) }
+    }
+
     pub fn as_ptr(&self) -> *mut ffi::SJ_DOM_document {
         self.ptr.as_ptr()
     }
 }
 
+/// Copies the tape and string buffer, so the copy stays valid when the
+/// original is parsed into again. Panics if the memory cannot be allocated.
+impl Clone for Document {
+    fn clone(&self) -> Self {
+        let ptr = map_ptr_result!(ffi::SJ_DOM_document_clone(self.ptr.as_ptr()))
+            .expect("out of memory copying a document");
+        Self { ptr }
+    }
+}
+
 impl_drop!(Document, ffi::SJ_DOM_document_free);
+
+/// A parsed document that owns everything its elements point into, so it can
+/// be returned from functions and stored in structs.
+///
+/// The DOM copies strings out of the input while parsing, so only the
+/// document's tape and string buffer are kept: the input and the parser are
+/// free to be dropped or reused right away.
+///
+/// ```ignore
+/// fn load_config(path: &str) -> Result<OwnedDocument> {
+///     OwnedDocument::load(path)
+/// }
+///
+/// let config = load_config("config.json")?;
+/// let port = config.root().at_pointer("/server/port")?.get_uint64()?;
+/// ```
+#[derive(Clone)]
+pub struct OwnedDocument {
+    doc: Document,
+}
+
+// A parsed document is only read through shared references, which simdjson
+// allows from any number of threads.
+unsafe impl Send for OwnedDocument {}
+unsafe impl Sync for OwnedDocument {}
+
+impl OwnedDocument {
+    /// Parses `json`, which needs no padding, with a parser owned by the
+    /// current thread.
+    pub fn parse(json: &str) -> Result<Self> {
+        pool::with_padded(json, |padded_string| {
+            pool::with_parser(|parser| Self::parse_with(parser, padded_string))
+        })
+    }
+
+    /// Parses `padded_string` with `parser`, applying its limits.
+    pub fn parse_with(parser: &Parser, padded_string: &str) -> Result<Self> {
+        let mut doc = Document::default();
+        parser.parse_into_document(&mut doc, padded_string)?;
+        Ok(Self { doc })
+    }
+
+    /// Loads and parses a JSON file. Compressed files are accepted when the
+    /// `gzip` or `zstd` feature is enabled.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let padded_string = load_padded_string(path)?;
+        pool::with_parser(|parser| Self::parse_with(parser, &padded_string))
+    }
+
+    pub fn root(&self) -> Element<'_> {
+        self.doc.root()
+    }
+
+    /// Approximate number of bytes held by the document's tape and string
+    /// buffer.
+    pub fn allocated_bytes(&self) -> usize {
+        self.doc.allocated_bytes()
+    }
+
+    pub fn into_document(self) -> Document {
+        self.doc
+    }
+
+    /// Turns the document into a reference-counted, read-only handle that
+    /// threads can share without re-parsing.
+    pub fn freeze(self) -> FrozenDocument {
+        FrozenDocument(Arc::new(self))
+    }
+}
+
+impl From<Document> for OwnedDocument {
+    fn from(doc: Document) -> Self {
+        Self { doc }
+    }
+}
+
+/// Compared and hashed by content, like [`Element`].
+impl PartialEq for OwnedDocument {
+    fn eq(&self, other: &Self) -> bool {
+        self.root() == other.root()
+    }
+}
+
+impl Eq for OwnedDocument {}
+
+impl PartialOrd for OwnedDocument {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OwnedDocument {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.root().cmp(&other.root())
+    }
+}
+
+impl std::hash::Hash for OwnedDocument {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.root().hash(state)
+    }
+}
+
+impl std::str::FromStr for OwnedDocument {
+    type Err = crate::SimdJsonError;
+
+    fn from_str(json: &str) -> Result<Self> {
+        Self::parse(json)
+    }
+}
+
+/// A parsed document shared read-only between threads; see
+/// [`OwnedDocument::freeze`].
+///
+/// Cloning only bumps a reference count. Each thread calls
+/// [`root`](OwnedDocument::root) on its own clone, and the tape is freed
+/// when the last clone is dropped.
+///
+/// ```ignore
+/// let doc = OwnedDocument::load("catalog.json")?.freeze();
+/// let workers: Vec<_> = (0..4)
+///     .map(|i| {
+///         let doc = doc.clone();
+///         std::thread::spawn(move || doc.root().at_pointer(&format!("/items/{i}"))?.get_uint64())
+///     })
+///     .collect();
+/// ```
+#[derive(Clone)]
+pub struct FrozenDocument(Arc<OwnedDocument>);
+
+impl std::ops::Deref for FrozenDocument {
+    type Target = OwnedDocument;
+
+    fn deref(&self) -> &OwnedDocument {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn parse_config() -> Result<OwnedDocument> {
+        let mut json = String::from(r#"{"server": {"port": 8080}, "name": "x"}"#);
+        let doc = OwnedDocument::parse(&json)?;
+        // The input is not needed any more.
+        json.clear();
+        Ok(doc)
+    }
+
+    #[test]
+    fn owned_document() {
+        let doc = parse_config().unwrap();
+        let handle = std::thread::spawn(move || {
+            let root = doc.root();
+            let port = root
+                .at_pointer("/server/port")
+                .unwrap()
+                .get_uint64()
+                .unwrap();
+            assert_eq!(port, 8080);
+            assert_eq!(root.at_pointer("/name").unwrap().get_string().unwrap(), "x");
+        });
+        handle.join().unwrap();
+
+        let parser = Parser::default();
+        let json = "[1, 2]".to_padded_string();
+        let doc = OwnedDocument::parse_with(&parser, &json).unwrap();
+        drop(parser);
+        assert_eq!(doc.root().get_array().unwrap().size(), 2);
+        assert!("[1,".parse::<OwnedDocument>().is_err());
+        // Documents dedupe by content.
+        let docs: std::collections::HashSet<OwnedDocument> =
+            [r#"{"a":1,"b":2}"#, r#"{"b":2,"a":1.0}"#]
+                .iter()
+                .map(|json| json.parse().unwrap())
+                .collect();
+        assert_eq!(docs.len(), 1);
+    }
+
+    #[test]
+    fn clone_document() {
+        let parser = Parser::default();
+        let mut doc = Document::default();
+        let copy_of_empty = doc.clone();
+        assert_eq!(copy_of_empty.allocated_bytes(), 0);
+        parser
+            .parse_into_document(&mut doc, &r#"{"a": "xyz"}"#.to_padded_string())
+            .unwrap();
+        let copy = doc.clone();
+        parser
+            .parse_into_document(&mut doc, &"[1]".to_padded_string())
+            .unwrap();
+        assert_eq!(
+            copy.root().at_pointer("/a").unwrap().get_string().unwrap(),
+            "xyz"
+        );
+        let owned = OwnedDocument::from(copy);
+        assert!(owned.clone() == owned);
+    }
+
+    #[test]
+    fn frozen_document() {
+        let doc = OwnedDocument::parse("[10, 20, 30, 40]").unwrap().freeze();
+        let sum: u64 = std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..4)
+                .map(|i| {
+                    let doc = doc.clone();
+                    scope.spawn(move || {
+                        let pointer = format!("/{i}");
+                        doc.root()
+                            .at_pointer(&pointer)
+                            .unwrap()
+                            .get_uint64()
+                            .unwrap()
+                    })
+                })
+                .collect();
+            workers.into_iter().map(|w| w.join().unwrap()).sum()
+        });
+        assert_eq!(sum, 100);
+    }
+}
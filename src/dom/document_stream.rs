@@ -2,19 +2,25 @@ use std::{marker::PhantomData, ptr::NonNull};
 
 use simdjson_sys as ffi;
 
-use super::Element;
+use super::{Element, Parser};
 use crate::{
     Result,
     macros::{impl_drop, map_ptr_result},
 };
 
-pub struct DocumentStream {
+/// Documents parsed lazily out of one input, which the stream borrows along
+/// with the parser.
+pub struct DocumentStream<'a> {
     ptr: NonNull<ffi::SJ_DOM_document_stream>,
+    _input: PhantomData<(&'a mut Parser, &'a str)>,
 }
 
-impl DocumentStream {
+impl<'a> DocumentStream<'a> {
     pub fn new(ptr: NonNull<ffi::SJ_DOM_document_stream>) -> Self {
-        Self { ptr }
+        Self {
+            ptr,
+            _input: PhantomData,
+        }
     }
 
     pub fn iter(&self) -> DocumentStreamIter<'_> {
@@ -26,13 +32,13 @@ impl DocumentStream {
     }
 }
 
-impl_drop!(DocumentStream, ffi::SJ_DOM_document_stream_free);
+impl_drop!(DocumentStream<'a>, ffi::SJ_DOM_document_stream_free);
 
 pub struct DocumentStreamIter<'a> {
     begin: NonNull<ffi::SJ_DOM_document_stream_iterator>,
     end: NonNull<ffi::SJ_DOM_document_stream_iterator>,
     running: bool,
-    _parser: PhantomData<&'a DocumentStream>,
+    _parser: PhantomData<&'a DocumentStream<'a>>,
 }
 
 impl<'a> DocumentStreamIter<'a> {
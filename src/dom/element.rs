@@ -2,9 +2,13 @@ use std::{marker::PhantomData, ptr::NonNull};
 
 use simdjson_sys as ffi;
 
-use super::{array::Array, document::Document, object::Object};
+use super::{
+    array::{Array, ArrayIter},
+    document::Document,
+    object::{Object, ObjectIter},
+};
 use crate::{
-    Result,
+    CompiledPointer, Result,
     macros::{impl_drop, map_primitive_result, map_ptr_result},
     utils::string_view_struct_to_str,
 };
@@ -54,19 +58,39 @@ impl<'a> Element<'a> {
         unsafe { ElementType::from(ffi::SJ_DOM_element_type(self.ptr.as_ptr())) }
     }
 
-    pub fn get_array(&self) -> Result<Array<'_>> {
+    pub fn get_array(&self) -> Result<Array<'a>> {
         map_ptr_result!(ffi::SJ_DOM_element_get_array(self.ptr.as_ptr())).map(Array::new)
     }
 
-    pub fn get_object(&self) -> Result<Object<'_>> {
+    pub fn get_object(&self) -> Result<Object<'a>> {
         map_ptr_result!(ffi::SJ_DOM_element_get_object(self.ptr.as_ptr())).map(Object::new)
     }
 
+    /// The fields of an object sorted by key, for deterministic processing.
+    /// The values are handles into the document, not copies. Duplicate keys
+    /// stay in document order.
+    pub fn get_object_sorted(&self) -> Result<Vec<(&'a str, Element<'a>)>> {
+        let mut fields: Vec<_> = self.get_object()?.iter().collect();
+        fields.sort_by_key(|(key, _)| *key);
+        Ok(fields)
+    }
+
     pub fn get_string(&self) -> Result<&'a str> {
         map_primitive_result!(ffi::SJ_DOM_element_get_string(self.ptr.as_ptr()))
             .map(string_view_struct_to_str)
     }
 
+    /// Shortens the string this element is to `len` bytes, in the document
+    /// itself.
+    ///
+    /// # Safety
+    ///
+    /// `len` must be a char boundary of the string, and no reference into
+    /// the string may be used afterwards.
+    pub(crate) unsafe fn truncate_string(&self, len: usize) {
+        unsafe { ffi::SJ_DOM_element_truncate_string(self.ptr.as_ptr(), len) }
+    }
+
     pub fn get_int64(&self) -> Result<i64> {
         map_primitive_result!(ffi::SJ_DOM_element_get_int64(self.ptr.as_ptr()))
     }
@@ -87,6 +111,61 @@ impl<'a> Element<'a> {
         self.get_type() == ElementType::NullValue
     }
 
+    // `Option` counterparts of the getters, for code that treats a value of
+    // the wrong type like a missing one, as `serde_json::Value` does.
+
+    pub fn as_bool(&self) -> Option<bool> {
+        self.get_bool().ok()
+    }
+
+    pub fn as_str(&self) -> Option<&'a str> {
+        self.get_string().ok()
+    }
+
+    /// `None` for floats and for integers that do not fit.
+    pub fn as_i64(&self) -> Option<i64> {
+        self.get_int64().ok()
+    }
+
+    /// `None` for floats and negative integers.
+    pub fn as_u64(&self) -> Option<u64> {
+        self.get_uint64().ok()
+    }
+
+    /// Integers are converted.
+    pub fn as_f64(&self) -> Option<f64> {
+        self.get_double().ok()
+    }
+
+    pub fn as_array(&self) -> Option<Array<'a>> {
+        self.get_array().ok()
+    }
+
+    pub fn as_object(&self) -> Option<Object<'a>> {
+        self.get_object().ok()
+    }
+
+    /// The elements of an array, with `None` keys, or the fields of an
+    /// object, in document order, so a tree walk needs one code path for
+    /// both. Scalars have no children.
+    ///
+    /// ```ignore
+    /// fn count_keys(element: &Element<'_>) -> usize {
+    ///     element
+    ///         .children()
+    ///         .map(|(key, child)| usize::from(key.is_some()) + count_keys(&child))
+    ///         .sum()
+    /// }
+    /// ```
+    pub fn children(&self) -> Children<'a> {
+        let inner = match self.get_type() {
+            ElementType::Array => self.get_array().map(|array| Inner::Array(array.iter())),
+            ElementType::Object => self.get_object().map(|object| Inner::Object(object.iter())),
+            _ => Ok(Inner::None),
+        };
+        Children(inner.unwrap_or(Inner::None))
+    }
+
     pub fn at_pointer(&self, json_pointer: &str) -> Result<Element<'a>> {
         map_ptr_result!(ffi::SJ_DOM_element_at_pointer(
             self.ptr.as_ptr(),
@@ -95,10 +174,99 @@ impl<'a> Element<'a> {
         ))
         .map(Element::new)
     }
+
+    /// Like [`at_pointer`](Self::at_pointer), with a pointer parsed ahead of
+    /// time.
+    pub fn at_compiled_pointer(&self, pointer: &CompiledPointer) -> Result<Element<'a>> {
+        self.at_path(pointer.tokens())
+    }
+
+    /// Resolves every pointer in `pointers` and returns the results in the
+    /// same order, each failing on its own like
+    /// [`at_compiled_pointer`](Self::at_compiled_pointer).
+    ///
+    /// The pointers are visited in sorted order and the elements along the
+    /// previous path are kept, so a prefix shared by many pointers, such as
+    /// `/data/user` in `/data/user/id` and `/data/user/name`, is only
+    /// looked up once. On wide objects, where every key lookup is a scan,
+    /// that beats one `at_pointer` call per pointer.
+    ///
+    /// ```ignore
+    /// let pointers = ["/user/id", "/user/name", "/meta/version"].map(|p| CompiledPointer::new(p).unwrap());
+    /// let [id, name, version] = <[_; 3]>::try_from(root.get_all(&pointers)).unwrap();
+    /// ```
+    pub fn get_all(&self, pointers: &[CompiledPointer]) -> Vec<Result<Element<'a>>> {
+        let mut order: Vec<usize> = (0..pointers.len()).collect();
+        order.sort_by(|&a, &b| pointers[a].keys().cmp(pointers[b].keys()));
+        let mut results: Vec<Option<Result<Element<'a>>>> = pointers.iter().map(|_| None).collect();
+        // The parents resolved for the previous pointer: `path[d]` is at depth
+        // `d + 1` below `self`.
+        let mut path: Vec<Element<'a>> = Vec::new();
+        let mut previous: &[String] = &[];
+        for i in order {
+            let (keys, tokens) = (pointers[i].keys(), pointers[i].tokens());
+            let parents = keys.len().saturating_sub(1);
+            let shared = previous
+                .iter()
+                .zip(&keys[..parents])
+                .take_while(|(a, b)| a == b)
+                .count();
+            path.truncate(shared);
+            previous = keys;
+            let resolved = (path.len()..parents)
+                .try_for_each(|depth| {
+                    let parent = path.last().unwrap_or(self);
+                    let child = parent.at_path(&tokens[depth..=depth])?;
+                    path.push(child);
+                    Ok(())
+                })
+                .and_then(|()| path.last().unwrap_or(self).at_path(&tokens[parents..]));
+            results[i] = Some(resolved);
+        }
+        results.into_iter().flatten().collect()
+    }
+
+    fn at_path(&self, tokens: &[ffi::SJ_path_token]) -> Result<Element<'a>> {
+        map_ptr_result!(ffi::SJ_DOM_element_at_path(
+            self.ptr.as_ptr(),
+            tokens.as_ptr(),
+            tokens.len()
+        ))
+        .map(Element::new)
+    }
 }
 
 impl_drop!(Element<'a>, ffi::SJ_DOM_element_free);
 
+/// Iterator over the children of an element; see [`Element::children`].
+pub struct Children<'a>(Inner<'a>);
+
+enum Inner<'a> {
+    Array(ArrayIter<'a>),
+    Object(ObjectIter<'a>),
+    None,
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = (Option<&'a str>, Element<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            Inner::Array(iter) => iter.next().map(|element| (None, element)),
+            Inner::Object(iter) => iter.next().map(|(key, element)| (Some(key), element)),
+            Inner::None => None,
+        }
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        match &mut self.0 {
+            Inner::Array(iter) => iter.nth(n).map(|element| (None, element)),
+            Inner::Object(iter) => iter.nth(n).map(|(key, element)| (Some(key), element)),
+            Inner::None => None,
+        }
+    }
+}
+
 #[cfg(feature = "serde_impl")]
 impl std::fmt::Display for Element<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -108,3 +276,97 @@ impl std::fmt::Display for Element<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ElementType;
+    use crate::{CompiledPointer, dom::Parser, prelude::*};
+
+    #[test]
+    fn get_all() {
+        let json = r#"{"data": {"user": {"id": 7, "name": "a"}, "tags": ["x", "y"]}, "v": 2}"#
+            .to_padded_string();
+        let mut parser = Parser::default();
+        let root = parser.parse(&json).unwrap();
+        let pointers = [
+            "/v",
+            "/data/user/name",
+            "/data/tags/1",
+            "/data/user/id",
+            "/data/user/missing",
+            "/data/user",
+            "/data/tags/9",
+            "",
+        ]
+        .map(|pointer| CompiledPointer::new(pointer).unwrap());
+        let results = root.get_all(&pointers);
+        assert_eq!(results.len(), pointers.len());
+        assert_eq!(results[0].as_ref().unwrap().get_uint64().unwrap(), 2);
+        assert_eq!(results[1].as_ref().unwrap().get_string().unwrap(), "a");
+        assert_eq!(results[2].as_ref().unwrap().get_string().unwrap(), "y");
+        assert_eq!(results[3].as_ref().unwrap().get_uint64().unwrap(), 7);
+        assert!(results[4].is_err());
+        assert_eq!(results[5].as_ref().unwrap().get_type(), ElementType::Object);
+        assert!(results[6].is_err());
+        assert_eq!(results[7].as_ref().unwrap().get_type(), ElementType::Object);
+        assert!(root.get_all(&[]).is_empty());
+    }
+
+    #[test]
+    fn children() {
+        let json = r#"{"a": [1, {"b": null}], "c": "x"}"#.to_padded_string();
+        let mut parser = Parser::default();
+        let root = parser.parse(&json).unwrap();
+        let keys: Vec<_> = root.children().map(|(key, _)| key).collect();
+        assert_eq!(keys, [Some("a"), Some("c")]);
+        let (_, array) = root.children().next().unwrap();
+        let items: Vec<_> = array
+            .children()
+            .map(|(key, child)| (key, child.get_type()))
+            .collect();
+        assert_eq!(
+            items,
+            [(None, ElementType::Int64), (None, ElementType::Object)]
+        );
+        assert_eq!(array.children().nth(1).unwrap().1.children().count(), 1);
+        let (_, scalar) = root.children().nth(1).unwrap();
+        assert!(scalar.children().next().is_none());
+        let mut children = array.children();
+        while children.next().is_some() {}
+        assert!(children.nth(1).is_none());
+    }
+
+    #[test]
+    fn object_sorted() {
+        let json = r#"{"b": 1, "a": {"z": 0}, "c": 2, "a": 3}"#.to_padded_string();
+        let mut parser = Parser::default();
+        let root = parser.parse(&json).unwrap();
+        let fields = root.get_object_sorted().unwrap();
+        let keys: Vec<_> = fields.iter().map(|(key, _)| *key).collect();
+        assert_eq!(keys, ["a", "a", "b", "c"]);
+        assert_eq!(fields[1].1.get_uint64().unwrap(), 3);
+        assert!(fields[0].1.at_pointer("/z").is_ok());
+        assert!(fields[2].1.get_object_sorted().is_err());
+    }
+
+    #[test]
+    fn as_accessors() {
+        let json =
+            r#"{"s": "x", "n": -1, "f": 1.5, "b": true, "a": [1], "o": {}}"#.to_padded_string();
+        let mut parser = Parser::default();
+        let root = parser.parse(&json).unwrap();
+        let at = |pointer| root.at_pointer(pointer).unwrap();
+
+        assert_eq!(at("/s").as_str(), Some("x"));
+        assert_eq!(at("/s").as_u64(), None);
+        assert_eq!(at("/n").as_i64(), Some(-1));
+        assert_eq!(at("/n").as_u64(), None);
+        assert_eq!(at("/n").as_f64(), Some(-1.0));
+        assert_eq!(at("/f").as_i64(), None);
+        assert_eq!(at("/b").as_bool(), Some(true));
+        assert_eq!(at("/a").as_array().map(|a| a.size()), Some(1));
+        assert!(at("/a").as_object().is_none());
+        assert!(at("/o").as_object().is_some());
+        assert_eq!(root.as_bool(), None);
+    }
+}
@@ -0,0 +1,180 @@
+use super::{Element, ElementType, Rewrite, Transformer};
+use crate::{Result, builder::StringBuilder, pointer::Segment};
+
+/// How [`emit`] lays a document out.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Layout {
+    /// Spaces per nesting level, one value per line; `None` writes everything
+    /// on one line.
+    pub indent: Option<usize>,
+    /// Write object fields sorted by key rather than in document order.
+    pub sort_keys: bool,
+}
+
+impl Layout {
+    pub(crate) fn pretty(indent: usize) -> Self {
+        Self {
+            indent: Some(indent),
+            ..Self::default()
+        }
+    }
+}
+
+/// Writes `element` and its children to `out`.
+pub(crate) fn emit(element: &Element<'_>, layout: Layout, out: &mut StringBuilder) -> Result<()> {
+    Emitter::new(layout, &mut Verbatim).root(element, out)
+}
+
+/// Keeps every key and value as it is.
+struct Verbatim;
+
+impl Transformer for Verbatim {}
+
+/// Writes DOM elements to a [`StringBuilder`], letting a [`Transformer`]
+/// rename keys and replace or remove values along the way.
+pub(crate) struct Emitter<'t, T: ?Sized> {
+    layout: Layout,
+    transformer: &'t mut T,
+    /// A newline followed by enough indentation for the deepest level seen,
+    /// so each line break is a single append.
+    newline: String,
+}
+
+impl<'t, T: Transformer + ?Sized> Emitter<'t, T> {
+    pub(crate) fn new(layout: Layout, transformer: &'t mut T) -> Self {
+        Self {
+            layout,
+            transformer,
+            newline: String::new(),
+        }
+    }
+
+    pub(crate) fn root(&mut self, element: &Element<'_>, out: &mut StringBuilder) -> Result<()> {
+        let mut path = Vec::new();
+        match self.transformer.rewrite(&path, element)? {
+            // There is nothing to remove the root from.
+            Rewrite::Remove => out.append_null(),
+            rewrite => self.value(element, rewrite, &mut path, out)?,
+        }
+        Ok(())
+    }
+
+    fn value<'a>(
+        &mut self,
+        element: &Element<'a>,
+        rewrite: Rewrite,
+        path: &mut Vec<Segment<'a>>,
+        out: &mut StringBuilder,
+    ) -> Result<()> {
+        match rewrite {
+            Rewrite::Keep => {}
+            Rewrite::Remove => unreachable!("removed values are filtered by the caller"),
+            Rewrite::Null => {
+                out.append_null();
+                return Ok(());
+            }
+            Rewrite::String(s) => {
+                out.append_string(&s);
+                return Ok(());
+            }
+            Rewrite::Raw(s) => {
+                out.append_raw(&s);
+                return Ok(());
+            }
+        }
+        match element.get_type() {
+            ElementType::Object => {
+                out.start_object();
+                let mut empty = true;
+                if self.layout.sort_keys {
+                    for (key, child) in element.get_object_sorted()? {
+                        self.field(key, &child, &mut empty, path, out)?;
+                    }
+                } else {
+                    for (key, child) in element.get_object()?.iter() {
+                        self.field(key, &child, &mut empty, path, out)?;
+                    }
+                }
+                self.close(empty, path.len(), out);
+                out.end_object();
+            }
+            ElementType::Array => {
+                out.start_array();
+                let mut empty = true;
+                for (index, child) in element.get_array()?.iter().enumerate() {
+                    path.push(Segment::Index(index));
+                    let rewrite = self.transformer.rewrite(path, &child)?;
+                    if rewrite != Rewrite::Remove {
+                        self.separate(&mut empty, path.len(), out);
+                        self.value(&child, rewrite, path, out)?;
+                    }
+                    path.pop();
+                }
+                self.close(empty, path.len(), out);
+                out.end_array();
+            }
+            ElementType::Int64 => out.append_i64(element.get_int64()?),
+            ElementType::UInt64 => out.append_u64(element.get_uint64()?),
+            ElementType::Double => out.append_f64(element.get_double()?),
+            ElementType::String => out.append_string(element.get_string()?),
+            ElementType::Bool => out.append_bool(element.get_bool()?),
+            ElementType::NullValue => out.append_null(),
+        }
+        Ok(())
+    }
+
+    fn field<'a>(
+        &mut self,
+        key: &'a str,
+        child: &Element<'a>,
+        empty: &mut bool,
+        path: &mut Vec<Segment<'a>>,
+        out: &mut StringBuilder,
+    ) -> Result<()> {
+        path.push(Segment::Key(key));
+        let rewrite = self.transformer.rewrite(path, child)?;
+        if rewrite != Rewrite::Remove {
+            self.separate(empty, path.len(), out);
+            match self.transformer.rename(path, key) {
+                Some(key) => out.append_string(&key),
+                None => out.append_string(key),
+            }
+            out.append_colon();
+            if self.layout.indent.is_some() {
+                out.append_raw(" ");
+            }
+            self.value(child, rewrite, path, out)?;
+        }
+        path.pop();
+        Ok(())
+    }
+
+    /// Starts an item at `depth`, after a comma unless it is the first.
+    fn separate(&mut self, empty: &mut bool, depth: usize, out: &mut StringBuilder) {
+        if !std::mem::take(empty) {
+            out.append_comma();
+        }
+        self.line_break(depth, out);
+    }
+
+    /// Puts the closing bracket of a non-empty container on its own line.
+    fn close(&mut self, empty: bool, depth: usize, out: &mut StringBuilder) {
+        if !empty {
+            self.line_break(depth, out);
+        }
+    }
+
+    fn line_break(&mut self, depth: usize, out: &mut StringBuilder) {
+        let Some(indent) = self.layout.indent else {
+            return;
+        };
+        let len = 1 + depth * indent;
+        if self.newline.is_empty() {
+            self.newline.push('\n');
+        }
+        while self.newline.len() < len {
+            self.newline.push(' ');
+        }
+        out.append_raw(&self.newline[..len]);
+    }
+}
@@ -0,0 +1,501 @@
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+};
+
+use super::{DisplayLimits, Element, ElementType};
+use crate::{Result, pointer::escape_segment, pool};
+
+/// Compares two elements semantically: object key order is ignored and
+/// numbers are compared by value, so `1`, `1.0` and `1e0` are all equal.
+pub fn deep_equal(a: &Element<'_>, b: &Element<'_>) -> Result<bool> {
+    match (a.get_type(), b.get_type()) {
+        (ElementType::NullValue, ElementType::NullValue) => Ok(true),
+        (ElementType::Bool, ElementType::Bool) => Ok(a.get_bool()? == b.get_bool()?),
+        (ElementType::String, ElementType::String) => Ok(a.get_string()? == b.get_string()?),
+        (ElementType::Array, ElementType::Array) => {
+            let (a, b) = (a.get_array()?, b.get_array()?);
+            if a.size() != b.size() {
+                return Ok(false);
+            }
+            for (x, y) in a.iter().zip(b.iter()) {
+                if !deep_equal(&x, &y)? {
+                    return Ok(false);
+                }
+            }
+            Ok(true)
+        }
+        (ElementType::Object, ElementType::Object) => {
+            let (a, b) = (a.get_object()?, b.get_object()?);
+            let mut right: Vec<_> = b.iter().collect();
+            right.sort_by_key(|(key, _)| *key);
+            let mut len = 0;
+            for (key, x) in a.iter() {
+                len += 1;
+                let Ok(i) = right.binary_search_by_key(&key, |(key, _)| *key) else {
+                    return Ok(false);
+                };
+                if !deep_equal(&x, &right[i].1)? {
+                    return Ok(false);
+                }
+            }
+            Ok(len == right.len())
+        }
+        (x, y) => match (number(a, x)?, number(b, y)?) {
+            (Some(x), Some(y)) => Ok(x == y),
+            _ => Ok(false),
+        },
+    }
+}
+
+/// Parses two JSON texts and compares them with [`deep_equal`]. This is what
+/// [`assert_json_matches!`](crate::assert_json_matches) uses.
+pub fn json_matches(a: &str, b: &str) -> Result<bool> {
+    pool::with_padded(a, |a| {
+        pool::with_padded(b, |b| {
+            pool::with_parser(|left| {
+                pool::with_parser(|right| deep_equal(&left.parse(a)?, &right.parse(b)?))
+            })
+        })
+    })
+}
+
+/// How [`differences`] matches the actual document against the expected
+/// one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareMode {
+    /// Both must be equal per [`deep_equal`].
+    Exact,
+    /// The expected document must be contained in the actual one: objects
+    /// may have extra fields and arrays extra trailing elements.
+    Include,
+}
+
+/// Lists where `actual` departs from `expected`, one line per difference,
+/// each starting with the JSON pointer of the value concerned. Empty if they
+/// match. Values are shown truncated, see [`Element::display_truncated`].
+pub fn differences(
+    actual: &Element<'_>,
+    expected: &Element<'_>,
+    mode: CompareMode,
+) -> Result<Vec<String>> {
+    let mut out = Vec::new();
+    diff(actual, expected, mode, &mut String::new(), &mut out)?;
+    Ok(out)
+}
+
+/// Parses two JSON texts and compares them with [`differences`]. This is
+/// what [`assert_json_eq!`](crate::assert_json_eq) and
+/// [`assert_json_include!`](crate::assert_json_include) use.
+pub fn json_differences(actual: &str, expected: &str, mode: CompareMode) -> Result<Vec<String>> {
+    pool::with_padded(actual, |a| {
+        pool::with_padded(expected, |e| {
+            pool::with_parser(|left| {
+                pool::with_parser(|right| differences(&left.parse(a)?, &right.parse(e)?, mode))
+            })
+        })
+    })
+}
+
+fn diff(
+    actual: &Element<'_>,
+    expected: &Element<'_>,
+    mode: CompareMode,
+    path: &mut String,
+    out: &mut Vec<String>,
+) -> Result<()> {
+    let show = |element: &Element<'_>| {
+        element
+            .display_truncated(DisplayLimits::default())
+            .to_string()
+    };
+    let label = |path: &str| {
+        if path.is_empty() {
+            "(root)".to_owned()
+        } else {
+            path.to_owned()
+        }
+    };
+    match (actual.get_type(), expected.get_type()) {
+        (ElementType::Object, ElementType::Object) => {
+            let mut fields: Vec<_> = actual.get_object()?.iter().collect();
+            fields.sort_by_key(|(key, _)| *key);
+            let mut seen = vec![false; fields.len()];
+            for (key, e) in expected.get_object()?.iter() {
+                let len = path.len();
+                push_segment(path, key);
+                match fields.binary_search_by_key(&key, |(key, _)| *key) {
+                    Ok(i) => {
+                        seen[i] = true;
+                        diff(&fields[i].1, &e, mode, path, out)?;
+                    }
+                    Err(_) => out.push(format!("{}: missing, expected {}", label(path), show(&e))),
+                }
+                path.truncate(len);
+            }
+            if mode == CompareMode::Exact {
+                for ((key, a), _) in fields.iter().zip(&seen).filter(|(_, seen)| !**seen) {
+                    let len = path.len();
+                    push_segment(path, key);
+                    out.push(format!("{}: unexpected {}", label(path), show(a)));
+                    path.truncate(len);
+                }
+            }
+        }
+        (ElementType::Array, ElementType::Array) => {
+            let mut actual = actual.get_array()?.iter();
+            let mut expected = expected.get_array()?.iter();
+            for i in 0.. {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&i.to_string());
+                match (actual.next(), expected.next()) {
+                    (Some(a), Some(e)) => diff(&a, &e, mode, path, out)?,
+                    (None, Some(e)) => {
+                        out.push(format!("{}: missing, expected {}", label(path), show(&e)))
+                    }
+                    (Some(a), None) if mode == CompareMode::Exact => {
+                        out.push(format!("{}: unexpected {}", label(path), show(&a)))
+                    }
+                    _ => break,
+                }
+                path.truncate(len);
+            }
+        }
+        _ => {
+            if !deep_equal(actual, expected)? {
+                out.push(format!(
+                    "{}: expected {}, found {}",
+                    label(path),
+                    show(expected),
+                    show(actual)
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn push_segment(path: &mut String, key: &str) {
+    path.push('/');
+    escape_segment(key, path);
+}
+
+/// Elements compare by content, consistently with [`deep_equal`]: values of
+/// different kinds order as null < booleans < numbers < strings < arrays <
+/// objects; numbers by value; strings by code point; arrays element by
+/// element; and objects as their fields sorted by key.
+impl Ord for Element<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (x, y) = (self.get_type(), other.get_type());
+        rank(x).cmp(&rank(y)).then_with(|| match (x, y) {
+            (ElementType::Bool, ElementType::Bool) => {
+                checked(self.get_bool()).cmp(&checked(other.get_bool()))
+            }
+            (ElementType::String, ElementType::String) => {
+                checked(self.get_string()).cmp(checked(other.get_string()))
+            }
+            (ElementType::Array, ElementType::Array) => checked(self.get_array())
+                .iter()
+                .cmp(checked(other.get_array()).iter()),
+            (ElementType::Object, ElementType::Object) => {
+                sorted_fields(self).cmp(&sorted_fields(other))
+            }
+            (ElementType::NullValue, ElementType::NullValue) => Ordering::Equal,
+            _ => checked(number(self, x)).cmp(&checked(number(other, y))),
+        })
+    }
+}
+
+impl PartialOrd for Element<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Element<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Element<'_> {}
+
+/// Hashes the content, so elements that are equal per [`deep_equal`] hash
+/// the same regardless of key order or number spelling.
+impl Hash for Element<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let ty = self.get_type();
+        rank(ty).hash(state);
+        match ty {
+            ElementType::NullValue => {}
+            ElementType::Bool => checked(self.get_bool()).hash(state),
+            ElementType::String => checked(self.get_string()).hash(state),
+            ElementType::Array => {
+                let array = checked(self.get_array());
+                for child in array.iter() {
+                    child.hash(state);
+                }
+                // Marks the end, so nested arrays hash unambiguously.
+                u8::MAX.hash(state);
+            }
+            ElementType::Object => sorted_fields(self).hash(state),
+            _ => checked(number(self, ty)).hash(state),
+        }
+    }
+}
+
+/// Position of each kind of value in the ordering; the number types share
+/// one.
+fn rank(ty: ElementType) -> u8 {
+    match ty {
+        ElementType::NullValue => 0,
+        ElementType::Bool => 1,
+        ElementType::Int64 | ElementType::UInt64 | ElementType::Double => 2,
+        ElementType::String => 3,
+        ElementType::Array => 4,
+        ElementType::Object => 5,
+    }
+}
+
+/// Unwraps a getter whose type `get_type` has already checked.
+fn checked<T>(value: Result<T>) -> T {
+    value.expect("element type was checked")
+}
+
+fn sorted_fields<'a>(element: &Element<'a>) -> Vec<(&'a str, Element<'a>)> {
+    let mut fields: Vec<_> = checked(element.get_object()).iter().collect();
+    fields.sort_by_key(|(key, _)| *key);
+    fields
+}
+
+#[derive(PartialEq)]
+enum Num {
+    Int(i128),
+    Float(f64),
+}
+
+impl Eq for Num {}
+
+impl Ord for Num {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Num::Int(x), Num::Int(y)) => x.cmp(y),
+            (Num::Float(x), Num::Float(y)) => x.total_cmp(y),
+            // Never equal once folded, but the conversion may round, in which
+            // case the integer orders first.
+            (Num::Int(x), Num::Float(y)) => (*x as f64).total_cmp(y).then(Ordering::Less),
+            (Num::Float(_), Num::Int(_)) => other.cmp(self).reverse(),
+        }
+    }
+}
+
+impl PartialOrd for Num {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for Num {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Num::Int(x) => x.hash(state),
+            Num::Float(x) => x.to_bits().hash(state),
+        }
+    }
+}
+
+fn number(element: &Element<'_>, ty: ElementType) -> Result<Option<Num>> {
+    Ok(match ty {
+        ElementType::Int64 => Some(Num::Int(element.get_int64()?.into())),
+        ElementType::UInt64 => Some(Num::Int(element.get_uint64()?.into())),
+        ElementType::Double => {
+            let v = element.get_double()?;
+            // Every double at or above 2^64 in magnitude is out of range for
+            // the integer types, so only smaller ones need to be folded.
+            if v.fract() == 0.0 && v.abs() < 18_446_744_073_709_551_616.0 {
+                Some(Num::Int(v as i128))
+            } else {
+                Some(Num::Float(v))
+            }
+        }
+        _ => None,
+    })
+}
+
+/// Asserts that two JSON texts are equal per [`dom::deep_equal`](crate::dom::deep_equal).
+///
+/// ```ignore
+/// assert_json_matches!(r#"{"a": 1, "b": [2]}"#, r#"{"b": [2.0], "a": 1}"#);
+/// ```
+#[macro_export]
+macro_rules! assert_json_matches {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                let left: &str = ::core::convert::AsRef::as_ref(left);
+                let right: &str = ::core::convert::AsRef::as_ref(right);
+                match $crate::dom::json_matches(left, right) {
+                    Ok(true) => {}
+                    Ok(false) => panic!(
+                        "assertion failed: JSON documents do not match\n  left: {}\n right: {}",
+                        left, right
+                    ),
+                    Err(e) => panic!("assertion failed: invalid JSON: {e}"),
+                }
+            }
+        }
+    };
+}
+
+/// Asserts that two JSON texts are equal per [`dom::deep_equal`](crate::dom::deep_equal),
+/// listing every difference by JSON pointer on failure.
+///
+/// ```ignore
+/// assert_json_eq!(response_body, r#"{"id": 1, "tags": ["a"]}"#);
+/// ```
+#[macro_export]
+macro_rules! assert_json_eq {
+    ($actual:expr, $expected:expr $(,)?) => {
+        $crate::__assert_json!($actual, $expected, $crate::dom::CompareMode::Exact, "equal")
+    };
+}
+
+/// Asserts that the expected JSON is contained in the actual JSON: objects
+/// may have extra fields and arrays extra trailing elements. Differences are
+/// listed by JSON pointer on failure.
+///
+/// ```ignore
+/// assert_json_include!(response_body, r#"{"status": "ok"}"#);
+/// ```
+#[macro_export]
+macro_rules! assert_json_include {
+    ($actual:expr, $expected:expr $(,)?) => {
+        $crate::__assert_json!(
+            $actual,
+            $expected,
+            $crate::dom::CompareMode::Include,
+            "included"
+        )
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __assert_json {
+    ($actual:expr, $expected:expr, $mode:expr, $what:literal) => {
+        match (&$actual, &$expected) {
+            (actual, expected) => {
+                let actual: &str = ::core::convert::AsRef::as_ref(actual);
+                let expected: &str = ::core::convert::AsRef::as_ref(expected);
+                match $crate::dom::json_differences(actual, expected, $mode) {
+                    Ok(differences) if differences.is_empty() => {}
+                    Ok(differences) => panic!(
+                        "assertion failed: JSON not {}\n  {}",
+                        $what,
+                        differences.join("\n  ")
+                    ),
+                    Err(e) => panic!("assertion failed: invalid JSON: {e}"),
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_ignoring_order_and_spelling() {
+        assert!(
+            json_matches(
+                r#"{"a": [1, 2.0], "b": {"c": null}}"#,
+                r#"{"b":{"c":null},"a":[1.0,2]}"#
+            )
+            .unwrap()
+        );
+        assert!(json_matches("-5", "-5e0").unwrap());
+        assert!(json_matches("18446744073709551615", "18446744073709551615").unwrap());
+        assert_json_matches!("[1, 1e2]", "[1.0, 100]");
+    }
+
+    #[test]
+    fn not_equal() {
+        assert!(!json_matches(r#"{"a": 1}"#, r#"{"a": 1, "b": 2}"#).unwrap());
+        assert!(!json_matches(r#"{"a": 1, "b": 2}"#, r#"{"a": 1}"#).unwrap());
+        assert!(!json_matches("[1, 2]", "[2, 1]").unwrap());
+        assert!(!json_matches("1", "\"1\"").unwrap());
+        assert!(!json_matches("1.5", "1").unwrap());
+    }
+
+    #[test]
+    fn pointer_labelled_differences() {
+        let actual = r#"{"a": [1, 2, 3], "b": {"c": "x", "d/e": true}, "extra": null}"#;
+        let expected = r#"{"a": [1, 5], "b": {"c": "x", "d/e": false, "f": 1}}"#;
+        assert_eq!(
+            json_differences(actual, expected, CompareMode::Exact).unwrap(),
+            [
+                "/a/1: expected 5, found 2",
+                "/a/2: unexpected 3",
+                "/b/d~1e: expected false, found true",
+                "/b/f: missing, expected 1",
+                "/extra: unexpected null",
+            ]
+        );
+        assert_eq!(
+            json_differences(actual, expected, CompareMode::Include)
+                .unwrap()
+                .len(),
+            3
+        );
+        assert_json_include!(actual, r#"{"a": [1.0, 2], "b": {}}"#);
+        assert_json_eq!(actual, actual);
+        assert_eq!(
+            json_differences("1", "[1]", CompareMode::Exact).unwrap(),
+            ["(root): expected [1], found 1"]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "/a: expected 2, found 1")]
+    fn assert_json_eq_panics() {
+        assert_json_eq!(r#"{"a": 1}"#, r#"{"a": 2}"#);
+    }
+
+    #[test]
+    fn hash_and_order() {
+        use std::collections::{BTreeSet, HashSet};
+
+        use crate::{dom::Parser, prelude::*};
+
+        let json =
+            r#"[{"a": 1, "b": [2]}, {"b": [2.0], "a": 1e0}, null, "x", [], 2.5, 2, true, {}]"#
+                .to_padded_string();
+        let mut parser = Parser::default();
+        let array = parser.parse(&json).unwrap().get_array().unwrap();
+        let elements: Vec<_> = array.iter().collect();
+        assert!(elements[0] == elements[1]);
+        assert_eq!(elements.iter().collect::<HashSet<_>>().len(), 8);
+
+        let sorted: Vec<String> = elements
+            .iter()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|e| crate::dom::canonical_json(e).unwrap())
+            .collect();
+        assert_eq!(
+            sorted,
+            [
+                "null",
+                "true",
+                "2",
+                "2.5",
+                r#""x""#,
+                "[]",
+                "{}",
+                r#"{"a":1,"b":[2]}"#
+            ]
+        );
+    }
+}
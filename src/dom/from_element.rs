@@ -0,0 +1,71 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::{Element, ElementType};
+use crate::Result;
+
+/// Conversion from a DOM element into a Rust value, for
+/// [`Array::as_vec_of`](super::Array::as_vec_of) and
+/// [`Object::to_hashmap_of`](super::Object::to_hashmap_of).
+///
+/// A lighter alternative to serde for flat data: the implementations call
+/// the element's typed getters directly and share their errors.
+pub trait FromElement<'a>: Sized {
+    fn from_element(element: &Element<'a>) -> Result<Self>;
+}
+
+macro_rules! impl_from_element {
+    ($($ty:ty => $getter:ident),* $(,)?) => {
+        $(
+            impl<'a> FromElement<'a> for $ty {
+                fn from_element(element: &Element<'a>) -> Result<Self> {
+                    element.$getter()
+                }
+            }
+        )*
+    };
+}
+
+impl_from_element! {
+    bool => get_bool,
+    i64 => get_int64,
+    u64 => get_uint64,
+    f64 => get_double,
+    &'a str => get_string,
+}
+
+impl<'a> FromElement<'a> for String {
+    fn from_element(element: &Element<'a>) -> Result<Self> {
+        element.get_string().map(str::to_owned)
+    }
+}
+
+/// `null` becomes `None`.
+impl<'a, T: FromElement<'a>> FromElement<'a> for Option<T> {
+    fn from_element(element: &Element<'a>) -> Result<Self> {
+        match element.get_type() {
+            ElementType::NullValue => Ok(None),
+            _ => T::from_element(element).map(Some),
+        }
+    }
+}
+
+/// Nested arrays, through [`Array::as_vec_of`](super::Array::as_vec_of).
+impl<'a, T: FromElement<'a>> FromElement<'a> for Vec<T> {
+    fn from_element(element: &Element<'a>) -> Result<Self> {
+        element.get_array()?.as_vec_of()
+    }
+}
+
+/// Nested objects, through [`Object::to_hashmap_of`](super::Object::to_hashmap_of).
+impl<'a, T: FromElement<'a>> FromElement<'a> for HashMap<String, T> {
+    fn from_element(element: &Element<'a>) -> Result<Self> {
+        element.get_object()?.to_hashmap_of()
+    }
+}
+
+/// Nested objects, through [`Object::to_btreemap_of`](super::Object::to_btreemap_of).
+impl<'a, T: FromElement<'a>> FromElement<'a> for BTreeMap<String, T> {
+    fn from_element(element: &Element<'a>) -> Result<Self> {
+        element.get_object()?.to_btreemap_of()
+    }
+}
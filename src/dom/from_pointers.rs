@@ -0,0 +1,60 @@
+use super::Element;
+use crate::Result;
+
+/// Builds a value out of elements found at fixed JSON pointers, for plucking
+/// scattered fields out of deep documents.
+///
+/// Usually derived with the `derive` feature. Each field is read from the
+/// pointer given with `#[simdjson(pointer = "...")]`, or from `/<field name>`,
+/// and converted with [`FromElement`](super::FromElement):
+///
+/// ```ignore
+/// #[derive(FromJsonPointers)]
+/// struct Request<'a> {
+///     #[simdjson(pointer = "/meta/request_id")]
+///     id: &'a str,
+///     #[simdjson(pointer = "/payload/items/0/price")]
+///     first_price: Option<f64>,
+///     status: u64,
+/// }
+///
+/// let request = Request::from_pointers(&root)?;
+/// ```
+///
+/// A field that fails is reported as [`SimdJsonError::AtPointer`](crate::SimdJsonError::AtPointer).
+/// `Option` fields are `None` when their pointer does not resolve.
+pub trait FromJsonPointers<'a>: Sized {
+    fn from_pointers(root: &Element<'a>) -> Result<Self>;
+}
+
+/// Used by the derive macro.
+#[doc(hidden)]
+pub mod __private {
+    use super::super::{Element, FromElement};
+    use crate::{Result, SimdJsonError};
+
+    pub fn field<'a, T: FromElement<'a>>(root: &Element<'a>, pointer: &str) -> Result<T> {
+        root.at_pointer(pointer)
+            .and_then(|element| T::from_element(&element))
+            .map_err(|source| at(pointer, source))
+    }
+
+    pub fn optional_field<'a, T: FromElement<'a>>(
+        root: &Element<'a>,
+        pointer: &str,
+    ) -> Result<Option<T>> {
+        match root.at_pointer(pointer) {
+            Ok(element) => {
+                Option::<T>::from_element(&element).map_err(|source| at(pointer, source))
+            }
+            Err(_) => Ok(None),
+        }
+    }
+
+    fn at(pointer: &str, source: SimdJsonError) -> SimdJsonError {
+        SimdJsonError::AtPointer {
+            pointer: pointer.to_owned(),
+            source: Box::new(source),
+        }
+    }
+}
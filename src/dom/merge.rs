@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use super::{
+    Element, ElementType,
+    emit::{Layout, emit},
+};
+use crate::{Result, builder::StringBuilder};
+
+/// How [`merge`] combines two arrays found at the same place.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// The overlay's array replaces the base's.
+    #[default]
+    Replace,
+    /// The overlay's elements are appended to the base's.
+    Concat,
+    /// Elements at the same index are merged; the longer array's extra
+    /// elements are kept.
+    MergeByIndex,
+}
+
+/// Deep-merges `overlay` onto `base` and returns the result as compact JSON,
+/// the way layered configuration files are combined.
+///
+/// Objects are merged key by key: keys keep the base's order and keys only
+/// in the overlay are appended. Arrays are combined according to `strategy`.
+/// Anything else, `null` included, is taken from the overlay.
+pub fn merge(base: &Element<'_>, overlay: &Element<'_>, strategy: MergeStrategy) -> Result<String> {
+    let mut out = StringBuilder::new();
+    merge_into(base, overlay, strategy, &mut out)?;
+    out.into_string()
+}
+
+fn merge_into(
+    base: &Element<'_>,
+    overlay: &Element<'_>,
+    strategy: MergeStrategy,
+    out: &mut StringBuilder,
+) -> Result<()> {
+    match (base.get_type(), overlay.get_type()) {
+        (ElementType::Object, ElementType::Object) => {
+            let overlay: Vec<_> = overlay.get_object()?.iter().collect();
+            // The last of duplicate keys wins, as in most parsers.
+            let index: HashMap<&str, usize> = overlay
+                .iter()
+                .enumerate()
+                .map(|(i, (key, _))| (*key, i))
+                .collect();
+            let mut merged = vec![false; overlay.len()];
+            out.start_object();
+            let mut first = true;
+            for (key, child) in base.get_object()?.iter() {
+                out.append_field_header(key, std::mem::take(&mut first));
+                match index.get(key) {
+                    Some(&i) => {
+                        merged[i] = true;
+                        merge_into(&child, &overlay[i].1, strategy, out)?;
+                    }
+                    None => emit(&child, Layout::default(), out)?,
+                }
+            }
+            for (i, (key, child)) in overlay.iter().enumerate() {
+                if !merged[i] && index[key] == i {
+                    out.append_field_header(key, std::mem::take(&mut first));
+                    emit(child, Layout::default(), out)?;
+                }
+            }
+            out.end_object();
+        }
+        (ElementType::Array, ElementType::Array) if strategy != MergeStrategy::Replace => {
+            let base = base.get_array()?;
+            let overlay = overlay.get_array()?;
+            out.start_array();
+            let mut first = true;
+            let mut separate = |out: &mut StringBuilder| {
+                if !std::mem::take(&mut first) {
+                    out.append_comma();
+                }
+            };
+            if strategy == MergeStrategy::Concat {
+                for child in base.iter().chain(overlay.iter()) {
+                    separate(out);
+                    emit(&child, Layout::default(), out)?;
+                }
+            } else {
+                let mut base = base.iter();
+                let mut overlay = overlay.iter();
+                loop {
+                    match (base.next(), overlay.next()) {
+                        (Some(b), Some(o)) => {
+                            separate(out);
+                            merge_into(&b, &o, strategy, out)?;
+                        }
+                        (Some(child), None) | (None, Some(child)) => {
+                            separate(out);
+                            emit(&child, Layout::default(), out)?;
+                        }
+                        (None, None) => break,
+                    }
+                }
+            }
+            out.end_array();
+        }
+        _ => emit(overlay, Layout::default(), out)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dom::Parser, prelude::*};
+
+    #[test]
+    fn merge_strategies() {
+        let mut base_parser = Parser::default();
+        let mut overlay_parser = Parser::default();
+        let base = r#"{"a": 1, "b": {"c": [1, {"x": 1}], "d": "keep"}, "e": [1]}"#;
+        let overlay = r#"{"b": {"c": [2], "n": null}, "a": {"new": true}, "f": "added"}"#;
+        let base = base_parser.parse(&base.to_padded_string()).unwrap();
+        let overlay = overlay_parser.parse(&overlay.to_padded_string()).unwrap();
+
+        let merged = |strategy| merge(&base, &overlay, strategy).unwrap();
+        assert_eq!(
+            merged(MergeStrategy::Replace),
+            r#"{"a":{"new":true},"b":{"c":[2],"d":"keep","n":null},"e":[1],"f":"added"}"#
+        );
+        assert_eq!(
+            merged(MergeStrategy::Concat),
+            r#"{"a":{"new":true},"b":{"c":[1,{"x":1},2],"d":"keep","n":null},"e":[1],"f":"added"}"#
+        );
+        assert_eq!(
+            merged(MergeStrategy::MergeByIndex),
+            r#"{"a":{"new":true},"b":{"c":[2,{"x":1}],"d":"keep","n":null},"e":[1],"f":"added"}"#
+        );
+    }
+}
@@ -1,13 +1,57 @@
 mod array;
+mod batch;
+mod canonical;
 mod document;
 mod document_stream;
 mod element;
+#[cfg(feature = "builder")]
+pub(crate) mod emit;
+mod equal;
+mod from_element;
+mod from_pointers;
+#[cfg(feature = "builder")]
+mod merge;
 mod object;
 mod parser;
+#[cfg(feature = "builder")]
+mod redact;
+#[cfg(feature = "builder")]
+mod rename;
+#[cfg(feature = "builder")]
+mod serialize;
+#[cfg(feature = "builder")]
+mod subset;
+mod truncated;
+mod visit;
 
 pub use array::{Array, ArrayIter};
-pub use document::Document;
+pub use batch::BatchBuilder;
+#[cfg(feature = "sha2")]
+pub use canonical::sha256;
+pub use canonical::{canonical_json, digest};
+pub use document::{Document, FrozenDocument, OwnedDocument};
 pub use document_stream::{DocumentStream, DocumentStreamIter};
-pub use element::{Element, ElementType};
+pub use element::{Children, Element, ElementType};
+pub use equal::{CompareMode, deep_equal, differences, json_differences, json_matches};
+pub use from_element::FromElement;
+#[doc(hidden)]
+pub use from_pointers::__private;
+pub use from_pointers::FromJsonPointers;
+#[cfg(feature = "builder")]
+pub use merge::{MergeStrategy, merge};
 pub use object::{Object, ObjectIter};
 pub use parser::Parser;
+#[cfg(feature = "builder")]
+pub use redact::redact;
+#[cfg(feature = "builder")]
+pub use rename::{KeyCase, rename_keys};
+#[cfg(feature = "builder")]
+pub use serialize::SerializerOptions;
+#[cfg(feature = "derive")]
+pub use simdjson_derive::FromJsonPointers;
+#[cfg(feature = "builder")]
+pub use subset::{KeyFilter, serialize_subset};
+pub use truncated::{DisplayLimits, TruncatedDisplay};
+pub use visit::{JsonVisitor, Walk, walk};
+#[cfg(feature = "builder")]
+pub use visit::{Rewrite, Transformer, transform};
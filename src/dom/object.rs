@@ -1,9 +1,16 @@
-use std::{marker::PhantomData, ptr::NonNull};
+use std::{
+    collections::{BTreeMap, HashMap},
+    marker::PhantomData,
+    ptr::NonNull,
+};
 
 use simdjson_sys as ffi;
 
-use super::{Element, document::Document};
-use crate::{macros::impl_drop, utils::string_view_struct_to_str};
+use super::{Element, FromElement, array::SIZE_SATURATED, document::Document};
+use crate::{
+    Result, SimdJsonError, macros::impl_drop, pointer::escape_segment,
+    utils::string_view_struct_to_str,
+};
 
 pub struct Object<'a> {
     ptr: NonNull<ffi::SJ_DOM_object>,
@@ -18,11 +25,62 @@ impl<'a> Object<'a> {
         }
     }
 
-    pub fn iter(&self) -> ObjectIter<'_> {
+    pub fn iter(&self) -> ObjectIter<'a> {
         let begin = unsafe { NonNull::new_unchecked(ffi::SJ_DOM_object_begin(self.ptr.as_ptr())) };
         let end = unsafe { NonNull::new_unchecked(ffi::SJ_DOM_object_end(self.ptr.as_ptr())) };
         ObjectIter::new(begin, end)
     }
+
+    /// The number of fields, saturating like [`Array::size`](super::Array::size).
+    pub fn size(&self) -> usize {
+        unsafe { ffi::SJ_DOM_object_size(self.ptr.as_ptr()) }
+    }
+
+    /// Converts every value with [`FromElement`] into a map allocated once,
+    /// for string-to-primitive payloads such as metrics and attributes.
+    /// Stops at the first value that does not convert, reporting its key in
+    /// a [`SimdJsonError::AtPointer`]. A repeated key keeps its last value.
+    ///
+    /// ```ignore
+    /// let gauges = root.at_key("gauges")?.get_object()?.to_hashmap_of::<f64>()?;
+    /// ```
+    pub fn to_hashmap_of<T: FromElement<'a>>(&self) -> Result<HashMap<String, T>> {
+        let mut out = HashMap::with_capacity(self.exact_size().unwrap_or(0));
+        self.convert_into(|key, value| {
+            out.insert(key.to_owned(), value);
+        })?;
+        Ok(out)
+    }
+
+    /// Like [`to_hashmap_of`](Self::to_hashmap_of), sorted by key.
+    pub fn to_btreemap_of<T: FromElement<'a>>(&self) -> Result<BTreeMap<String, T>> {
+        let mut out = BTreeMap::new();
+        self.convert_into(|key, value| {
+            out.insert(key.to_owned(), value);
+        })?;
+        Ok(out)
+    }
+
+    fn convert_into<T: FromElement<'a>>(&self, mut insert: impl FnMut(&str, T)) -> Result<()> {
+        for (key, element) in self.iter() {
+            let value = T::from_element(&element).map_err(|e| {
+                let mut pointer = String::from("/");
+                escape_segment(key, &mut pointer);
+                SimdJsonError::AtPointer {
+                    pointer,
+                    source: Box::new(e),
+                }
+            })?;
+            insert(key, value);
+        }
+        Ok(())
+    }
+
+    /// The number of fields, or `None` for objects too large for `size` to
+    /// report.
+    pub(crate) fn exact_size(&self) -> Option<usize> {
+        Some(self.size()).filter(|&len| len != SIZE_SATURATED)
+    }
 }
 
 pub struct ObjectIter<'a> {
@@ -56,9 +114,38 @@ impl<'a> ObjectIter<'a> {
         unsafe { ffi::SJ_DOM_object_iterator_step(self.begin.as_ptr()) }
     }
 
+    /// Shortens the key of the field returned last to `len` bytes, in the
+    /// document itself.
+    ///
+    /// # Safety
+    ///
+    /// `len` must be a char boundary of the key, and no reference into the
+    /// key may be used afterwards.
+    pub(crate) unsafe fn truncate_key(&mut self, len: usize) {
+        unsafe { ffi::SJ_DOM_object_iterator_truncate_key(self.begin.as_ptr(), len) }
+    }
+
     pub fn not_equal(&self) -> bool {
         unsafe { ffi::SJ_DOM_object_iterator_not_equal(self.begin.as_ptr(), self.end.as_ptr()) }
     }
+
+    /// Skips up to `n` of the fields [`next`](Iterator::next) would return
+    /// and returns how many it skipped; see [`ArrayIter::advance`](super::ArrayIter::advance).
+    pub fn advance(&mut self, n: usize) -> usize {
+        if n == 0 {
+            return 0;
+        }
+        // The element returned last is still current until stepped over.
+        let current = usize::from(std::mem::take(&mut self.running));
+        let stepped = unsafe {
+            ffi::SJ_DOM_object_iterator_advance(
+                self.begin.as_ptr(),
+                self.end.as_ptr(),
+                n.saturating_add(current),
+            )
+        };
+        stepped - current
+    }
 }
 
 impl<'a> Drop for ObjectIter<'a> {
@@ -78,13 +165,67 @@ impl<'a> Iterator for ObjectIter<'a> {
             self.step();
         }
 
-        if self.not_equal() {
-            self.running = true;
-            Some(self.get())
-        } else {
-            None
-        }
+        self.running = self.not_equal();
+        self.running.then(|| self.get())
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.advance(n);
+        self.next()
+    }
+
+    fn count(mut self) -> usize {
+        self.advance(usize::MAX)
     }
 }
 
 impl_drop!(Object<'a>, ffi::SJ_DOM_object_free);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dom::Parser, prelude::*};
+
+    #[test]
+    fn exhausted() {
+        let mut parser = Parser::default();
+        let json = r#"{"a": 1, "b": 2}"#.to_padded_string();
+        let root = parser.parse(&json).unwrap().get_object().unwrap();
+        let mut fields = root.iter();
+        assert_eq!(fields.nth(1).unwrap().0, "b");
+        assert!(fields.next().is_none());
+        assert_eq!(fields.advance(2), 0);
+        assert!(fields.nth(1).is_none());
+        assert_eq!(fields.count(), 0);
+    }
+
+    #[test]
+    fn to_maps() {
+        let mut parser = Parser::default();
+        let json = r#"{"cpu": 0.5, "mem": 12, "disk": -1e3, "a/b": "x"}"#.to_padded_string();
+        let root = parser.parse(&json).unwrap().get_object().unwrap();
+        assert_eq!(root.size(), 4);
+        assert!(matches!(
+            root.to_hashmap_of::<f64>(),
+            Err(SimdJsonError::AtPointer { pointer, .. }) if pointer == "/a~1b"
+        ));
+        let map = root.to_hashmap_of::<Option<f64>>();
+        assert!(map.is_err());
+
+        let json = r#"{"cpu": 0.5, "mem": 12, "disk": -1e3}"#.to_padded_string();
+        let mut parser = Parser::default();
+        let root = parser.parse(&json).unwrap().get_object().unwrap();
+        let map = root.to_hashmap_of::<f64>().unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["mem"], 12.0);
+        let sorted = root.to_btreemap_of::<f64>().unwrap();
+        assert_eq!(sorted.keys().collect::<Vec<_>>(), ["cpu", "disk", "mem"]);
+
+        let json = r#"{"a": {"x": [1]}, "b": {}}"#.to_padded_string();
+        let mut parser = Parser::default();
+        let root = parser.parse(&json).unwrap().get_object().unwrap();
+        let nested = root.to_btreemap_of::<HashMap<String, Vec<u64>>>().unwrap();
+        assert_eq!(nested["a"]["x"], [1]);
+        assert!(nested["b"].is_empty());
+    }
+}
@@ -1,18 +1,33 @@
-use std::ptr::NonNull;
+use std::{cell::RefCell, ops::Range, path::Path, ptr::NonNull};
 
 use ffi::DEFAULT_BATCH_SIZE;
 use simdjson_sys as ffi;
 
-use super::{document::Document, document_stream::DocumentStream, element::Element};
+use super::{OwnedDocument, document::Document, document_stream::DocumentStream, element::Element};
 use crate::{
-    Result,
-    macros::{impl_drop, map_ptr_result},
+    BomPolicy, ParseLimits, ParseStats, Result, SimdJsonError,
+    macros::{impl_drop, map_primitive_result, map_ptr_result},
+    padded_string::load_padded_string,
+    trace::ParseSpan,
+    validate::Validator,
 };
 
 pub struct Parser {
     ptr: NonNull<ffi::SJ_DOM_parser>,
+    limits: ParseLimits,
+    bom: BomPolicy,
+    /// Input length of the document held by the parser, if the last parse
+    /// succeeded.
+    parsed_len: Option<usize>,
+    // `parse_into_document` only borrows the parser.
+    stats: RefCell<ParseStats>,
+    validator: Option<Validator>,
 }
 
+// A simdjson parser has no thread affinity; it only must not be used from two
+// threads at once, which `&mut self` already rules out.
+unsafe impl Send for Parser {}
+
 impl Default for Parser {
     fn default() -> Self {
         Self::new(ffi::SIMDJSON_MAXSIZE_BYTES)
@@ -22,16 +37,348 @@ impl Default for Parser {
 impl Parser {
     pub fn new(max_capacity: usize) -> Self {
         let ptr = unsafe { NonNull::new_unchecked(ffi::SJ_DOM_parser_new(max_capacity)) };
-        Self { ptr }
+        Self {
+            ptr,
+            limits: ParseLimits {
+                max_document_bytes: max_capacity,
+                ..ParseLimits::default()
+            },
+            bom: BomPolicy::default(),
+            parsed_len: None,
+            stats: RefCell::default(),
+            validator: None,
+        }
+    }
+
+    /// Creates a parser that rejects documents exceeding `limits`.
+    ///
+    /// [`Parser::parse_many`] and [`Parser::parse_batch`] only apply the size
+    /// and depth limits.
+    pub fn with_limits(limits: ParseLimits) -> Self {
+        let ptr = unsafe {
+            NonNull::new_unchecked(ffi::SJ_DOM_parser_new_with_max_depth(
+                limits.max_document_bytes,
+                limits.simdjson_max_depth(),
+            ))
+        };
+        Self {
+            ptr,
+            limits,
+            bom: BomPolicy::default(),
+            parsed_len: None,
+            stats: RefCell::default(),
+            validator: None,
+        }
+    }
+
+    pub fn limits(&self) -> &ParseLimits {
+        &self.limits
+    }
+
+    pub fn bom_policy(&self) -> BomPolicy {
+        self.bom
+    }
+
+    /// Sets what [`Parser::parse`], [`Parser::load`],
+    /// [`Parser::parse_many`] and [`Parser::document_boundaries`] do with a
+    /// UTF-8 byte order mark. It is skipped by default.
+    pub fn set_bom_policy(&mut self, policy: BomPolicy) {
+        self.bom = policy;
     }
 
+    /// Largest document in bytes the parser can handle without growing its
+    /// buffers. Starts at 0 and grows on demand up to `max_capacity`.
+    pub fn capacity(&self) -> usize {
+        unsafe { ffi::SJ_DOM_parser_capacity(self.ptr.as_ptr()) }
+    }
+
+    /// Approximate number of bytes held by the parser's internal buffers:
+    /// the stage 1 indexes, the depth stacks and the document's tape and
+    /// string buffer. Allocator overhead is not included.
+    pub fn allocated_bytes(&self) -> usize {
+        unsafe { ffi::SJ_DOM_parser_allocated_bytes(self.ptr.as_ptr()) }
+    }
+
+    /// Counters over every [`Parser::parse`] and
+    /// [`Parser::parse_into_document`] call so far, including those made by
+    /// [`Parser::load`]. Documents read through [`Parser::parse_many`] or
+    /// checked with [`Parser::validate`] are not counted.
+    pub fn stats(&self) -> ParseStats {
+        self.stats.borrow().clone()
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats.take();
+    }
+
+    /// Parses `padded_string` into the parser's own document. The element
+    /// borrows the parser, so the next parse replaces it; see
+    /// [`parse_owned`](Self::parse_owned) to keep several documents.
     pub fn parse(&mut self, padded_string: &str) -> Result<Element<'_>> {
-        map_ptr_result!(ffi::SJ_DOM_parser_parse(
-            self.ptr.as_ptr(),
-            padded_string.as_ptr().cast(),
-            padded_string.len()
-        ))
-        .map(Element::new)
+        let ptr = self.ptr;
+        let capacity = || unsafe { ffi::SJ_DOM_parser_capacity(ptr.as_ptr()) };
+        let span = ParseSpan::enter("dom::Parser::parse", padded_string.len(), capacity);
+        // simdjson skips a byte order mark itself.
+        let result = self.bom.check(padded_string).and_then(|()| {
+            map_ptr_result!(ffi::SJ_DOM_parser_parse(
+                ptr.as_ptr(),
+                padded_string.as_ptr().cast(),
+                padded_string.len()
+            ))
+            .map(Element::new)
+        });
+        span.finish(&result, capacity);
+        let result = self.check_limits(result);
+        self.stats
+            .borrow_mut()
+            .record(padded_string.len(), &result, capacity());
+        self.parsed_len = result.is_ok().then_some(padded_string.len());
+        result
+    }
+
+    /// Parses `bytes` in whatever encoding they arrived in: UTF-8 with or
+    /// without a byte order mark, UTF-16 in either byte order, or the
+    /// charset named in `content_type`, such as
+    /// `application/json; charset=utf-16le`. Input that is not plain UTF-8
+    /// is transcoded into a padded buffer first; see
+    /// [`charset::to_padded_utf8`](crate::charset::to_padded_utf8).
+    ///
+    /// ```ignore
+    /// let content_type = headers.get("content-type").and_then(|v| v.to_str().ok());
+    /// let root = parser.parse_auto(&body, content_type)?;
+    /// ```
+    pub fn parse_auto(&mut self, bytes: &[u8], content_type: Option<&str>) -> Result<Element<'_>> {
+        let json = crate::charset::to_padded_utf8(bytes, content_type)?;
+        self.parse(&json)
+    }
+
+    /// Parses `padded_string` into a document of its own, which does not
+    /// borrow the parser. The parser only lends its reusable stage 1 buffers,
+    /// so one parser can produce any number of documents that are alive at
+    /// once, alongside [`parse`](Self::parse).
+    ///
+    /// ```ignore
+    /// let users = parser.parse_owned(&users_json)?;
+    /// let orders = parser.parse_owned(&orders_json)?;
+    /// join(users.root(), orders.root());
+    /// ```
+    pub fn parse_owned(&self, padded_string: &str) -> Result<OwnedDocument> {
+        OwnedDocument::parse_with(self, padded_string)
+    }
+
+    /// Copies the document of the last successful [`Parser::parse`] out of
+    /// the parser, so it can be kept while the parser moves on to the next
+    /// input. The copy is sized for that document, however large the
+    /// parser's buffers have grown.
+    ///
+    /// Fails with [`SimdJsonError::Uninitialized`] if the last parse failed
+    /// or the parser was used for [`Parser::parse_many`] since.
+    ///
+    /// ```ignore
+    /// let first = parser.parse(&a)?.get_uint64()?;
+    /// let kept = parser.snapshot()?;
+    /// parser.parse(&b)?;
+    /// assert_eq!(kept.root().get_uint64()?, first);
+    /// ```
+    pub fn snapshot(&self) -> Result<OwnedDocument> {
+        let len = self.parsed_len.ok_or(SimdJsonError::Uninitialized)?;
+        map_ptr_result!(ffi::SJ_DOM_parser_copy_document(self.ptr.as_ptr(), len))
+            .map(|ptr| Document::new(ptr).into())
+    }
+
+    /// Loads and parses a JSON file. Compressed files are accepted when the
+    /// `gzip` or `zstd` feature is enabled.
+    pub fn load<P: AsRef<Path>>(&mut self, path: P) -> Result<Element<'_>> {
+        let padded_string = load_padded_string(path)?;
+        // The DOM copies everything it needs out of the input, so the buffer
+        // can be dropped once parsing is done.
+        self.parse(&padded_string)
+    }
+
+    /// Checks that `padded_string` is well-formed JSON and valid UTF-8
+    /// without building a document: an ondemand parser, created on first use
+    /// and reused after, reads every value through. The document of the last
+    /// [`parse`](Self::parse) is left alone. Only the size and depth limits
+    /// apply.
+    pub fn validate(&mut self, padded_string: &str) -> Result<()> {
+        self.bom.check(padded_string)?;
+        let limits = &self.limits;
+        self.validator
+            .get_or_insert_with(|| {
+                Validator::new(limits.max_document_bytes, limits.simdjson_max_depth())
+            })
+            .validate(padded_string)
+    }
+
+    /// Runs only simdjson's stage 1 (the SIMD structural scan) over
+    /// `padded_string` and returns the byte offsets of every structural
+    /// character: brackets, braces, colons, commas and the first byte of each
+    /// scalar. Nothing is parsed and only UTF-8 and basic string structure are
+    /// validated.
+    ///
+    /// This is an experimental escape hatch for building custom stage-2 logic;
+    /// the exact set of indexes follows simdjson and may change with it.
+    pub fn stage1(&mut self, padded_string: &str) -> Result<&[u32]> {
+        let result = unsafe {
+            ffi::SJ_DOM_parser_stage1(
+                self.ptr.as_ptr(),
+                padded_string.as_ptr().cast(),
+                padded_string.len(),
+            )
+        };
+        structural_indexes(result)
+    }
+
+    /// [`stage1`](Self::stage1) over a window that may end partway through a
+    /// document: only the documents complete within it are indexed, and
+    /// none being complete is a [`SimdJsonError::Capacity`] error.
+    fn stage1_partial(&mut self, window: &str) -> Result<&[u32]> {
+        let result = unsafe {
+            ffi::SJ_DOM_parser_stage1_partial(
+                self.ptr.as_ptr(),
+                window.as_ptr().cast(),
+                window.len(),
+            )
+        };
+        structural_indexes(result)
+    }
+
+    /// Byte ranges of the top-level documents in a buffer of concatenated
+    /// JSON, such as NDJSON or whitespace-separated values, found from the
+    /// [`stage1`](Self::stage1) indexes without parsing the documents.
+    ///
+    /// Meant for sharding large inputs across workers before parsing. Only
+    /// brackets are matched, so a malformed document may still be reported
+    /// as a range; it fails once it is parsed. Unbalanced brackets are
+    /// errors. The ranges are offsets into `padded_string`, byte order mark
+    /// included.
+    pub fn document_boundaries(&mut self, padded_string: &str) -> Result<Vec<Range<usize>>> {
+        let json = self.bom.apply(padded_string)?;
+        let bom_len = padded_string.len() - json.len();
+        let bytes = json.as_bytes();
+        if bytes.iter().all(u8::is_ascii_whitespace) {
+            return Ok(Vec::new());
+        }
+        let indexes = self.stage1(json)?;
+        Ok(find_documents(bytes, indexes, usize::MAX)?
+            .into_iter()
+            .map(|range| range.start + bom_len..range.end + bom_len)
+            .collect())
+    }
+
+    /// Number of top-level documents in a buffer of concatenated JSON; see
+    /// [`document_boundaries`](Self::document_boundaries).
+    pub fn count_documents(&mut self, padded_string: &str) -> Result<usize> {
+        self.document_boundaries(padded_string).map(|b| b.len())
+    }
+
+    /// Byte offsets of the opening quotes of every object key equal to `key`,
+    /// at any depth and in any of the documents in `padded_string`, found
+    /// from the [`stage1`](Self::stage1) indexes without parsing.
+    ///
+    /// Keys are compared after unescaping, so `"user\u005fid"` matches
+    /// `user_id`, while the same text inside a string value never does. This
+    /// makes a cheap pre-filter for inputs that are mostly skipped. Offsets
+    /// count the byte order mark, as in
+    /// [`document_boundaries`](Self::document_boundaries).
+    ///
+    /// ```ignore
+    /// if parser.find_key_positions(&line, "user_id")?.is_empty() {
+    ///     continue;
+    /// }
+    /// ```
+    pub fn find_key_positions(&mut self, padded_string: &str, key: &str) -> Result<Vec<usize>> {
+        let json = self.bom.apply(padded_string)?;
+        let bom_len = padded_string.len() - json.len();
+        let bytes = json.as_bytes();
+        if bytes.iter().all(u8::is_ascii_whitespace) {
+            return Ok(Vec::new());
+        }
+        // Unescaping only reads the kernel stage 1 set up, not its indexes.
+        let parser = self.ptr;
+        let indexes = self.stage1(json)?;
+        let mut positions = Vec::new();
+        let mut unescaped = Vec::new();
+        for pair in indexes.windows(2) {
+            let (start, colon) = (pair[0] as usize, pair[1] as usize);
+            if bytes[start] != b'"' || bytes[colon] != b':' {
+                continue;
+            }
+            // Only whitespace separates the closing quote from the colon.
+            let Some(end) = bytes[start + 1..colon].iter().rposition(|&b| b == b'"') else {
+                continue;
+            };
+            let raw = &json[start + 1..start + 1 + end];
+            let found = if raw.contains('\\') {
+                unescape(parser, &bytes[start + 1..], raw.len(), &mut unescaped)
+                    .is_some_and(|raw| raw == key.as_bytes())
+            } else {
+                raw == key
+            };
+            if found {
+                positions.push(start + bom_len);
+            }
+        }
+        Ok(positions)
+    }
+
+    /// Parses the first document in a buffer of back-to-back JSON values and
+    /// returns it with the number of bytes read, up to the end of that
+    /// document. Parsing `&padded_string[consumed..]` next continues with the
+    /// following one, which lets callers frame streams that have no newlines
+    /// between values.
+    ///
+    /// The document's end is found as in
+    /// [`document_boundaries`](Self::document_boundaries), scanning little
+    /// more than the document itself, so framing a whole buffer this way
+    /// stays linear in its size. An input holding only whitespace is an
+    /// [`SimdJsonError::Empty`] error.
+    ///
+    /// ```ignore
+    /// let json = r#"{"a": 1}[2]"#.to_padded_string();
+    /// let (first, consumed) = parser.parse_prefix(&json)?;
+    /// assert_eq!(consumed, 8);
+    /// ```
+    pub fn parse_prefix(&mut self, padded_string: &str) -> Result<(Element<'_>, usize)> {
+        let json = self.bom.apply(padded_string)?;
+        let bom_len = padded_string.len() - json.len();
+        if json.bytes().all(|b| b.is_ascii_whitespace()) {
+            return Err(SimdJsonError::Empty);
+        }
+        let consumed = self.first_document_end(json)? + bom_len;
+        // The rest of the buffer pads the prefix.
+        let element = self.parse(&padded_string[..consumed])?;
+        Ok((element, consumed))
+    }
+
+    /// End of the first document of `json`. Stage 1 only scans a window
+    /// after the document's start, doubled until the document fits, so
+    /// framing a buffer document by document stays linear in its size.
+    fn first_document_end(&mut self, json: &str) -> Result<usize> {
+        let mut window = PREFIX_WINDOW;
+        loop {
+            window = window.min(json.len());
+            while !json.is_char_boundary(window) {
+                window += 1;
+            }
+            if window == json.len() {
+                let indexes = self.stage1(json)?;
+                let first = find_documents(json.as_bytes(), indexes, 1)?;
+                return Ok(first.first().ok_or(SimdJsonError::Empty)?.end);
+            }
+            let bytes = &json.as_bytes()[..window];
+            // A scalar running up to the end of the window may go on past it.
+            let end = self
+                .stage1_partial(&json[..window])
+                .and_then(|indexes| find_documents(bytes, indexes, 1))
+                .ok()
+                .and_then(|first| first.first().map(|range| range.end))
+                .filter(|&end| end < window);
+            if let Some(end) = end {
+                return Ok(end);
+            }
+            window = window.saturating_mul(2);
+        }
     }
 
     pub fn parse_into_document<'d>(
@@ -39,36 +386,152 @@ impl Parser {
         doc: &'d mut Document,
         padded_string: &str,
     ) -> Result<Element<'d>> {
-        map_ptr_result!(ffi::SJ_DOM_parser_parse_into_document(
-            self.ptr.as_ptr(),
-            doc.as_ptr(),
-            padded_string.as_ptr().cast(),
-            padded_string.len()
-        ))
-        .map(Element::new)
+        let capacity = || self.capacity();
+        let span = ParseSpan::enter(
+            "dom::Parser::parse_into_document",
+            padded_string.len(),
+            capacity,
+        );
+        let result = self.bom.check(padded_string).and_then(|()| {
+            map_ptr_result!(ffi::SJ_DOM_parser_parse_into_document(
+                self.ptr.as_ptr(),
+                doc.as_ptr(),
+                padded_string.as_ptr().cast(),
+                padded_string.len()
+            ))
+            .map(Element::new)
+        });
+        span.finish(&result, capacity);
+        let result = self.check_limits(result);
+        self.stats
+            .borrow_mut()
+            .record(padded_string.len(), &result, capacity());
+        result
     }
 
-    pub fn parse_many(&mut self, padded_string: &str) -> Result<DocumentStream> {
+    fn check_limits<'e>(&self, element: Result<Element<'e>>) -> Result<Element<'e>> {
+        let element = element?;
+        if self.limits.checks_contents() {
+            self.limits.check_element(&element)?;
+        }
+        Ok(element)
+    }
+
+    /// Parses the concatenated or newline-delimited documents of
+    /// `padded_string` one by one while the stream is iterated.
+    ///
+    /// Of the parser's [`ParseLimits`], only the document size and depth are
+    /// enforced: the string, key and array limits are not checked on the
+    /// streamed documents.
+    pub fn parse_many<'a>(&'a mut self, padded_string: &'a str) -> Result<DocumentStream<'a>> {
         self.parse_batch(padded_string, DEFAULT_BATCH_SIZE)
     }
 
-    pub fn parse_batch(
-        &mut self,
-        padded_string: &str,
+    pub fn parse_batch<'a>(
+        &'a mut self,
+        padded_string: &'a str,
         batch_size: usize,
-    ) -> Result<DocumentStream> {
-        map_ptr_result!(ffi::SJ_DOM_parser_parse_many(
-            self.ptr.as_ptr(),
-            padded_string.as_ptr().cast(),
-            padded_string.len(),
-            batch_size
-        ))
-        .map(DocumentStream::new)
+    ) -> Result<DocumentStream<'a>> {
+        // Documents are parsed lazily while the stream is iterated, into the
+        // parser's own document; the span only covers setting up the stream.
+        self.parsed_len = None;
+        let capacity = || self.capacity();
+        let span = ParseSpan::enter("dom::Parser::parse_many", padded_string.len(), capacity);
+        let result = self.bom.check(padded_string).and_then(|()| {
+            map_ptr_result!(ffi::SJ_DOM_parser_parse_many(
+                self.ptr.as_ptr(),
+                padded_string.as_ptr().cast(),
+                padded_string.len(),
+                batch_size
+            ))
+            .map(DocumentStream::new)
+        });
+        span.finish(&result, capacity);
+        result
     }
 }
 
 impl_drop!(Parser, ffi::SJ_DOM_parser_free);
 
+/// Bytes [`Parser::parse_prefix`] scans first for the end of a document.
+const PREFIX_WINDOW: usize = 4096;
+
+fn structural_indexes<'a>(result: ffi::SJ_structural_indexes_result) -> Result<&'a [u32]> {
+    if result.error != 0 {
+        return Err(result.error.into());
+    }
+    Ok(unsafe { std::slice::from_raw_parts(result.data, result.len) })
+}
+
+/// Byte ranges of the first `limit` top-level documents in `bytes`, from its
+/// stage 1 `indexes`. The brackets after the last one are not checked.
+fn find_documents(bytes: &[u8], indexes: &[u32], limit: usize) -> Result<Vec<Range<usize>>> {
+    let mut boundaries = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, &index) in indexes.iter().enumerate() {
+        let index = index as usize;
+        match (bytes[index], depth) {
+            (b'{' | b'[', 0) => {
+                start = index;
+                depth = 1;
+            }
+            (b'{' | b'[', _) => depth += 1,
+            (b'}' | b']', 0) => return Err(SimdJsonError::TapeError),
+            (b'}' | b']', _) => {
+                depth -= 1;
+                if depth == 0 {
+                    boundaries.push(start..index + 1);
+                    if boundaries.len() == limit {
+                        return Ok(boundaries);
+                    }
+                }
+            }
+            (b',' | b':', 0) => return Err(SimdJsonError::TapeError),
+            (_, 0) => {
+                // A scalar document runs up to the next structural
+                // character, less the whitespace before it.
+                let next = indexes.get(i + 1).map_or(bytes.len(), |&n| n as usize);
+                let len = bytes[index..next]
+                    .iter()
+                    .rposition(|b| !b.is_ascii_whitespace())
+                    .map_or(0, |last| last + 1);
+                boundaries.push(index..index + len);
+                if boundaries.len() == limit {
+                    return Ok(boundaries);
+                }
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return Err(SimdJsonError::IncompleteArrayOrObject);
+    }
+    Ok(boundaries)
+}
+
+/// Unescapes the string whose contents start at `src` and span `len` bytes
+/// into `out` with simdjson's string parser, or `None` if an escape is
+/// malformed. `src` must lie in a padded string and end at a closing quote.
+fn unescape<'b>(
+    parser: NonNull<ffi::SJ_DOM_parser>,
+    src: &[u8],
+    len: usize,
+    out: &'b mut Vec<u8>,
+) -> Option<&'b [u8]> {
+    out.clear();
+    out.reserve(len + ffi::SIMDJSON_PADDING);
+    let written = map_primitive_result!(ffi::SJ_DOM_parser_unescape_string(
+        parser.as_ptr(),
+        src.as_ptr().cast(),
+        out.as_mut_ptr().cast()
+    ))
+    .ok()?;
+    // simdjson wrote `written` bytes, never more than the escaped text.
+    unsafe { out.set_len(written) };
+    Some(out)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -82,6 +545,248 @@ mod tests {
         assert_eq!(elem.get_uint64().unwrap(), 1);
     }
 
+    #[test]
+    fn capacity_grows() {
+        let mut parser = Parser::default();
+        assert_eq!(parser.capacity(), 0);
+        parser.parse(&"[1, 2, 3]".to_padded_string()).unwrap();
+        assert!(parser.capacity() >= 9);
+    }
+
+    #[test]
+    fn stats() {
+        let mut parser = Parser::default();
+        let json = "[1, 2, 3]".to_padded_string();
+        parser.parse(&json).unwrap();
+        parser.parse(&json).unwrap();
+        assert!(parser.parse(&"[1,".to_padded_string()).is_err());
+        let stats = parser.stats();
+        assert_eq!(stats.documents, 2);
+        assert_eq!(stats.bytes, 2 * json.len() as u64);
+        assert_eq!(stats.error_count(), 1);
+        assert_eq!(stats.peak_capacity, parser.capacity());
+        parser.reset_stats();
+        assert_eq!(parser.stats(), ParseStats::default());
+    }
+
+    #[test]
+    fn allocated_bytes_are_reused() {
+        let mut parser = Parser::default();
+        assert_eq!(parser.allocated_bytes(), 0);
+        let json = r#"{"a": [1, 2, {"b": null}]}"#.to_padded_string();
+        let root = parser.parse(&json).unwrap();
+        for _ in 0..100 {
+            root.at_pointer("/a/2/b").unwrap();
+        }
+        drop(root);
+        let allocated = parser.allocated_bytes();
+        assert!(allocated > json.len());
+        for _ in 0..100 {
+            parser.validate(&json).unwrap();
+        }
+        assert_eq!(parser.allocated_bytes(), allocated);
+    }
+
+    #[test]
+    fn validate() {
+        let mut parser = Parser::default();
+        assert!(parser.validate(&"[1, 2, 3]".to_padded_string()).is_ok());
+        assert!(parser.validate(&"[1, 2,".to_padded_string()).is_err());
+    }
+
+    #[test]
+    fn stage1() {
+        let mut parser = Parser::default();
+        let indexes = parser
+            .stage1(&r#"{"a": [1, true]}"#.to_padded_string())
+            .unwrap();
+        assert_eq!(indexes, [0, 1, 4, 6, 7, 8, 10, 14, 15]);
+    }
+
+    #[test]
+    fn document_boundaries() {
+        let mut parser = Parser::default();
+        let json = "{\"a\": [1]} 12  \"x y\"\n[[], {}]\ntrue".to_padded_string();
+        let ranges = parser.document_boundaries(&json).unwrap();
+        let docs: Vec<_> = ranges.into_iter().map(|r| &json[r]).collect();
+        assert_eq!(docs, ["{\"a\": [1]}", "12", "\"x y\"", "[[], {}]", "true"]);
+        assert_eq!(parser.count_documents(&"".to_padded_string()).unwrap(), 0);
+        assert!(
+            parser
+                .count_documents(&"[1] [2".to_padded_string())
+                .is_err()
+        );
+        assert!(parser.count_documents(&"1, 2".to_padded_string()).is_err());
+    }
+
+    #[test]
+    fn find_key_positions() {
+        let mut parser = Parser::default();
+        let json = r#"{"id": 1, "user_id": "id", "a": {"user\u005fid" : 2}, "b": ["user_id"]}
+{"user_id": null} {"x": "user_id"}"#
+            .to_padded_string();
+        assert_eq!(
+            parser.find_key_positions(&json, "user_id").unwrap(),
+            [10, 33, 73]
+        );
+        assert_eq!(parser.find_key_positions(&json, "id").unwrap(), [1]);
+        assert_eq!(
+            parser
+                .find_key_positions(&r#"{"\ud83d\ude00": 1}"#.to_padded_string(), "\u{1F600}")
+                .unwrap(),
+            [1]
+        );
+        // A malformed escape matches nothing.
+        let json = r#"{"a\q": 1, "b\"c": 2}"#.to_padded_string();
+        assert!(parser.find_key_positions(&json, "aq").unwrap().is_empty());
+        assert_eq!(parser.find_key_positions(&json, "b\"c").unwrap(), [11]);
+        assert!(
+            parser
+                .find_key_positions(&"".to_padded_string(), "a")
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn parse_prefix() {
+        let json = "{\"a\": [1]}[2]  \"x\" 12\n".to_padded_string();
+        let mut parser = Parser::default();
+        let mut rest = &json[..];
+        let mut docs = Vec::new();
+        while !rest.trim().is_empty() {
+            let (element, consumed) = parser.parse_prefix(rest).unwrap();
+            docs.push(crate::dom::canonical_json(&element).unwrap());
+            rest = &rest[consumed..];
+        }
+        assert_eq!(docs, ["{\"a\":[1]}", "[2]", "\"x\"", "12"]);
+
+        // Documents straddling the first window, strings cut by it included.
+        let big = format!("[\"{}\"]", "é".repeat(3000));
+        let json = format!("{big} 7 {big}{}", "\"s\" ".repeat(2000)).into_padded_string();
+        let mut rest = &json[..];
+        let mut count = 0;
+        while !rest.trim().is_empty() {
+            let (element, consumed) = parser.parse_prefix(rest).unwrap();
+            if count == 1 {
+                assert_eq!(element.get_uint64().unwrap(), 7);
+            }
+            rest = &rest[consumed..];
+            count += 1;
+        }
+        assert_eq!(count, 2003);
+
+        let (element, consumed) = parser.parse_prefix(&"[1] [2".to_padded_string()).unwrap();
+        assert_eq!((element.get_array().unwrap().size(), consumed), (1, 3));
+        drop(element);
+        assert!(matches!(
+            parser.parse_prefix(&" \n".to_padded_string()),
+            Err(SimdJsonError::Empty)
+        ));
+        assert!(
+            parser
+                .parse_prefix(&"[1, tru] 2".to_padded_string())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn snapshot() {
+        let mut parser = Parser::default();
+        assert!(matches!(
+            parser.snapshot(),
+            Err(SimdJsonError::Uninitialized)
+        ));
+        let big = format!("[{}\"s\"]", "1,".repeat(10_000)).into_padded_string();
+        parser.parse(&big).unwrap();
+        parser
+            .parse(&r#"{"name": "first", "n": [1, 2]}"#.to_padded_string())
+            .unwrap();
+        let kept = parser.snapshot().unwrap();
+        assert!(kept.allocated_bytes() < parser.allocated_bytes() / 10);
+        parser
+            .parse(&r#"{"name": "second"}"#.to_padded_string())
+            .unwrap();
+        assert_eq!(
+            kept.root()
+                .at_pointer("/name")
+                .unwrap()
+                .get_string()
+                .unwrap(),
+            "first"
+        );
+        assert_eq!(
+            kept.root()
+                .at_pointer("/n/1")
+                .unwrap()
+                .get_uint64()
+                .unwrap(),
+            2
+        );
+        assert!(parser.parse(&"[".to_padded_string()).is_err());
+        assert!(parser.snapshot().is_err());
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        let json = "\u{FEFF}[1] 2".to_padded_string();
+        let mut parser = Parser::default();
+        let ranges = parser.document_boundaries(&json).unwrap();
+        assert_eq!(ranges, [3..6, 7..8]);
+        assert_eq!(parser.parse_many(&json).unwrap().iter().count(), 2);
+        assert_eq!(
+            parser
+                .parse(&"\u{FEFF} [1]".to_padded_string())
+                .unwrap()
+                .get_array()
+                .unwrap()
+                .size(),
+            1
+        );
+
+        parser.set_bom_policy(BomPolicy::Reject);
+        assert!(matches!(
+            parser.parse(&json),
+            Err(SimdJsonError::ByteOrderMark)
+        ));
+        assert!(parser.document_boundaries(&json).is_err());
+        assert!(parser.parse_many(&json).is_err());
+        assert!(parser.parse(&"[1]".to_padded_string()).is_ok());
+        assert_eq!(parser.stats().errors["ByteOrderMark"], 1);
+    }
+
+    #[test]
+    fn parse_owned() {
+        let mut parser = Parser::default();
+        let docs: Vec<_> = ["[1]", "{\"a\": 2}", "3"]
+            .iter()
+            .map(|json| parser.parse_owned(&json.to_padded_string()).unwrap())
+            .collect();
+        let root = parser.parse(&"4".to_padded_string()).unwrap();
+        assert_eq!(root.get_uint64().unwrap(), 4);
+        assert_eq!(
+            docs[0]
+                .root()
+                .at_pointer("/0")
+                .unwrap()
+                .get_uint64()
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            docs[1]
+                .root()
+                .at_pointer("/a")
+                .unwrap()
+                .get_uint64()
+                .unwrap(),
+            2
+        );
+        assert_eq!(docs[2].root().get_uint64().unwrap(), 3);
+        drop(root);
+        assert!(parser.parse_owned(&"[".to_padded_string()).is_err());
+    }
+
     #[test]
     fn parse_into_document() {
         let ps = "[1,2,3]".to_padded_string();
@@ -0,0 +1,100 @@
+use super::{Element, Rewrite, Transformer, transform};
+use crate::{
+    Result,
+    builder::StringBuilder,
+    pointer::{Segment, array_index, parse_pointer},
+};
+
+const REDACTED: &str = "[REDACTED]";
+
+/// Re-serializes `element` compactly with the values `patterns` select
+/// replaced by `"[REDACTED]"`, so payloads can be logged without leaking
+/// secrets.
+///
+/// A pattern starting with `/` is a JSON pointer, in which `*` matches any
+/// key or index; anything else is a key name, matched at any depth. Objects
+/// and arrays that match are replaced whole.
+///
+/// ```ignore
+/// let safe = redact(&root, &["password", "/cards/*/number"])?;
+/// ```
+pub fn redact(element: &Element<'_>, patterns: &[&str]) -> Result<String> {
+    let patterns = patterns
+        .iter()
+        .map(|pattern| {
+            if pattern.starts_with('/') {
+                parse_pointer(pattern).map(Pattern::Pointer)
+            } else {
+                Ok(Pattern::Key(pattern))
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut out = StringBuilder::new();
+    transform(element, &mut Redactor { patterns }, &mut out)?;
+    out.into_string()
+}
+
+enum Pattern<'p> {
+    Key(&'p str),
+    Pointer(Vec<String>),
+}
+
+impl Pattern<'_> {
+    fn matches(&self, path: &[Segment<'_>]) -> bool {
+        match self {
+            Pattern::Key(key) => path.last() == Some(&Segment::Key(key)),
+            Pattern::Pointer(tokens) => {
+                tokens.len() == path.len()
+                    && tokens.iter().zip(path).all(|(token, segment)| {
+                        token == "*"
+                            || match segment {
+                                Segment::Key(key) => key == token,
+                                Segment::Index(index) => array_index(token) == Some(*index),
+                            }
+                    })
+            }
+        }
+    }
+}
+
+struct Redactor<'p> {
+    patterns: Vec<Pattern<'p>>,
+}
+
+impl Transformer for Redactor<'_> {
+    fn rewrite(&mut self, path: &[Segment<'_>], _element: &Element<'_>) -> Result<Rewrite> {
+        if self.patterns.iter().any(|p| p.matches(path)) {
+            Ok(Rewrite::String(REDACTED.to_owned()))
+        } else {
+            Ok(Rewrite::Keep)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SimdJsonError, dom::Parser, prelude::*};
+
+    #[test]
+    fn redact_values() {
+        let json =
+            r#"{"user": {"name": "a", "password": "x"}, "cards": [{"number": "1", "exp": 2}],
+            "token": {"a": 1}, "password": 3}"#
+                .to_padded_string();
+        let mut parser = Parser::default();
+        let root = parser.parse(&json).unwrap();
+        assert_eq!(
+            redact(&root, &["password", "/cards/*/number", "/token"]).unwrap(),
+            r#"{"user":{"name":"a","password":"[REDACTED]"},"cards":[{"number":"[REDACTED]","exp":2}],"token":"[REDACTED]","password":"[REDACTED]"}"#
+        );
+        assert_eq!(
+            redact(&root, &["/cards/0"]).unwrap(),
+            r#"{"user":{"name":"a","password":"x"},"cards":["[REDACTED]"],"token":{"a":1},"password":3}"#
+        );
+        assert!(matches!(
+            redact(&root, &["/a~2"]),
+            Err(SimdJsonError::InvalidJsonPointer)
+        ));
+    }
+}
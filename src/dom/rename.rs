@@ -0,0 +1,136 @@
+use super::{Element, Transformer, transform};
+use crate::{Result, builder::StringBuilder, pointer::Segment};
+
+/// Naming convention for [`rename_keys`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyCase {
+    /// `user_id`, as in Rust.
+    Snake,
+    /// `userId`, as in JavaScript.
+    Camel,
+}
+
+impl KeyCase {
+    /// Converts `key`, or returns `None` if it already follows the
+    /// convention.
+    pub fn convert(self, key: &str) -> Option<String> {
+        let converted = match self {
+            KeyCase::Snake => to_snake(key),
+            KeyCase::Camel => to_camel(key),
+        };
+        (converted != key).then_some(converted)
+    }
+}
+
+/// Re-serializes `element` compactly with every object key, at any depth,
+/// converted to `case`. Values are copied as they are.
+///
+/// Acronyms are kept together: `userID` and `HTTPServer` become `user_id`
+/// and `http_server`. Leading underscores are kept, so `_id` stays as it is
+/// either way.
+pub fn rename_keys(element: &Element<'_>, case: KeyCase) -> Result<String> {
+    let mut out = StringBuilder::new();
+    transform(element, &mut Renamer(case), &mut out)?;
+    out.into_string()
+}
+
+struct Renamer(KeyCase);
+
+impl Transformer for Renamer {
+    fn rename(&mut self, _path: &[Segment<'_>], key: &str) -> Option<String> {
+        self.0.convert(key)
+    }
+}
+
+fn to_snake(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    let mut out = String::with_capacity(key.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() {
+            let prev = i.checked_sub(1).map(|i| chars[i]);
+            let next = chars.get(i + 1);
+            let starts_word = match prev {
+                None | Some('_') => false,
+                Some(prev) if prev.is_uppercase() => next.is_some_and(|c| c.is_lowercase()),
+                Some(_) => true,
+            };
+            if starts_word {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn to_camel(key: &str) -> String {
+    let mut out = String::with_capacity(key.len());
+    let mut upper_next = false;
+    for c in key.chars() {
+        if c == '_' && !out.trim_start_matches('_').is_empty() {
+            upper_next = true;
+        } else if upper_next {
+            out.extend(c.to_uppercase());
+            upper_next = false;
+        } else {
+            out.push(c);
+        }
+    }
+    if upper_next {
+        out.push('_');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dom::Parser, prelude::*};
+
+    #[test]
+    fn convert_keys() {
+        for (camel, snake) in [
+            ("userId", "user_id"),
+            ("userID", "user_id"),
+            ("HTTPServer", "http_server"),
+            ("a1B2", "a1_b2"),
+            ("_id", "_id"),
+            ("plain", "plain"),
+        ] {
+            assert_eq!(to_snake(camel), snake, "{camel}");
+        }
+        for (snake, camel) in [
+            ("user_id", "userId"),
+            ("_private_key", "_privateKey"),
+            ("a__b", "aB"),
+            ("trailing_", "trailing_"),
+            ("plain", "plain"),
+        ] {
+            assert_eq!(to_camel(snake), camel, "{snake}");
+        }
+        assert_eq!(KeyCase::Camel.convert("userId"), None);
+    }
+
+    #[test]
+    fn rename_document_keys() {
+        let json =
+            r#"{"userId": 1, "lastLogin": {"ipAddress": "x", "tags": [{"tagName": "a_b"}]}}"#
+                .to_padded_string();
+        let mut parser = Parser::default();
+        let root = parser.parse(&json).unwrap();
+        let snake = rename_keys(&root, KeyCase::Snake).unwrap();
+        assert_eq!(
+            snake,
+            r#"{"user_id":1,"last_login":{"ip_address":"x","tags":[{"tag_name":"a_b"}]}}"#
+        );
+        drop(root);
+        let snake = snake.into_padded_string();
+        let root = parser.parse(&snake).unwrap();
+        assert_eq!(
+            rename_keys(&root, KeyCase::Camel).unwrap(),
+            r#"{"userId":1,"lastLogin":{"ipAddress":"x","tags":[{"tagName":"a_b"}]}}"#
+        );
+    }
+}
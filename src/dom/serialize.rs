@@ -0,0 +1,99 @@
+use super::{
+    Element, canonical_json,
+    emit::{Layout, emit},
+};
+use crate::{Result, builder::StringBuilder};
+
+/// How [`Element::serialize_with`] writes a subtree. The default is compact,
+/// with fields in document order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SerializerOptions {
+    /// Spaces per nesting level, one value per line; `None` writes everything
+    /// on one line.
+    pub indent: Option<usize>,
+    /// Write object fields sorted by key.
+    pub sort_keys: bool,
+    /// Write the [`canonical_json`] form, ignoring the other options.
+    pub canonical: bool,
+}
+
+impl SerializerOptions {
+    pub fn compact() -> Self {
+        Self::default()
+    }
+
+    /// Indented by two spaces.
+    pub fn pretty() -> Self {
+        Self {
+            indent: Some(2),
+            ..Self::default()
+        }
+    }
+
+    pub fn canonical() -> Self {
+        Self {
+            canonical: true,
+            ..Self::default()
+        }
+    }
+}
+
+impl Element<'_> {
+    /// Re-serializes this element and its children with `options`.
+    ///
+    /// ```ignore
+    /// let user = root.at_pointer("/data/user")?;
+    /// println!("{}", user.serialize_with(&SerializerOptions::pretty())?);
+    /// ```
+    pub fn serialize_with(&self, options: &SerializerOptions) -> Result<String> {
+        if options.canonical {
+            return canonical_json(self);
+        }
+        let layout = Layout {
+            indent: options.indent,
+            sort_keys: options.sort_keys,
+        };
+        let mut out = StringBuilder::new();
+        emit(self, layout, &mut out)?;
+        out.into_string()
+    }
+
+    /// Writes this element compactly to `out`.
+    #[cfg(all(feature = "tracing", feature = "serde_impl"))]
+    pub(crate) fn append_to(&self, out: &mut StringBuilder) -> Result<()> {
+        emit(self, Layout::default(), out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dom::Parser, prelude::*};
+
+    #[test]
+    fn serialize_with() {
+        let json = r#"{"z": 1, "a": {"y": [1.5, "x"], "b": {}, "c": []}}"#.to_padded_string();
+        let mut parser = Parser::default();
+        let root = parser.parse(&json).unwrap();
+        let inner = root.at_pointer("/a").unwrap();
+
+        assert_eq!(
+            root.serialize_with(&SerializerOptions::compact()).unwrap(),
+            r#"{"z":1,"a":{"y":[1.5,"x"],"b":{},"c":[]}}"#
+        );
+        assert_eq!(
+            inner
+                .serialize_with(&SerializerOptions {
+                    sort_keys: true,
+                    ..SerializerOptions::pretty()
+                })
+                .unwrap(),
+            "{\n  \"b\": {},\n  \"c\": [],\n  \"y\": [\n    1.5,\n    \"x\"\n  ]\n}"
+        );
+        assert_eq!(
+            root.serialize_with(&SerializerOptions::canonical())
+                .unwrap(),
+            r#"{"a":{"b":{},"c":[],"y":[1.5,"x"]},"z":1}"#
+        );
+    }
+}
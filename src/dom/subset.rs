@@ -0,0 +1,75 @@
+use super::{
+    Element,
+    emit::{Layout, emit},
+};
+use crate::{Result, builder::StringBuilder};
+
+/// Which fields [`serialize_subset`] keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyFilter<'k> {
+    /// Only these keys.
+    Allow(&'k [&'k str]),
+    /// Every key but these.
+    Deny(&'k [&'k str]),
+}
+
+impl KeyFilter<'_> {
+    fn keeps(&self, key: &str) -> bool {
+        match self {
+            KeyFilter::Allow(keys) => keys.contains(&key),
+            KeyFilter::Deny(keys) => !keys.contains(&key),
+        }
+    }
+}
+
+/// Re-serializes the object `element` compactly with only the top-level
+/// fields `filter` keeps, in their original order, to trim verbose payloads
+/// before forwarding them. The kept values are copied whole.
+///
+/// Fails with [`SimdJsonError::IncorrectType`](crate::SimdJsonError) if
+/// `element` is not an object.
+///
+/// ```ignore
+/// let trimmed = serialize_subset(&root, KeyFilter::Allow(&["id", "status"]))?;
+/// ```
+pub fn serialize_subset(element: &Element<'_>, filter: KeyFilter<'_>) -> Result<String> {
+    let object = element.get_object()?;
+    let mut out = StringBuilder::new();
+    out.start_object();
+    let mut first = true;
+    for (key, child) in object.iter().filter(|(key, _)| filter.keeps(key)) {
+        out.append_field_header(key, std::mem::take(&mut first));
+        emit(&child, Layout::default(), &mut out)?;
+    }
+    out.end_object();
+    out.into_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dom::Parser, prelude::*};
+
+    #[test]
+    fn subset() {
+        let json = r#"{"id": 1, "debug": {"trace": [1, 2]}, "name": "a\"b", "tags": []}"#
+            .to_padded_string();
+        let mut parser = Parser::default();
+        let root = parser.parse(&json).unwrap();
+        assert_eq!(
+            serialize_subset(&root, KeyFilter::Allow(&["name", "id", "missing"])).unwrap(),
+            r#"{"id":1,"name":"a\"b"}"#
+        );
+        assert_eq!(
+            serialize_subset(&root, KeyFilter::Deny(&["debug"])).unwrap(),
+            r#"{"id":1,"name":"a\"b","tags":[]}"#
+        );
+        assert_eq!(
+            serialize_subset(&root, KeyFilter::Allow(&[])).unwrap(),
+            "{}"
+        );
+        drop(root);
+        let json = "[1]".to_padded_string();
+        assert!(serialize_subset(&parser.parse(&json).unwrap(), KeyFilter::Deny(&[])).is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use std::fmt::{self, Write as _};
+
+use super::{Element, ElementType, canonical::normalize_double};
+use crate::utils::escape_json_string;
+
+const ELLIPSIS: &str = "…";
+
+/// Bounds on what [`Element::display_truncated`] prints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayLimits {
+    /// Deepest nesting printed; deeper arrays and objects become `[…]` and
+    /// `{…}`.
+    pub max_depth: usize,
+    /// Elements printed per array and fields per object before `…`.
+    pub max_items: usize,
+    /// Characters printed per string before `…`.
+    pub max_string_len: usize,
+}
+
+impl Default for DisplayLimits {
+    /// Enough to recognize a document in a log line: 4 levels, 8 items and
+    /// 64 characters.
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            max_items: 8,
+            max_string_len: 64,
+        }
+    }
+}
+
+/// Compact rendering of an element within [`DisplayLimits`]; see
+/// [`Element::display_truncated`].
+pub struct TruncatedDisplay<'e, 'a> {
+    element: &'e Element<'a>,
+    limits: DisplayLimits,
+}
+
+impl<'a> Element<'a> {
+    /// Displays the element as compact JSON cut down to `limits`, so huge or
+    /// sensitive documents can be logged without dumping them whole. Elided
+    /// parts are marked with `…`, which makes the output invalid JSON once
+    /// anything is cut.
+    ///
+    /// ```ignore
+    /// tracing::warn!(payload = %root.display_truncated(DisplayLimits::default()), "rejected");
+    /// ```
+    pub fn display_truncated(&self, limits: DisplayLimits) -> TruncatedDisplay<'_, 'a> {
+        TruncatedDisplay {
+            element: self,
+            limits,
+        }
+    }
+}
+
+impl fmt::Display for TruncatedDisplay<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_truncated(self.element, &self.limits, 0, &mut String::new(), f)
+    }
+}
+
+fn write_truncated(
+    element: &Element<'_>,
+    limits: &DisplayLimits,
+    depth: usize,
+    scratch: &mut String,
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    // Errors cannot be reported through `fmt`, so unreadable values are
+    // shown as `?`.
+    match element.get_type() {
+        ElementType::NullValue => f.write_str("null"),
+        ElementType::Bool => match element.get_bool() {
+            Ok(v) => write!(f, "{v}"),
+            Err(_) => f.write_char('?'),
+        },
+        ElementType::Int64 => match element.get_int64() {
+            Ok(v) => write!(f, "{v}"),
+            Err(_) => f.write_char('?'),
+        },
+        ElementType::UInt64 => match element.get_uint64() {
+            Ok(v) => write!(f, "{v}"),
+            Err(_) => f.write_char('?'),
+        },
+        ElementType::Double => match element.get_double() {
+            Ok(v) => f.write_str(&normalize_double(v)),
+            Err(_) => f.write_char('?'),
+        },
+        ElementType::String => match element.get_string() {
+            Ok(s) => write_string(s, limits.max_string_len, scratch, f),
+            Err(_) => f.write_char('?'),
+        },
+        ElementType::Array => {
+            let Ok(array) = element.get_array() else {
+                return f.write_char('?');
+            };
+            if depth >= limits.max_depth {
+                return write!(f, "[{ELLIPSIS}]");
+            }
+            f.write_char('[')?;
+            for (i, child) in array.iter().enumerate() {
+                if i > 0 {
+                    f.write_char(',')?;
+                }
+                if i == limits.max_items {
+                    f.write_str(ELLIPSIS)?;
+                    break;
+                }
+                write_truncated(&child, limits, depth + 1, scratch, f)?;
+            }
+            f.write_char(']')
+        }
+        ElementType::Object => {
+            let Ok(object) = element.get_object() else {
+                return f.write_char('?');
+            };
+            if depth >= limits.max_depth {
+                return write!(f, "{{{ELLIPSIS}}}");
+            }
+            f.write_char('{')?;
+            for (i, (key, child)) in object.iter().enumerate() {
+                if i > 0 {
+                    f.write_char(',')?;
+                }
+                if i == limits.max_items {
+                    f.write_str(ELLIPSIS)?;
+                    break;
+                }
+                write_string(key, limits.max_string_len, scratch, f)?;
+                f.write_char(':')?;
+                write_truncated(&child, limits, depth + 1, scratch, f)?;
+            }
+            f.write_char('}')
+        }
+    }
+}
+
+fn write_string(
+    s: &str,
+    max_len: usize,
+    scratch: &mut String,
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    let cut = s.char_indices().nth(max_len).map(|(i, _)| i);
+    scratch.clear();
+    scratch.push('"');
+    escape_json_string(&s[..cut.unwrap_or(s.len())], scratch).map_err(|_| fmt::Error)?;
+    if cut.is_some() {
+        scratch.push_str(ELLIPSIS);
+    }
+    scratch.push('"');
+    f.write_str(scratch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dom::Parser, prelude::*};
+
+    #[test]
+    fn truncated() {
+        let mut parser = Parser::default();
+        let json = r#"{"a": [1, 2, 3, 4], "b": {"c": {"d": 1}}, "s": "héllo\nworld", "e": 1.5}"#;
+        let root = parser.parse(&json.to_padded_string()).unwrap();
+        let limits = DisplayLimits {
+            max_depth: 2,
+            max_items: 3,
+            max_string_len: 4,
+        };
+        assert_eq!(
+            root.display_truncated(limits).to_string(),
+            r#"{"a":[1,2,3,…],"b":{"c":{…}},"s":"héll…",…}"#
+        );
+        let all = DisplayLimits {
+            max_depth: usize::MAX,
+            max_items: usize::MAX,
+            max_string_len: usize::MAX,
+        };
+        assert_eq!(
+            root.display_truncated(all).to_string(),
+            r#"{"a":[1,2,3,4],"b":{"c":{"d":1}},"s":"héllo\nworld","e":1.5}"#
+        );
+    }
+}
@@ -0,0 +1,199 @@
+#[cfg(feature = "builder")]
+use super::emit::{Emitter, Layout};
+use super::{Array, Element, ElementType, Object};
+use crate::Result;
+#[cfg(feature = "builder")]
+use crate::{builder::StringBuilder, pointer::Segment};
+
+/// Whether [`walk`] should descend into the container it just entered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Walk {
+    #[default]
+    Continue,
+    /// Skip the children; the matching `leave_*` callback is not called
+    /// either.
+    Skip,
+}
+
+/// Callbacks for [`walk`]. Every method has a no-op default, so implementors
+/// only override what they need.
+pub trait JsonVisitor {
+    fn enter_object(&mut self, _object: &Object<'_>) -> Result<Walk> {
+        Ok(Walk::Continue)
+    }
+
+    fn leave_object(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called before the value of each field.
+    fn key(&mut self, _key: &str) -> Result<()> {
+        Ok(())
+    }
+
+    fn enter_array(&mut self, _array: &Array<'_>) -> Result<Walk> {
+        Ok(Walk::Continue)
+    }
+
+    fn leave_array(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called for strings, numbers, booleans and nulls.
+    fn scalar(&mut self, _element: &Element<'_>) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Visits `element` depth first, in document order.
+pub fn walk<V: JsonVisitor + ?Sized>(element: &Element<'_>, visitor: &mut V) -> Result<()> {
+    match element.get_type() {
+        ElementType::Object => {
+            let object = element.get_object()?;
+            if visitor.enter_object(&object)? == Walk::Continue {
+                for (key, child) in object.iter() {
+                    visitor.key(key)?;
+                    walk(&child, visitor)?;
+                }
+                visitor.leave_object()?;
+            }
+        }
+        ElementType::Array => {
+            let array = element.get_array()?;
+            if visitor.enter_array(&array)? == Walk::Continue {
+                for child in array.iter() {
+                    walk(&child, visitor)?;
+                }
+                visitor.leave_array()?;
+            }
+        }
+        _ => visitor.scalar(element)?,
+    }
+    Ok(())
+}
+
+/// What [`transform`] writes in place of a value.
+#[cfg(feature = "builder")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rewrite {
+    /// Copy the value, applying the transformer to its children.
+    Keep,
+    /// Drop the value, and its key when it is an object field.
+    Remove,
+    Null,
+    String(String),
+    /// Already serialized JSON, written as is.
+    Raw(String),
+}
+
+/// Edits applied by [`transform`]. `path` is the location of the value or key
+/// being looked at, the key itself included.
+#[cfg(feature = "builder")]
+pub trait Transformer {
+    /// Returns the new name for an object key, or `None` to keep it.
+    fn rename(&mut self, _path: &[Segment<'_>], _key: &str) -> Option<String> {
+        None
+    }
+
+    fn rewrite(&mut self, _path: &[Segment<'_>], _element: &Element<'_>) -> Result<Rewrite> {
+        Ok(Rewrite::Keep)
+    }
+}
+
+/// Serializes `element` into `out` compactly, letting `transformer` rename
+/// keys and replace or remove values along the way.
+#[cfg(feature = "builder")]
+pub fn transform<T: Transformer + ?Sized>(
+    element: &Element<'_>,
+    transformer: &mut T,
+    out: &mut StringBuilder,
+) -> Result<()> {
+    Emitter::new(Layout::default(), transformer).root(element, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dom::Parser, prelude::*};
+
+    #[derive(Default)]
+    struct Counter {
+        depth: usize,
+        max_depth: usize,
+        keys: Vec<String>,
+        scalars: usize,
+    }
+
+    impl JsonVisitor for Counter {
+        fn enter_object(&mut self, _object: &Object<'_>) -> Result<Walk> {
+            self.depth += 1;
+            self.max_depth = self.max_depth.max(self.depth);
+            Ok(Walk::Continue)
+        }
+
+        fn leave_object(&mut self) -> Result<()> {
+            self.depth -= 1;
+            Ok(())
+        }
+
+        fn key(&mut self, key: &str) -> Result<()> {
+            self.keys.push(key.to_owned());
+            Ok(())
+        }
+
+        fn enter_array(&mut self, _array: &Array<'_>) -> Result<Walk> {
+            Ok(Walk::Skip)
+        }
+
+        fn scalar(&mut self, _element: &Element<'_>) -> Result<()> {
+            self.scalars += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn walk_document() {
+        let json = r#"{"a": {"b": 1, "c": [1, 2, 3]}, "d": null}"#.to_padded_string();
+        let mut parser = Parser::default();
+        let element = parser.parse(&json).unwrap();
+        let mut counter = Counter::default();
+        walk(&element, &mut counter).unwrap();
+        assert_eq!(counter.keys, ["a", "b", "c", "d"]);
+        assert_eq!(counter.max_depth, 2);
+        assert_eq!(counter.depth, 0);
+        assert_eq!(counter.scalars, 2);
+    }
+
+    #[cfg(feature = "builder")]
+    struct Redact;
+
+    #[cfg(feature = "builder")]
+    impl Transformer for Redact {
+        fn rename(&mut self, _path: &[Segment<'_>], key: &str) -> Option<String> {
+            (key == "user").then(|| "account".to_owned())
+        }
+
+        fn rewrite(&mut self, path: &[Segment<'_>], _element: &Element<'_>) -> Result<Rewrite> {
+            Ok(match path.last() {
+                Some(Segment::Key("password")) => Rewrite::String("***".to_owned()),
+                Some(Segment::Key("debug")) => Rewrite::Remove,
+                _ => Rewrite::Keep,
+            })
+        }
+    }
+
+    #[cfg(feature = "builder")]
+    #[test]
+    fn transform_document() {
+        let json = r#"{"debug": true, "user": {"name": "a", "password": "hunter2"}, "n": [1, -2]}"#
+            .to_padded_string();
+        let mut parser = Parser::default();
+        let element = parser.parse(&json).unwrap();
+        let mut out = StringBuilder::new();
+        transform(&element, &mut Redact, &mut out).unwrap();
+        assert_eq!(
+            out.view().unwrap(),
+            r#"{"account":{"name":"a","password":"***"},"n":[1,-2]}"#
+        );
+    }
+}
@@ -31,6 +31,9 @@ pub enum SimdJsonError {
     #[error("Problem while parsing a number")]
     NumberError,
 
+    #[error("The integer value exceeds 64 bits")]
+    BigintError,
+
     #[error("The input is not valid UTF-8")]
     Utf8Error,
 
@@ -100,12 +103,98 @@ pub enum SimdJsonError {
     #[error("todo")]
     NumErrorCodes,
 
+    #[error("The input starts with a UTF-8 byte order mark")]
+    ByteOrderMark,
+
+    #[error("The document exceeds the configured `{0}` limit")]
+    LimitExceeded(&'static str),
+
+    #[error("Misused string builder: {0}")]
+    BuilderMisuse(&'static str),
+
+    #[error("Cannot decode the input: {0}")]
+    Charset(String),
+
+    #[cfg(feature = "base64")]
+    #[error("Invalid base64 at byte {0}")]
+    Base64(usize),
+
+    #[error("Array element {index}: {source}")]
+    AtIndex {
+        index: usize,
+        source: Box<SimdJsonError>,
+    },
+
+    #[error("At {pointer}: {source}")]
+    AtPointer {
+        pointer: String,
+        source: Box<SimdJsonError>,
+    },
+
     #[error("todo")]
     StdIoError(#[from] std::io::Error),
 
     #[cfg(feature = "serde_impl")]
     #[error("serde: {0}")]
     Serde(String),
+
+    #[cfg(feature = "arrow")]
+    #[error("arrow: {0}")]
+    Arrow(String),
+}
+
+impl SimdJsonError {
+    /// Name of the variant, without its payload. Stable enough to use as a
+    /// metrics label.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SimdJsonError::Capacity => "Capacity",
+            SimdJsonError::MemAlloc => "MemAlloc",
+            SimdJsonError::TapeError => "TapeError",
+            SimdJsonError::DepthError => "DepthError",
+            SimdJsonError::StringError => "StringError",
+            SimdJsonError::TAtomError => "TAtomError",
+            SimdJsonError::FAtomError => "FAtomError",
+            SimdJsonError::NAtomError => "NAtomError",
+            SimdJsonError::NumberError => "NumberError",
+            SimdJsonError::BigintError => "BigintError",
+            SimdJsonError::Utf8Error => "Utf8Error",
+            SimdJsonError::Uninitialized => "Uninitialized",
+            SimdJsonError::Empty => "Empty",
+            SimdJsonError::UnescapedChars => "UnescapedChars",
+            SimdJsonError::UnclosedString => "UnclosedString",
+            SimdJsonError::UnsupportedArchitecture => "UnsupportedArchitecture",
+            SimdJsonError::IncorrectType => "IncorrectType",
+            SimdJsonError::NumberOutOfRange => "NumberOutOfRange",
+            SimdJsonError::IndexOutOfBounds => "IndexOutOfBounds",
+            SimdJsonError::NoSuchField => "NoSuchField",
+            SimdJsonError::IoError => "IoError",
+            SimdJsonError::InvalidJsonPointer => "InvalidJsonPointer",
+            SimdJsonError::InvalidUriFragment => "InvalidUriFragment",
+            SimdJsonError::UnexpectedError => "UnexpectedError",
+            SimdJsonError::ParserInUse => "ParserInUse",
+            SimdJsonError::OutOfOrderIteration => "OutOfOrderIteration",
+            SimdJsonError::InsufficientPadding => "InsufficientPadding",
+            SimdJsonError::IncompleteArrayOrObject => "IncompleteArrayOrObject",
+            SimdJsonError::ScalarDocumentAsValue => "ScalarDocumentAsValue",
+            SimdJsonError::OutOfBounds => "OutOfBounds",
+            SimdJsonError::TailingContent => "TailingContent",
+            SimdJsonError::NumErrorCodes => "NumErrorCodes",
+            SimdJsonError::ByteOrderMark => "ByteOrderMark",
+            SimdJsonError::LimitExceeded(_) => "LimitExceeded",
+            SimdJsonError::BuilderMisuse(_) => "BuilderMisuse",
+            SimdJsonError::Charset(_) => "Charset",
+            #[cfg(feature = "base64")]
+            SimdJsonError::Base64(_) => "Base64",
+            SimdJsonError::AtIndex { .. } => "AtIndex",
+            SimdJsonError::AtPointer { .. } => "AtPointer",
+            SimdJsonError::StdIoError(_) => "StdIoError",
+            #[cfg(feature = "serde_impl")]
+            SimdJsonError::Serde(_) => "Serde",
+            #[cfg(feature = "arrow")]
+            SimdJsonError::Arrow(_) => "Arrow",
+        }
+    }
 }
 
 impl From<i32> for SimdJsonError {
@@ -120,28 +209,29 @@ impl From<i32> for SimdJsonError {
             7 => SimdJsonError::FAtomError,
             8 => SimdJsonError::NAtomError,
             9 => SimdJsonError::NumberError,
-            10 => SimdJsonError::Utf8Error,
-            11 => SimdJsonError::Uninitialized,
-            12 => SimdJsonError::Empty,
-            13 => SimdJsonError::UnescapedChars,
-            14 => SimdJsonError::UnclosedString,
-            15 => SimdJsonError::UnsupportedArchitecture,
-            16 => SimdJsonError::IncorrectType,
-            17 => SimdJsonError::NumberOutOfRange,
-            18 => SimdJsonError::IndexOutOfBounds,
-            19 => SimdJsonError::NoSuchField,
-            20 => SimdJsonError::IoError,
-            21 => SimdJsonError::InvalidJsonPointer,
-            22 => SimdJsonError::InvalidUriFragment,
-            23 => SimdJsonError::UnexpectedError,
-            24 => SimdJsonError::ParserInUse,
-            25 => SimdJsonError::OutOfOrderIteration,
-            26 => SimdJsonError::InsufficientPadding,
-            27 => SimdJsonError::IncompleteArrayOrObject,
-            28 => SimdJsonError::ScalarDocumentAsValue,
-            29 => SimdJsonError::OutOfBounds,
-            30 => SimdJsonError::TailingContent,
-            31 => SimdJsonError::NumErrorCodes,
+            10 => SimdJsonError::BigintError,
+            11 => SimdJsonError::Utf8Error,
+            12 => SimdJsonError::Uninitialized,
+            13 => SimdJsonError::Empty,
+            14 => SimdJsonError::UnescapedChars,
+            15 => SimdJsonError::UnclosedString,
+            16 => SimdJsonError::UnsupportedArchitecture,
+            17 => SimdJsonError::IncorrectType,
+            18 => SimdJsonError::NumberOutOfRange,
+            19 => SimdJsonError::IndexOutOfBounds,
+            20 => SimdJsonError::NoSuchField,
+            21 => SimdJsonError::IoError,
+            22 => SimdJsonError::InvalidJsonPointer,
+            23 => SimdJsonError::InvalidUriFragment,
+            24 => SimdJsonError::UnexpectedError,
+            25 => SimdJsonError::ParserInUse,
+            26 => SimdJsonError::OutOfOrderIteration,
+            27 => SimdJsonError::InsufficientPadding,
+            28 => SimdJsonError::IncompleteArrayOrObject,
+            29 => SimdJsonError::ScalarDocumentAsValue,
+            30 => SimdJsonError::OutOfBounds,
+            31 => SimdJsonError::TailingContent,
+            32 => SimdJsonError::NumErrorCodes,
             x => panic!("Unknown error code: {}", x),
         }
     }
@@ -160,3 +250,24 @@ impl serde::ser::Error for SimdJsonError {
         SimdJsonError::Serde(msg.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::SimdJsonError;
+
+    #[test]
+    fn from_code() {
+        // Values of simdjson's `error_code` enum.
+        for (code, kind) in [
+            (9, "NumberError"),
+            (10, "BigintError"),
+            (15, "UnclosedString"),
+            (16, "UnsupportedArchitecture"),
+            (17, "IncorrectType"),
+            (31, "TailingContent"),
+            (32, "NumErrorCodes"),
+        ] {
+            assert_eq!(SimdJsonError::from(code).kind(), kind);
+        }
+    }
+}
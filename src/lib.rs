@@ -1,18 +1,77 @@
+// Each parser only uses some of the FFI helpers.
+#[cfg_attr(
+    not(all(feature = "dom", feature = "ondemand")),
+    allow(unused_imports, unused_macros)
+)]
 mod macros;
 
+#[cfg(feature = "allocator")]
+pub mod alloc;
+#[cfg(any(feature = "dom", feature = "ondemand"))]
+mod bom;
+#[cfg(feature = "builder")]
 pub mod builder;
+pub mod charset;
+#[cfg(feature = "dom")]
 pub mod dom;
 mod error;
+#[cfg(any(feature = "dom", feature = "ondemand"))]
+mod limits;
+mod minify;
+#[cfg(feature = "dom")]
+pub mod ndjson;
+mod number;
+#[cfg(feature = "ondemand")]
 pub mod ondemand;
 pub mod padded_string;
+#[cfg(any(feature = "dom", feature = "ondemand"))]
+mod pointer;
+mod pool;
 pub mod prelude;
-mod utils;
+#[cfg(all(feature = "dom", feature = "builder"))]
+mod prettify;
+#[cfg(any(feature = "dom", feature = "ondemand"))]
+mod stats;
+#[cfg(any(feature = "dom", feature = "ondemand"))]
+mod trace;
+pub mod utils;
+#[cfg(feature = "dom")]
+mod validate;
 
+#[cfg(any(feature = "dom", feature = "ondemand"))]
+pub use bom::BomPolicy;
 pub use error::{Result, SimdJsonError};
+#[cfg(any(feature = "dom", feature = "ondemand"))]
+pub use limits::{ParseLimits, StringLimitPolicy};
+pub use minify::{minify, minify_to};
+pub use number::{Number, RawNumber};
+#[cfg(any(feature = "dom", feature = "ondemand"))]
+pub use pointer::{CompiledPointer, Segment, escape_segment};
+pub use pool::release_thread_local_parsers;
+#[cfg(all(feature = "dom", feature = "builder"))]
+pub use prettify::prettify;
 pub use simdjson_sys::{SIMDJSON_MAXSIZE_BYTES, SIMDJSON_PADDING};
+#[cfg(any(feature = "dom", feature = "ondemand"))]
+pub use stats::ParseStats;
+#[cfg(feature = "dom")]
+pub use validate::{is_valid_json, validate};
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "tokio")]
+pub mod async_io;
+#[cfg(feature = "base64")]
+pub mod base64;
+#[cfg(feature = "dom")]
+pub mod body;
+#[cfg(feature = "rayon")]
+pub mod parallel;
 #[cfg(feature = "serde_impl")]
 pub mod serde;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(any(feature = "axum", feature = "actix-web"))]
+pub mod web;
 
 #[cfg(test)]
 mod tests {}
@@ -0,0 +1,297 @@
+use simdjson_sys as ffi;
+
+#[cfg(feature = "dom")]
+use crate::dom::{Element, ElementType};
+#[cfg(feature = "ondemand")]
+use crate::ondemand::{Event, Events};
+use crate::{Result, SimdJsonError};
+
+/// Upper bounds on what a parser accepts, for services that parse untrusted
+/// input.
+///
+/// Pass them to [`dom::Parser::with_limits`](crate::dom::Parser::with_limits)
+/// or [`ondemand::Parser::with_limits`](crate::ondemand::Parser::with_limits);
+/// everything built on top of those parsers, serde included, inherits them.
+/// The document size and nesting depth are enforced by simdjson itself. The
+/// string, key and array limits need an extra pass over the document, which
+/// is only made when one of them is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// Largest accepted input in bytes. Exceeding it is a
+    /// [`SimdJsonError::Capacity`] error.
+    pub max_document_bytes: usize,
+    /// Deepest accepted nesting of arrays and objects. Exceeding it is a
+    /// [`SimdJsonError::DepthError`].
+    pub max_depth: usize,
+    /// Longest accepted string or object key, in bytes after unescaping.
+    pub max_string_len: usize,
+    /// Longest accepted object key, in bytes after unescaping. Keys are held
+    /// to `max_string_len` as well.
+    pub max_key_len: usize,
+    /// What happens to strings and keys over their limit.
+    pub string_policy: StringLimitPolicy,
+    /// Largest accepted number of elements in one array.
+    pub max_array_len: usize,
+}
+
+impl Default for ParseLimits {
+    /// simdjson's own limits: 4 GiB documents and 1024 levels of nesting.
+    fn default() -> Self {
+        Self {
+            max_document_bytes: ffi::SIMDJSON_MAXSIZE_BYTES,
+            max_depth: ffi::DEFAULT_MAX_DEPTH,
+            max_string_len: usize::MAX,
+            max_key_len: usize::MAX,
+            string_policy: StringLimitPolicy::Reject,
+            max_array_len: usize::MAX,
+        }
+    }
+}
+
+/// What a parser does with a string or object key longer than
+/// [`ParseLimits::max_string_len`] or [`ParseLimits::max_key_len`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StringLimitPolicy {
+    /// Fail with [`SimdJsonError::LimitExceeded`].
+    #[default]
+    Reject,
+    /// Cut the string down to the limit, at a character boundary. Only the
+    /// DOM parser can change a parsed document; the ondemand parser rejects
+    /// such strings anyway.
+    Truncate,
+}
+
+impl ParseLimits {
+    /// Whether the string, key or array limits are set, so documents have to
+    /// be walked after parsing.
+    pub(crate) fn checks_contents(&self) -> bool {
+        self.max_string_len != usize::MAX
+            || self.max_key_len != usize::MAX
+            || self.max_array_len != usize::MAX
+    }
+
+    /// Whether ondemand documents have to be walked up front: the ondemand
+    /// parser only finds deep nesting once it gets there.
+    #[cfg(feature = "ondemand")]
+    pub(crate) fn checks_ondemand(&self) -> bool {
+        self.checks_contents() || self.max_depth != ffi::DEFAULT_MAX_DEPTH
+    }
+
+    /// The depth to configure simdjson with: it counts the document itself
+    /// as a level.
+    #[cfg(feature = "dom")]
+    pub(crate) fn simdjson_max_depth(&self) -> usize {
+        self.max_depth.saturating_add(1)
+    }
+
+    #[cfg(feature = "dom")]
+    pub(crate) fn check_element(&self, element: &Element<'_>) -> Result<()> {
+        match element.get_type() {
+            ElementType::Array => {
+                let array = element.get_array()?;
+                if array.size() > self.max_array_len {
+                    return Err(SimdJsonError::LimitExceeded("max_array_len"));
+                }
+                array
+                    .iter()
+                    .try_for_each(|child| self.check_element(&child))
+            }
+            ElementType::Object => {
+                let object = element.get_object()?;
+                let mut fields = object.iter();
+                while let Some((key, child)) = fields.next() {
+                    if let Some(len) = self.check_key(key, true)? {
+                        // `check_key` found a char boundary, and `key` is not
+                        // used again.
+                        unsafe { fields.truncate_key(len) };
+                    }
+                    self.check_element(&child)?;
+                }
+                Ok(())
+            }
+            ElementType::String => {
+                if let Some(len) = self.check_string(element.get_string()?, true)? {
+                    // As above; nothing else borrows the new document yet.
+                    unsafe { element.truncate_string(len) };
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Consumes `events`, checking the string and array limits as well as
+    /// the depth, which the ondemand parser does not enforce up front.
+    #[cfg(feature = "ondemand")]
+    pub(crate) fn check_events(&self, events: Events<'_>) -> Result<()> {
+        // Element counts of the open containers; `None` for objects.
+        let mut counts: Vec<Option<usize>> = Vec::new();
+        for event in events {
+            let event = event?;
+            if let Some(Some(count)) = counts.last_mut() {
+                if !matches!(event, Event::EndArray) {
+                    *count += 1;
+                    if *count > self.max_array_len {
+                        return Err(SimdJsonError::LimitExceeded("max_array_len"));
+                    }
+                }
+            }
+            match event {
+                Event::StartObject | Event::StartArray => {
+                    if counts.len() == self.max_depth {
+                        return Err(SimdJsonError::DepthError);
+                    }
+                    counts.push(matches!(event, Event::StartArray).then_some(0));
+                }
+                Event::EndObject | Event::EndArray => {
+                    counts.pop();
+                }
+                Event::Key(s) => {
+                    self.check_key(s, false)?;
+                }
+                Event::String(s) => {
+                    self.check_string(s, false)?;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// The length to cut `key` down to, if it is too long and `truncate` is
+    /// allowed by the policy.
+    fn check_key(&self, key: &str, truncate: bool) -> Result<Option<usize>> {
+        if key.len() > self.max_key_len {
+            return self.over_limit(key, self.max_key_len, "max_key_len", truncate);
+        }
+        self.check_string(key, truncate)
+    }
+
+    fn check_string(&self, s: &str, truncate: bool) -> Result<Option<usize>> {
+        if s.len() > self.max_string_len {
+            return self.over_limit(s, self.max_string_len, "max_string_len", truncate);
+        }
+        Ok(None)
+    }
+
+    fn over_limit(
+        &self,
+        s: &str,
+        max: usize,
+        limit: &'static str,
+        truncate: bool,
+    ) -> Result<Option<usize>> {
+        if truncate && self.string_policy == StringLimitPolicy::Truncate {
+            Ok((0..=max).rev().find(|&len| s.is_char_boundary(len)))
+        } else {
+            Err(SimdJsonError::LimitExceeded(limit))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "dom")]
+    use crate::dom;
+    #[cfg(feature = "ondemand")]
+    use crate::ondemand;
+    use crate::prelude::*;
+
+    fn limits() -> ParseLimits {
+        ParseLimits {
+            max_document_bytes: 64,
+            max_depth: 3,
+            max_string_len: 3,
+            max_key_len: usize::MAX,
+            string_policy: StringLimitPolicy::Reject,
+            max_array_len: 2,
+        }
+    }
+
+    #[cfg(feature = "dom")]
+    #[test]
+    fn dom_limits() {
+        let mut parser = dom::Parser::with_limits(limits());
+        for (json, ok) in [
+            (r#"{"abc": [1, [2, "xyz"]]}"#, true),
+            (r#"[[[[1]]]]"#, false),
+            (r#"["abcd"]"#, false),
+            (r#"{"abcd": 1}"#, false),
+            (r#"[1, 2, 3]"#, false),
+        ] {
+            assert_eq!(parser.parse(&json.to_padded_string()).is_ok(), ok, "{json}");
+        }
+        let long = format!("[{}1]", "1,".repeat(40)).into_padded_string();
+        assert!(matches!(parser.parse(&long), Err(SimdJsonError::Capacity)));
+    }
+
+    #[cfg(feature = "dom")]
+    #[test]
+    fn dom_key_limits() {
+        let json =
+            r#"{"id": "abcdef", "long_key": {"k": "\u00e9\u00e9\u00e9"}}"#.to_padded_string();
+        let mut parser = dom::Parser::with_limits(ParseLimits {
+            max_string_len: 5,
+            max_key_len: 4,
+            ..ParseLimits::default()
+        });
+        assert!(matches!(
+            parser.parse(&json),
+            Err(SimdJsonError::LimitExceeded("max_string_len"))
+        ));
+
+        let mut parser = dom::Parser::with_limits(ParseLimits {
+            max_string_len: 5,
+            max_key_len: 4,
+            string_policy: StringLimitPolicy::Truncate,
+            ..ParseLimits::default()
+        });
+        let root = parser.parse(&json).unwrap();
+        assert_eq!(
+            root.at_pointer("/id").unwrap().get_string().unwrap(),
+            "abcde"
+        );
+        // Each "é" is two bytes; a third would go past the limit.
+        assert_eq!(
+            root.at_pointer("/long/k").unwrap().get_string().unwrap(),
+            "éé"
+        );
+        let keys: Vec<_> = root.get_object().unwrap().iter().map(|(k, _)| k).collect();
+        assert_eq!(keys, ["id", "long"]);
+    }
+
+    #[cfg(feature = "ondemand")]
+    #[test]
+    fn ondemand_limits() {
+        let mut parser = ondemand::Parser::with_limits(limits());
+        for (json, ok) in [
+            (r#"{"abc": [1, [2, "xyz"]]}"#, true),
+            (r#"[[[[1]]]]"#, false),
+            (r#"["abcd"]"#, false),
+            (r#"[1, 2, 3]"#, false),
+        ] {
+            assert_eq!(
+                parser.iterate(&json.to_padded_string()).is_ok(),
+                ok,
+                "{json}"
+            );
+        }
+        let keys = ondemand::Parser::with_limits(ParseLimits {
+            max_key_len: 2,
+            string_policy: StringLimitPolicy::Truncate,
+            ..ParseLimits::default()
+        })
+        .iterate(&r#"{"abc": "long value"}"#.to_padded_string())
+        .map(|_| ());
+        assert!(matches!(
+            keys,
+            Err(SimdJsonError::LimitExceeded("max_key_len"))
+        ));
+
+        // The document is rewound after the check.
+        let json = r#"{"abc": [1, 2]}"#.to_padded_string();
+        let mut doc = parser.iterate(&json).unwrap();
+        assert_eq!(doc.at_pointer("/abc/1").unwrap().get_uint64().unwrap(), 2);
+    }
+}
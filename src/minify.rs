@@ -0,0 +1,45 @@
+use simdjson_sys as ffi;
+
+use crate::{Result, macros::map_primitive_result};
+
+/// Removes all insignificant whitespace from a JSON document using simdjson's
+/// SIMD minifier. The input is not parsed, so this is much cheaper than a
+/// parse/serialize round trip, but it also does not validate the document.
+pub fn minify(json: &str) -> Result<String> {
+    let mut buf = Vec::with_capacity(json.len());
+    minify_to(json, &mut buf)?;
+    // Only ASCII whitespace outside of strings is removed, so the output is
+    // still valid UTF-8.
+    Ok(unsafe { String::from_utf8_unchecked(buf) })
+}
+
+/// Minifies `json` and appends the result to `dst`, reusing its allocation.
+pub fn minify_to(json: &str, dst: &mut Vec<u8>) -> Result<()> {
+    let start = dst.len();
+    dst.reserve(json.len());
+    let written = map_primitive_result!(ffi::SJ_minify(
+        json.as_ptr().cast(),
+        json.len(),
+        dst.as_mut_ptr().add(start).cast()
+    ))?;
+    unsafe { dst.set_len(start + written) };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minify_whitespace() {
+        let json = "{ \"a\" : [1, 2,\n 3], \"b\": \" x y \" }";
+        assert_eq!(minify(json).unwrap(), r#"{"a":[1,2,3],"b":" x y "}"#);
+    }
+
+    #[test]
+    fn minify_to_appends() {
+        let mut buf = b"prefix:".to_vec();
+        minify_to("[ true , null ]", &mut buf).unwrap();
+        assert_eq!(buf, b"prefix:[true,null]");
+    }
+}
@@ -0,0 +1,529 @@
+//! Reading and writing newline-delimited JSON (JSON Lines).
+//!
+//! [`Reader`] parses one record per line with a reused DOM parser, enforcing a
+//! maximum line length and an [`InvalidRecord`] policy. [`Writer`] emits one
+//! compact record per line. Both sides keep [`Counters`].
+//!
+//! For trusted input that is already in memory, [`crate::dom::Parser::parse_many`]
+//! avoids the per-line copy and is faster. Code that cuts its own streams
+//! into batches can use [`split_records`] and [`complete_records_len`].
+
+use std::io::{BufRead, Read, Write};
+#[cfg(feature = "serde_impl")]
+use std::path::Path;
+
+use simdjson_sys as ffi;
+
+#[cfg(feature = "serde_impl")]
+use crate::builder::StringBuilder;
+pub use crate::utils::{SplitRecords, split_records};
+use crate::{ParseLimits, Result, SimdJsonError, dom, utils::validate_utf8};
+
+const DEFAULT_MAX_LINE_LEN: usize = 16 * 1024 * 1024;
+
+/// What to do with a line that is too long or not valid JSON. Errors reading
+/// the input are always returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InvalidRecord {
+    /// Stop and return the error.
+    #[default]
+    Fail,
+    /// Count the line in [`Counters::skipped`] and continue with the next one.
+    Skip,
+}
+
+/// Running totals for a [`Reader`] or [`Writer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Counters {
+    /// Records successfully read or written.
+    pub records: usize,
+    /// Records dropped under [`InvalidRecord::Skip`].
+    pub skipped: usize,
+    /// Bytes consumed from the input or written to the output, newlines
+    /// included.
+    pub bytes: usize,
+}
+
+/// Streams records out of a newline-delimited JSON source.
+pub struct Reader<R> {
+    inner: R,
+    parser: dom::Parser,
+    line: Vec<u8>,
+    max_line_len: usize,
+    on_invalid: InvalidRecord,
+    counters: Counters,
+    /// 1-based number and byte offset of the line in `line`.
+    line_number: usize,
+    line_offset: usize,
+}
+
+impl<R: BufRead> Reader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            parser: dom::Parser::default(),
+            line: Vec::new(),
+            max_line_len: DEFAULT_MAX_LINE_LEN,
+            on_invalid: InvalidRecord::default(),
+            counters: Counters::default(),
+            line_number: 0,
+            line_offset: 0,
+        }
+    }
+
+    /// Sets the longest accepted line in bytes, excluding the line terminator.
+    /// Longer lines are reported as [`SimdJsonError::Capacity`].
+    pub fn max_line_len(mut self, max_line_len: usize) -> Self {
+        self.max_line_len = max_line_len;
+        self
+    }
+
+    /// Parses every record with [`dom::Parser::with_limits`].
+    pub fn limits(mut self, limits: ParseLimits) -> Self {
+        self.parser = dom::Parser::with_limits(limits);
+        self
+    }
+
+    pub fn on_invalid(mut self, on_invalid: InvalidRecord) -> Self {
+        self.on_invalid = on_invalid;
+        self
+    }
+
+    pub fn counters(&self) -> Counters {
+        self.counters
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Calls `f` with the root element of every record until the input is
+    /// exhausted or `f` returns an error.
+    pub fn for_each<F>(&mut self, mut f: F) -> Result<Counters>
+    where
+        F: FnMut(dom::Element<'_>) -> Result<()>,
+    {
+        while let Some(line) = self.next_line() {
+            if let Err(e) = line {
+                self.reject(e)?;
+                continue;
+            }
+            let error = match self.parser.parse(padded_line(&self.line)) {
+                Ok(element) => {
+                    f(element)?;
+                    self.counters.records += 1;
+                    continue;
+                }
+                Err(e) => e,
+            };
+            self.reject(error)?;
+        }
+        Ok(self.counters)
+    }
+
+    /// Deserializes every record into `T`. Records that fail to deserialize
+    /// are subject to the same [`InvalidRecord`] policy as malformed lines.
+    #[cfg(feature = "serde_impl")]
+    pub fn records<T>(self) -> Records<R, T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        Records {
+            reader: self,
+            _record: std::marker::PhantomData,
+        }
+    }
+
+    /// Deserializes every record into `T`, collecting the lines that fail
+    /// into an [`IngestReport`] instead of stopping at them. Only I/O errors
+    /// end the load early.
+    #[cfg(feature = "serde_impl")]
+    pub fn load<T>(&mut self) -> Result<(Vec<T>, IngestReport)>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let mut records = Vec::new();
+        let mut report = IngestReport::default();
+        while let Some(line) = self.next_line() {
+            let record = line.and_then(|()| {
+                self.parser
+                    .parse(padded_line(&self.line))
+                    .and_then(|element| crate::serde::de::from_element(&element))
+            });
+            match record {
+                Ok(record) => {
+                    records.push(record);
+                    self.counters.records += 1;
+                }
+                Err(error @ SimdJsonError::StdIoError(_)) => return Err(error),
+                Err(error) => {
+                    report.failed.push(FailedLine {
+                        line: self.line_number,
+                        offset: self.line_offset,
+                        error,
+                    });
+                    self.counters.skipped += 1;
+                }
+            }
+        }
+        report.lines = self.line_number;
+        report.bytes = self.counters.bytes;
+        Ok((records, report))
+    }
+
+    /// Applies the [`InvalidRecord`] policy to `error`. I/O errors are always
+    /// returned, since reading again would most likely fail the same way.
+    fn reject(&mut self, error: SimdJsonError) -> Result<()> {
+        if let SimdJsonError::StdIoError(_) = error {
+            return Err(error);
+        }
+        match self.on_invalid {
+            InvalidRecord::Fail => Err(error),
+            InvalidRecord::Skip => {
+                self.counters.skipped += 1;
+                Ok(())
+            }
+        }
+    }
+
+    /// Reads the next non-blank line into `self.line`. Returns `None` at end of
+    /// input and `Some(Err(_))` for a line that cannot be handed to the parser.
+    fn next_line(&mut self) -> Option<Result<()>> {
+        loop {
+            self.line.clear();
+            self.line_offset = self.counters.bytes;
+            let limit = (self.max_line_len as u64).saturating_add(1);
+            let read = match Read::take(&mut self.inner, limit).read_until(b'\n', &mut self.line) {
+                Ok(0) => return None,
+                Ok(n) => n,
+                Err(e) => return Some(Err(e.into())),
+            };
+            self.line_number += 1;
+            self.counters.bytes += read;
+
+            if self.line.last() == Some(&b'\n') {
+                self.line.pop();
+            } else if self.line.len() > self.max_line_len {
+                if let Err(e) = self.discard_line() {
+                    return Some(Err(e));
+                }
+                return Some(Err(SimdJsonError::Capacity));
+            }
+            if self.line.last() == Some(&b'\r') {
+                self.line.pop();
+            }
+
+            if self.line.iter().all(u8::is_ascii_whitespace) {
+                continue;
+            }
+            if !validate_utf8(&self.line) {
+                return Some(Err(SimdJsonError::Utf8Error));
+            }
+            self.line.reserve(ffi::SIMDJSON_PADDING);
+            return Some(Ok(()));
+        }
+    }
+
+    /// Consumes the rest of an over-long line without buffering it.
+    fn discard_line(&mut self) -> Result<()> {
+        loop {
+            let buf = self.inner.fill_buf()?;
+            if buf.is_empty() {
+                return Ok(());
+            }
+            let (used, done) = match memchr::memchr(b'\n', buf) {
+                Some(i) => (i + 1, true),
+                None => (buf.len(), false),
+            };
+            self.inner.consume(used);
+            self.counters.bytes += used;
+            if done {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// What [`load_many`] or [`Reader::load`] could not read.
+#[cfg(feature = "serde_impl")]
+#[derive(Debug, Default)]
+pub struct IngestReport {
+    /// Lines read, blank ones included.
+    pub lines: usize,
+    /// Bytes read, newlines included.
+    pub bytes: usize,
+    /// The lines that did not become records, in input order.
+    pub failed: Vec<FailedLine>,
+}
+
+#[cfg(feature = "serde_impl")]
+impl IngestReport {
+    /// Whether every non-blank line became a record.
+    pub fn is_clean(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// A line that was too long, not valid JSON or not a valid `T`.
+#[cfg(feature = "serde_impl")]
+#[derive(Debug)]
+pub struct FailedLine {
+    /// 1-based line number.
+    pub line: usize,
+    /// Byte offset of the start of the line.
+    pub offset: usize,
+    pub error: SimdJsonError,
+}
+
+/// Reads the NDJSON file at `path` into a `Vec<T>`, skipping the lines
+/// that fail and reporting them with their line number, offset and error.
+///
+/// ```ignore
+/// let (events, report) = ndjson::load_many::<Event, _>("events.ndjson")?;
+/// for failed in &report.failed {
+///     eprintln!("line {}: {}", failed.line, failed.error);
+/// }
+/// ```
+#[cfg(feature = "serde_impl")]
+pub fn load_many<T, P>(path: P) -> Result<(Vec<T>, IngestReport)>
+where
+    T: serde::de::DeserializeOwned,
+    P: AsRef<Path>,
+{
+    let file = std::fs::File::open(path)?;
+    Reader::new(std::io::BufReader::new(file)).load()
+}
+
+/// Typed iterator over the records of a [`Reader`]; see [`Reader::records`].
+#[cfg(feature = "serde_impl")]
+pub struct Records<R, T> {
+    reader: Reader<R>,
+    _record: std::marker::PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "serde_impl")]
+impl<R: BufRead, T> Records<R, T> {
+    pub fn counters(&self) -> Counters {
+        self.reader.counters
+    }
+
+    pub fn into_reader(self) -> Reader<R> {
+        self.reader
+    }
+}
+
+#[cfg(feature = "serde_impl")]
+impl<R, T> Iterator for Records<R, T>
+where
+    R: BufRead,
+    T: serde::de::DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let reader = &mut self.reader;
+        loop {
+            let error = match reader.next_line()? {
+                Ok(()) => {
+                    let record = reader
+                        .parser
+                        .parse(padded_line(&reader.line))
+                        .and_then(|element| crate::serde::de::from_element(&element));
+                    match record {
+                        Ok(record) => {
+                            reader.counters.records += 1;
+                            return Some(Ok(record));
+                        }
+                        Err(e) => e,
+                    }
+                }
+                Err(e) => e,
+            };
+            if let Err(e) = reader.reject(error) {
+                return Some(Err(e));
+            }
+        }
+    }
+}
+
+/// Writes one compact JSON record per line.
+pub struct Writer<W> {
+    inner: W,
+    #[cfg(feature = "serde_impl")]
+    builder: StringBuilder,
+    line: Vec<u8>,
+    counters: Counters,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            #[cfg(feature = "serde_impl")]
+            builder: StringBuilder::new(),
+            line: Vec::new(),
+            counters: Counters::default(),
+        }
+    }
+
+    pub fn counters(&self) -> Counters {
+        self.counters
+    }
+
+    /// Writes an already serialized JSON value. It is minified first so that a
+    /// pretty-printed value still occupies a single line.
+    pub fn write_raw(&mut self, json: &str) -> Result<()> {
+        self.line.clear();
+        crate::minify_to(json, &mut self.line)?;
+        self.line.push(b'\n');
+        self.inner.write_all(&self.line)?;
+        self.counters.records += 1;
+        self.counters.bytes += self.line.len();
+        Ok(())
+    }
+
+    /// Serializes `value` with the SIMD builder and writes it as one line.
+    #[cfg(feature = "serde_impl")]
+    pub fn write<T: serde::Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.builder.clear();
+        value.serialize(&mut crate::serde::ser::BuilderSerializer::new(
+            &mut self.builder,
+        ))?;
+        self.builder.append_char('\n');
+        let line = self.builder.view()?;
+        self.inner.write_all(line.as_bytes())?;
+        self.counters.records += 1;
+        self.counters.bytes += line.len();
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.inner.flush()?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Serializes every item of `records` to `writer`, one per line.
+#[cfg(feature = "serde_impl")]
+pub fn write_many<W, I>(writer: W, records: I) -> Result<Counters>
+where
+    W: Write,
+    I: IntoIterator,
+    I::Item: serde::Serialize,
+{
+    let mut writer = Writer::new(writer);
+    for record in records {
+        writer.write(&record)?;
+    }
+    writer.flush()?;
+    Ok(writer.counters())
+}
+
+/// The length of the longest prefix of `buf` made of whole lines: one past
+/// its last `\n`, or 0 if there is none. When reading a stream in blocks,
+/// parse `buf[..n]` and carry the rest over to the next block.
+pub fn complete_records_len(buf: &[u8]) -> usize {
+    memchr::memrchr(b'\n', buf).map_or(0, |i| i + 1)
+}
+
+fn padded_line(line: &[u8]) -> &str {
+    // `next_line` has checked the bytes with `validate_utf8` and reserved
+    // `SIMDJSON_PADDING` bytes of spare capacity.
+    unsafe { std::str::from_utf8_unchecked(line) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_lines() {
+        let input = "{\"a\":1}\n\n[1,2]\r\n  \n3";
+        let mut reader = Reader::new(input.as_bytes());
+        let mut types = Vec::new();
+        let counters = reader
+            .for_each(|element| {
+                types.push(element.get_type());
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            types,
+            [
+                dom::ElementType::Object,
+                dom::ElementType::Array,
+                dom::ElementType::Int64
+            ]
+        );
+        assert_eq!(counters.records, 3);
+        assert_eq!(counters.bytes, input.len());
+    }
+
+    #[test]
+    fn skip_invalid() {
+        let input = "1\n[\n\"too long for the limit\"\n2\n";
+        let mut reader = Reader::new(input.as_bytes())
+            .max_line_len(8)
+            .on_invalid(InvalidRecord::Skip);
+        let mut values = Vec::new();
+        let counters = reader
+            .for_each(|element| {
+                values.push(element.get_uint64()?);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(values, [1, 2]);
+        assert_eq!(counters.skipped, 2);
+    }
+
+    #[test]
+    fn skip_keeps_io_errors() {
+        struct Broken;
+
+        impl Read for Broken {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("broken"))
+            }
+        }
+
+        let mut reader =
+            Reader::new(std::io::BufReader::new(Broken)).on_invalid(InvalidRecord::Skip);
+        assert!(matches!(
+            reader.for_each(|_| Ok(())),
+            Err(SimdJsonError::StdIoError(_))
+        ));
+        assert_eq!(reader.counters().skipped, 0);
+    }
+
+    #[test]
+    fn fail_on_invalid() {
+        let mut reader = Reader::new("1\n[\n2\n".as_bytes());
+        assert!(reader.for_each(|_| Ok(())).is_err());
+        assert_eq!(reader.counters().records, 1);
+    }
+
+    #[test]
+    fn split() {
+        let input = "{\"a\":1}\n\n[1,2]\r\n  \n3";
+        assert_eq!(
+            split_records(input).collect::<Vec<_>>(),
+            ["{\"a\":1}", "[1,2]", "3"]
+        );
+        assert_eq!(split_records("\n\r\n").next(), None);
+        assert_eq!(complete_records_len(input.as_bytes()), input.len() - 1);
+        assert_eq!(complete_records_len(b"[1, 2"), 0);
+    }
+
+    #[test]
+    fn write_raw() {
+        let mut writer = Writer::new(Vec::new());
+        writer.write_raw("{\n  \"a\": [1, 2]\n}").unwrap();
+        writer.write_raw("true").unwrap();
+        assert_eq!(writer.counters().records, 2);
+        assert_eq!(writer.into_inner(), b"{\"a\":[1,2]}\ntrue\n");
+    }
+}
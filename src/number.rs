@@ -0,0 +1,233 @@
+use crate::{Result, SimdJsonError, utils::parse_double};
+
+/// Name of the newtype struct [`Number`] is (de)serialized as, which the
+/// crate's serde support recognizes to pass raw number text through.
+#[cfg(feature = "serde_impl")]
+pub(crate) const NUMBER_TOKEN: &str = "$simdjson_rust::Number";
+
+/// A JSON number as it was read, for applications that must write numbers
+/// back exactly as they found them.
+///
+/// Integers that fit 64 bits are held as integers; their text has only one
+/// spelling. Other numbers, floats and larger integers, are held as
+/// [`Raw`](Number::Raw) text by the ondemand deserializer, so `1.50`,
+/// `1e2` and `123456789012345678901234567890` are written back unchanged by
+/// [`StringBuilder::append_number`](crate::builder::StringBuilder::append_number)
+/// and [`to_string`](crate::serde::ser::to_string). The DOM only keeps parsed
+/// values, so deserializing from an `Element` yields [`F64`](Number::F64)
+/// for floats instead.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Number {
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    /// Number text in JSON syntax; see [`Number::from_raw`].
+    Raw(RawNumber),
+}
+
+/// The text of a [`Number::Raw`], known to be a JSON number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawNumber(String);
+
+impl RawNumber {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Number {
+    /// Wraps `text` as a [`Raw`](Number::Raw) number, failing with
+    /// [`SimdJsonError::NumberError`] unless it is a JSON number.
+    pub fn from_raw(text: impl Into<String>) -> Result<Self> {
+        let text = text.into();
+        if is_json_number(text.as_bytes()) {
+            Ok(Number::Raw(RawNumber(text)))
+        } else {
+            Err(SimdJsonError::NumberError)
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Number::I64(v) => Some(*v),
+            Number::U64(v) => i64::try_from(*v).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Number::I64(v) => u64::try_from(*v).ok(),
+            Number::U64(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// The nearest `f64`; `None` for raw text beyond its range.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Number::I64(v) => Some(*v as f64),
+            Number::U64(v) => Some(*v as f64),
+            Number::F64(v) => Some(*v),
+            Number::Raw(text) => parse_double(text.as_str().as_bytes()).ok(),
+        }
+    }
+}
+
+impl From<i64> for Number {
+    fn from(v: i64) -> Self {
+        Number::I64(v)
+    }
+}
+
+impl From<u64> for Number {
+    fn from(v: u64) -> Self {
+        Number::U64(v)
+    }
+}
+
+impl From<f64> for Number {
+    fn from(v: f64) -> Self {
+        Number::F64(v)
+    }
+}
+
+/// `-? (0 | [1-9][0-9]*) (\.[0-9]+)? ([eE][+-]?[0-9]+)?`
+pub(crate) fn is_json_number(text: &[u8]) -> bool {
+    fn digits(text: &[u8]) -> usize {
+        text.iter().take_while(|b| b.is_ascii_digit()).count()
+    }
+
+    let mut rest = text.strip_prefix(b"-").unwrap_or(text);
+    match digits(rest) {
+        0 => return false,
+        n if n > 1 && rest[0] == b'0' => return false,
+        n => rest = &rest[n..],
+    }
+    if let Some(fraction) = rest.strip_prefix(b".") {
+        match digits(fraction) {
+            0 => return false,
+            n => rest = &fraction[n..],
+        }
+    }
+    if let Some(exponent) = rest.strip_prefix(b"e").or_else(|| rest.strip_prefix(b"E")) {
+        let exponent = exponent
+            .strip_prefix(b"+")
+            .or_else(|| exponent.strip_prefix(b"-"))
+            .unwrap_or(exponent);
+        match digits(exponent) {
+            0 => return false,
+            n => rest = &exponent[n..],
+        }
+    }
+    rest.is_empty()
+}
+
+#[cfg(feature = "serde_impl")]
+mod serde_impl {
+    use std::fmt;
+
+    use serde::{
+        Deserialize, Deserializer, Serialize, Serializer,
+        de::{Error, MapAccess, Visitor},
+    };
+
+    use super::{NUMBER_TOKEN, Number, RawNumber, is_json_number};
+
+    impl Serialize for Number {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self {
+                Number::I64(v) => serializer.serialize_i64(*v),
+                Number::U64(v) => serializer.serialize_u64(*v),
+                Number::F64(v) => serializer.serialize_f64(*v),
+                // Other serializers see the text as a string, which does not
+                // deserialize back into a `Number`.
+                Number::Raw(text) => {
+                    serializer.serialize_newtype_struct(NUMBER_TOKEN, text.as_str())
+                }
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Number {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_newtype_struct(NUMBER_TOKEN, NumberVisitor)
+        }
+    }
+
+    struct NumberVisitor;
+
+    impl<'de> Visitor<'de> for NumberVisitor {
+        type Value = Number;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a JSON number")
+        }
+
+        fn visit_i64<E: Error>(self, v: i64) -> Result<Number, E> {
+            Ok(Number::I64(v))
+        }
+
+        fn visit_u64<E: Error>(self, v: u64) -> Result<Number, E> {
+            Ok(Number::U64(v))
+        }
+
+        fn visit_f64<E: Error>(self, v: f64) -> Result<Number, E> {
+            Ok(Number::F64(v))
+        }
+
+        /// Raw text, which the ondemand deserializer hands over as the one
+        /// entry of a map keyed by [`NUMBER_TOKEN`], so that a JSON string
+        /// is never taken for a number.
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Number, A::Error> {
+            if map.next_key::<String>()?.as_deref() != Some(NUMBER_TOKEN) {
+                return Err(A::Error::invalid_type(serde::de::Unexpected::Map, &self));
+            }
+            let text: String = map.next_value()?;
+            if is_json_number(text.as_bytes()) {
+                Ok(Number::Raw(RawNumber(text)))
+            } else {
+                Err(A::Error::invalid_value(
+                    serde::de::Unexpected::Str(&text),
+                    &self,
+                ))
+            }
+        }
+
+        fn visit_newtype_struct<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<Number, D::Error> {
+            deserializer.deserialize_any(self)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_numbers() {
+        for text in [
+            "0",
+            "-0",
+            "1.50",
+            "1e2",
+            "-2E-10",
+            "123456789012345678901234567890",
+        ] {
+            assert_eq!(
+                Number::from_raw(text).unwrap(),
+                Number::Raw(RawNumber(text.into()))
+            );
+        }
+        for text in ["", "-", "01", "1.", ".5", "1e", "+1", "1 ", "0x10", "NaN"] {
+            assert!(Number::from_raw(text).is_err(), "{text}");
+        }
+        assert_eq!(Number::from_raw("1e2").unwrap().as_f64(), Some(100.0));
+        assert_eq!(Number::from_raw("1e400").unwrap().as_f64(), None);
+        assert_eq!(Number::U64(u64::MAX).as_i64(), None);
+        assert_eq!(Number::I64(-1).as_u64(), None);
+    }
+}
@@ -5,7 +5,7 @@ use simdjson_sys as ffi;
 use super::{array_iterator::ArrayIterator, document::Document, value::Value};
 use crate::{
     error::Result,
-    macros::{impl_drop, map_result},
+    macros::{impl_drop, map_primitive_result, map_result},
     utils::string_view_to_str,
 };
 
@@ -81,6 +81,33 @@ impl<'a> Array<'a> {
         Ok(ArrayIterator::new(begin, end))
     }
 
+    /// Converts an array of numbers to `f64`, counting the elements first
+    /// and then parsing them in one tight loop. Integers are converted; any
+    /// other element is an
+    /// [`IncorrectType`](crate::SimdJsonError::IncorrectType) error.
+    pub fn to_vec_f64(&mut self) -> Result<Vec<f64>> {
+        self.copy_numbers(ffi::SJ_OD_array_copy_f64)
+    }
+
+    /// Converts an array of integers to `i64`, like
+    /// [`to_vec_f64`](Self::to_vec_f64). Floats, integers above `i64::MAX`
+    /// and non-numbers are errors.
+    pub fn to_vec_i64(&mut self) -> Result<Vec<i64>> {
+        self.copy_numbers(ffi::SJ_OD_array_copy_i64)
+    }
+
+    fn copy_numbers<T>(
+        &mut self,
+        copy: unsafe extern "C" fn(*mut ffi::SJ_OD_array, *mut T, usize) -> ffi::SJ_size_t_result,
+    ) -> Result<Vec<T>> {
+        let len = self.count_elements()?;
+        let mut out = Vec::with_capacity(len);
+        let written = map_primitive_result!(copy(self.ptr.as_mut(), out.as_mut_ptr(), len))?;
+        // `copy` initialized the first `written` values.
+        unsafe { out.set_len(written) };
+        Ok(out)
+    }
+
     pub fn raw_json(&mut self) -> Result<&'a str> {
         let sv = map_result!(
             ffi::SJ_OD_array_raw_json(self.ptr.as_mut()),
@@ -92,3 +119,22 @@ impl<'a> Array<'a> {
 }
 
 impl_drop!(Array<'a>, ffi::SJ_OD_array_free);
+
+#[cfg(test)]
+mod tests {
+    use crate::{SimdJsonError, ondemand::Parser, prelude::*};
+
+    #[test]
+    fn to_vec() {
+        let mut parser = Parser::default();
+        let json = r#"{"a": [1, -2, 3], "b": [0.5, 2], "c": [1, 2.5]}"#.to_padded_string();
+        let mut doc = parser.iterate(&json).unwrap();
+        let mut obj = doc.get_object().unwrap();
+        let mut a = obj.find_field("a").unwrap().get_array().unwrap();
+        assert_eq!(a.to_vec_i64().unwrap(), [1, -2, 3]);
+        let mut b = obj.find_field("b").unwrap().get_array().unwrap();
+        assert_eq!(b.to_vec_f64().unwrap(), [0.5, 2.0]);
+        let mut c = obj.find_field("c").unwrap().get_array().unwrap();
+        assert!(matches!(c.to_vec_i64(), Err(SimdJsonError::IncorrectType)));
+    }
+}
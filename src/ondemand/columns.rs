@@ -0,0 +1,228 @@
+use super::{Array, Document, Value};
+use crate::{Result, SimdJsonError, pointer::escape_segment};
+
+/// The Rust type a [`Columns`] field is read as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    Bool,
+    I64,
+    U64,
+    /// Integers are accepted and converted.
+    F64,
+    String,
+}
+
+/// The values of one field across all rows.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnData {
+    Bool(Vec<bool>),
+    I64(Vec<i64>),
+    U64(Vec<u64>),
+    F64(Vec<f64>),
+    String(Vec<String>),
+}
+
+impl ColumnData {
+    fn new(ty: ColumnType) -> Self {
+        match ty {
+            ColumnType::Bool => ColumnData::Bool(Vec::new()),
+            ColumnType::I64 => ColumnData::I64(Vec::new()),
+            ColumnType::U64 => ColumnData::U64(Vec::new()),
+            ColumnType::F64 => ColumnData::F64(Vec::new()),
+            ColumnType::String => ColumnData::String(Vec::new()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            ColumnData::Bool(v) => v.len(),
+            ColumnData::I64(v) => v.len(),
+            ColumnData::U64(v) => v.len(),
+            ColumnData::F64(v) => v.len(),
+            ColumnData::String(v) => v.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn push(&mut self, value: &mut Value<'_>) -> Result<()> {
+        match self {
+            ColumnData::Bool(v) => v.push(value.get_bool()?),
+            ColumnData::I64(v) => v.push(value.get_int64()?),
+            ColumnData::U64(v) => v.push(value.get_uint64()?),
+            ColumnData::F64(v) => v.push(value.get_double()?),
+            ColumnData::String(v) => v.push(value.get_string(false)?.to_owned()),
+        }
+        Ok(())
+    }
+
+    fn push_default(&mut self) {
+        match self {
+            ColumnData::Bool(v) => v.push(false),
+            ColumnData::I64(v) => v.push(0),
+            ColumnData::U64(v) => v.push(0),
+            ColumnData::F64(v) => v.push(0.0),
+            ColumnData::String(v) => v.push(String::new()),
+        }
+    }
+}
+
+/// One extracted field. Rows where it is missing or `null` hold the type's
+/// default in `data` and `false` in `valid`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Column {
+    pub name: String,
+    pub data: ColumnData,
+    pub valid: Vec<bool>,
+}
+
+impl Column {
+    pub fn null_count(&self) -> usize {
+        self.valid.iter().filter(|valid| !**valid).count()
+    }
+}
+
+/// Fields to pull out of an array of objects into one `Vec` per field, in a
+/// single ondemand pass, instead of deserializing every row into a struct
+/// and transposing.
+///
+/// Fields that are not asked for are skipped without being parsed. Only the
+/// first occurrence of a duplicated key counts.
+///
+/// ```ignore
+/// let columns = Columns::new()
+///     .column("id", ColumnType::U64)
+///     .column("price", ColumnType::F64)
+///     .extract(&mut doc)?;
+/// let ColumnData::U64(ids) = &columns[0].data else { unreachable!() };
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Columns {
+    fields: Vec<(String, ColumnType)>,
+}
+
+impl Columns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn column(mut self, name: impl Into<String>, ty: ColumnType) -> Self {
+        self.fields.push((name.into(), ty));
+        self
+    }
+
+    /// Extracts the columns from a document whose root is the array.
+    pub fn extract(&self, doc: &mut Document<'_, '_>) -> Result<Vec<Column>> {
+        self.extract_array(&mut doc.get_array()?)
+    }
+
+    /// Extracts the columns from `array`, returning them in the order they
+    /// were added. Each element must be an object; a value of the wrong type
+    /// fails with an [`SimdJsonError::AtPointer`] naming it.
+    pub fn extract_array(&self, array: &mut Array<'_>) -> Result<Vec<Column>> {
+        let mut columns: Vec<_> = self
+            .fields
+            .iter()
+            .map(|(name, ty)| Column {
+                name: name.clone(),
+                data: ColumnData::new(*ty),
+                valid: Vec::new(),
+            })
+            .collect();
+        let mut seen = vec![false; columns.len()];
+        for (row, element) in array.iter()?.enumerate() {
+            seen.fill(false);
+            let fields = element
+                .and_then(|mut element| element.get_object()?.iter())
+                .map_err(|source| at(row, None, source))?;
+            for field in fields {
+                let mut field = field.map_err(|source| at(row, None, source))?;
+                let key = field
+                    .unescaped_key(false)
+                    .map_err(|source| at(row, None, source))?;
+                let Some(index) = columns.iter().position(|column| column.name == key) else {
+                    continue;
+                };
+                if seen[index] {
+                    continue;
+                }
+                seen[index] = true;
+                let column = &mut columns[index];
+                let mut value = field.take_value();
+                let read = value.is_null().and_then(|null| {
+                    if !null {
+                        column.data.push(&mut value)?;
+                    }
+                    Ok(!null)
+                });
+                let valid = read.map_err(|source| at(row, Some(&column.name), source))?;
+                if !valid {
+                    column.data.push_default();
+                }
+                column.valid.push(valid);
+            }
+            for (column, seen) in columns.iter_mut().zip(&seen) {
+                if !seen {
+                    column.data.push_default();
+                    column.valid.push(false);
+                }
+            }
+        }
+        Ok(columns)
+    }
+}
+
+fn at(row: usize, field: Option<&str>, source: SimdJsonError) -> SimdJsonError {
+    let mut pointer = format!("/{row}");
+    if let Some(field) = field {
+        pointer.push('/');
+        escape_segment(field, &mut pointer);
+    }
+    SimdJsonError::AtPointer {
+        pointer,
+        source: Box::new(source),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ondemand::Parser, prelude::*};
+
+    #[test]
+    fn extract_columns() {
+        let json = r#"[
+            {"id": 1, "name": "a", "price": 1.5, "skip": {"x": [1, 2]}},
+            {"name": "b", "id": 2, "price": 3},
+            {"id": 3, "price": null, "id": 4}
+        ]"#
+        .to_padded_string();
+        let spec = Columns::new()
+            .column("id", ColumnType::U64)
+            .column("price", ColumnType::F64)
+            .column("name", ColumnType::String);
+        let mut parser = Parser::default();
+        let mut doc = parser.iterate(&json).unwrap();
+        let columns = spec.extract(&mut doc).unwrap();
+
+        assert_eq!(columns[0].data, ColumnData::U64(vec![1, 2, 3]));
+        assert_eq!(columns[0].null_count(), 0);
+        assert_eq!(columns[1].data, ColumnData::F64(vec![1.5, 3.0, 0.0]));
+        assert_eq!(columns[1].valid, [true, true, false]);
+        assert_eq!(
+            columns[2].data,
+            ColumnData::String(vec!["a".into(), "b".into(), String::new()])
+        );
+        assert_eq!(columns[2].null_count(), 1);
+        drop(doc);
+
+        let json = r#"[{"id": 1}, {"id": "2"}]"#.to_padded_string();
+        let mut doc = parser.iterate(&json).unwrap();
+        match spec.extract(&mut doc) {
+            Err(SimdJsonError::AtPointer { pointer, .. }) => assert_eq!(pointer, "/1/id"),
+            other => panic!("{other:?}"),
+        }
+    }
+}
@@ -4,9 +4,11 @@ use simdjson_sys as ffi;
 
 use super::{JsonType, array::Array, number::Number, object::Object, parser::Parser, value::Value};
 use crate::{
-    error::Result,
-    macros::{impl_drop, map_result},
-    utils::string_view_to_str,
+    CompiledPointer,
+    error::{Result, SimdJsonError},
+    macros::{impl_drop, map_primitive_result, map_result},
+    number::is_json_number,
+    utils::{string_view_struct_to_str, string_view_to_str},
 };
 
 pub struct Document<'p, 's> {
@@ -23,6 +25,12 @@ impl<'p, 's> Document<'p, 's> {
         }
     }
 
+    /// Moves back to the start of the document so it can be iterated again.
+    /// Values obtained before are invalidated.
+    pub fn rewind(&mut self) {
+        unsafe { ffi::SJ_OD_document_rewind(self.ptr.as_ptr()) }
+    }
+
     pub fn get_uint64(&mut self) -> Result<u64> {
         map_result!(
             primitive,
@@ -117,6 +125,18 @@ impl<'p, 's> Document<'p, 's> {
         .map(Value::new)
     }
 
+    /// Like [`at_pointer`](Self::at_pointer), with a pointer parsed ahead of
+    /// time. The document is rewound first, so lookups can come in any order.
+    pub fn at_compiled_pointer<'a>(&mut self, pointer: &CompiledPointer) -> Result<Value<'a>> {
+        let tokens = pointer.tokens();
+        map_result!(
+            ffi::SJ_OD_document_at_path(self.ptr.as_mut(), tokens.as_ptr(), tokens.len()),
+            ffi::SJ_OD_value_result_error,
+            ffi::SJ_OD_value_result_value_unsafe
+        )
+        .map(Value::new)
+    }
+
     pub fn get_number<'a>(&mut self) -> Result<Number<'a>> {
         map_result!(
             ffi::SJ_OD_document_get_number(self.ptr.as_mut()),
@@ -144,13 +164,28 @@ impl<'p, 's> Document<'p, 's> {
         )?;
         Ok(JsonType::from(json_type))
     }
+
+    /// [`Value::get_raw_number_as_str`] for a document that is a single
+    /// number.
+    pub fn get_raw_number_as_str(&mut self) -> Result<&'s str> {
+        if self.json_type()? != JsonType::Number {
+            return Err(SimdJsonError::IncorrectType);
+        }
+        let token = map_primitive_result!(ffi::SJ_OD_document_raw_json_token(self.ptr.as_mut()))
+            .map(string_view_struct_to_str)?
+            .trim_end_matches([' ', '\t', '\n', '\r']);
+        if !is_json_number(token.as_bytes()) {
+            return Err(SimdJsonError::NumberError);
+        }
+        Ok(token)
+    }
 }
 
 impl_drop!(Document<'p, 's>, ffi::SJ_OD_document_free);
 
 #[cfg(test)]
 mod tests {
-    use crate::{ondemand, prelude::*};
+    use crate::{SimdJsonError, ondemand, prelude::*};
 
     #[test]
     fn get_bool() {
@@ -172,4 +207,58 @@ mod tests {
             assert!(doc.get_bool().is_err());
         }
     }
+
+    #[test]
+    fn raw_number() {
+        let mut parser = ondemand::Parser::default();
+        let json = "123456789012345678901234567890 ".to_padded_string();
+        let mut doc = parser.iterate(&json).unwrap();
+        assert_eq!(
+            doc.get_raw_number_as_str().unwrap(),
+            "123456789012345678901234567890"
+        );
+        drop(doc);
+
+        let json = r#"{"a": 1.000000000000000000001, "b": "1"}"#.to_padded_string();
+        let mut doc = parser.iterate(&json).unwrap();
+        let mut object = doc.get_object().unwrap();
+        assert_eq!(
+            object
+                .find_field("a")
+                .unwrap()
+                .get_raw_number_as_str()
+                .unwrap(),
+            "1.000000000000000000001"
+        );
+        assert!(
+            object
+                .find_field("b")
+                .unwrap()
+                .get_raw_number_as_str()
+                .is_err()
+        );
+        drop(object);
+        drop(doc);
+
+        for bad in ["1x", "01", "-"] {
+            let json = bad.to_padded_string();
+            let mut doc = parser.iterate(&json).unwrap();
+            assert!(
+                matches!(doc.get_raw_number_as_str(), Err(SimdJsonError::NumberError)),
+                "{bad}"
+            );
+            drop(doc);
+
+            let json = format!("[{bad}]").to_padded_string();
+            let mut doc = parser.iterate(&json).unwrap();
+            let mut value = doc.get_array().unwrap().at(0).unwrap();
+            assert!(
+                matches!(
+                    value.get_raw_number_as_str(),
+                    Err(SimdJsonError::NumberError)
+                ),
+                "{bad}"
+            );
+        }
+    }
 }
@@ -0,0 +1,232 @@
+use std::marker::PhantomData;
+
+use super::{
+    JsonType, NumberType, array_iterator::ArrayIterator, document::Document, number::Number,
+    object_iterator::ObjectIterator, value::Value,
+};
+use crate::Result;
+
+/// A single token of a JSON document, as produced by [`Events`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'a> {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    /// An object key; the next event is its value.
+    Key(&'a str),
+    String(&'a str),
+    Int64(i64),
+    UInt64(u64),
+    Double(f64),
+    Bool(bool),
+    Null,
+}
+
+enum Frame<'a> {
+    Object(ObjectIterator<'a>),
+    Array(ArrayIterator<'a>),
+}
+
+/// Pull-based event stream over an ondemand document.
+///
+/// Every value is visited, so this is no faster than a full parse, but it lets
+/// callers build their own data structures or transcode without the DOM or
+/// serde. The stream ends after the first error.
+pub struct Events<'a> {
+    stack: Vec<Frame<'a>>,
+    pending: Option<Value<'a>>,
+    root: Option<Result<Event<'a>>>,
+    done: bool,
+    _doc: PhantomData<&'a mut Document<'a, 'a>>,
+}
+
+impl<'a> Events<'a> {
+    pub fn new(doc: &'a mut Document<'_, '_>) -> Self {
+        let (pending, root) = match root_event(doc) {
+            Ok(None) => match doc.get_value() {
+                Ok(value) => (Some(value), None),
+                Err(e) => (None, Some(Err(e))),
+            },
+            root => (None, root.transpose()),
+        };
+        Self {
+            stack: Vec::new(),
+            pending,
+            root,
+            done: false,
+            _doc: PhantomData,
+        }
+    }
+
+    /// Streams the events of a single value, e.g. one found with
+    /// [`Value::at_pointer`] or a [`Projection`](super::Projection).
+    pub fn from_value(value: Value<'a>) -> Self {
+        Self {
+            stack: Vec::new(),
+            pending: Some(value),
+            root: None,
+            done: false,
+            _doc: PhantomData,
+        }
+    }
+
+    /// Nesting depth of the current position; 0 outside of any container.
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    fn step(&mut self) -> Option<Result<Event<'a>>> {
+        if let Some(root) = self.root.take() {
+            return Some(root);
+        }
+        if let Some(value) = self.pending.take() {
+            return Some(self.open(value));
+        }
+        let event = match self.stack.last_mut()? {
+            Frame::Object(fields) => match fields.next() {
+                Some(field) => field.and_then(|mut field| {
+                    let key = field.unescaped_key(false)?;
+                    self.pending = Some(field.take_value());
+                    Ok(Event::Key(key))
+                }),
+                None => {
+                    self.stack.pop();
+                    Ok(Event::EndObject)
+                }
+            },
+            Frame::Array(elements) => match elements.next() {
+                Some(element) => element.and_then(|value| self.open(value)),
+                None => {
+                    self.stack.pop();
+                    Ok(Event::EndArray)
+                }
+            },
+        };
+        Some(event)
+    }
+
+    fn open(&mut self, mut value: Value<'a>) -> Result<Event<'a>> {
+        Ok(match value.json_type()? {
+            JsonType::Object => {
+                let fields = value.get_object()?.iter()?;
+                self.stack.push(Frame::Object(fields));
+                Event::StartObject
+            }
+            JsonType::Array => {
+                let elements = value.get_array()?.iter()?;
+                self.stack.push(Frame::Array(elements));
+                Event::StartArray
+            }
+            JsonType::Number => number_event(value.get_number()?),
+            JsonType::String => Event::String(value.get_string(false)?),
+            JsonType::Boolean => Event::Bool(value.get_bool()?),
+            JsonType::Null => {
+                value.is_null()?;
+                Event::Null
+            }
+        })
+    }
+}
+
+impl<'a> Iterator for Events<'a> {
+    type Item = Result<Event<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let event = self.step();
+        if !matches!(event, Some(Ok(_))) {
+            self.done = true;
+        }
+        event
+    }
+}
+
+/// Scalar documents cannot be turned into a `Value`, so their only event is
+/// read straight from the document.
+fn root_event<'a>(doc: &mut Document<'_, '_>) -> Result<Option<Event<'a>>> {
+    Ok(Some(match doc.json_type()? {
+        JsonType::Object | JsonType::Array => return Ok(None),
+        JsonType::Number => number_event(doc.get_number()?),
+        JsonType::String => Event::String(doc.get_string()?),
+        JsonType::Boolean => Event::Bool(doc.get_bool()?),
+        JsonType::Null => {
+            doc.is_null()?;
+            Event::Null
+        }
+    }))
+}
+
+fn number_event<'a>(mut number: Number<'_>) -> Event<'a> {
+    match number.get_number_type() {
+        NumberType::UnsignedInteger => Event::UInt64(number.get_uint64()),
+        NumberType::SignedInteger => Event::Int64(number.get_int64()),
+        NumberType::FloatingPointNumber => Event::Double(number.get_double()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ondemand::Parser, prelude::*};
+
+    #[test]
+    fn event_stream() {
+        let json = r#"{"a": [1, -2, 3.5], "b": {"c": "x"}, "d": [true, null]}"#.to_padded_string();
+        let mut parser = Parser::default();
+        let mut doc = parser.iterate(&json).unwrap();
+        let events: Vec<_> = Events::new(&mut doc).collect::<Result<_>>().unwrap();
+        assert_eq!(
+            events,
+            [
+                Event::StartObject,
+                Event::Key("a"),
+                Event::StartArray,
+                Event::Int64(1),
+                Event::Int64(-2),
+                Event::Double(3.5),
+                Event::EndArray,
+                Event::Key("b"),
+                Event::StartObject,
+                Event::Key("c"),
+                Event::String("x"),
+                Event::EndObject,
+                Event::Key("d"),
+                Event::StartArray,
+                Event::Bool(true),
+                Event::Null,
+                Event::EndArray,
+                Event::EndObject,
+            ]
+        );
+    }
+
+    #[test]
+    fn value_events() {
+        let json = r#"{"a": {"b": [1, "x"]}}"#.to_padded_string();
+        let mut parser = Parser::default();
+        let mut doc = parser.iterate(&json).unwrap();
+        let value = doc.at_pointer("/a/b").unwrap();
+        let events: Vec<_> = Events::from_value(value).collect::<Result<_>>().unwrap();
+        assert_eq!(
+            events,
+            [
+                Event::StartArray,
+                Event::Int64(1),
+                Event::String("x"),
+                Event::EndArray
+            ]
+        );
+    }
+
+    #[test]
+    fn scalar_document() {
+        let json = "42".to_padded_string();
+        let mut parser = Parser::default();
+        let mut doc = parser.iterate(&json).unwrap();
+        let events: Vec<_> = Events::new(&mut doc).collect::<Result<_>>().unwrap();
+        assert_eq!(events, [Event::Int64(42)]);
+    }
+}
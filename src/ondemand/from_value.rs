@@ -0,0 +1,42 @@
+use super::Value;
+use crate::Result;
+
+/// Conversion from an ondemand value into a Rust value, for
+/// [`Object::to_map_of`](super::Object::to_map_of). The ondemand counterpart
+/// of [`dom::FromElement`](crate::dom::FromElement).
+///
+/// Strings are read without replacing invalid escapes.
+pub trait FromValue<'a>: Sized {
+    fn from_value(value: &mut Value<'a>) -> Result<Self>;
+}
+
+macro_rules! impl_from_value {
+    ($($ty:ty => $get:expr),* $(,)?) => {
+        $(
+            impl<'a> FromValue<'a> for $ty {
+                fn from_value(value: &mut Value<'a>) -> Result<Self> {
+                    $get(value)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_value! {
+    bool => Value::get_bool,
+    i64 => Value::get_int64,
+    u64 => Value::get_uint64,
+    f64 => Value::get_double,
+    &'a str => |value: &mut Value<'a>| value.get_string(false),
+    String => |value: &mut Value<'a>| value.get_string(false).map(str::to_owned),
+}
+
+/// `null` becomes `None`.
+impl<'a, T: FromValue<'a>> FromValue<'a> for Option<T> {
+    fn from_value(value: &mut Value<'a>) -> Result<Self> {
+        if value.is_null()? {
+            return Ok(None);
+        }
+        T::from_value(value).map(Some)
+    }
+}
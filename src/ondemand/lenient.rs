@@ -0,0 +1,236 @@
+use super::{Event, Events, Parser, Segment};
+use crate::{SimdJsonError, builder::StringBuilder, pointer::escape_segment};
+
+/// What [`Parser::iterate_lenient`] salvaged from a damaged document.
+#[derive(Debug)]
+pub struct Recovered {
+    /// Everything read before parsing stopped, as compact JSON with the open
+    /// arrays and objects closed. A key whose value could not be read is
+    /// left out. `None` if not even the start of the document was readable.
+    pub json: Option<String>,
+    /// The problems met, in order. Empty if the document is valid.
+    pub errors: Vec<RecoveryError>,
+}
+
+/// One problem met by [`Parser::iterate_lenient`].
+#[derive(Debug)]
+pub struct RecoveryError {
+    pub error: SimdJsonError,
+    /// JSON pointer to the value that was being read, when known.
+    pub pointer: Option<String>,
+    /// Byte offset in the input, when known.
+    pub offset: Option<usize>,
+}
+
+struct Level<'a> {
+    object: bool,
+    len: usize,
+    /// Key of the value being read, in objects.
+    key: Option<&'a str>,
+}
+
+impl Parser {
+    /// Parses as much of `padded_string` as possible, for data-recovery
+    /// tooling that would rather keep a damaged document's readable prefix
+    /// than reject all of it.
+    ///
+    /// The ondemand parser stops at the first error; everything read up to
+    /// that point is returned. A truncated document, which the parser would
+    /// reject outright, is first cut before any unterminated string and has
+    /// its open arrays and objects closed, so both the truncation and the
+    /// error it then leads to are reported.
+    ///
+    /// ```ignore
+    /// let recovered = parser.iterate_lenient(&r#"{"a": [1, 2], "b": "trunc"#.to_padded_string());
+    /// assert_eq!(recovered.json.as_deref(), Some(r#"{"a":[1,2]}"#));
+    /// ```
+    pub fn iterate_lenient(&mut self, padded_string: &String) -> Recovered {
+        let mut errors = Vec::new();
+        let repaired;
+        let mut input = padded_string;
+        if let Some((error, offset, json)) = repair(input.as_bytes()) {
+            errors.push(RecoveryError {
+                error,
+                pointer: None,
+                offset: Some(offset),
+            });
+            repaired = json;
+            input = &repaired;
+        }
+        let mut doc = match self.iterate(input) {
+            Ok(doc) => doc,
+            Err(error) => {
+                errors.push(RecoveryError {
+                    error,
+                    pointer: None,
+                    offset: None,
+                });
+                return Recovered { json: None, errors };
+            }
+        };
+
+        let mut out = StringBuilder::with_capacity(input.len());
+        let mut levels: Vec<Level<'_>> = Vec::new();
+        let mut written = false;
+        for event in Events::new(&mut doc) {
+            let event = match event {
+                Ok(event) => event,
+                Err(error) => {
+                    errors.push(RecoveryError {
+                        error,
+                        pointer: Some(pointer(&levels)),
+                        offset: None,
+                    });
+                    break;
+                }
+            };
+            if let Event::Key(key) = event {
+                if let Some(level) = levels.last_mut() {
+                    level.key = Some(key);
+                }
+                continue;
+            }
+            if let Event::EndObject | Event::EndArray = event {
+                levels.pop();
+                if event == Event::EndObject {
+                    out.end_object();
+                } else {
+                    out.end_array();
+                }
+                continue;
+            }
+            if let Some(level) = levels.last_mut() {
+                if level.object {
+                    out.append_field_header(level.key.unwrap_or_default(), level.len == 0);
+                } else if level.len > 0 {
+                    out.append_comma();
+                }
+                level.len += 1;
+            }
+            written = true;
+            match event {
+                Event::StartObject | Event::StartArray => {
+                    let object = event == Event::StartObject;
+                    levels.push(Level {
+                        object,
+                        len: 0,
+                        key: None,
+                    });
+                    if object {
+                        out.start_object();
+                    } else {
+                        out.start_array();
+                    }
+                }
+                Event::String(s) => out.append_string(s),
+                Event::Int64(v) => out.append_i64(v),
+                Event::UInt64(v) => out.append_u64(v),
+                Event::Double(v) => out.append_f64(v),
+                Event::Bool(v) => out.append_bool(v),
+                Event::Null => out.append_null(),
+                Event::Key(_) | Event::EndObject | Event::EndArray => unreachable!(),
+            }
+        }
+        for level in levels.iter().rev() {
+            if level.object {
+                out.end_object();
+            } else {
+                out.end_array();
+            }
+        }
+        let json = written.then(|| out.into_string().ok()).flatten();
+        Recovered { json, errors }
+    }
+}
+
+/// Pointer to the value being read at the innermost open container.
+fn pointer(levels: &[Level<'_>]) -> String {
+    let last = levels.len().saturating_sub(1);
+    let segments = levels.iter().enumerate().filter_map(|(i, level)| {
+        if level.object {
+            level.key.map(Segment::Key)
+        } else if i == last {
+            Some(Segment::Index(level.len))
+        } else {
+            Some(Segment::Index(level.len - 1))
+        }
+    });
+    segments
+        .map(|segment| {
+            let mut pointer = String::from("/");
+            escape_segment(&segment.to_string(), &mut pointer);
+            pointer
+        })
+        .collect()
+}
+
+/// Makes a truncated document balanced: cuts it before a string that is
+/// never closed and closes the arrays and objects left open. Returns the
+/// problem found, its offset and the repaired copy, or `None` if the
+/// document is not truncated.
+fn repair(json: &[u8]) -> Option<(SimdJsonError, usize, String)> {
+    let mut closers = Vec::new();
+    let mut string = None;
+    let mut escaped = false;
+    for (i, &b) in json.iter().enumerate() {
+        match (string, b) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), b'\\') => escaped = true,
+            (Some(_), b'"') => string = None,
+            (Some(_), _) => {}
+            (None, b'"') => string = Some(i),
+            (None, b'{') => closers.push(b'}'),
+            (None, b'[') => closers.push(b']'),
+            (None, b'}' | b']') if closers.last() == Some(&b) => {
+                closers.pop();
+            }
+            // A mismatched bracket is left for the parser to report.
+            _ => {}
+        }
+    }
+    let (error, cut) = match string {
+        Some(quote) => (SimdJsonError::UnclosedString, quote),
+        None if !closers.is_empty() => (SimdJsonError::IncompleteArrayOrObject, json.len()),
+        None => return None,
+    };
+    let mut repaired = String::with_capacity(cut + closers.len() + crate::SIMDJSON_PADDING);
+    // `cut` is at a quote or the end, so on a character boundary.
+    repaired.push_str(std::str::from_utf8(&json[..cut]).ok()?);
+    repaired.extend(closers.iter().rev().map(|&b| b as char));
+    Some((error, cut, repaired))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn recover_prefix() {
+        let mut parser = Parser::default();
+        let valid = parser.iterate_lenient(&r#"{"a": [1]}"#.to_padded_string());
+        assert_eq!(valid.json.as_deref(), Some(r#"{"a":[1]}"#));
+        assert!(valid.errors.is_empty());
+
+        let json = r#"{"a": [1, {"b": true}], "c": {"d": "x\"y", "e": "trunc"#;
+        let recovered = parser.iterate_lenient(&json.to_padded_string());
+        assert_eq!(
+            recovered.json.as_deref(),
+            Some(r#"{"a":[1,{"b":true}],"c":{"d":"x\"y"}}"#)
+        );
+        assert_eq!(recovered.errors[0].offset, Some(json.rfind('"').unwrap()));
+        assert_eq!(recovered.errors.len(), 2);
+        assert_eq!(recovered.errors[1].pointer.as_deref(), Some("/c/e"));
+
+        let broken = parser.iterate_lenient(&"[1, 2, tru]".to_padded_string());
+        assert_eq!(broken.json.as_deref(), Some("[1,2]"));
+        assert_eq!(broken.errors[0].pointer.as_deref(), Some("/2"));
+
+        let cut = parser.iterate_lenient(&"[[1, 2], [3".to_padded_string());
+        assert!(matches!(
+            cut.errors[0].error,
+            SimdJsonError::IncompleteArrayOrObject
+        ));
+        assert_eq!(cut.json.as_deref(), Some("[[1,2],[3]]"));
+    }
+}
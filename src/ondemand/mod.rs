@@ -1,21 +1,42 @@
 mod array;
 mod array_iterator;
+mod columns;
 mod document;
+mod events;
 mod field;
+mod from_value;
 mod json_type;
+#[cfg(feature = "builder")]
+mod lenient;
 mod number;
 mod object;
 mod object_iterator;
 mod parser;
+mod probe;
+mod projection;
+mod shard;
+#[cfg(feature = "builder")]
+mod splice;
+mod top_level;
 mod value;
 
+pub use crate::pointer::Segment;
 pub use array::Array;
 pub use array_iterator::ArrayIterator;
+pub use columns::{Column, ColumnData, ColumnType, Columns};
 pub use document::Document;
+pub use events::{Event, Events};
 pub use field::Field;
+pub use from_value::FromValue;
 pub use json_type::{JsonType, NumberType};
+#[cfg(feature = "builder")]
+pub use lenient::{Recovered, RecoveryError};
 pub use number::Number;
 pub use object::Object;
 pub use object_iterator::ObjectIterator;
 pub use parser::Parser;
+pub use probe::Probe;
+pub use projection::Projection;
+pub use shard::{Shard, par_map_lines, shard_lines};
+pub use top_level::TopLevelArray;
 pub use value::Value;
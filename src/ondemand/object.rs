@@ -1,8 +1,8 @@
-use std::{marker::PhantomData, ptr::NonNull};
+use std::{collections::HashMap, marker::PhantomData, ptr::NonNull};
 
 use simdjson_sys as ffi;
 
-use super::{document::Document, object_iterator::ObjectIterator, value::Value};
+use super::{FromValue, document::Document, object_iterator::ObjectIterator, value::Value};
 use crate::{
     error::Result,
     macros::{impl_drop, map_result},
@@ -98,6 +98,31 @@ impl<'a> Object<'a> {
         )
     }
 
+    /// Converts every field's value with [`FromValue`] into a map sized from
+    /// [`count_fields`](Self::count_fields). Stops at the first value that
+    /// does not convert; a repeated key keeps its last value.
+    pub fn to_map_of<T: FromValue<'a>>(&mut self) -> Result<HashMap<String, T>> {
+        let mut map = HashMap::with_capacity(self.count_fields()?);
+        self.extend_map(&mut map)?;
+        Ok(map)
+    }
+
+    /// Like [`to_map_of`](Self::to_map_of), into any map type, such as a
+    /// `BTreeMap` or a `HashMap` with another hasher.
+    pub fn extend_map<T, M>(&mut self, map: &mut M) -> Result<()>
+    where
+        T: FromValue<'a>,
+        M: Extend<(String, T)>,
+    {
+        for field in self.iter()? {
+            let mut field = field?;
+            let key = field.unescaped_key(false)?.to_owned();
+            let value = T::from_value(&mut field.take_value())?;
+            map.extend([(key, value)]);
+        }
+        Ok(())
+    }
+
     pub fn reset(&mut self) -> Result<bool> {
         map_result!(
             primitive,
@@ -109,3 +134,29 @@ impl<'a> Object<'a> {
 }
 
 impl_drop!(Object<'a>, ffi::SJ_OD_object_free);
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{ondemand::Parser, prelude::*};
+
+    #[test]
+    fn to_map_of() {
+        let mut parser = Parser::default();
+        let json = r#"{"a\nb": 1.5, "c": 2, "d": null}"#.to_padded_string();
+        let map = {
+            let mut doc = parser.iterate(&json).unwrap();
+            let mut object = doc.get_object().unwrap();
+            object.to_map_of::<Option<f64>>().unwrap()
+        };
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["a\nb"], Some(1.5));
+        assert_eq!(map["d"], None);
+
+        let mut doc = parser.iterate(&json).unwrap();
+        let mut sorted = BTreeMap::new();
+        let result = doc.get_object().unwrap().extend_map::<u64, _>(&mut sorted);
+        assert!(result.is_err());
+    }
+}
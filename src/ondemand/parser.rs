@@ -2,14 +2,21 @@ use std::ptr::NonNull;
 
 use simdjson_sys as ffi;
 
-use super::document::Document;
+use super::{document::Document, events::Events};
+#[cfg(feature = "allocator")]
+use crate::alloc::{BufferAllocator, PaddedBuffer};
 use crate::{
+    BomPolicy, ParseLimits, ParseStats,
     error::Result,
     macros::{impl_drop, map_result},
+    trace::ParseSpan,
 };
 
 pub struct Parser {
     ptr: NonNull<ffi::SJ_OD_parser>,
+    limits: ParseLimits,
+    bom: BomPolicy,
+    stats: ParseStats,
 }
 
 impl Default for Parser {
@@ -21,21 +28,146 @@ impl Default for Parser {
 impl Parser {
     pub fn new(max_capacity: usize) -> Self {
         let ptr = unsafe { NonNull::new_unchecked(ffi::SJ_OD_parser_new(max_capacity)) };
-        Self { ptr }
+        Self {
+            ptr,
+            limits: ParseLimits {
+                max_document_bytes: max_capacity,
+                ..ParseLimits::default()
+            },
+            bom: BomPolicy::default(),
+            stats: ParseStats::default(),
+        }
+    }
+
+    /// Creates a parser that rejects documents exceeding `limits`.
+    ///
+    /// Unless only the document size is limited, [`Parser::iterate`] walks
+    /// the whole document once to check it and then rewinds it, so lazy
+    /// access no longer skips the unread parts.
+    pub fn with_limits(limits: ParseLimits) -> Self {
+        let mut parser = Self::new(limits.max_document_bytes);
+        parser.limits = limits;
+        parser
+    }
+
+    pub fn limits(&self) -> &ParseLimits {
+        &self.limits
+    }
+
+    pub fn bom_policy(&self) -> BomPolicy {
+        self.bom
+    }
+
+    /// Sets what [`Parser::iterate`] does with a UTF-8 byte order mark. It is
+    /// skipped by default.
+    pub fn set_bom_policy(&mut self, policy: BomPolicy) {
+        self.bom = policy;
+    }
+
+    /// Largest document in bytes the parser can handle without growing its
+    /// buffers.
+    pub fn capacity(&self) -> usize {
+        unsafe { ffi::SJ_OD_parser_capacity(self.ptr.as_ptr()) }
+    }
+
+    /// Approximate number of bytes held by the parser's internal buffers:
+    /// the stage 1 indexes, the depth stacks and the string buffer. Allocator
+    /// overhead is not included.
+    pub fn allocated_bytes(&self) -> usize {
+        unsafe { ffi::SJ_OD_parser_allocated_bytes(self.ptr.as_ptr()) }
+    }
+
+    /// Sizes the parser's buffers for documents of up to `capacity` bytes,
+    /// shrinking them if they are larger. The buffer strings are unescaped
+    /// into is part of this: simdjson sizes it at 5/3 of the capacity, enough
+    /// for any string in such a document, and reuses it for every document.
+    ///
+    /// Pre-sizing avoids reallocating while a workload of large documents
+    /// warms up; shrinking after a large document returns its memory.
+    /// Documents larger than `capacity` still grow the buffers again, up to
+    /// [`Parser::max_capacity`].
+    pub fn allocate(&mut self, capacity: usize) -> Result<()> {
+        let code = unsafe { ffi::SJ_OD_parser_allocate(self.ptr.as_ptr(), capacity) };
+        if code != 0 {
+            return Err(code.into());
+        }
+        Ok(())
+    }
+
+    /// Largest document in bytes the parser grows its buffers for.
+    pub fn max_capacity(&self) -> usize {
+        unsafe { ffi::SJ_OD_parser_max_capacity(self.ptr.as_ptr()) }
+    }
+
+    /// Caps how far the buffers may grow, and so the size of accepted
+    /// documents. Buffers that are already larger are kept, and documents
+    /// that fit them still accepted, until the next [`Parser::allocate`].
+    pub fn set_max_capacity(&mut self, max_capacity: usize) {
+        unsafe { ffi::SJ_OD_parser_set_max_capacity(self.ptr.as_ptr(), max_capacity) };
+        self.limits.max_document_bytes = max_capacity;
+    }
+
+    /// Counters over every [`Parser::iterate`] call so far. Values are parsed
+    /// while the document is accessed, so only errors found by `iterate`
+    /// itself are counted.
+    pub fn stats(&self) -> ParseStats {
+        self.stats.clone()
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = ParseStats::default();
     }
 
     pub fn iterate<'p, 's>(&'p mut self, padded_string: &'s String) -> Result<Document<'p, 's>> {
-        map_result!(
-            ffi::SJ_OD_parser_iterate_padded_string_view(
-                self.ptr.as_mut(),
-                padded_string.as_ptr().cast(),
-                padded_string.len(),
-                padded_string.capacity()
-            ),
-            ffi::SJ_OD_document_result_error,
-            ffi::SJ_OD_document_result_value_unsafe
-        )
-        .map(Document::new)
+        self.iterate_in(padded_string, padded_string.capacity())
+    }
+
+    /// Like [`iterate`](Self::iterate), for a buffer from a custom
+    /// allocator.
+    #[cfg(feature = "allocator")]
+    pub fn iterate_buffer<'p, 's, A: BufferAllocator>(
+        &'p mut self,
+        buffer: &'s PaddedBuffer<A>,
+    ) -> Result<Document<'p, 's>> {
+        self.iterate_in(buffer, buffer.capacity() + ffi::SIMDJSON_PADDING)
+    }
+
+    /// Iterates `json`, which is followed by readable memory up to
+    /// `readable` bytes from its start, as a line inside a padded buffer is.
+    pub(crate) fn iterate_in<'p, 's>(
+        &'p mut self,
+        padded_string: &'s str,
+        readable: usize,
+    ) -> Result<Document<'p, 's>> {
+        // Only stage 1 runs here; values are parsed while the document is
+        // accessed, outside of the span.
+        let ptr = self.ptr;
+        let capacity = || unsafe { ffi::SJ_OD_parser_capacity(ptr.as_ptr()) };
+        let span = ParseSpan::enter("ondemand::Parser::iterate", padded_string.len(), capacity);
+        // simdjson skips a byte order mark itself.
+        let result = self.bom.check(padded_string).and_then(|()| {
+            map_result!(
+                ffi::SJ_OD_parser_iterate_padded_string_view(
+                    self.ptr.as_mut(),
+                    padded_string.as_ptr().cast(),
+                    padded_string.len(),
+                    readable
+                ),
+                ffi::SJ_OD_document_result_error,
+                ffi::SJ_OD_document_result_value_unsafe
+            )
+            .map(Document::new)
+        });
+        span.finish(&result, capacity);
+        let result = result.and_then(|mut doc| {
+            if self.limits.checks_ondemand() {
+                self.limits.check_events(Events::new(&mut doc))?;
+                doc.rewind();
+            }
+            Ok(doc)
+        });
+        self.stats.record(padded_string.len(), &result, capacity());
+        result
     }
 }
 
@@ -46,6 +178,55 @@ mod tests {
     use super::*;
     use crate::padded_string::make_padded_string;
 
+    #[test]
+    fn buffer_capacity() {
+        let mut parser = Parser::default();
+        parser.allocate(1 << 20).unwrap();
+        assert_eq!(parser.capacity(), 1 << 20);
+        let presized = parser.allocated_bytes();
+        assert!(presized > 1 << 20);
+        parser.allocate(1024).unwrap();
+        assert!(parser.allocated_bytes() < presized);
+
+        parser.set_max_capacity(8);
+        assert_eq!(parser.max_capacity(), 8);
+        assert!(parser.allocate(16).is_err());
+        parser.allocate(4).unwrap();
+        let ps = make_padded_string("[1, 2, 3, 4]");
+        assert!(parser.iterate(&ps).is_err());
+    }
+
+    #[test]
+    fn stats() {
+        let mut parser = Parser::new(8);
+        parser.iterate(&make_padded_string("[1]")).unwrap();
+        assert!(parser.iterate(&make_padded_string("[1, 2, 3, 4]")).is_err());
+        let stats = parser.stats();
+        assert_eq!((stats.documents, stats.bytes), (1, 3));
+        assert_eq!(stats.errors.get("Capacity"), Some(&1));
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        let mut parser = Parser::default();
+        let ps = make_padded_string("\u{FEFF}\n[1]");
+        assert_eq!(
+            parser
+                .iterate(&ps)
+                .unwrap()
+                .get_array()
+                .unwrap()
+                .count_elements()
+                .unwrap(),
+            1
+        );
+        parser.set_bom_policy(BomPolicy::Reject);
+        assert!(matches!(
+            parser.iterate(&ps),
+            Err(crate::SimdJsonError::ByteOrderMark)
+        ));
+    }
+
     #[test]
     fn test_new() {
         let mut parser = Parser::default();
@@ -0,0 +1,105 @@
+use super::{JsonType, Parser, Value};
+use crate::Result;
+
+/// The shape of a document, measured by [`Parser::probe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Probe {
+    /// Type of the root value.
+    pub json_type: JsonType,
+    /// Elements of a root array or fields of a root object; 0 for a scalar.
+    pub len: usize,
+    /// Values at every depth, the root and containers included.
+    pub values: usize,
+    /// Deepest nesting of arrays and objects; 0 for a scalar.
+    pub max_depth: usize,
+    /// Length of the input.
+    pub bytes: usize,
+}
+
+impl Parser {
+    /// Measures the document in `padded_string` in one pass, so callers can
+    /// choose how to process it, streaming large arrays and building a DOM
+    /// for small objects, say, before doing so.
+    ///
+    /// Nothing is built: only the first byte of each value is looked at, and
+    /// numbers and strings are skipped without being parsed or unescaped. As
+    /// a result a malformed scalar is not noticed; structural errors are.
+    ///
+    /// ```ignore
+    /// let probe = parser.probe(&json)?;
+    /// if probe.json_type == JsonType::Array && probe.len > 10_000 {
+    ///     return stream(&json);
+    /// }
+    /// ```
+    pub fn probe(&mut self, padded_string: &String) -> Result<Probe> {
+        let mut probe = Probe {
+            json_type: JsonType::Null,
+            len: 0,
+            values: 1,
+            max_depth: 0,
+            bytes: padded_string.len(),
+        };
+        let mut doc = self.iterate(padded_string)?;
+        probe.json_type = doc.json_type()?;
+        if matches!(probe.json_type, JsonType::Array | JsonType::Object) {
+            probe.len = measure(doc.get_value()?, 1, &mut probe)?;
+        }
+        Ok(probe)
+    }
+}
+
+/// Counts the children of the container `value` at `depth` and everything
+/// under them into `probe`, returning the number of children.
+fn measure(mut value: Value<'_>, depth: usize, probe: &mut Probe) -> Result<usize> {
+    probe.max_depth = probe.max_depth.max(depth);
+    let mut visit = |mut child: Value<'_>| -> Result<()> {
+        probe.values += 1;
+        if matches!(child.json_type()?, JsonType::Array | JsonType::Object) {
+            measure(child, depth + 1, probe)?;
+        }
+        Ok(())
+    };
+    let mut len = 0;
+    if value.json_type()? == JsonType::Array {
+        for child in value.get_array()?.iter()? {
+            visit(child?)?;
+            len += 1;
+        }
+    } else {
+        for field in value.get_object()?.iter()? {
+            visit(field?.take_value())?;
+            len += 1;
+        }
+    }
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn probe_shapes() {
+        let mut parser = Parser::default();
+        let json = r#"{"a": [1, [2, {}], "x"], "b": {"c": null}, "d": true}"#.to_padded_string();
+        assert_eq!(
+            parser.probe(&json).unwrap(),
+            Probe {
+                json_type: JsonType::Object,
+                len: 3,
+                values: 10,
+                max_depth: 4,
+                bytes: json.len(),
+            }
+        );
+
+        let probe = parser.probe(&"[]".to_padded_string()).unwrap();
+        assert_eq!((probe.len, probe.values, probe.max_depth), (0, 1, 1));
+        let probe = parser.probe(&" \"text\" ".to_padded_string()).unwrap();
+        assert_eq!(probe.json_type, JsonType::String);
+        assert_eq!((probe.len, probe.values, probe.max_depth), (0, 1, 0));
+
+        assert!(parser.probe(&"[1, [2]".to_padded_string()).is_err());
+    }
+}
@@ -0,0 +1,283 @@
+use super::{JsonType, document::Document, value::Value};
+use crate::{
+    Result,
+    pointer::{Segment, array_index, parse_pointer},
+};
+
+/// A set of JSON pointers to extract from an ondemand document in a single
+/// pass.
+///
+/// Selectors use JSON pointer syntax (`/a/b/0`, with `~0`/`~1` escapes). A `*`
+/// segment matches every field of an object or every element of an array.
+/// Everything that is not on the way to a selector is skipped by the
+/// underlying iterator instead of being parsed.
+///
+/// Once a selector matches, the value is handed out whole, so selecting both
+/// `/a` and `/a/b` only reports `/a`.
+#[derive(Debug, Default)]
+pub struct Projection {
+    root: Node,
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    selector: Option<usize>,
+    children: Vec<Child>,
+    wildcard: Option<Box<Node>>,
+}
+
+/// A segment under a node: an object key, and the array index it also
+/// denotes if it is one.
+#[derive(Debug)]
+struct Child {
+    key: String,
+    index: Option<usize>,
+    node: Node,
+}
+
+impl Node {
+    fn child(&self, key: &str) -> Option<&Node> {
+        self.children
+            .iter()
+            .find(|child| child.key == key)
+            .map(|child| &child.node)
+            .or(self.wildcard.as_deref())
+    }
+
+    fn element(&self, index: usize) -> Option<&Node> {
+        self.children
+            .iter()
+            .find(|child| child.index == Some(index))
+            .map(|child| &child.node)
+            .or(self.wildcard.as_deref())
+    }
+
+    fn child_mut(&mut self, key: String) -> &mut Node {
+        if key == "*" {
+            return self.wildcard.get_or_insert_with(Default::default);
+        }
+        let pos = match self.children.iter().position(|child| child.key == key) {
+            Some(pos) => pos,
+            None => {
+                self.children.push(Child {
+                    index: array_index(&key),
+                    key,
+                    node: Node::default(),
+                });
+                self.children.len() - 1
+            }
+        };
+        &mut self.children[pos].node
+    }
+
+    /// Adds everything `other` selects below this node.
+    fn merge(&mut self, other: &Node) {
+        if let Some(selector) = other.selector {
+            self.selector = Some(self.selector.map_or(selector, |own| own.min(selector)));
+        }
+        for child in &other.children {
+            self.child_mut(child.key.clone()).merge(&child.node);
+        }
+        if let Some(wildcard) = &other.wildcard {
+            self.wildcard
+                .get_or_insert_with(Default::default)
+                .merge(wildcard);
+        }
+    }
+
+    /// Copies the `*` subtree into every literal sibling. A value is walked
+    /// only once, through the first node it matches, so a literal segment
+    /// must also reach what the wildcard selects.
+    fn merge_wildcards(&mut self) {
+        if let Some(wildcard) = &mut self.wildcard {
+            wildcard.merge_wildcards();
+            for child in &mut self.children {
+                child.node.merge(wildcard);
+            }
+        }
+        for child in &mut self.children {
+            child.node.merge_wildcards();
+        }
+    }
+}
+
+impl Projection {
+    /// Compiles `selectors`. The index of each selector in the iterator is what
+    /// gets reported to the callback of [`Projection::for_each`].
+    pub fn new<I, S>(selectors: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut root = Node::default();
+        for (index, selector) in selectors.into_iter().enumerate() {
+            let mut node = &mut root;
+            for segment in parse_pointer(selector.as_ref())? {
+                node = node.child_mut(segment);
+            }
+            node.selector.get_or_insert(index);
+        }
+        root.merge_wildcards();
+        Ok(Self { root })
+    }
+
+    /// Walks `doc` once and calls `f` with the selector index, the path of the
+    /// match and its value for every match, in document order.
+    pub fn for_each<F>(&self, doc: &mut Document<'_, '_>, mut f: F) -> Result<()>
+    where
+        F: FnMut(usize, &[Segment<'_>], Value<'_>) -> Result<()>,
+    {
+        let value = doc.get_value()?;
+        walk(&self.root, value, &mut Vec::new(), &mut f)
+    }
+
+    /// Builds a `serde_json::Value` holding only the selected parts of `doc`,
+    /// nested the same way as in the source document, and deserializes it
+    /// into `T`. Array elements keep their index, so elements before a match
+    /// that no selector reached are `null`.
+    #[cfg(feature = "serde_impl")]
+    pub fn deserialize<T>(&self, doc: &mut Document<'_, '_>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let mut out = serde_json::Value::Null;
+        self.for_each(doc, |_, path, mut value| {
+            let value = crate::serde::value::ondemand_value_to_value(&mut value)?;
+            insert_at(&mut out, path, value);
+            Ok(())
+        })?;
+        serde_json::from_value(out).map_err(|e| crate::SimdJsonError::Serde(e.to_string()))
+    }
+}
+
+fn walk<'a, F>(
+    node: &Node,
+    mut value: Value<'a>,
+    path: &mut Vec<Segment<'a>>,
+    f: &mut F,
+) -> Result<()>
+where
+    F: FnMut(usize, &[Segment<'_>], Value<'_>) -> Result<()>,
+{
+    if let Some(selector) = node.selector {
+        return f(selector, path, value);
+    }
+    match value.json_type()? {
+        JsonType::Object => {
+            for field in value.get_object()?.iter()? {
+                let mut field = field?;
+                let key = field.unescaped_key(false)?;
+                if let Some(child) = node.child(key) {
+                    path.push(Segment::Key(key));
+                    walk(child, field.take_value(), path, f)?;
+                    path.pop();
+                }
+            }
+        }
+        JsonType::Array => {
+            for (index, element) in value.get_array()?.iter()?.enumerate() {
+                let element = element?;
+                if let Some(child) = node.element(index) {
+                    path.push(Segment::Index(index));
+                    walk(child, element, path, f)?;
+                    path.pop();
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Stores `value` in `out` at `path`, creating the objects and arrays on the
+/// way. Arrays are padded with `null` up to the index.
+#[cfg(feature = "serde_impl")]
+fn insert_at(out: &mut serde_json::Value, path: &[Segment<'_>], value: serde_json::Value) {
+    use serde_json::{Map, Value as Json};
+
+    let mut slot = out;
+    for segment in path {
+        slot = match *segment {
+            Segment::Key(key) => {
+                if !slot.is_object() {
+                    *slot = Json::Object(Map::new());
+                }
+                slot.as_object_mut()
+                    .unwrap()
+                    .entry(key)
+                    .or_insert(Json::Null)
+            }
+            Segment::Index(index) => {
+                if !slot.is_array() {
+                    *slot = Json::Array(Vec::new());
+                }
+                let array = slot.as_array_mut().unwrap();
+                if array.len() <= index {
+                    array.resize(index + 1, Json::Null);
+                }
+                &mut array[index]
+            }
+        };
+    }
+    *slot = value;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ondemand::Parser, prelude::*};
+
+    #[test]
+    fn select_paths() {
+        let json = r#"{"id": 7, "skip": {"big": [1, 2, 3]}, "items": [{"k": "x", "v": 1}, {"k": "y", "v": 2}]}"#
+            .to_padded_string();
+        let projection = Projection::new(["/id", "/items/*/k"]).unwrap();
+        let mut parser = Parser::default();
+        let mut doc = parser.iterate(&json).unwrap();
+
+        let mut seen = Vec::new();
+        projection
+            .for_each(&mut doc, |selector, path, mut value| {
+                let text = match selector {
+                    0 => value.get_uint64()?.to_string(),
+                    _ => value.get_string(false)?.to_owned(),
+                };
+                seen.push((selector, path.len(), text));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            seen,
+            [
+                (0, 1, "7".to_string()),
+                (1, 3, "x".to_string()),
+                (1, 3, "y".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn literal_and_wildcard_overlap() {
+        let json = r#"{"items": [{"k": "x", "v": 1}, {"k": "y", "v": 2}]}"#.to_padded_string();
+        let projection = Projection::new(["/items/*/k", "/items/0/v"]).unwrap();
+        let mut parser = Parser::default();
+        let mut doc = parser.iterate(&json).unwrap();
+
+        let mut seen = Vec::new();
+        projection
+            .for_each(&mut doc, |selector, path, _| {
+                let path: String = path.iter().map(|segment| format!("/{segment}")).collect();
+                seen.push((selector, path));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(
+            seen,
+            [
+                (0, "/items/0/k".to_string()),
+                (1, "/items/0/v".to_string()),
+                (0, "/items/1/k".to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use super::{Document, Parser};
+use crate::{
+    Result,
+    utils::{split_lines_at, split_records},
+};
+
+/// A run of whole lines of a newline-delimited buffer, made by
+/// [`shard_lines`] to be iterated on its own thread with its own parser.
+///
+/// The lines are iterated in place: a line inside a padded buffer is followed
+/// by readable bytes, which is all the ondemand parser needs.
+#[derive(Debug, Clone, Copy)]
+pub struct Shard<'s> {
+    buffer: &'s String,
+    start: usize,
+    end: usize,
+    index: usize,
+}
+
+impl<'s> Shard<'s> {
+    /// Position of the shard among those of its buffer.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The text of the shard's lines.
+    pub fn as_str(&self) -> &'s str {
+        &self.buffer[self.start..self.end]
+    }
+
+    /// Calls `f` with each non-blank line of the shard as a document, in
+    /// order. A malformed line only fails its own entry.
+    pub fn map<T, F>(&self, parser: &mut Parser, mut f: F) -> Vec<Result<T>>
+    where
+        F: FnMut(&mut Document<'_, 's>) -> Result<T>,
+    {
+        let base = self.buffer.as_ptr() as usize;
+        let capacity = self.buffer.capacity();
+        split_records(self.as_str())
+            .map(|line| {
+                let start = line.as_ptr() as usize - base;
+                let mut doc = parser.iterate_in(line, capacity - start)?;
+                f(&mut doc)
+            })
+            .collect()
+    }
+}
+
+/// Cuts newline-delimited `padded_string` into at most `shards` shards of
+/// roughly equal size, each ending right after a newline.
+///
+/// Hand the shards to separate threads, each with its own parser, and
+/// concatenate their [`Shard::map`] results in shard order to get the lines
+/// in input order; [`par_map_lines`] does just that.
+pub fn shard_lines(padded_string: &String, shards: usize) -> Vec<Shard<'_>> {
+    let base = padded_string.as_ptr() as usize;
+    let target = padded_string.len().div_ceil(shards.max(1));
+    split_lines_at(padded_string, target)
+        .into_iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let start = chunk.as_ptr() as usize - base;
+            Shard {
+                buffer: padded_string,
+                start,
+                end: start + chunk.len(),
+                index,
+            }
+        })
+        .collect()
+}
+
+/// Iterates every non-blank line of `padded_string` on `threads` scoped
+/// threads and calls `f` with its document. Results are returned in line
+/// order; a malformed line only fails its own entry.
+///
+/// ```ignore
+/// let ids = ondemand::par_map_lines(&input, 8, |doc| doc.at_pointer("/id")?.get_uint64());
+/// ```
+pub fn par_map_lines<T, F>(padded_string: &String, threads: usize, f: F) -> Vec<Result<T>>
+where
+    T: Send,
+    F: Fn(&mut Document<'_, '_>) -> Result<T> + Sync,
+{
+    let shards = shard_lines(padded_string, threads);
+    let f = &f;
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = shards
+            .into_iter()
+            .map(|shard| scope.spawn(move || shard.map(&mut Parser::default(), f)))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn shards_in_order() {
+        let input: String = (0..1000).map(|i| format!("{{\"i\": {i}}}\n")).collect();
+        let input = input.into_padded_string();
+        let shards = shard_lines(&input, 4);
+        assert_eq!(shards.len(), 4);
+        assert!(shards.iter().all(|shard| shard.as_str().ends_with('\n')));
+        let joined: String = shards.iter().map(|shard| shard.as_str()).collect();
+        assert_eq!(joined, input.as_str());
+
+        let values = par_map_lines(&input, 4, |doc| doc.at_pointer("/i")?.get_uint64());
+        let values: Vec<u64> = values.into_iter().collect::<Result<_>>().unwrap();
+        assert_eq!(values, (0..1000).collect::<Vec<_>>());
+
+        let input = "1\n[\n\n3\r\n".to_padded_string();
+        let values = par_map_lines(&input, 2, |doc| doc.get_uint64());
+        assert_eq!(values.len(), 3);
+        assert!(values[1].is_err());
+        assert_eq!(values[2].as_ref().unwrap(), &3);
+    }
+}
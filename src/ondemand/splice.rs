@@ -0,0 +1,209 @@
+use std::ops::Range;
+
+use super::{JsonType, Parser};
+use crate::{
+    Result, SimdJsonError,
+    pointer::{array_index, parse_pointer},
+    utils::{escape_json_string, needs_escaping},
+};
+
+enum Edit<'v> {
+    Set(&'v str),
+    Remove,
+}
+
+impl Parser {
+    /// Returns the document in `padded_string` with the value at `pointer`
+    /// replaced by `value`, which is written as is and must be valid JSON.
+    ///
+    /// Only the bytes of the old value are replaced; the rest of the
+    /// document, formatting included, is copied unchanged, so a small edit
+    /// costs one pass of the ondemand parser up to the target rather than a
+    /// deserialize, modify and serialize cycle. A missing object key is
+    /// added after the last field, and `-` or the array's length as the last
+    /// reference token appends to an array.
+    ///
+    /// ```ignore
+    /// let json = parser.set_at_pointer(&json, "/user/name", r#""Alice""#)?;
+    /// ```
+    pub fn set_at_pointer(
+        &mut self,
+        padded_string: &String,
+        pointer: &str,
+        value: &str,
+    ) -> Result<String> {
+        self.splice(padded_string, pointer, Edit::Set(value))
+    }
+
+    /// Returns the document in `padded_string` without the object field or
+    /// array element at `pointer`, and without the comma that separated it
+    /// from its neighbours. Like [`set_at_pointer`](Self::set_at_pointer),
+    /// the rest of the document is copied unchanged.
+    pub fn remove_at_pointer(&mut self, padded_string: &String, pointer: &str) -> Result<String> {
+        self.splice(padded_string, pointer, Edit::Remove)
+    }
+
+    fn splice(&mut self, padded_string: &String, pointer: &str, edit: Edit<'_>) -> Result<String> {
+        let tokens = parse_pointer(pointer)?;
+        let Some(last) = tokens.last() else {
+            // The root itself.
+            return match edit {
+                Edit::Set(value) => Ok(value.to_owned()),
+                Edit::Remove => Err(SimdJsonError::InvalidJsonPointer),
+            };
+        };
+        // The pointer up to the last slash is the parent's, still escaped.
+        let parent_pointer = &pointer[..pointer.rfind('/').unwrap_or(0)];
+
+        let base = padded_string.as_ptr() as usize;
+        let span = |s: &str| {
+            let start = s.as_ptr() as usize - base;
+            start..start + s.len()
+        };
+        let mut doc = self.iterate(padded_string)?;
+        let mut parent = doc.at_pointer(parent_pointer)?;
+        // The opening bracket; the token is not consumed.
+        let open = span(parent.raw_json_token()).start;
+        let mut children: Vec<Range<usize>> = Vec::new();
+        let mut target = None;
+        let is_object = match parent.json_type()? {
+            JsonType::Object => {
+                for field in parent.get_object()?.iter()? {
+                    let mut field = field?;
+                    if target.is_none() && field.unescaped_key(false)? == last {
+                        target = Some(children.len());
+                    }
+                    children.push(span(field.take_value().raw_json()?));
+                }
+                true
+            }
+            JsonType::Array => {
+                for element in parent.get_array()?.iter()? {
+                    children.push(span(element?.raw_json()?));
+                }
+                target = array_index(last).filter(|&index| index < children.len());
+                false
+            }
+            _ => return Err(SimdJsonError::IncorrectType),
+        };
+
+        let json = padded_string.as_str();
+        let (range, text) = match (edit, target) {
+            (Edit::Set(value), Some(i)) => (children[i].clone(), value.to_owned()),
+            (Edit::Remove, Some(i)) => {
+                let range = if i > 0 {
+                    // `, "key": value` after the previous child.
+                    children[i - 1].end..children[i].end
+                } else if children.len() > 1 {
+                    // Up to the next child, comma and spacing included.
+                    let after = children[0].end;
+                    let comma = after + json[after..].find(',').unwrap_or(0);
+                    let next = comma
+                        + 1
+                        + (json[comma + 1..].len() - json[comma + 1..].trim_start().len());
+                    open + 1..next
+                } else {
+                    open + 1..children[0].end
+                };
+                (range, String::new())
+            }
+            (Edit::Set(value), None) => {
+                let appends =
+                    !is_object && (last == "-" || array_index(last) == Some(children.len()));
+                if !is_object && !appends {
+                    return Err(SimdJsonError::IndexOutOfBounds);
+                }
+                let (at, mut text) = match children.last() {
+                    Some(end) => (end.end, ",".to_owned()),
+                    None => (open + 1, String::new()),
+                };
+                if is_object {
+                    text.push('"');
+                    if needs_escaping(last) {
+                        escape_json_string(last, &mut text)?;
+                    } else {
+                        text.push_str(last);
+                    }
+                    text.push_str("\":");
+                }
+                text.push_str(value);
+                (at..at, text)
+            }
+            (Edit::Remove, None) => {
+                return Err(if is_object {
+                    SimdJsonError::NoSuchField
+                } else {
+                    SimdJsonError::IndexOutOfBounds
+                });
+            }
+        };
+        let mut out = String::with_capacity(json.len() - range.len() + text.len());
+        out.push_str(&json[..range.start]);
+        out.push_str(&text);
+        out.push_str(&json[range.end..]);
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn splice_at_pointer() {
+        let mut parser = Parser::default();
+        let json = r#"{"a": [1, {"b": 2}, 3], "c": {}, "d~/": "x", "e": []}"#.to_padded_string();
+        let set =
+            |parser: &mut Parser, pointer, value| parser.set_at_pointer(&json, pointer, value);
+
+        assert_eq!(
+            set(&mut parser, "/a/1/b", "[true]").unwrap(),
+            r#"{"a": [1, {"b": [true]}, 3], "c": {}, "d~/": "x", "e": []}"#
+        );
+        assert_eq!(
+            set(&mut parser, "/d~0~1", "null").unwrap(),
+            r#"{"a": [1, {"b": 2}, 3], "c": {}, "d~/": null, "e": []}"#
+        );
+        assert_eq!(
+            set(&mut parser, "/c/new\"key", "1").unwrap(),
+            r#"{"a": [1, {"b": 2}, 3], "c": {"new\"key":1}, "d~/": "x", "e": []}"#
+        );
+        assert_eq!(
+            set(&mut parser, "/a/-", "4").unwrap(),
+            r#"{"a": [1, {"b": 2}, 3,4], "c": {}, "d~/": "x", "e": []}"#
+        );
+        assert_eq!(
+            set(&mut parser, "/e/0", "4").unwrap(),
+            r#"{"a": [1, {"b": 2}, 3], "c": {}, "d~/": "x", "e": [4]}"#
+        );
+        assert_eq!(set(&mut parser, "", "0").unwrap(), "0");
+        assert!(matches!(
+            set(&mut parser, "/a/7", "0"),
+            Err(SimdJsonError::IndexOutOfBounds)
+        ));
+
+        let remove = |parser: &mut Parser, pointer| parser.remove_at_pointer(&json, pointer);
+        assert_eq!(
+            remove(&mut parser, "/a").unwrap(),
+            r#"{"c": {}, "d~/": "x", "e": []}"#
+        );
+        assert_eq!(
+            remove(&mut parser, "/a/1").unwrap(),
+            r#"{"a": [1, 3], "c": {}, "d~/": "x", "e": []}"#
+        );
+        assert_eq!(
+            remove(&mut parser, "/e").unwrap(),
+            r#"{"a": [1, {"b": 2}, 3], "c": {}, "d~/": "x"}"#
+        );
+        assert_eq!(
+            remove(&mut parser, "/a/1/b").unwrap(),
+            r#"{"a": [1, {}, 3], "c": {}, "d~/": "x", "e": []}"#
+        );
+        assert!(matches!(
+            remove(&mut parser, "/c/x"),
+            Err(SimdJsonError::NoSuchField)
+        ));
+        assert!(remove(&mut parser, "").is_err());
+    }
+}
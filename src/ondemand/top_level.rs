@@ -0,0 +1,80 @@
+use super::{ArrayIterator, Document, Parser};
+use crate::Result;
+
+/// The elements of a document that is one large array, such as a paginated
+/// API response, handed out one at a time as the text they have in the
+/// document. Created by [`Parser::iter_top_level_array`].
+///
+/// Nothing is built for the elements: each is skipped over by the ondemand
+/// iterator, so memory use stays at the input and the parser's indexes,
+/// however many elements there are. Each slice can be forwarded as is or
+/// parsed on its own.
+pub struct TopLevelArray<'p, 's> {
+    // Declared first so it is dropped before the document it iterates.
+    iter: ArrayIterator<'s>,
+    _doc: Document<'p, 's>,
+}
+
+impl<'s> Iterator for TopLevelArray<'_, 's> {
+    type Item = Result<&'s str>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next()
+            .map(|element| element.and_then(|mut element| element.raw_json()))
+    }
+}
+
+impl Parser {
+    /// Iterates over the elements of the array `padded_string` holds; see
+    /// [`TopLevelArray`]. A document that is not an array fails with
+    /// [`SimdJsonError::IncorrectType`](crate::SimdJsonError::IncorrectType).
+    pub fn iter_top_level_array<'p, 's>(
+        &'p mut self,
+        padded_string: &'s String,
+    ) -> Result<TopLevelArray<'p, 's>> {
+        let mut doc = self.iterate(padded_string)?;
+        let iter = doc.get_array()?.iter()?;
+        Ok(TopLevelArray { iter, _doc: doc })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn top_level_elements() {
+        let json = r#" [ {"id": 1, "tags": ["a", "b"]}, [1, [2]] , "s\"" , 3.5, null ] "#
+            .to_padded_string();
+        let mut parser = Parser::default();
+        let items: Vec<_> = parser
+            .iter_top_level_array(&json)
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(
+            items,
+            [
+                r#"{"id": 1, "tags": ["a", "b"]}"#,
+                "[1, [2]]",
+                r#""s\"""#,
+                "3.5",
+                "null"
+            ]
+        );
+
+        let json = "[]".to_padded_string();
+        assert_eq!(parser.iter_top_level_array(&json).unwrap().count(), 0);
+        let json = r#"{"a": 1}"#.to_padded_string();
+        assert!(parser.iter_top_level_array(&json).is_err());
+        let json = "[1, {]".to_padded_string();
+        assert!(
+            parser
+                .iter_top_level_array(&json)
+                .unwrap()
+                .any(|item| item.is_err())
+        );
+    }
+}
@@ -4,9 +4,10 @@ use simdjson_sys as ffi;
 
 use super::{JsonType, array::Array, document::Document, number::Number, object::Object};
 use crate::{
-    error::Result,
-    macros::{impl_drop, map_result},
-    utils::string_view_to_str,
+    error::{Result, SimdJsonError},
+    macros::{impl_drop, map_primitive_result, map_result},
+    number::is_json_number,
+    utils::{string_view_struct_to_str, string_view_to_str},
 };
 
 pub struct Value<'a> {
@@ -116,6 +117,42 @@ impl<'a> Value<'a> {
         )
     }
 
+    /// The text of a scalar exactly as it appears in the document, such as
+    /// the digits of a number too large for any numeric type. The value is not
+    /// consumed.
+    pub fn raw_json_token(&mut self) -> &'a str {
+        let token = string_view_struct_to_str(unsafe {
+            ffi::SJ_OD_value_raw_json_token(self.ptr.as_mut())
+        });
+        // simdjson includes the whitespace up to the next structural.
+        token.trim_end_matches([' ', '\t', '\n', '\r'])
+    }
+
+    /// The text of the value as it appears in the document. Arrays and
+    /// objects are consumed; scalars are handled like
+    /// [`raw_json_token`](Self::raw_json_token).
+    pub fn raw_json(&mut self) -> Result<&'a str> {
+        let json = map_primitive_result!(ffi::SJ_OD_value_raw_json(self.ptr.as_mut()))
+            .map(string_view_struct_to_str)?;
+        Ok(json.trim_end_matches([' ', '\t', '\n', '\r']))
+    }
+
+    /// The text of a number exactly as written, for callers that parse it
+    /// themselves, into an `i128` or a decimal type, without going through a
+    /// lossy `f64` first. Fails with [`SimdJsonError::IncorrectType`] if the
+    /// value is not a number and with [`SimdJsonError::NumberError`] if the
+    /// token is not valid JSON number syntax. The value is not consumed.
+    pub fn get_raw_number_as_str(&mut self) -> Result<&'a str> {
+        if self.json_type()? != JsonType::Number {
+            return Err(SimdJsonError::IncorrectType);
+        }
+        let token = self.raw_json_token();
+        if !is_json_number(token.as_bytes()) {
+            return Err(SimdJsonError::NumberError);
+        }
+        Ok(token)
+    }
+
     pub fn json_type(&mut self) -> Result<JsonType> {
         let json_type = map_result!(
             primitive,
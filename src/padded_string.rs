@@ -1,19 +1,113 @@
-use std::{io::Read, path::Path};
+use std::{
+    io::Read,
+    ops::{Deref, DerefMut},
+    path::Path,
+};
 
 use simdjson_sys as ffi;
 
+use crate::pool;
+
+#[cfg(feature = "gzip")]
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+#[cfg(feature = "zstd")]
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
 pub fn make_padded_string(s: &str) -> String {
     let mut ps = String::with_capacity(s.len() + ffi::SIMDJSON_PADDING);
     ps.push_str(s);
     ps
 }
 
+/// Reads a whole file into a padded string.
+///
+/// With the `gzip` or `zstd` features enabled, compressed files are detected by
+/// their magic bytes and decompressed transparently.
 pub fn load_padded_string<P: AsRef<Path>>(path: P) -> std::io::Result<String> {
-    let mut file = std::fs::File::open(path)?;
+    let file = std::fs::File::open(path)?;
     let len = file.metadata()?.len() as usize;
-    let mut buf = String::with_capacity(len + ffi::SIMDJSON_PADDING);
-    file.read_to_string(&mut buf)?;
-    Ok(buf)
+    read_with_size_hint(file, len)
+}
+
+/// Reads everything from `reader` into a padded string, decompressing it first
+/// if needed (see [`load_padded_string`]).
+pub fn read_padded_string<R: Read>(reader: R) -> std::io::Result<String> {
+    read_with_size_hint(reader, 0)
+}
+
+fn read_with_size_hint<R: Read>(reader: R, size_hint: usize) -> std::io::Result<String> {
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    {
+        use std::io::{BufRead, BufReader};
+
+        let mut reader = BufReader::new(reader);
+        let magic = reader.fill_buf()?;
+        #[cfg(feature = "gzip")]
+        if magic.starts_with(&GZIP_MAGIC) {
+            return read_to_padded_string(flate2::bufread::MultiGzDecoder::new(reader), 0);
+        }
+        #[cfg(feature = "zstd")]
+        if magic.starts_with(&ZSTD_MAGIC) {
+            return read_to_padded_string(zstd::stream::read::Decoder::with_buffer(reader)?, 0);
+        }
+        read_to_padded_string(reader, size_hint)
+    }
+    #[cfg(not(any(feature = "gzip", feature = "zstd")))]
+    read_to_padded_string(reader, size_hint)
+}
+
+fn read_to_padded_string<R: Read>(mut reader: R, size_hint: usize) -> std::io::Result<String> {
+    let mut buf = String::with_capacity(size_hint + ffi::SIMDJSON_PADDING);
+    reader.read_to_string(&mut buf)?;
+    Ok(buf.into_padded_string())
+}
+
+/// A padded buffer rented from a per-thread pool with [`rent_padded_string`].
+/// It goes back to the pool of the thread that drops it.
+#[derive(Debug)]
+pub struct PooledPaddedString(String);
+
+/// Rents an empty buffer with room for `len` bytes of JSON plus padding, for
+/// request-scoped parsing without a fresh allocation per request.
+///
+/// Buffers are pooled per thread in power-of-two size classes up to 16 MiB;
+/// [`crate::release_thread_local_parsers`] frees them. Growing the string
+/// past `len` may reallocate it without padding.
+///
+/// ```ignore
+/// let mut json = rent_padded_string(body.len());
+/// json.push_str(body);
+/// let doc = parser.parse(&json)?;
+/// ```
+pub fn rent_padded_string(len: usize) -> PooledPaddedString {
+    PooledPaddedString(pool::take_buffer(len.saturating_add(ffi::SIMDJSON_PADDING)))
+}
+
+impl PooledPaddedString {
+    /// Takes the buffer out of the pool for good.
+    pub fn into_string(mut self) -> String {
+        std::mem::take(&mut self.0)
+    }
+}
+
+impl Deref for PooledPaddedString {
+    type Target = String;
+
+    fn deref(&self) -> &String {
+        &self.0
+    }
+}
+
+impl DerefMut for PooledPaddedString {
+    fn deref_mut(&mut self) -> &mut String {
+        &mut self.0
+    }
+}
+
+impl Drop for PooledPaddedString {
+    fn drop(&mut self) {
+        pool::give_back_buffer(std::mem::take(&mut self.0));
+    }
 }
 
 pub trait ToPaddedString {
@@ -36,6 +130,12 @@ impl ToPaddedString for &mut str {
     }
 }
 
+impl ToPaddedString for String {
+    fn to_padded_string(&self) -> String {
+        make_padded_string(self)
+    }
+}
+
 impl IntoPaddedString for String {
     fn into_padded_string(mut self) -> String {
         if self.capacity() < self.len() + ffi::SIMDJSON_PADDING {
@@ -46,4 +146,50 @@ impl IntoPaddedString for String {
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_plain() {
+        let ps = read_padded_string(&b"[1,2,3]"[..]).unwrap();
+        assert_eq!(ps, "[1,2,3]");
+        assert!(ps.capacity() >= ps.len() + ffi::SIMDJSON_PADDING);
+    }
+
+    #[test]
+    fn rent_pooled() {
+        let mut json = rent_padded_string(5);
+        json.push_str("[1,2]");
+        let ptr = json.as_ptr();
+        assert!(json.capacity() >= json.len() + ffi::SIMDJSON_PADDING);
+        drop(json);
+        let json = rent_padded_string(10);
+        assert!(json.is_empty());
+        assert_eq!(json.as_ptr(), ptr);
+        let owned = json.into_string();
+        assert_eq!(owned.as_ptr(), ptr);
+        // Owned buffers are not returned to the pool.
+        let other = rent_padded_string(10);
+        assert_ne!(other.as_ptr(), owned.as_ptr());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn read_gzip() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"{\"a\":1}").unwrap();
+        let compressed = encoder.finish().unwrap();
+        let ps = read_padded_string(compressed.as_slice()).unwrap();
+        assert_eq!(ps, "{\"a\":1}");
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn read_zstd() {
+        let compressed = zstd::encode_all(&b"{\"a\":1}"[..], 0).unwrap();
+        let ps = read_padded_string(compressed.as_slice()).unwrap();
+        assert_eq!(ps, "{\"a\":1}");
+    }
+}
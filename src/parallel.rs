@@ -0,0 +1,87 @@
+//! Parsing newline-delimited buffers on every core with rayon.
+//!
+//! The buffer is cut into chunks on line boundaries, every worker thread
+//! parses its chunks with its own [`dom::Parser`], and the results come back
+//! in input order. Lines are parsed in place: a line inside a padded buffer is
+//! followed by readable bytes, which is all simdjson needs.
+
+use rayon::prelude::*;
+
+use crate::{Result, dom, ndjson::split_records, utils::split_lines_at};
+
+/// Chunks smaller than this are not worth a task of their own.
+const MIN_CHUNK_LEN: usize = 1 << 20;
+
+/// Parses every non-blank line of `padded_string` in parallel and calls `f`
+/// with its root element. Results are returned in line order; a malformed
+/// line only fails its own entry.
+///
+/// Like [`dom::Parser::parse`], `padded_string` must have
+/// [`crate::SIMDJSON_PADDING`] bytes of spare capacity.
+pub fn par_parse_many<T, F>(padded_string: &str, f: F) -> Vec<Result<T>>
+where
+    T: Send,
+    F: Fn(dom::Element<'_>) -> Result<T> + Sync,
+{
+    chunks(padded_string, rayon::current_num_threads())
+        .into_par_iter()
+        .map_init(dom::Parser::default, |parser, chunk| {
+            split_records(chunk)
+                .map(|line| parser.parse(line).and_then(&f))
+                .collect::<Vec<_>>()
+        })
+        .flatten()
+        .collect()
+}
+
+/// Deserializes every non-blank line of `padded_string` into `T` in
+/// parallel, in line order.
+#[cfg(feature = "serde_impl")]
+pub fn par_deserialize_many<T>(padded_string: &str) -> Vec<Result<T>>
+where
+    T: serde::de::DeserializeOwned + Send,
+{
+    par_parse_many(padded_string, |element| {
+        crate::serde::de::from_element(&element)
+    })
+}
+
+/// Splits `s` into at most roughly `4 * threads` chunks that each end right
+/// after a newline, or at the end of `s`.
+fn chunks(s: &str, threads: usize) -> Vec<&str> {
+    split_lines_at(s, (s.len() / (threads.max(1) * 4)).max(MIN_CHUNK_LEN))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn parse_in_order() {
+        let input: String = (0..10_000).map(|i| format!("{{\"i\": {i}}}\n")).collect();
+        let input = input.into_padded_string();
+        let values = par_parse_many(&input, |element| element.at_pointer("/i")?.get_uint64());
+        let values: Vec<u64> = values.into_iter().collect::<Result<_>>().unwrap();
+        assert_eq!(values, (0..10_000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn errors_stay_local() {
+        let input = "1\n[\n\n3\r\n".to_padded_string();
+        let values = par_parse_many(&input, |element| element.get_uint64());
+        assert_eq!(values.len(), 3);
+        assert_eq!(values[0].as_ref().unwrap(), &1);
+        assert!(values[1].is_err());
+        assert_eq!(values[2].as_ref().unwrap(), &3);
+    }
+
+    #[test]
+    fn split_on_lines() {
+        let input = "a\nbb\nccc\n".repeat(MIN_CHUNK_LEN / 4);
+        let chunks = chunks(&input, 1);
+        assert!(chunks.len() > 1);
+        assert!(chunks[..chunks.len() - 1].iter().all(|c| c.ends_with('\n')));
+        assert_eq!(chunks.concat(), input);
+    }
+}
@@ -0,0 +1,222 @@
+use simdjson_sys as ffi;
+
+use crate::{Result, SimdJsonError};
+
+/// One step of a path into a document: an object key or an array index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+impl std::fmt::Display for Segment<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Segment::Key(key) => f.write_str(key),
+            Segment::Index(index) => write!(f, "{index}"),
+        }
+    }
+}
+
+/// A JSON pointer parsed once and evaluated many times.
+///
+/// `at_pointer` re-parses its pointer string on every call. A compiled pointer
+/// keeps the unescaped keys and array indexes, and the whole lookup runs in a
+/// single call into simdjson. Use it with
+/// [`dom::Element::at_compiled_pointer`](crate::dom::Element::at_compiled_pointer)
+/// and
+/// [`ondemand::Document::at_compiled_pointer`](crate::ondemand::Document::at_compiled_pointer).
+///
+/// ```ignore
+/// let pointer = CompiledPointer::new("/user/name")?;
+/// for line in lines {
+///     let doc = parser.parse(&line)?;
+///     println!("{}", doc.at_compiled_pointer(&pointer)?.get_string()?);
+/// }
+/// ```
+pub struct CompiledPointer {
+    pointer: String,
+    keys: Vec<String>,
+    /// Points into `keys`, whose heap buffers never move or change.
+    tokens: Vec<ffi::SJ_path_token>,
+}
+
+// The raw pointers in `tokens` only refer to the immutable strings in `keys`.
+unsafe impl Send for CompiledPointer {}
+unsafe impl Sync for CompiledPointer {}
+
+impl CompiledPointer {
+    /// Parses `pointer` (`/a/b/0`, with `~0`/`~1` escapes). The empty pointer
+    /// refers to the whole document.
+    pub fn new(pointer: &str) -> Result<Self> {
+        Ok(Self::from_keys(pointer.to_owned(), parse_pointer(pointer)?))
+    }
+
+    fn from_keys(pointer: String, keys: Vec<String>) -> Self {
+        let tokens = keys
+            .iter()
+            .map(|key| ffi::SJ_path_token {
+                key: key.as_ptr().cast(),
+                key_len: key.len(),
+                index: array_index(key).unwrap_or(usize::MAX),
+            })
+            .collect();
+        Self {
+            pointer,
+            keys,
+            tokens,
+        }
+    }
+
+    /// The pointer this was compiled from.
+    pub fn as_str(&self) -> &str {
+        &self.pointer
+    }
+
+    /// The unescaped reference tokens.
+    #[cfg(feature = "dom")]
+    pub(crate) fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    pub(crate) fn tokens(&self) -> &[ffi::SJ_path_token] {
+        &self.tokens
+    }
+}
+
+impl Clone for CompiledPointer {
+    fn clone(&self) -> Self {
+        Self::from_keys(self.pointer.clone(), self.keys.clone())
+    }
+}
+
+impl std::fmt::Debug for CompiledPointer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("CompiledPointer")
+            .field(&self.pointer)
+            .finish()
+    }
+}
+
+impl std::str::FromStr for CompiledPointer {
+    type Err = SimdJsonError;
+
+    fn from_str(pointer: &str) -> Result<Self> {
+        Self::new(pointer)
+    }
+}
+
+/// Splits a JSON pointer into its unescaped reference tokens.
+pub(crate) fn parse_pointer(pointer: &str) -> Result<Vec<String>> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rest = pointer
+        .strip_prefix('/')
+        .ok_or(SimdJsonError::InvalidJsonPointer)?;
+    rest.split('/')
+        .map(|segment| {
+            let mut out = String::with_capacity(segment.len());
+            let mut chars = segment.chars();
+            while let Some(c) = chars.next() {
+                match (c, c == '~') {
+                    (_, false) => out.push(c),
+                    (_, true) => match chars.next() {
+                        Some('0') => out.push('~'),
+                        Some('1') => out.push('/'),
+                        _ => return Err(SimdJsonError::InvalidJsonPointer),
+                    },
+                }
+            }
+            Ok(out)
+        })
+        .collect()
+}
+
+/// Appends `segment` to `out` as a JSON pointer reference token, with `~`
+/// and `/` escaped as `~0` and `~1`. The `/` before the token is left to the
+/// caller.
+///
+/// ```ignore
+/// let mut pointer = String::from("/users/");
+/// escape_segment("a/b", &mut pointer);
+/// assert_eq!(pointer, "/users/a~1b");
+/// ```
+pub fn escape_segment(segment: &str, out: &mut String) {
+    for c in segment.chars() {
+        match c {
+            '~' => out.push_str("~0"),
+            '/' => out.push_str("~1"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// The array index a reference token denotes: digits without leading zeros.
+pub(crate) fn array_index(token: &str) -> Option<usize> {
+    let digits = token.bytes().all(|b| b.is_ascii_digit());
+    if token.is_empty() || !digits || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(all(feature = "dom", feature = "ondemand"))]
+    use crate::{dom, ondemand, prelude::*};
+
+    #[test]
+    fn parse_pointers() {
+        assert_eq!(parse_pointer("").unwrap(), Vec::<String>::new());
+        assert_eq!(parse_pointer("/a~1b/~0/*").unwrap(), ["a/b", "~", "*"]);
+        let mut pointer = String::from("/");
+        escape_segment("a/b~c", &mut pointer);
+        assert_eq!(pointer, "/a~1b~0c");
+        assert_eq!(parse_pointer(&pointer).unwrap(), ["a/b~c"]);
+        assert!(parse_pointer("a").is_err());
+        assert!(parse_pointer("/a~2").is_err());
+        assert_eq!(array_index("10"), Some(10));
+        assert_eq!(array_index("01"), None);
+        assert_eq!(array_index("-"), None);
+    }
+
+    #[cfg(all(feature = "dom", feature = "ondemand"))]
+    #[test]
+    fn lookups() {
+        let json = r#"{"a": {"b/c": [10, {"0": "x"}]}, "n": 1}"#.to_padded_string();
+        let pointer = CompiledPointer::new("/a/b~1c/1/0").unwrap();
+        let missing: CompiledPointer = "/a/zz".parse().unwrap();
+        let scalar = pointer.clone();
+        assert_eq!(scalar.as_str(), "/a/b~1c/1/0");
+
+        let mut parser = dom::Parser::default();
+        let root = parser.parse(&json).unwrap();
+        assert_eq!(
+            root.at_compiled_pointer(&pointer)
+                .unwrap()
+                .get_string()
+                .unwrap(),
+            "x"
+        );
+        assert!(root.at_compiled_pointer(&missing).is_err());
+        let empty = CompiledPointer::new("").unwrap();
+        assert_eq!(
+            root.at_compiled_pointer(&empty).unwrap().get_type(),
+            dom::ElementType::Object
+        );
+        drop(root);
+
+        let mut parser = ondemand::Parser::default();
+        let mut doc = parser.iterate(&json).unwrap();
+        // Each lookup starts over, so the order does not matter.
+        for _ in 0..2 {
+            let mut value = doc.at_compiled_pointer(&pointer).unwrap();
+            assert_eq!(value.get_string(false).unwrap(), "x");
+            assert!(doc.at_compiled_pointer(&missing).is_err());
+        }
+        let n = CompiledPointer::new("/n/0").unwrap();
+        assert!(doc.at_compiled_pointer(&n).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+//! Per-thread parsers and padded buffers behind the one-shot helpers
+//! ([`crate::validate`], [`crate::dom::json_matches`], `serde::de::from_str`,
+//! ...), so they are as fast as code that keeps its own parser around. The
+//! buffers are also available directly through
+//! [`crate::padded_string::rent_padded_string`].
+
+use std::cell::RefCell;
+
+#[cfg(feature = "dom")]
+use simdjson_sys as ffi;
+
+#[cfg(feature = "builder")]
+use crate::builder::StringBuilder;
+#[cfg(feature = "dom")]
+use crate::dom;
+
+/// How many parsers each thread keeps. Helpers that need two at once, like
+/// `json_matches`, still reuse both.
+#[cfg(any(feature = "dom", feature = "builder"))]
+const MAX_POOLED: usize = 2;
+
+/// Padded buffers are pooled in power-of-two size classes from 1 KiB to
+/// 16 MiB; larger ones are freed when returned.
+const MIN_CLASS_BITS: u32 = 10;
+const MAX_CLASS_BITS: u32 = 24;
+const CLASSES: usize = (MAX_CLASS_BITS - MIN_CLASS_BITS + 1) as usize;
+/// How many buffers each thread keeps per size class.
+const MAX_POOLED_PER_CLASS: usize = 4;
+
+thread_local! {
+    #[cfg(feature = "dom")]
+    static PARSERS: RefCell<Vec<dom::Parser>> = const { RefCell::new(Vec::new()) };
+    #[cfg(feature = "builder")]
+    static BUILDERS: RefCell<Vec<StringBuilder>> = const { RefCell::new(Vec::new()) };
+    static BUFFERS: RefCell<[Vec<String>; CLASSES]> =
+        const { RefCell::new([const { Vec::new() }; CLASSES]) };
+}
+
+#[cfg(any(feature = "dom", feature = "builder"))]
+fn take<T: Default>(pool: &'static std::thread::LocalKey<RefCell<Vec<T>>>) -> T {
+    pool.try_with(|pool| pool.borrow_mut().pop())
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+#[cfg(any(feature = "dom", feature = "builder"))]
+fn give_back<T>(pool: &'static std::thread::LocalKey<RefCell<Vec<T>>>, value: T) {
+    // Fails only while the thread is shutting down; the value is dropped then.
+    let _ = pool.try_with(|pool| {
+        let mut pool = pool.borrow_mut();
+        if pool.len() < MAX_POOLED {
+            pool.push(value);
+        }
+    });
+}
+
+/// Runs `f` with a DOM parser owned by the current thread.
+#[cfg(feature = "dom")]
+pub(crate) fn with_parser<T>(f: impl FnOnce(&mut dom::Parser) -> T) -> T {
+    let mut parser = take(&PARSERS);
+    let result = f(&mut parser);
+    give_back(&PARSERS, parser);
+    result
+}
+
+/// Runs `f` with an empty string builder owned by the current thread.
+#[cfg(feature = "builder")]
+pub(crate) fn with_builder<T>(f: impl FnOnce(&mut StringBuilder) -> T) -> T {
+    let mut builder = take(&BUILDERS);
+    let result = f(&mut builder);
+    builder.clear();
+    give_back(&BUILDERS, builder);
+    result
+}
+
+/// Copies `json` into a padded buffer owned by the current thread and runs
+/// `f` on it.
+#[cfg(feature = "dom")]
+pub(crate) fn with_padded<T>(json: &str, f: impl FnOnce(&str) -> T) -> T {
+    let mut buf = take_buffer(json.len() + ffi::SIMDJSON_PADDING);
+    buf.push_str(json);
+    let result = f(&buf);
+    give_back_buffer(buf);
+    result
+}
+
+/// An empty string with room for at least `capacity` bytes, from the
+/// current thread's pool when one of the right size class is available.
+pub(crate) fn take_buffer(capacity: usize) -> String {
+    let bits = match capacity.checked_next_power_of_two() {
+        Some(size) => size.trailing_zeros().max(MIN_CLASS_BITS),
+        None => return String::with_capacity(capacity),
+    };
+    if bits > MAX_CLASS_BITS {
+        return String::with_capacity(capacity);
+    }
+    let class = (bits - MIN_CLASS_BITS) as usize;
+    BUFFERS
+        .try_with(|pool| pool.borrow_mut()[class].pop())
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| String::with_capacity(1 << bits))
+}
+
+/// Returns a buffer to the size class its capacity covers.
+pub(crate) fn give_back_buffer(mut buf: String) {
+    let capacity = buf.capacity();
+    if capacity < 1 << MIN_CLASS_BITS {
+        return;
+    }
+    let bits = capacity.ilog2();
+    if bits > MAX_CLASS_BITS {
+        return;
+    }
+    buf.clear();
+    let class = (bits - MIN_CLASS_BITS) as usize;
+    // Fails only while the thread is shutting down; the buffer is dropped then.
+    let _ = BUFFERS.try_with(|pool| {
+        let class = &mut pool.borrow_mut()[class];
+        if class.len() < MAX_POOLED_PER_CLASS {
+            class.push(buf);
+        }
+    });
+}
+
+/// Frees the parsers, builders and buffers the one-shot helpers keep for the current
+/// thread. They grow to fit the largest document seen, so memory-sensitive
+/// applications may want to call this after parsing an unusually large one.
+/// They are recreated on next use.
+pub fn release_thread_local_parsers() {
+    #[cfg(feature = "dom")]
+    let _ = PARSERS.try_with(|pool| pool.borrow_mut().clear());
+    #[cfg(feature = "builder")]
+    let _ = BUILDERS.try_with(|pool| pool.borrow_mut().clear());
+    let _ = BUFFERS.try_with(|pool| pool.borrow_mut().iter_mut().for_each(Vec::clear));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "dom")]
+    #[test]
+    fn reuse() {
+        let capacity = with_padded("[1, 2]", |json| {
+            with_parser(|parser| {
+                parser.parse(json).unwrap();
+                parser.capacity()
+            })
+        });
+        assert!(capacity > 0);
+        assert_eq!(with_parser(|parser| parser.capacity()), capacity);
+        // Nested use gets a second parser.
+        with_parser(|a| with_parser(|b| assert_ne!(a.capacity(), b.capacity())));
+        release_thread_local_parsers();
+        assert_eq!(with_parser(|parser| parser.capacity()), 0);
+    }
+
+    #[test]
+    fn buffer_size_classes() {
+        let buf = take_buffer(100);
+        assert_eq!(buf.capacity(), 1024);
+        let ptr = buf.as_ptr();
+        give_back_buffer(buf);
+        // Served from the pool, but only to requests of the same size class.
+        let bigger = take_buffer(3000);
+        assert_ne!(bigger.as_ptr(), ptr);
+        let buf = take_buffer(1000);
+        assert_eq!(buf.as_ptr(), ptr);
+        give_back_buffer(buf);
+        assert!(take_buffer(64 << 20).capacity() >= 64 << 20);
+    }
+}
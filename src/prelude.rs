@@ -1,3 +1,3 @@
 pub use crate::padded_string::{
-    IntoPaddedString, ToPaddedString, load_padded_string, make_padded_string,
+    IntoPaddedString, ToPaddedString, load_padded_string, make_padded_string, read_padded_string,
 };
@@ -0,0 +1,48 @@
+use crate::{
+    Result,
+    builder::StringBuilder,
+    dom::emit::{Layout, emit},
+    pool,
+};
+
+/// Parses `json` and re-emits it indented by two spaces per level, one value
+/// per line. Invalid documents are rejected with the parse error.
+///
+/// The document is parsed by a parser owned by the current thread and written
+/// with a [`StringBuilder`], so no setup is needed. Numbers are re-emitted
+/// from their parsed values, so a double like `1e2` may be spelled
+/// differently than in the input.
+pub fn prettify(json: &str) -> Result<String> {
+    pool::with_padded(json, |padded_string| {
+        pool::with_parser(|parser| {
+            let root = parser.parse(padded_string)?;
+            let mut out = StringBuilder::with_capacity(json.len() * 2);
+            emit(&root, Layout::pretty(2), &mut out)?;
+            out.into_string()
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prettify_nested() {
+        let json = r#"{"a":[1,{"b":null}],"c":{},"d":[],"e":"x\"y"}"#;
+        let expected = r#"{
+  "a": [
+    1,
+    {
+      "b": null
+    }
+  ],
+  "c": {},
+  "d": [],
+  "e": "x\"y"
+}"#;
+        assert_eq!(prettify(json).unwrap(), expected);
+        assert_eq!(prettify(" true ").unwrap(), "true");
+        assert!(prettify("[1,").is_err());
+    }
+}
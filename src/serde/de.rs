@@ -1,15 +1,176 @@
 use crate::dom::{ArrayIter, Element, ElementType, ObjectIter};
 use crate::error::SimdJsonError;
+use crate::{
+    BomPolicy, pool,
+    utils::{parse_double, parse_i64, parse_u64, validate_utf8},
+};
 
 use serde::de::{
-    Deserialize, DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
-    VariantAccess, Visitor,
+    Deserialize, DeserializeOwned, DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess,
+    VariantAccess, Visitor, value::BorrowedStrDeserializer,
 };
+use simdjson_sys as ffi;
+
+/// The newtype name [`helpers::numbers`](super::helpers::numbers) asks for,
+/// telling an [`Element`] deserializer to copy the array's numbers out in one
+/// pass.
+pub(crate) const NUMBERS_TOKEN: &str = "$simdjson_rust::numbers";
+
+/// The numeric `deserialize_*` methods, written against `get_int64`,
+/// `get_uint64` and `get_double` so [`Element`] and [`Number`] behave alike.
+macro_rules! deserialize_numbers {
+    () => {
+        fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            let v = self.get_int64()?;
+            let narrow = i8::try_from(v)
+                .map_err(|_| de_error(&format!("i64 value {v} out of range for i8")))?;
+            visitor.visit_i8(narrow)
+        }
+
+        fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            let v = self.get_int64()?;
+            let narrow = i16::try_from(v)
+                .map_err(|_| de_error(&format!("i64 value {v} out of range for i16")))?;
+            visitor.visit_i16(narrow)
+        }
+
+        fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            let v = self.get_int64()?;
+            let narrow = i32::try_from(v)
+                .map_err(|_| de_error(&format!("i64 value {v} out of range for i32")))?;
+            visitor.visit_i32(narrow)
+        }
+
+        fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            visitor.visit_i64(self.get_int64()?)
+        }
+
+        fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            let v = self.get_uint64()?;
+            let narrow = u8::try_from(v)
+                .map_err(|_| de_error(&format!("u64 value {v} out of range for u8")))?;
+            visitor.visit_u8(narrow)
+        }
+
+        fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            let v = self.get_uint64()?;
+            let narrow = u16::try_from(v)
+                .map_err(|_| de_error(&format!("u64 value {v} out of range for u16")))?;
+            visitor.visit_u16(narrow)
+        }
+
+        fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            let v = self.get_uint64()?;
+            let narrow = u32::try_from(v)
+                .map_err(|_| de_error(&format!("u64 value {v} out of range for u32")))?;
+            visitor.visit_u32(narrow)
+        }
+
+        fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            visitor.visit_u64(self.get_uint64()?)
+        }
+
+        fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            visitor.visit_f32(self.get_double()? as f32)
+        }
+
+        fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            visitor.visit_f64(self.get_double()?)
+        }
+    };
+}
+
+/// The `deserialize_*` methods that take only a visitor, handed to
+/// [`ElementDeserializer`] with the default options.
+macro_rules! forward_to_element_deserializer {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                ElementDeserializer::from(self).$method(visitor)
+            }
+        )*
+    };
+}
 
 fn de_error(msg: &str) -> SimdJsonError {
     SimdJsonError::Serde(msg.to_owned())
 }
 
+/// Opt-in coercions for sloppy upstream data. The default is strict: JSON
+/// types must match the Rust types they are read into.
+///
+/// The options do not reach fields of `#[serde(flatten)]` structs: serde
+/// buffers those values itself before reading them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeserializerOptions {
+    /// Read strings holding a JSON number, such as `"42"` or `" -1.5 "`, into
+    /// integer and float fields.
+    pub coerce_numbers: bool,
+    /// Read `"true"` and `"false"` strings and the numbers `0` and `1` into
+    /// bool fields.
+    pub coerce_bools: bool,
+    /// Read floats without a fractional part, such as `3.0` or `1e3`, into
+    /// integer fields, as long as they are in range. Other floats are still
+    /// errors.
+    pub integral_floats: bool,
+    /// What [`from_str_with`] does with a UTF-8 byte order mark.
+    pub bom: BomPolicy,
+}
+
+impl DeserializerOptions {
+    /// Every coercion enabled.
+    pub fn lenient() -> Self {
+        Self {
+            coerce_numbers: true,
+            coerce_bools: true,
+            integral_floats: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// Deserializes `T` from `element`.
+///
+/// Strings and keys are borrowed from the document, so `&'a str`,
+/// `&'a [u8]` and `Cow<'a, str>` fields marked `#[serde(borrow)]` copy
+/// nothing; without the attribute serde fills a `Cow` with an owned copy.
+/// `Box<T>` is read like the `T` it holds, and `Box<str>` allocates the
+/// string once. `Rc<T>` and `Arc<T>`, with the `serde_rc` feature, go
+/// through serde's own impls, which read a `Box<T>` and copy it into the
+/// shared allocation.
 pub fn from_element<'a, T>(element: &'a Element<'a>) -> Result<T, SimdJsonError>
 where
     T: Deserialize<'a>,
@@ -18,132 +179,209 @@ where
     Ok(t)
 }
 
-impl<'de, 'a> Deserializer<'de> for &'a Element<'a> {
-    type Error = SimdJsonError;
+/// Like [`from_element`], applying `options` to every value in the document.
+pub fn from_element_with<'a, T>(
+    element: &'a Element<'a>,
+    options: DeserializerOptions,
+) -> Result<T, SimdJsonError>
+where
+    T: Deserialize<'a>,
+{
+    T::deserialize(ElementDeserializer { element, options })
+}
 
-    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        match self.get_type() {
-            ElementType::NullValue => self.deserialize_unit(visitor),
-            ElementType::Bool => self.deserialize_bool(visitor),
-            ElementType::String => self.deserialize_string(visitor),
-            ElementType::UInt64 => self.deserialize_u64(visitor),
-            ElementType::Int64 => self.deserialize_i64(visitor),
-            ElementType::Array => self.deserialize_seq(visitor),
-            ElementType::Object => self.deserialize_map(visitor),
-            ElementType::Double => self.deserialize_f64(visitor),
-        }
-    }
+/// Deserializes the value `pointer` resolves to under `element`, for pulling
+/// one part out of a larger response:
+///
+/// ```ignore
+/// let items: Vec<Item> = from_pointer(&root, "/data/items")?;
+/// ```
+///
+/// Any failure, a pointer that does not resolve included, is reported as
+/// [`SimdJsonError::AtPointer`] naming `pointer`.
+pub fn from_pointer<'a, T>(element: &Element<'a>, pointer: &str) -> Result<T, SimdJsonError>
+where
+    T: Deserialize<'a>,
+{
+    element
+        .at_pointer(pointer)
+        .and_then(|target| T::deserialize(&target))
+        .map_err(|source| SimdJsonError::AtPointer {
+            pointer: pointer.to_owned(),
+            source: Box::new(source),
+        })
+}
 
-    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        visitor.visit_bool(self.get_bool()?)
-    }
+/// Parses `json` and deserializes it into `T` in one call.
+///
+/// The input does not need to be padded: it is copied into a padded buffer
+/// and parsed with a parser that is kept per thread, so repeated calls cost
+/// no more than managing a parser by hand. See
+/// [`crate::release_thread_local_parsers`].
+pub fn from_str<T>(json: &str) -> Result<T, SimdJsonError>
+where
+    T: DeserializeOwned,
+{
+    pool::with_padded(json, |json| {
+        pool::with_parser(|parser| from_element(&parser.parse(json)?))
+    })
+}
 
-    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        visitor.visit_unit()
-    }
+/// Like [`from_str`], applying `options` to every value in the document.
+pub fn from_str_with<T>(json: &str, options: DeserializerOptions) -> Result<T, SimdJsonError>
+where
+    T: DeserializeOwned,
+{
+    options.bom.check(json)?;
+    pool::with_padded(json, |json| {
+        pool::with_parser(|parser| from_element_with(&parser.parse(json)?, options))
+    })
+}
 
-    fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        let v = self.get_int64()?;
-        let narrow =
-            i8::try_from(v).map_err(|_| de_error(&format!("i64 value {v} out of range for i8")))?;
-        visitor.visit_i8(narrow)
+/// Like [`from_str`], for bytes that still need UTF-8 validation.
+pub fn from_slice<T>(json: &[u8]) -> Result<T, SimdJsonError>
+where
+    T: DeserializeOwned,
+{
+    if !validate_utf8(json) {
+        return Err(SimdJsonError::Utf8Error);
     }
+    // Checked just above.
+    from_str(unsafe { std::str::from_utf8_unchecked(json) })
+}
 
-    fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        let v = self.get_int64()?;
-        let narrow = i16::try_from(v)
-            .map_err(|_| de_error(&format!("i64 value {v} out of range for i16")))?;
-        visitor.visit_i16(narrow)
+/// Deserializes one element with a set of [`DeserializerOptions`], passing
+/// them on to the element's children.
+#[derive(Clone, Copy)]
+struct ElementDeserializer<'a, 'de> {
+    element: &'a Element<'de>,
+    options: DeserializerOptions,
+}
+
+impl<'de> ElementDeserializer<'_, 'de> {
+    /// The element's text, when it is a string to read as a number.
+    fn numeric_string(self) -> Option<&'de str> {
+        if !self.options.coerce_numbers || self.element.get_type() != ElementType::String {
+            return None;
+        }
+        self.element.get_string().ok().map(str::trim)
     }
 
-    fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        let v = self.get_int64()?;
-        let narrow = i32::try_from(v)
-            .map_err(|_| de_error(&format!("i64 value {v} out of range for i32")))?;
-        visitor.visit_i32(narrow)
+    /// The element's value, when it is a float to read as an integer. Strings
+    /// only count when they are written as floats, so large integers are not
+    /// rounded on the way.
+    fn integral_float(self) -> Option<f64> {
+        if !self.options.integral_floats {
+            return None;
+        }
+        match self.numeric_string() {
+            Some(s) if s.contains(['.', 'e', 'E']) => parse_double(s.as_bytes()).ok(),
+            Some(_) => None,
+            None if self.element.get_type() == ElementType::Double => {
+                self.element.get_double().ok()
+            }
+            None => None,
+        }
     }
 
-    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        visitor.visit_i64(self.get_int64()?)
+    fn get_int64(self) -> Result<i64, SimdJsonError> {
+        if let Some(v) = self.integral_float() {
+            // `i64::MAX as f64` is 2^63, the first value out of range.
+            return if v.fract() == 0.0 && v >= i64::MIN as f64 && v < i64::MAX as f64 {
+                Ok(v as i64)
+            } else {
+                Err(de_error(&format!("expected an i64, got the number {v}")))
+            };
+        }
+        match self.numeric_string() {
+            Some(s) => parse_i64(s.as_bytes()),
+            None => self.element.get_int64(),
+        }
     }
 
-    fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        let v = self.get_uint64()?;
-        let narrow =
-            u8::try_from(v).map_err(|_| de_error(&format!("u64 value {v} out of range for u8")))?;
-        visitor.visit_u8(narrow)
+    fn get_uint64(self) -> Result<u64, SimdJsonError> {
+        if let Some(v) = self.integral_float() {
+            return if v.fract() == 0.0 && v >= 0.0 && v < u64::MAX as f64 {
+                Ok(v as u64)
+            } else {
+                Err(de_error(&format!("expected a u64, got the number {v}")))
+            };
+        }
+        match self.numeric_string() {
+            Some(s) => parse_u64(s.as_bytes()),
+            None => self.element.get_uint64(),
+        }
     }
 
-    fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        let v = self.get_uint64()?;
-        let narrow = u16::try_from(v)
-            .map_err(|_| de_error(&format!("u64 value {v} out of range for u16")))?;
-        visitor.visit_u16(narrow)
+    fn get_double(self) -> Result<f64, SimdJsonError> {
+        match self.numeric_string() {
+            Some(s) => parse_double(s.as_bytes()),
+            None => self.element.get_double(),
+        }
     }
 
-    fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
-    where
-        V: Visitor<'de>,
-    {
-        let v = self.get_uint64()?;
-        let narrow = u32::try_from(v)
-            .map_err(|_| de_error(&format!("u64 value {v} out of range for u32")))?;
-        visitor.visit_u32(narrow)
+    fn get_bool(self) -> Result<bool, SimdJsonError> {
+        if !self.options.coerce_bools {
+            return self.element.get_bool();
+        }
+        match self.element.get_type() {
+            ElementType::String => match self.element.get_string()? {
+                "true" => Ok(true),
+                "false" => Ok(false),
+                s => Err(de_error(&format!("expected a bool, got the string {s:?}"))),
+            },
+            ElementType::Int64 | ElementType::UInt64 => match self.element.get_int64()? {
+                0 => Ok(false),
+                1 => Ok(true),
+                v => Err(de_error(&format!("expected a bool, got the number {v}"))),
+            },
+            _ => self.element.get_bool(),
+        }
     }
+}
 
-    fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+impl<'de> Deserializer<'de> for ElementDeserializer<'_, 'de> {
+    type Error = SimdJsonError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u64(self.get_uint64()?)
+        match self.element.get_type() {
+            ElementType::NullValue => self.deserialize_unit(visitor),
+            ElementType::Bool => self.deserialize_bool(visitor),
+            // Borrowed, so values buffered for `#[serde(flatten)]` and
+            // untagged enums can still be read into `&str` fields.
+            ElementType::String => self.deserialize_str(visitor),
+            ElementType::UInt64 => self.deserialize_u64(visitor),
+            ElementType::Int64 => self.deserialize_i64(visitor),
+            ElementType::Array => self.deserialize_seq(visitor),
+            ElementType::Object => self.deserialize_map(visitor),
+            ElementType::Double => self.deserialize_f64(visitor),
+        }
     }
 
-    fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_f32(self.get_double()? as f32)
+        visitor.visit_bool(self.get_bool()?)
     }
 
-    fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_f64(self.get_double()?)
+        visitor.visit_unit()
     }
 
+    deserialize_numbers!();
+
     fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        let s = self.get_string()?;
+        let s = self.element.get_string()?;
         let mut chars = s.chars();
         match (chars.next(), chars.next()) {
             (Some(c), None) => visitor.visit_char(c),
@@ -155,37 +393,48 @@ impl<'de, 'a> Deserializer<'de> for &'a Element<'a> {
     where
         V: Visitor<'de>,
     {
-        let s = self.get_string()?;
-        visitor.visit_str(s)
+        visitor.visit_borrowed_str(self.element.get_string()?)
     }
 
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        let s = self.get_string()?;
+        let s = self.element.get_string()?;
         visitor.visit_string(s.to_owned())
     }
 
+    /// Strings are handed out as their UTF-8 bytes, borrowed from the parser;
+    /// anything else is read as an array of numbers.
     fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        match self.element.get_type() {
+            ElementType::String => {
+                visitor.visit_borrowed_bytes(self.element.get_string()?.as_bytes())
+            }
+            _ => self.deserialize_seq(visitor),
+        }
     }
 
     fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        self.deserialize_seq(visitor)
+        match self.element.get_type() {
+            ElementType::String => {
+                visitor.visit_byte_buf(self.element.get_string()?.as_bytes().to_vec())
+            }
+            _ => self.deserialize_seq(visitor),
+        }
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        if self.is_null() {
+        if self.element.is_null() {
             visitor.visit_none()
         } else {
             visitor.visit_some(self)
@@ -205,22 +454,37 @@ impl<'de, 'a> Deserializer<'de> for &'a Element<'a> {
 
     fn deserialize_newtype_struct<V>(
         self,
-        _name: &'static str,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_newtype_struct(self)
+        if name != NUMBERS_TOKEN {
+            return visitor.visit_newtype_struct(self);
+        }
+        // Arrays of numbers are copied out in one pass, skipping the
+        // per-element `Element` handles and FFI calls.
+        // Copied numbers cannot be coerced into bools or integers, so that
+        // path is off then.
+        if !self.options.coerce_bools && !self.options.integral_floats {
+            if let Ok(numbers) = self.element.get_array()?.to_vec_numbers() {
+                return visitor.visit_seq(NumberSeq(numbers.into_iter()));
+            }
+        }
+        self.deserialize_seq(visitor)
     }
 
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        let array = self.get_array()?;
-        let iter = array.iter();
-        visitor.visit_seq(SeqAccessor(iter))
+        let array = self.element.get_array()?;
+        visitor.visit_seq(SeqAccessor {
+            iter: array.iter(),
+            remaining: array.exact_size(),
+            options: self.options,
+        })
     }
 
     fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
@@ -246,9 +510,9 @@ impl<'de, 'a> Deserializer<'de> for &'a Element<'a> {
     where
         V: Visitor<'de>,
     {
-        let object = self.get_object()?;
+        let object = self.element.get_object()?;
         let iter = object.iter();
-        visitor.visit_map(MapAccessor::new(iter))
+        visitor.visit_map(MapAccessor::new(iter, object.exact_size(), self.options))
     }
 
     fn deserialize_struct<V>(
@@ -260,6 +524,10 @@ impl<'de, 'a> Deserializer<'de> for &'a Element<'a> {
     where
         V: Visitor<'de>,
     {
+        // Keys go to the visitor as strings, never as indexes into `_fields`:
+        // serde_derive lists every alias in `_fields`, so positions there do
+        // not line up with the field indexes its `visit_u64` expects. Field
+        // names are matched by the visitor itself.
         self.deserialize_map(visitor)
     }
 
@@ -272,26 +540,22 @@ impl<'de, 'a> Deserializer<'de> for &'a Element<'a> {
     where
         V: Visitor<'de>,
     {
-        match self.get_type() {
+        match self.element.get_type() {
             ElementType::String => {
-                let s = self.get_string()?;
-                let de: serde::de::value::StrDeserializer<'_, SimdJsonError> =
-                    s.into_deserializer();
+                let de = BorrowedStrDeserializer::<SimdJsonError>::new(self.element.get_string()?);
                 visitor.visit_enum(de)
             }
             ElementType::Object => {
-                let object = self.get_object()?;
+                let object = self.element.get_object()?;
                 let mut iter = object.iter();
                 let pair = iter.next();
                 drop(iter);
                 match pair {
-                    Some((variant, value)) => {
-                        let variant_owned = String::from(variant);
-                        visitor.visit_enum(EnumDeserializer {
-                            variant: variant_owned,
-                            value,
-                        })
-                    }
+                    Some((variant, value)) => visitor.visit_enum(EnumDeserializer {
+                        variant,
+                        value,
+                        options: self.options,
+                    }),
                     None => Err(de_error("expected an object with a single key for enum")),
                 }
             }
@@ -314,37 +578,270 @@ impl<'de, 'a> Deserializer<'de> for &'a Element<'a> {
     }
 }
 
-struct SeqAccessor<'a>(ArrayIter<'a>);
+impl<'de> Deserializer<'de> for &Element<'de> {
+    type Error = SimdJsonError;
+
+    forward_to_element_deserializer! {
+        deserialize_any deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32
+        deserialize_i64 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_f32 deserialize_f64 deserialize_char deserialize_str deserialize_string
+        deserialize_bytes deserialize_byte_buf deserialize_option deserialize_unit
+        deserialize_seq deserialize_map deserialize_identifier deserialize_ignored_any
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        ElementDeserializer::from(self).deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        ElementDeserializer::from(self).deserialize_newtype_struct(name, visitor)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        ElementDeserializer::from(self).deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        ElementDeserializer::from(self).deserialize_tuple_struct(name, len, visitor)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        ElementDeserializer::from(self).deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        ElementDeserializer::from(self).deserialize_enum(name, variants, visitor)
+    }
+}
+
+impl<'a, 'de> From<&'a Element<'de>> for ElementDeserializer<'a, 'de> {
+    /// Strict, with the default options.
+    fn from(element: &'a Element<'de>) -> Self {
+        Self {
+            element,
+            options: DeserializerOptions::default(),
+        }
+    }
+}
+
+struct SeqAccessor<'de> {
+    iter: ArrayIter<'de>,
+    remaining: Option<usize>,
+    options: DeserializerOptions,
+}
 
-impl<'de, 'a> SeqAccess<'de> for SeqAccessor<'a> {
+impl<'de> SeqAccess<'de> for SeqAccessor<'de> {
     type Error = SimdJsonError;
 
     fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
     where
         T: DeserializeSeed<'de>,
     {
-        match self.0.next() {
-            Some(element) => seed.deserialize(&element).map(Some),
+        match self.iter.next() {
+            Some(element) => {
+                if let Some(remaining) = &mut self.remaining {
+                    *remaining -= 1;
+                }
+                seed.deserialize(ElementDeserializer {
+                    element: &element,
+                    options: self.options,
+                })
+                .map(Some)
+            }
             None => Ok(None),
         }
     }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.remaining
+    }
+}
+
+struct NumberSeq(std::vec::IntoIter<ffi::SJ_DOM_number>);
+
+impl<'de> SeqAccess<'de> for NumberSeq {
+    type Error = SimdJsonError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.0
+            .next()
+            .map(|number| seed.deserialize(Number::from(number)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
 }
 
-struct MapAccessor<'a> {
-    iter: ObjectIter<'a>,
-    pending_value: Option<Element<'a>>,
+/// An element of an all-number array, copied out of the tape.
+#[derive(Clone, Copy)]
+enum Number {
+    Int64(i64),
+    UInt64(u64),
+    Double(f64),
 }
 
-impl<'a> MapAccessor<'a> {
-    fn new(iter: ObjectIter<'a>) -> Self {
+impl From<ffi::SJ_DOM_number> for Number {
+    fn from(number: ffi::SJ_DOM_number) -> Self {
+        match ElementType::from(number.element_type) {
+            ElementType::Int64 => Number::Int64(number.bits as i64),
+            ElementType::UInt64 => Number::UInt64(number.bits),
+            _ => Number::Double(f64::from_bits(number.bits)),
+        }
+    }
+}
+
+impl Number {
+    // The same conversions simdjson makes for an element of this type.
+
+    fn get_int64(self) -> Result<i64, SimdJsonError> {
+        match self {
+            Number::Int64(v) => Ok(v),
+            Number::UInt64(_) => Err(SimdJsonError::NumberOutOfRange),
+            Number::Double(_) => Err(SimdJsonError::IncorrectType),
+        }
+    }
+
+    fn get_uint64(self) -> Result<u64, SimdJsonError> {
+        match self {
+            Number::Int64(v) => u64::try_from(v).map_err(|_| SimdJsonError::NumberOutOfRange),
+            Number::UInt64(v) => Ok(v),
+            Number::Double(_) => Err(SimdJsonError::IncorrectType),
+        }
+    }
+
+    fn get_double(self) -> Result<f64, SimdJsonError> {
+        Ok(match self {
+            Number::Int64(v) => v as f64,
+            Number::UInt64(v) => v as f64,
+            Number::Double(v) => v,
+        })
+    }
+}
+
+impl<'de> Deserializer<'de> for Number {
+    type Error = SimdJsonError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Number::Int64(v) => visitor.visit_i64(v),
+            Number::UInt64(v) => visitor.visit_u64(v),
+            Number::Double(v) => visitor.visit_f64(v),
+        }
+    }
+
+    deserialize_numbers!();
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool char str string bytes byte_buf seq tuple tuple_struct map struct
+        enum identifier ignored_any
+    }
+}
+
+struct MapAccessor<'de> {
+    iter: ObjectIter<'de>,
+    pending_value: Option<Element<'de>>,
+    // Lets `HashMap` and `BTreeMap` targets allocate once.
+    remaining: Option<usize>,
+    options: DeserializerOptions,
+}
+
+impl<'de> MapAccessor<'de> {
+    fn new(iter: ObjectIter<'de>, remaining: Option<usize>, options: DeserializerOptions) -> Self {
         Self {
             iter,
             pending_value: None,
+            remaining,
+            options,
         }
     }
 }
 
-impl<'de, 'a> MapAccess<'de> for MapAccessor<'a> {
+impl<'de> MapAccess<'de> for MapAccessor<'de> {
     type Error = SimdJsonError;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
@@ -354,9 +851,13 @@ impl<'de, 'a> MapAccess<'de> for MapAccessor<'a> {
         match self.iter.next() {
             Some((key, value)) => {
                 self.pending_value = Some(value);
-                let de: serde::de::value::StringDeserializer<SimdJsonError> =
-                    String::from(key).into_deserializer();
-                seed.deserialize(de).map(Some)
+                if let Some(remaining) = &mut self.remaining {
+                    *remaining -= 1;
+                }
+                // Keys borrow from the document, so matching struct fields
+                // allocates nothing.
+                seed.deserialize(BorrowedStrDeserializer::<SimdJsonError>::new(key))
+                    .map(Some)
             }
             None => Ok(None),
         }
@@ -367,35 +868,60 @@ impl<'de, 'a> MapAccess<'de> for MapAccessor<'a> {
         V: DeserializeSeed<'de>,
     {
         match self.pending_value.take() {
-            Some(value) => seed.deserialize(&value),
+            Some(value) => seed.deserialize(ElementDeserializer {
+                element: &value,
+                options: self.options,
+            }),
             None => Err(de_error("next_value_seed called before next_key_seed")),
         }
     }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.remaining
+    }
 }
 
-struct EnumDeserializer<'a> {
-    variant: String,
-    value: Element<'a>,
+struct EnumDeserializer<'de> {
+    variant: &'de str,
+    value: Element<'de>,
+    options: DeserializerOptions,
 }
 
-impl<'de, 'a> EnumAccess<'de> for EnumDeserializer<'a> {
+impl<'de> EnumAccess<'de> for EnumDeserializer<'de> {
     type Error = SimdJsonError;
-    type Variant = VariantDeserializer<'a>;
+    type Variant = VariantDeserializer<'de>;
 
     fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
     where
         V: DeserializeSeed<'de>,
     {
-        let de: serde::de::value::StringDeserializer<SimdJsonError> =
-            self.variant.into_deserializer();
-        let variant = seed.deserialize(de)?;
-        Ok((variant, VariantDeserializer(self.value)))
+        let variant =
+            seed.deserialize(BorrowedStrDeserializer::<SimdJsonError>::new(self.variant))?;
+        Ok((
+            variant,
+            VariantDeserializer {
+                value: self.value,
+                options: self.options,
+            },
+        ))
     }
 }
 
-struct VariantDeserializer<'a>(Element<'a>);
+struct VariantDeserializer<'de> {
+    value: Element<'de>,
+    options: DeserializerOptions,
+}
+
+impl<'de> VariantDeserializer<'de> {
+    fn value(&self) -> ElementDeserializer<'_, 'de> {
+        ElementDeserializer {
+            element: &self.value,
+            options: self.options,
+        }
+    }
+}
 
-impl<'de, 'a> VariantAccess<'de> for VariantDeserializer<'a> {
+impl<'de> VariantAccess<'de> for VariantDeserializer<'de> {
     type Error = SimdJsonError;
 
     fn unit_variant(self) -> Result<(), Self::Error> {
@@ -408,14 +934,14 @@ impl<'de, 'a> VariantAccess<'de> for VariantDeserializer<'a> {
     where
         T: DeserializeSeed<'de>,
     {
-        seed.deserialize(&self.0)
+        seed.deserialize(self.value())
     }
 
     fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        serde::Deserializer::deserialize_seq(&self.0, visitor)
+        self.value().deserialize_seq(visitor)
     }
 
     fn struct_variant<V>(
@@ -426,6 +952,6 @@ impl<'de, 'a> VariantAccess<'de> for VariantDeserializer<'a> {
     where
         V: Visitor<'de>,
     {
-        serde::Deserializer::deserialize_map(&self.0, visitor)
+        self.value().deserialize_map(visitor)
     }
 }
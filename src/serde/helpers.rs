@@ -0,0 +1,211 @@
+//! Modules for `#[serde(with = "...")]` covering numbers carried as JSON
+//! strings, as many APIs do for IDs that do not fit in a JavaScript number.
+//!
+//! Deserializing accepts the string or a plain number, and parses the string
+//! with simdjson's number parser, so `"1.5"` is read the way `1.5` would be.
+//! Serializing writes the number as a string.
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Tweet {
+//!     #[serde(with = "simdjson_rust::serde::helpers::u64_as_string")]
+//!     id: u64,
+//! }
+//! ```
+
+use std::fmt;
+
+use serde::de::{self, Deserializer, Unexpected, Visitor};
+
+use crate::{serde::de::NUMBERS_TOKEN, utils};
+
+macro_rules! integer_as_string {
+    ($(#[$doc:meta])* $module:ident, $ty:ty, $parse:path, $expecting:literal) => {
+        $(#[$doc])*
+        pub mod $module {
+            use super::*;
+
+            pub fn serialize<S: serde::Serializer>(
+                value: &$ty,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(value)
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<$ty, D::Error> {
+                deserializer.deserialize_any(NumberVisitor)
+            }
+
+            struct NumberVisitor;
+
+            impl<'de> Visitor<'de> for NumberVisitor {
+                type Value = $ty;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str($expecting)
+                }
+
+                fn visit_str<E: de::Error>(self, v: &str) -> Result<$ty, E> {
+                    $parse(v.as_bytes()).map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
+                }
+
+                fn visit_u64<E: de::Error>(self, v: u64) -> Result<$ty, E> {
+                    v.try_into()
+                        .map_err(|_| E::invalid_value(Unexpected::Unsigned(v), &self))
+                }
+
+                fn visit_i64<E: de::Error>(self, v: i64) -> Result<$ty, E> {
+                    v.try_into()
+                        .map_err(|_| E::invalid_value(Unexpected::Signed(v), &self))
+                }
+            }
+        }
+    };
+}
+
+integer_as_string!(
+    /// A `u64` written as a decimal string.
+    u64_as_string,
+    u64,
+    utils::parse_u64,
+    "an unsigned integer or a string holding one"
+);
+
+integer_as_string!(
+    /// An `i64` written as a decimal string.
+    i64_as_string,
+    i64,
+    utils::parse_i64,
+    "an integer or a string holding one"
+);
+
+/// An `f64` written as a string, in the shortest form that reads back as the
+/// same value.
+pub mod f64_as_string {
+    use super::*;
+
+    pub fn serialize<S: serde::Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        deserializer.deserialize_any(NumberVisitor)
+    }
+
+    struct NumberVisitor;
+
+    impl<'de> Visitor<'de> for NumberVisitor {
+        type Value = f64;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a number or a string holding one")
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<f64, E> {
+            utils::parse_double(v.as_bytes())
+                .map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<f64, E> {
+            Ok(v as f64)
+        }
+
+        fn visit_i64<E: de::Error>(self, v: i64) -> Result<f64, E> {
+            Ok(v as f64)
+        }
+
+        fn visit_f64<E: de::Error>(self, v: f64) -> Result<f64, E> {
+            Ok(v)
+        }
+    }
+}
+
+/// A `Vec` of numbers, such as `Vec<f64>` or `Vec<u32>`, read from a DOM
+/// [`Element`](crate::dom::Element) in one pass over the array instead of one
+/// element at a time. An array holding anything but numbers, or a document
+/// read with the `coerce_bools` or `integral_floats` option, falls back to
+/// the element-at-a-time path; other deserializers always use it.
+///
+/// ```ignore
+/// #[derive(Deserialize)]
+/// struct Series {
+///     #[serde(with = "simdjson_rust::serde::helpers::numbers")]
+///     samples: Vec<f64>,
+/// }
+/// ```
+pub mod numbers {
+    use std::marker::PhantomData;
+
+    use serde::{
+        Deserialize, Serialize,
+        de::{SeqAccess, value::SeqAccessDeserializer},
+    };
+
+    use super::*;
+
+    pub fn serialize<T: Serialize, S: serde::Serializer>(
+        value: &[T],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<T>, D::Error> {
+        deserializer.deserialize_newtype_struct(NUMBERS_TOKEN, NumbersVisitor(PhantomData))
+    }
+
+    struct NumbersVisitor<T>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de>> Visitor<'de> for NumbersVisitor<T> {
+        type Value = Vec<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an array of numbers")
+        }
+
+        fn visit_newtype_struct<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<Vec<T>, D::Error> {
+            Vec::deserialize(deserializer)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Vec<T>, A::Error> {
+            Vec::deserialize(SeqAccessDeserializer::new(seq))
+        }
+    }
+}
+
+/// Bytes written as a standard base64 string, with the `base64` feature.
+/// Decoding accepts the string with or without its padding.
+#[cfg(feature = "base64")]
+pub mod base64 {
+    use super::*;
+
+    pub fn serialize<T: AsRef<[u8]>, S: serde::Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&crate::base64::encode(value.as_ref()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        deserializer.deserialize_str(Base64Visitor)
+    }
+
+    struct Base64Visitor;
+
+    impl<'de> Visitor<'de> for Base64Visitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a base64 string")
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<Vec<u8>, E> {
+            crate::base64::decode(v).map_err(|_| E::invalid_value(Unexpected::Str(v), &self))
+        }
+    }
+}
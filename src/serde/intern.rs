@@ -0,0 +1,161 @@
+//! Sharing of repeated strings while deserializing.
+//!
+//! Large arrays of records often repeat a handful of strings: status codes,
+//! country names, enum-like tags. Read into `String`s, every row allocates
+//! its own copy. Read into [`Interned`] fields, or `Arc<str>` fields with
+//! `#[serde(deserialize_with = "interned")]`, inside [`with_cache`], equal
+//! strings share one allocation held by a [`StringCache`]:
+//!
+//! ```ignore
+//! #[derive(Deserialize)]
+//! struct Row {
+//!     country: Interned,
+//!     #[serde(deserialize_with = "simdjson_rust::serde::intern::interned")]
+//!     status: Arc<str>,
+//! }
+//!
+//! let mut cache = StringCache::new();
+//! let rows: Vec<Row> = with_cache(&mut cache, || from_element(&root))?;
+//! ```
+//!
+//! serde gives deserializers no way to hand out an `Arc`, so the cache is
+//! installed for the current thread while the closure runs, whichever
+//! deserializer it uses. Outside [`with_cache`] every string gets its own
+//! allocation.
+
+use std::{borrow::Borrow, cell::RefCell, collections::HashSet, fmt, ops::Deref, sync::Arc};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Visitor};
+
+thread_local! {
+    static ACTIVE: RefCell<Option<StringCache>> = const { RefCell::new(None) };
+}
+
+/// The strings interned so far. Keeping a cache across calls shares strings
+/// between them too.
+#[derive(Debug, Default)]
+pub struct StringCache {
+    strings: HashSet<Arc<str>>,
+}
+
+impl StringCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The cached copy of `s`, added if it is new.
+    pub fn intern(&mut self, s: &str) -> Arc<str> {
+        if let Some(cached) = self.strings.get(s) {
+            return cached.clone();
+        }
+        let cached: Arc<str> = Arc::from(s);
+        self.strings.insert(cached.clone());
+        cached
+    }
+
+    /// Number of distinct strings.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Drops the cache's references; strings still held elsewhere live on.
+    pub fn clear(&mut self) {
+        self.strings.clear();
+    }
+}
+
+/// Runs `f` with `cache` interning the strings read into [`Interned`] and
+/// [`interned`] fields on this thread. Calls may nest; the innermost cache
+/// is used.
+pub fn with_cache<R>(cache: &mut StringCache, f: impl FnOnce() -> R) -> R {
+    /// Puts the caches back where they were, even if `f` panics.
+    struct Restore<'c> {
+        cache: &'c mut StringCache,
+        previous: Option<StringCache>,
+    }
+
+    impl Drop for Restore<'_> {
+        fn drop(&mut self) {
+            let active = ACTIVE.with(|active| active.replace(self.previous.take()));
+            *self.cache = active.unwrap_or_default();
+        }
+    }
+
+    let previous = ACTIVE.with(|active| active.replace(Some(std::mem::take(cache))));
+    let _restore = Restore { cache, previous };
+    f()
+}
+
+fn intern(s: &str) -> Arc<str> {
+    ACTIVE.with(|active| match &mut *active.borrow_mut() {
+        Some(cache) => cache.intern(s),
+        None => Arc::from(s),
+    })
+}
+
+/// A string shared with every equal string read under the same
+/// [`with_cache`] call. Usable as a map key.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interned(pub Arc<str>);
+
+impl Deref for Interned {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Interned {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Interned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl fmt::Display for Interned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Serialize for Interned {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Interned {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        interned(deserializer).map(Interned)
+    }
+}
+
+/// `deserialize_with` function reading a string into an `Arc<str>` through
+/// the active cache.
+pub fn interned<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Arc<str>, D::Error> {
+    struct InternVisitor;
+
+    impl Visitor<'_> for InternVisitor {
+        type Value = Arc<str>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a string")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Arc<str>, E> {
+            Ok(intern(v))
+        }
+    }
+
+    deserializer.deserialize_str(InternVisitor)
+}
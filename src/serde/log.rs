@@ -0,0 +1,86 @@
+//! Values logged as compact JSON through `tracing`, with the `tracing`
+//! feature.
+//!
+//! [`json`] and [`json_element`] wrap a value as a `tracing` field that is
+//! serialized only when a subscriber records it, into a string builder
+//! pooled per thread, so logging does not allocate a `String` per event:
+//!
+//! ```ignore
+//! tracing::info!(user = json(&user), request = json_element(&body), "login");
+//! ```
+//!
+//! A value that fails to serialize is logged as `<error: ...>`, the way
+//! `Element`'s `Display` does.
+
+use std::fmt;
+
+use serde::Serialize;
+use tracing::field::{DisplayValue, display};
+
+use crate::{Result, builder::StringBuilder, dom::Element, pool};
+
+/// A `tracing` field holding `value` as compact JSON.
+pub fn json<T: Serialize + ?Sized>(value: &T) -> DisplayValue<Json<'_, T>> {
+    display(Json(value))
+}
+
+/// A `tracing` field holding `element` and its children as compact JSON.
+pub fn json_element<'a>(element: &'a Element<'a>) -> DisplayValue<JsonElement<'a>> {
+    display(JsonElement(element))
+}
+
+/// See [`json`].
+pub struct Json<'a, T: ?Sized>(&'a T);
+
+impl<T: Serialize + ?Sized> fmt::Display for Json<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_pooled(f, |builder| {
+            self.0
+                .serialize(&mut super::ser::BuilderSerializer::new(builder))
+        })
+    }
+}
+
+/// See [`json_element`].
+pub struct JsonElement<'a>(&'a Element<'a>);
+
+impl fmt::Display for JsonElement<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_pooled(f, |builder| self.0.append_to(builder))
+    }
+}
+
+fn write_pooled(
+    f: &mut fmt::Formatter<'_>,
+    append: impl FnOnce(&mut StringBuilder) -> Result<()>,
+) -> fmt::Result {
+    pool::with_builder(
+        |builder| match append(builder).and_then(|()| builder.view()) {
+            Ok(json) => f.write_str(json),
+            Err(e) => write!(f, "<error: {e}>"),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::{dom::Parser, prelude::*};
+
+    #[test]
+    fn json_fields() {
+        let map = BTreeMap::from([("a", vec![1, 2]), ("b", vec![])]);
+        assert_eq!(json(&map).to_string(), r#"{"a":[1,2],"b":[]}"#);
+        assert!(json(&f64::NAN).to_string().starts_with("<error: "));
+
+        let ps = r#" {"k": [true, null, "v"]} "#.to_padded_string();
+        let mut parser = Parser::default();
+        let root = parser.parse(&ps).unwrap();
+        assert_eq!(json_element(&root).to_string(), r#"{"k":[true,null,"v"]}"#);
+
+        // Recorded like any other field.
+        tracing::info!(map = json(&map), root = json_element(&root), "logged");
+    }
+}
@@ -1,3 +1,10 @@
 pub mod de;
+pub mod helpers;
+pub mod intern;
+#[cfg(feature = "tracing")]
+pub mod log;
+pub mod ondemand;
 pub mod ser;
+mod size;
+pub mod tracked;
 pub mod value;
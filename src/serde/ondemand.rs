@@ -0,0 +1,361 @@
+use serde::de::{
+    Deserialize, DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess, VariantAccess,
+    Visitor,
+    value::{BorrowedStrDeserializer, MapDeserializer},
+};
+
+use crate::error::SimdJsonError;
+use crate::number::NUMBER_TOKEN;
+use crate::ondemand::{
+    ArrayIterator, Document, JsonType, Number, NumberType, ObjectIterator, Value,
+};
+
+fn de_error(msg: &str) -> SimdJsonError {
+    SimdJsonError::Serde(msg.to_owned())
+}
+
+/// Deserializes `T` straight from an ondemand document, without building a
+/// DOM or intermediate values. Strings and keys are borrowed from the parser
+/// where `T` allows it.
+pub fn from_document<'a, T>(doc: &'a mut Document<'_, '_>) -> Result<T, SimdJsonError>
+where
+    T: Deserialize<'a>,
+{
+    T::deserialize(OndemandDeserializer::new(doc))
+}
+
+/// A streaming `serde::Deserializer` over an ondemand document.
+///
+/// Values are handed to the visitor as the document is iterated, which makes
+/// it suitable for `serde_transcode`: JSON can be re-encoded into any
+/// `serde::Serializer` in a single pass. Values the visitor ignores are
+/// skipped by the ondemand iterator instead of being parsed.
+///
+/// Reading never goes back, so `#[serde(untagged)]` and
+/// `#[serde(tag = "...")]` enums, and `#[serde(flatten)]` fields, are read
+/// through serde's own buffering: the value is visited once into serde's
+/// content tree, strings still borrowed from the parser, and the variants are
+/// tried against that. Only the value being matched is buffered, not the
+/// rest of the document.
+pub struct OndemandDeserializer<'a> {
+    source: Source<'a>,
+}
+
+enum Source<'a> {
+    Value(Value<'a>),
+    // Scalar documents cannot be turned into a `Value`, so the root scalar is
+    // read up front.
+    Scalar(Scalar<'a>),
+    // A root number, with its text for `Number`. Numbers beyond 64-bit
+    // integers and `f64` only fail once they are read as something else.
+    Number(Result<Scalar<'a>, SimdJsonError>, &'a str),
+    Error(SimdJsonError),
+}
+
+#[derive(Clone, Copy)]
+enum Scalar<'a> {
+    Null,
+    Bool(bool),
+    Str(&'a str),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+}
+
+impl<'a> OndemandDeserializer<'a> {
+    pub fn new(doc: &'a mut Document<'_, '_>) -> Self {
+        let source = match root(doc) {
+            Ok(source) => source,
+            Err(e) => Source::Error(e),
+        };
+        Self { source }
+    }
+
+    fn from_value(value: Value<'a>) -> Self {
+        Self {
+            source: Source::Value(value),
+        }
+    }
+}
+
+fn root<'a>(doc: &'a mut Document<'_, '_>) -> Result<Source<'a>, SimdJsonError> {
+    Ok(match doc.json_type()? {
+        JsonType::Object | JsonType::Array => Source::Value(doc.get_value()?),
+        JsonType::Number => {
+            let raw = doc.get_raw_number_as_str()?;
+            Source::Number(doc.get_number().map(number), raw)
+        }
+        JsonType::String => Source::Scalar(Scalar::Str(doc.get_string()?)),
+        JsonType::Boolean => Source::Scalar(Scalar::Bool(doc.get_bool()?)),
+        JsonType::Null => {
+            doc.is_null()?;
+            Source::Scalar(Scalar::Null)
+        }
+    })
+}
+
+fn number<'a>(mut number: Number<'_>) -> Scalar<'a> {
+    match number.get_number_type() {
+        NumberType::UnsignedInteger => Scalar::U64(number.get_uint64()),
+        NumberType::SignedInteger => Scalar::I64(number.get_int64()),
+        NumberType::FloatingPointNumber => Scalar::F64(number.get_double()),
+    }
+}
+
+fn visit_scalar<'de, V>(scalar: Scalar<'de>, visitor: V) -> Result<V::Value, SimdJsonError>
+where
+    V: Visitor<'de>,
+{
+    match scalar {
+        Scalar::Null => visitor.visit_unit(),
+        Scalar::Bool(v) => visitor.visit_bool(v),
+        Scalar::Str(v) => visitor.visit_borrowed_str(v),
+        Scalar::I64(v) => visitor.visit_i64(v),
+        Scalar::U64(v) => visitor.visit_u64(v),
+        Scalar::F64(v) => visitor.visit_f64(v),
+    }
+}
+
+fn visit_value<'de, V>(mut value: Value<'de>, visitor: V) -> Result<V::Value, SimdJsonError>
+where
+    V: Visitor<'de>,
+{
+    match value.json_type()? {
+        JsonType::Object => visitor.visit_map(MapAccessor {
+            iter: value.get_object()?.iter()?,
+            value: None,
+        }),
+        JsonType::Array => visitor.visit_seq(SeqAccessor(value.get_array()?.iter()?)),
+        JsonType::Number => visit_scalar(number(value.get_number()?), visitor),
+        JsonType::String => visitor.visit_borrowed_str(value.get_string(false)?),
+        JsonType::Boolean => visitor.visit_bool(value.get_bool()?),
+        JsonType::Null => {
+            value.is_null()?;
+            visitor.visit_unit()
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> for OndemandDeserializer<'de> {
+    type Error = SimdJsonError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.source {
+            Source::Value(value) => visit_value(value, visitor),
+            Source::Scalar(scalar) => visit_scalar(scalar, visitor),
+            Source::Number(scalar, _) => visit_scalar(scalar?, visitor),
+            Source::Error(e) => Err(e),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.source {
+            Source::Scalar(Scalar::Null) => visitor.visit_none(),
+            Source::Value(mut value) => {
+                if value.is_null()? {
+                    visitor.visit_none()
+                } else {
+                    visitor.visit_some(Self::from_value(value))
+                }
+            }
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if name != NUMBER_TOKEN {
+            return visitor.visit_newtype_struct(self);
+        }
+        // Integers have one spelling; anything else keeps its text.
+        let (scalar, raw) = match self.source {
+            Source::Value(mut value) => {
+                let raw = value.get_raw_number_as_str()?;
+                (value.get_number().map(number), raw)
+            }
+            Source::Number(scalar, raw) => (scalar, raw),
+            Source::Error(e) => return Err(e),
+            Source::Scalar(_) => return Err(de_error("expected a number")),
+        };
+        match scalar {
+            Ok(Scalar::I64(v)) => visitor.visit_i64(v),
+            Ok(Scalar::U64(v)) => visitor.visit_u64(v),
+            _ => visitor.visit_map(MapDeserializer::new(std::iter::once((NUMBER_TOKEN, raw)))),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        let mut value = match self.source {
+            Source::Scalar(Scalar::Str(s)) => {
+                return visitor.visit_enum(BorrowedStrDeserializer::<SimdJsonError>::new(s));
+            }
+            Source::Value(value) => value,
+            Source::Error(e) => return Err(e),
+            Source::Scalar(_) | Source::Number(..) => {
+                return Err(de_error("expected a string or object for enum"));
+            }
+        };
+        match value.json_type()? {
+            JsonType::String => {
+                let s = value.get_string(false)?;
+                visitor.visit_enum(BorrowedStrDeserializer::<SimdJsonError>::new(s))
+            }
+            JsonType::Object => {
+                let mut iter = value.get_object()?.iter()?;
+                match iter.next() {
+                    Some(field) => {
+                        let mut field = field?;
+                        let variant = field.unescaped_key(false)?;
+                        visitor.visit_enum(EnumAccessor {
+                            variant,
+                            value: field.take_value(),
+                        })
+                    }
+                    None => Err(de_error("expected an object with a single key for enum")),
+                }
+            }
+            _ => Err(de_error("expected a string or object for enum")),
+        }
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        // Dropping an unread value lets the ondemand iterator skip it.
+        drop(self);
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier
+    }
+}
+
+struct SeqAccessor<'a>(ArrayIterator<'a>);
+
+impl<'de> SeqAccess<'de> for SeqAccessor<'de> {
+    type Error = SimdJsonError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        match self.0.next() {
+            Some(value) => seed
+                .deserialize(OndemandDeserializer::from_value(value?))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+struct MapAccessor<'a> {
+    iter: ObjectIterator<'a>,
+    value: Option<Value<'a>>,
+}
+
+impl<'de> MapAccess<'de> for MapAccessor<'de> {
+    type Error = SimdJsonError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some(field) => {
+                let mut field = field?;
+                let key = field.unescaped_key(false)?;
+                self.value = Some(field.take_value());
+                seed.deserialize(BorrowedStrDeserializer::new(key))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| de_error("next_value_seed called before next_key_seed"))?;
+        seed.deserialize(OndemandDeserializer::from_value(value))
+    }
+}
+
+struct EnumAccessor<'a> {
+    variant: &'a str,
+    value: Value<'a>,
+}
+
+impl<'de> EnumAccess<'de> for EnumAccessor<'de> {
+    type Error = SimdJsonError;
+    type Variant = OndemandDeserializer<'de>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant =
+            seed.deserialize(BorrowedStrDeserializer::<SimdJsonError>::new(self.variant))?;
+        Ok((variant, OndemandDeserializer::from_value(self.value)))
+    }
+}
+
+impl<'de> VariantAccess<'de> for OndemandDeserializer<'de> {
+    type Error = SimdJsonError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_map(visitor)
+    }
+}
@@ -1,5 +1,10 @@
+use std::io::Write;
+
 use crate::builder::StringBuilder;
 use crate::error::SimdJsonError;
+use crate::number::{NUMBER_TOKEN, is_json_number};
+
+pub use super::size::estimate_serialized_size;
 
 use serde::ser::{
     Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
@@ -13,7 +18,8 @@ pub fn to_string<T: Serialize>(value: &T) -> Result<String, SimdJsonError> {
     builder.into_string()
 }
 
-/// Serialize a value to a JSON string with a pre-allocated capacity hint.
+/// Serialize a value to a JSON string with a pre-allocated capacity hint,
+/// such as one from [`estimate_serialized_size`].
 pub fn to_string_with_capacity<T: Serialize>(
     value: &T,
     capacity: usize,
@@ -23,13 +29,115 @@ pub fn to_string_with_capacity<T: Serialize>(
     builder.into_string()
 }
 
+/// Flush threshold of a [`StreamingArrayWriter`] unless configured otherwise.
+const DEFAULT_CHUNK_LEN: usize = 64 * 1024;
+
+/// Serializes a sequence of values into `inner` as one JSON array, holding at
+/// most about one chunk of output in memory.
+///
+/// Elements are serialized with the SIMD builder and handed to `inner`
+/// whenever the buffered output reaches the chunk length, so arrays far
+/// larger than memory can be written. Call [`StreamingArrayWriter::finish`]
+/// to close the array; a writer that is dropped instead leaves it
+/// unterminated.
+pub struct StreamingArrayWriter<W> {
+    inner: W,
+    builder: StringBuilder,
+    /// Holds the element being serialized until it is known to be whole.
+    element: StringBuilder,
+    chunk_len: usize,
+    len: usize,
+}
+
+impl<W: Write> StreamingArrayWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self::with_chunk_len(inner, DEFAULT_CHUNK_LEN)
+    }
+
+    /// Creates a writer that flushes every `chunk_len` bytes. An element
+    /// larger than that is still buffered whole.
+    pub fn with_chunk_len(inner: W, chunk_len: usize) -> Self {
+        Self {
+            inner,
+            builder: StringBuilder::with_capacity(chunk_len.saturating_add(chunk_len / 4)),
+            element: StringBuilder::new(),
+            chunk_len,
+            len: 0,
+        }
+    }
+
+    /// Number of elements written so far.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Appends one element to the array. An element that fails to serialize
+    /// leaves the array as it was, so writing can go on.
+    pub fn write<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SimdJsonError> {
+        self.element.clear();
+        value.serialize(&mut BuilderSerializer::new(&mut self.element))?;
+        if self.len == 0 {
+            self.builder.start_array();
+        } else {
+            self.builder.append_comma();
+        }
+        self.builder.append_raw(self.element.view()?);
+        self.len += 1;
+        if self.builder.size() >= self.chunk_len {
+            self.flush_chunk()?;
+        }
+        Ok(())
+    }
+
+    /// Closes the array, flushes everything to `inner` and returns it.
+    pub fn finish(mut self) -> Result<W, SimdJsonError> {
+        if self.len == 0 {
+            self.builder.start_array();
+        }
+        self.builder.end_array();
+        self.flush_chunk()?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn flush_chunk(&mut self) -> Result<(), SimdJsonError> {
+        self.inner.write_all(self.builder.view()?.as_bytes())?;
+        self.builder.clear();
+        Ok(())
+    }
+}
+
+/// Serializes every item of `items` into `writer` as one JSON array; see
+/// [`StreamingArrayWriter`].
+pub fn to_writer_array<W, I>(writer: W, items: I) -> Result<W, SimdJsonError>
+where
+    W: Write,
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    let mut writer = StreamingArrayWriter::new(writer);
+    for item in items {
+        writer.write(&item)?;
+    }
+    writer.finish()
+}
+
 pub struct BuilderSerializer<'a> {
     builder: &'a mut StringBuilder,
+    /// The next string is the text of a [`Number::Raw`](crate::Number::Raw).
+    raw_number: bool,
 }
 
 impl<'a> BuilderSerializer<'a> {
     pub fn new(builder: &'a mut StringBuilder) -> Self {
-        Self { builder }
+        Self {
+            builder,
+            raw_number: false,
+        }
     }
 }
 
@@ -110,7 +218,14 @@ impl<'a> Serializer for &'a mut BuilderSerializer<'a> {
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
-        self.builder.append_string(v);
+        if std::mem::take(&mut self.raw_number) {
+            if !is_json_number(v.as_bytes()) {
+                return Err(SimdJsonError::NumberError);
+            }
+            self.builder.append_raw(v);
+        } else {
+            self.builder.append_string(v);
+        }
         Ok(())
     }
 
@@ -154,9 +269,10 @@ impl<'a> Serializer for &'a mut BuilderSerializer<'a> {
 
     fn serialize_newtype_struct<T: ?Sized + Serialize>(
         self,
-        _name: &'static str,
+        name: &'static str,
         value: &T,
     ) -> Result<Self::Ok, Self::Error> {
+        self.raw_number = name == NUMBER_TOKEN;
         value.serialize(self)
     }
 
@@ -168,8 +284,7 @@ impl<'a> Serializer for &'a mut BuilderSerializer<'a> {
         value: &T,
     ) -> Result<Self::Ok, Self::Error> {
         self.builder.start_object();
-        self.builder.append_string(variant);
-        self.builder.append_colon();
+        self.builder.append_field_header(variant, true);
         value.serialize(&mut BuilderSerializer::new(self.builder))?;
         self.builder.end_object();
         Ok(())
@@ -203,8 +318,7 @@ impl<'a> Serializer for &'a mut BuilderSerializer<'a> {
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
         self.builder.start_object();
-        self.builder.append_string(variant);
-        self.builder.append_colon();
+        self.builder.append_field_header(variant, true);
         self.builder.start_array();
         Ok(SeqSerializer {
             builder: self.builder,
@@ -236,8 +350,7 @@ impl<'a> Serializer for &'a mut BuilderSerializer<'a> {
         _len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
         self.builder.start_object();
-        self.builder.append_string(variant);
-        self.builder.append_colon();
+        self.builder.append_field_header(variant, true);
         self.builder.start_object();
         Ok(MapSerializer {
             builder: self.builder,
@@ -347,12 +460,8 @@ impl<'a> SerializeStruct for MapSerializer<'a> {
         key: &'static str,
         value: &T,
     ) -> Result<(), Self::Error> {
-        if !self.first {
-            self.builder.append_comma();
-        }
+        self.builder.append_field_header(key, self.first);
         self.first = false;
-        self.builder.append_string(key);
-        self.builder.append_colon();
         value.serialize(&mut BuilderSerializer::new(self.builder))
     }
 
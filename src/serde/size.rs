@@ -0,0 +1,459 @@
+use std::fmt::{self, Write};
+
+use serde::ser::{
+    Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+    SerializeTupleStruct, SerializeTupleVariant, Serializer,
+};
+
+use crate::{error::SimdJsonError, number::NUMBER_TOKEN};
+
+/// The length in bytes of the compact JSON [`to_string`](super::ser::to_string)
+/// writes for `value`, computed without writing it.
+///
+/// Strings, integers and punctuation are counted exactly; floats are
+/// counted by their shortest round-trip form, which may differ from the
+/// builder's by a few bytes. Values the serializer rejects, such as `NaN`,
+/// are counted as if they were accepted. Pass the result to
+/// [`to_string_with_capacity`](super::ser::to_string_with_capacity) or
+/// [`StringBuilder::with_capacity`](crate::builder::StringBuilder::with_capacity).
+///
+/// ```ignore
+/// let capacity = estimate_serialized_size(&records);
+/// let json = to_string_with_capacity(&records, capacity)?;
+/// ```
+pub fn estimate_serialized_size<T: Serialize + ?Sized>(value: &T) -> usize {
+    let mut estimator = SizeEstimator::default();
+    // Counting never fails; an error could only come from `value` itself.
+    let _ = value.serialize(&mut estimator);
+    estimator.len
+}
+
+#[derive(Default)]
+struct SizeEstimator {
+    len: usize,
+    /// The next string is the text of a [`Number::Raw`](crate::Number::Raw).
+    raw_number: bool,
+}
+
+impl SizeEstimator {
+    fn add_str(&mut self, s: &str) {
+        self.len += 2 + s.len();
+        for &byte in s.as_bytes() {
+            self.len += match byte {
+                b'"' | b'\\' | b'\n' | b'\t' | b'\r' | 0x08 | 0x0c => 1,
+                0..=0x1f => 5,
+                _ => 0,
+            };
+        }
+    }
+
+    fn add_display(&mut self, v: impl fmt::Display) {
+        let mut counter = Counter(0);
+        let _ = write!(counter, "{v}");
+        self.len += counter.0;
+    }
+
+    fn add_f64(&mut self, v: f64) {
+        let (mut plain, mut exponent) = (Counter(0), Counter(0));
+        let _ = write!(plain, "{v}");
+        let _ = write!(exponent, "{v:e}");
+        self.len += plain.0.min(exponent.0);
+    }
+
+    /// `{"variant":` and the closing `}`.
+    fn add_variant(&mut self, variant: &str) {
+        self.add_str(variant);
+        self.len += 3;
+    }
+}
+
+/// A [`fmt::Write`] that only counts bytes.
+struct Counter(usize);
+
+impl Write for Counter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+struct Compound<'a> {
+    estimator: &'a mut SizeEstimator,
+    first: bool,
+}
+
+impl Compound<'_> {
+    fn separator(&mut self) {
+        if !std::mem::take(&mut self.first) {
+            self.estimator.len += 1;
+        }
+    }
+
+    fn element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SimdJsonError> {
+        self.separator();
+        value.serialize(&mut *self.estimator)
+    }
+
+    fn field<T: ?Sized + Serialize>(&mut self, key: &str, value: &T) -> Result<(), SimdJsonError> {
+        self.separator();
+        self.estimator.add_str(key);
+        self.estimator.len += 1;
+        value.serialize(&mut *self.estimator)
+    }
+
+    fn end(self) -> Result<(), SimdJsonError> {
+        self.estimator.len += 1;
+        Ok(())
+    }
+}
+
+impl<'a> Serializer for &'a mut SizeEstimator {
+    type Ok = ();
+    type Error = SimdJsonError;
+
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), SimdJsonError> {
+        self.len += if v { 4 } else { 5 };
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), SimdJsonError> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), SimdJsonError> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), SimdJsonError> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), SimdJsonError> {
+        self.len += usize::from(v < 0);
+        self.serialize_u64(v.unsigned_abs())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), SimdJsonError> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), SimdJsonError> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), SimdJsonError> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), SimdJsonError> {
+        self.len += v.checked_ilog10().unwrap_or(0) as usize + 1;
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), SimdJsonError> {
+        self.serialize_f64(v as f64)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), SimdJsonError> {
+        self.add_f64(v);
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), SimdJsonError> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), SimdJsonError> {
+        if std::mem::take(&mut self.raw_number) {
+            self.len += v.len();
+        } else {
+            self.add_str(v);
+        }
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), SimdJsonError> {
+        self.len += 2 + v.len().saturating_sub(1);
+        for byte in v {
+            self.add_display(byte);
+        }
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), SimdJsonError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), SimdJsonError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), SimdJsonError> {
+        self.len += 4;
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), SimdJsonError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), SimdJsonError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), SimdJsonError> {
+        self.raw_number = name == NUMBER_TOKEN;
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), SimdJsonError> {
+        self.add_variant(variant);
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'a>, SimdJsonError> {
+        self.len += 1;
+        Ok(Compound {
+            estimator: self,
+            first: true,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Compound<'a>, SimdJsonError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a>, SimdJsonError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Compound<'a>, SimdJsonError> {
+        self.add_variant(variant);
+        self.serialize_seq(None)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Compound<'a>, SimdJsonError> {
+        self.serialize_seq(len)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a>, SimdJsonError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a>, SimdJsonError> {
+        self.serialize_tuple_variant(name, variant_index, variant, len)
+    }
+}
+
+impl SerializeSeq for Compound<'_> {
+    type Ok = ();
+    type Error = SimdJsonError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SimdJsonError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), SimdJsonError> {
+        Compound::end(self)
+    }
+}
+
+impl SerializeTuple for Compound<'_> {
+    type Ok = ();
+    type Error = SimdJsonError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SimdJsonError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), SimdJsonError> {
+        Compound::end(self)
+    }
+}
+
+impl SerializeTupleStruct for Compound<'_> {
+    type Ok = ();
+    type Error = SimdJsonError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SimdJsonError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), SimdJsonError> {
+        Compound::end(self)
+    }
+}
+
+impl SerializeTupleVariant for Compound<'_> {
+    type Ok = ();
+    type Error = SimdJsonError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SimdJsonError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), SimdJsonError> {
+        Compound::end(self)
+    }
+}
+
+impl SerializeMap for Compound<'_> {
+    type Ok = ();
+    type Error = SimdJsonError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), SimdJsonError> {
+        self.element(key)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SimdJsonError> {
+        self.estimator.len += 1;
+        value.serialize(&mut *self.estimator)
+    }
+
+    fn end(self) -> Result<(), SimdJsonError> {
+        Compound::end(self)
+    }
+}
+
+impl SerializeStruct for Compound<'_> {
+    type Ok = ();
+    type Error = SimdJsonError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SimdJsonError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), SimdJsonError> {
+        Compound::end(self)
+    }
+}
+
+impl SerializeStructVariant for Compound<'_> {
+    type Ok = ();
+    type Error = SimdJsonError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SimdJsonError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), SimdJsonError> {
+        Compound::end(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Serialize;
+
+    use super::*;
+    use crate::{Number, serde::ser::to_string};
+
+    #[derive(Serialize)]
+    enum Shape {
+        Unit,
+        Circle(u32),
+        Pair(i8, i8),
+        Rect { w: u16, h: u16 },
+    }
+
+    #[derive(Serialize)]
+    struct Record {
+        id: i64,
+        name: String,
+        tags: Vec<&'static str>,
+        score: Option<f64>,
+        shapes: Vec<Shape>,
+        extra: BTreeMap<String, bool>,
+        exact: Number,
+        blob: Bytes,
+    }
+
+    /// Written through `serialize_bytes`.
+    struct Bytes(&'static [u8]);
+
+    impl Serialize for Bytes {
+        fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+            s.serialize_bytes(self.0)
+        }
+    }
+
+    #[test]
+    fn exact_for_everything_but_floats() {
+        let record = Record {
+            id: -1234,
+            name: "quote \" slash \\ tab \t bell \u{7} é".into(),
+            tags: vec!["a", "", "ccc"],
+            score: None,
+            shapes: vec![
+                Shape::Unit,
+                Shape::Circle(0),
+                Shape::Pair(-1, 10),
+                Shape::Rect { w: 3, h: 65535 },
+            ],
+            extra: [("x".into(), true), ("yy".into(), false)].into(),
+            exact: Number::from_raw("1.50").unwrap(),
+            blob: Bytes(&[0, 10, 255]),
+        };
+        let json = to_string(&record).unwrap();
+        assert_eq!(estimate_serialized_size(&record), json.len(), "{json}");
+        for value in [u64::MAX, 0, 9, 10, 99, 100] {
+            assert_eq!(estimate_serialized_size(&value), value.to_string().len());
+        }
+        assert_eq!(estimate_serialized_size(&i64::MIN), 20);
+        // `[0.5,1e300,-2.25]`
+        assert_eq!(estimate_serialized_size(&[0.5, 1e300, -2.25]), 17);
+    }
+}
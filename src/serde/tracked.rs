@@ -0,0 +1,92 @@
+//! Fields that tell a missing key from an explicit `null`, for PATCH-style
+//! updates where the two mean different things.
+//!
+//! Declare each such field as [`Tracked`] with `#[serde(default)]`, which
+//! fills in [`Tracked::Absent`] for keys that are not in the input:
+//!
+//! ```ignore
+//! #[derive(Deserialize)]
+//! struct UserPatch {
+//!     #[serde(default)]
+//!     nickname: Tracked<String>,
+//! }
+//!
+//! let patch: UserPatch = from_str(body)?;
+//! patch.nickname.apply(&mut user.nickname);
+//! ```
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Whether a field was in the input, and if so what it held.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Tracked<T> {
+    /// The key was missing.
+    #[default]
+    Absent,
+    /// The key was present with `null`.
+    Null,
+    /// The key was present with a value.
+    Value(T),
+}
+
+impl<T> Tracked<T> {
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Tracked::Absent)
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Tracked::Null)
+    }
+
+    /// The key was present, `null` or not.
+    pub fn is_present(&self) -> bool {
+        !self.is_absent()
+    }
+
+    pub fn value(&self) -> Option<&T> {
+        match self {
+            Tracked::Value(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// `None` if absent, `Some(None)` if `null`.
+    pub fn into_option(self) -> Option<Option<T>> {
+        match self {
+            Tracked::Absent => None,
+            Tracked::Null => Some(None),
+            Tracked::Value(v) => Some(Some(v)),
+        }
+    }
+
+    /// Applies the field to `target` as a PATCH would: an absent field leaves
+    /// it alone and `null` clears it.
+    pub fn apply(self, target: &mut Option<T>) {
+        if let Some(value) = self.into_option() {
+            *target = value;
+        }
+    }
+}
+
+impl<T> From<Option<T>> for Tracked<T> {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Tracked::Null, Tracked::Value)
+    }
+}
+
+/// An absent field is written as `null`; add
+/// `#[serde(skip_serializing_if = "Tracked::is_absent")]` to leave it out.
+impl<T: Serialize> Serialize for Tracked<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Tracked::Value(v) => serializer.serialize_some(v),
+            _ => serializer.serialize_none(),
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Tracked<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Option::deserialize(deserializer).map(Tracked::from)
+    }
+}
@@ -2,6 +2,7 @@ use serde_json::{Map, Number, Value};
 
 use crate::dom::{Element, ElementType};
 use crate::error::SimdJsonError;
+use crate::ondemand::{self, JsonType, NumberType};
 
 const MAX_NESTING_DEPTH: usize = 128;
 
@@ -10,11 +11,37 @@ const MAX_NESTING_DEPTH: usize = 128;
 /// Enforces a maximum nesting depth to guard against stack overflow from
 /// adversarial inputs with extreme nesting (e.g. `[[[[...]]]]` repeated
 /// thousands of times).
+///
+/// Every object key becomes its own `String`: `serde_json::Map` owns its
+/// keys, so repeated keys cannot share an allocation. When that cost matters,
+/// deserialize with [`from_element`](crate::serde::de::from_element) into a
+/// type that borrows its keys (`&str`) from the document instead.
 pub fn element_to_value(element: &Element<'_>) -> Result<Value, SimdJsonError> {
-    element_to_value_inner(element, 0)
+    element_to_value_inner(element, 0, &[])
+}
+
+/// Like [`element_to_value`], but leaves out object fields whose key is in
+/// `skip_keys`, at any depth, without converting their values.
+///
+/// The DOM still holds the skipped fields; what is saved is copying them
+/// into `Value`s, which for heavy fields such as `"debug"` or
+/// `"raw_payload"` is most of the conversion.
+///
+/// ```ignore
+/// let value = element_to_value_skipping(&root, &["debug", "raw_payload"])?;
+/// ```
+pub fn element_to_value_skipping(
+    element: &Element<'_>,
+    skip_keys: &[&str],
+) -> Result<Value, SimdJsonError> {
+    element_to_value_inner(element, 0, skip_keys)
 }
 
-fn element_to_value_inner(element: &Element<'_>, depth: usize) -> Result<Value, SimdJsonError> {
+fn element_to_value_inner(
+    element: &Element<'_>,
+    depth: usize,
+    skip_keys: &[&str],
+) -> Result<Value, SimdJsonError> {
     if depth > MAX_NESTING_DEPTH {
         return Err(SimdJsonError::Serde(format!(
             "nesting depth exceeds maximum of {MAX_NESTING_DEPTH}"
@@ -46,7 +73,7 @@ fn element_to_value_inner(element: &Element<'_>, depth: usize) -> Result<Value,
             let array = element.get_array()?;
             let mut vec = Vec::with_capacity(array.size());
             for child in array.iter() {
-                vec.push(element_to_value_inner(&child, depth + 1)?);
+                vec.push(element_to_value_inner(&child, depth + 1, skip_keys)?);
             }
             Ok(Value::Array(vec))
         }
@@ -54,12 +81,138 @@ fn element_to_value_inner(element: &Element<'_>, depth: usize) -> Result<Value,
             let object = element.get_object()?;
             let mut map = Map::new();
             for (key, child) in object.iter() {
+                if skip_keys.contains(&key) {
+                    continue;
+                }
                 map.insert(
                     String::from(key),
-                    element_to_value_inner(&child, depth + 1)?,
+                    element_to_value_inner(&child, depth + 1, skip_keys)?,
+                );
+            }
+            Ok(Value::Object(map))
+        }
+    }
+}
+
+/// Convert an ondemand `Value` into a `serde_json::Value`, consuming it from
+/// the document.
+///
+/// Uses the same nesting limit as [`element_to_value`].
+pub fn ondemand_value_to_value(value: &mut ondemand::Value<'_>) -> Result<Value, SimdJsonError> {
+    ondemand_value_to_value_inner(value, 0, false)
+}
+
+/// Like [`ondemand_value_to_value`], but never loses numeric precision:
+/// integers that don't fit an `i64`/`u64` and decimals no `f64` holds
+/// exactly, such as `0.10000000000000000555` or `1e400`, become a
+/// `Value::String` holding their original text. Every other number stays a
+/// `Value::Number` and is re-emitted with the value it was written with.
+pub fn ondemand_value_to_value_exact(
+    value: &mut ondemand::Value<'_>,
+) -> Result<Value, SimdJsonError> {
+    ondemand_value_to_value_inner(value, 0, true)
+}
+
+fn ondemand_value_to_value_inner(
+    value: &mut ondemand::Value<'_>,
+    depth: usize,
+    exact: bool,
+) -> Result<Value, SimdJsonError> {
+    if depth > MAX_NESTING_DEPTH {
+        return Err(SimdJsonError::Serde(format!(
+            "nesting depth exceeds maximum of {MAX_NESTING_DEPTH}"
+        )));
+    }
+
+    match value.json_type()? {
+        JsonType::Null => {
+            value.is_null()?;
+            Ok(Value::Null)
+        }
+        JsonType::Boolean => Ok(Value::Bool(value.get_bool()?)),
+        JsonType::String => Ok(Value::String(value.get_string(false)?.to_owned())),
+        JsonType::Number if exact => exact_number(value.get_raw_number_as_str()?),
+        JsonType::Number => {
+            let mut number = value.get_number()?;
+            match number.get_number_type() {
+                NumberType::SignedInteger => Ok(Value::Number(Number::from(number.get_int64()))),
+                NumberType::UnsignedInteger => Ok(Value::Number(Number::from(number.get_uint64()))),
+                NumberType::FloatingPointNumber => {
+                    let v = number.get_double();
+                    match Number::from_f64(v) {
+                        Some(n) => Ok(Value::Number(n)),
+                        None => Err(SimdJsonError::Serde(format!(
+                            "cannot represent {v} as a JSON number (NaN or Infinity)"
+                        ))),
+                    }
+                }
+            }
+        }
+        JsonType::Array => {
+            let mut vec = Vec::new();
+            for child in value.get_array()?.iter()? {
+                vec.push(ondemand_value_to_value_inner(
+                    &mut child?,
+                    depth + 1,
+                    exact,
+                )?);
+            }
+            Ok(Value::Array(vec))
+        }
+        JsonType::Object => {
+            let mut map = Map::new();
+            for field in value.get_object()?.iter()? {
+                let mut field = field?;
+                let key = String::from(field.unescaped_key(false)?);
+                map.insert(
+                    key,
+                    ondemand_value_to_value_inner(&mut field.take_value(), depth + 1, exact)?,
                 );
             }
             Ok(Value::Object(map))
         }
     }
 }
+
+/// A number token as a `Value::Number` if that is lossless, else as its text.
+fn exact_number(token: &str) -> Result<Value, SimdJsonError> {
+    if !token.contains(['.', 'e', 'E']) {
+        if let Ok(v) = token.parse::<i64>() {
+            return Ok(Value::Number(v.into()));
+        }
+        if let Ok(v) = token.parse::<u64>() {
+            return Ok(Value::Number(v.into()));
+        }
+        return Ok(Value::String(token.to_owned()));
+    }
+    // `f64`'s `Display` is the shortest text that reads back as the same
+    // double, so the double is exact when that text is the token's value.
+    let exact = token
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite() && decimal(&v.to_string()) == decimal(token));
+    match exact.and_then(Number::from_f64) {
+        Some(n) => Ok(Value::Number(n)),
+        None => Ok(Value::String(token.to_owned())),
+    }
+}
+
+/// A well-formed number's value as its sign, its significant digits and the
+/// power of ten of the last digit. Every zero is `(false, "", 0)`.
+fn decimal(number: &str) -> (bool, String, i64) {
+    let negative = number.starts_with('-');
+    let number = number.trim_start_matches('-');
+    let (mantissa, exponent) = match number.find(['e', 'E']) {
+        Some(i) => (&number[..i], number[i + 1..].parse::<i64>().unwrap_or(0)),
+        None => (number, 0),
+    };
+    let (int, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{int}{fraction}");
+    let trimmed = digits.trim_end_matches('0');
+    let exponent = exponent - fraction.len() as i64 + (digits.len() - trimmed.len()) as i64;
+    let significant = trimmed.trim_start_matches('0');
+    if significant.is_empty() {
+        return (false, String::new(), 0);
+    }
+    (negative, significant.to_owned(), exponent)
+}
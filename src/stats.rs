@@ -0,0 +1,36 @@
+use std::collections::HashMap;
+
+use crate::Result;
+
+/// Counters a parser keeps over its lifetime, for dashboards of long-running
+/// services. Read them with `stats()` on [`dom::Parser`](crate::dom::Parser)
+/// or [`ondemand::Parser`](crate::ondemand::Parser).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseStats {
+    /// Documents parsed successfully.
+    pub documents: u64,
+    /// Input bytes of the documents parsed successfully.
+    pub bytes: u64,
+    /// Failed parses, keyed by [`SimdJsonError::kind`](crate::SimdJsonError::kind).
+    pub errors: HashMap<&'static str, u64>,
+    /// Largest capacity the parser's buffers reached, in bytes.
+    pub peak_capacity: usize,
+}
+
+impl ParseStats {
+    /// Failed parses of any kind.
+    pub fn error_count(&self) -> u64 {
+        self.errors.values().sum()
+    }
+
+    pub(crate) fn record<T>(&mut self, bytes: usize, result: &Result<T>, capacity: usize) {
+        match result {
+            Ok(_) => {
+                self.documents += 1;
+                self.bytes += bytes as u64;
+            }
+            Err(e) => *self.errors.entry(e.kind()).or_default() += 1,
+        }
+        self.peak_capacity = self.peak_capacity.max(capacity);
+    }
+}
@@ -0,0 +1,116 @@
+//! [proptest] strategies for property-testing code built on simdjson.
+//!
+//! [`json_document`] generates structurally valid JSON of any shape, with
+//! strings covering escapes, control characters and non-ASCII text.
+//! [`near_valid_document`] damages such documents in one place, the way
+//! truncated or hand-edited input is damaged, to exercise error paths. Both
+//! yield padded strings ready for the parsers. Cloning a `String` does not
+//! keep its spare capacity, so call
+//! [`to_padded_string`](crate::padded_string::ToPaddedString::to_padded_string)
+//! again on copies.
+//!
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn parses(doc in simdjson_rust::testing::json_document()) {
+//!         let mut parser = dom::Parser::default();
+//!         prop_assert!(parser.parse(&doc).is_ok());
+//!     }
+//! }
+//! ```
+
+use proptest::{
+    collection::vec,
+    num::f64,
+    prelude::*,
+    sample::{Index, select},
+};
+
+use crate::{padded_string::make_padded_string, utils::escape_json_string};
+
+/// Characters [`near_valid_document`] inserts: structure, string and number
+/// syntax and raw control characters.
+const DAMAGE: &[char] = &[
+    ',', ':', '[', ']', '{', '}', '"', '\\', '-', '.', 'e', '0', 't', 'n', '\0', '\u{1f}',
+];
+
+const WHITESPACE: &[&str] = &["", " ", "\n", "\t", "\r\n  "];
+
+/// Compact JSON text of up to 8 levels of nesting, without padding.
+pub fn json_value() -> BoxedStrategy<String> {
+    let leaf = prop_oneof![
+        Just("null".to_owned()),
+        any::<bool>().prop_map(|v| v.to_string()),
+        any::<i64>().prop_map(|v| v.to_string()),
+        any::<u64>().prop_map(|v| v.to_string()),
+        (f64::POSITIVE | f64::NEGATIVE | f64::NORMAL | f64::SUBNORMAL | f64::ZERO)
+            .prop_map(|v| format!("{v:?}")),
+        any::<String>().prop_map(|s| quote(&s)),
+    ];
+    leaf.prop_recursive(8, 256, 8, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..8).prop_map(|items| format!("[{}]", items.join(","))),
+            vec((any::<String>(), inner), 0..8).prop_map(|fields| {
+                let fields: Vec<_> = fields
+                    .iter()
+                    .map(|(key, value)| format!("{}:{value}", quote(key)))
+                    .collect();
+                format!("{{{}}}", fields.join(","))
+            }),
+        ]
+    })
+    .boxed()
+}
+
+/// A valid document from [`json_value`], with whitespace around it, in a
+/// padded string.
+pub fn json_document() -> impl Strategy<Value = String> {
+    (select(WHITESPACE), json_value(), select(WHITESPACE))
+        .prop_map(|(before, json, after)| make_padded_string(&format!("{before}{json}{after}")))
+}
+
+/// A valid document from [`json_value`] that is cut short, or has one
+/// character removed, replaced or inserted, in a padded string. The result is
+/// usually, but not always, invalid: removing a space, say, does no harm.
+pub fn near_valid_document() -> impl Strategy<Value = String> {
+    (json_value(), 0..4u8, any::<Index>(), select(DAMAGE)).prop_map(|(json, kind, at, c)| {
+        let boundaries: Vec<_> = json.char_indices().map(|(i, _)| i).collect();
+        let i = boundaries[at.index(boundaries.len())];
+        let next = json[i..].chars().next().map_or(i, |c| i + c.len_utf8());
+        let damaged = match kind {
+            0 => json[..i].to_owned(),
+            1 => format!("{}{}", &json[..i], &json[next..]),
+            2 => format!("{}{c}{}", &json[..i], &json[next..]),
+            _ => format!("{}{c}{}", &json[..i], &json[i..]),
+        };
+        make_padded_string(&damaged)
+    })
+}
+
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    escape_json_string(s, &mut out).expect("escaping a generated string");
+    out.push('"');
+    out
+}
+
+#[cfg(all(test, feature = "dom"))]
+mod tests {
+    use super::*;
+    use crate::dom::Parser;
+
+    proptest! {
+        #[test]
+        fn documents_are_valid(doc in json_document()) {
+            let mut parser = Parser::default();
+            prop_assert!(parser.parse(&doc).is_ok(), "{doc}");
+        }
+
+        #[test]
+        fn damaged_documents_are_handled(doc in near_valid_document()) {
+            let mut parser = Parser::default();
+            let _ = parser.parse(&doc);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+//! `tracing` instrumentation of parse calls, compiled to nothing unless the
+//! `tracing` feature is enabled.
+//!
+//! Every parse runs inside a `simdjson.parse` span (DEBUG) carrying the API
+//! name and input size. When it finishes, one event is emitted with the
+//! outcome. Its `monotonic_counter.*` and `histogram.*` fields follow the
+//! naming that `tracing-opentelemetry`'s metrics layer turns into metrics.
+
+use crate::Result;
+
+#[cfg(feature = "tracing")]
+pub(crate) struct ParseSpan {
+    span: tracing::span::EnteredSpan,
+    start: std::time::Instant,
+    bytes: usize,
+    capacity: usize,
+}
+
+#[cfg(feature = "tracing")]
+impl ParseSpan {
+    pub(crate) fn enter(api: &'static str, bytes: usize, capacity: impl FnOnce() -> usize) -> Self {
+        let span = tracing::debug_span!("simdjson.parse", api, bytes).entered();
+        Self {
+            span,
+            start: std::time::Instant::now(),
+            bytes,
+            capacity: capacity(),
+        }
+    }
+
+    pub(crate) fn finish<T>(self, result: &Result<T>, capacity: impl FnOnce() -> usize) {
+        let duration_us = self.start.elapsed().as_micros() as u64;
+        let capacity = capacity();
+        if capacity > self.capacity {
+            tracing::debug!(
+                monotonic_counter.simdjson_parser_grows = 1u64,
+                old_capacity = self.capacity,
+                new_capacity = capacity,
+                "parser capacity grew"
+            );
+        }
+        match result {
+            Ok(_) => tracing::debug!(
+                monotonic_counter.simdjson_parsed_bytes = self.bytes as u64,
+                histogram.simdjson_parse_duration_us = duration_us,
+                "parsed"
+            ),
+            Err(e) => tracing::debug!(
+                monotonic_counter.simdjson_parse_errors = 1u64,
+                histogram.simdjson_parse_duration_us = duration_us,
+                error.kind = e.kind(),
+                error.message = %e,
+                "parse failed"
+            ),
+        }
+        self.span.exit();
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct ParseSpan;
+
+#[cfg(not(feature = "tracing"))]
+impl ParseSpan {
+    #[inline(always)]
+    pub(crate) fn enter(
+        _api: &'static str,
+        _bytes: usize,
+        _capacity: impl FnOnce() -> usize,
+    ) -> Self {
+        ParseSpan
+    }
+
+    #[inline(always)]
+    pub(crate) fn finish<T>(self, _result: &Result<T>, _capacity: impl FnOnce() -> usize) {}
+}
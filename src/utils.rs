@@ -1,9 +1,15 @@
+#[cfg(feature = "ondemand")]
 use std::ptr::NonNull;
 
 use simdjson_sys as ffi;
 
+#[cfg(feature = "builder")]
+use crate::pool;
+use crate::{Result, SimdJsonError};
+
+#[cfg(feature = "ondemand")]
 #[inline]
-pub fn string_view_to_str<'a>(sv: NonNull<ffi::STD_string_view>) -> &'a str {
+pub(crate) fn string_view_to_str<'a>(sv: NonNull<ffi::STD_string_view>) -> &'a str {
     let s = unsafe {
         let s = std::slice::from_raw_parts(
             ffi::STD_string_view_data(sv.as_ptr()).cast(),
@@ -16,9 +22,391 @@ pub fn string_view_to_str<'a>(sv: NonNull<ffi::STD_string_view>) -> &'a str {
 }
 
 #[inline]
-pub fn string_view_struct_to_str<'a>(sv: ffi::SJ_string_view) -> &'a str {
+pub(crate) fn string_view_struct_to_str<'a>(sv: ffi::SJ_string_view) -> &'a str {
     unsafe {
         let s = std::slice::from_raw_parts(sv.data.cast(), sv.len);
         std::str::from_utf8_unchecked(s)
     }
 }
+
+/// Checks whether `buf` is valid UTF-8 using simdjson's SIMD validator.
+///
+/// This is the same fast path the parser uses and works on any buffer, not
+/// only JSON documents.
+pub fn validate_utf8(buf: &[u8]) -> bool {
+    unsafe { ffi::SJ_validate_utf8(buf.as_ptr().cast(), buf.len()) }
+}
+
+/// Parses `buf` as a JSON number with simdjson's number parser: exact
+/// rounding, no locale, no allocation.
+///
+/// The whole input must be one number in JSON syntax, so `"1.5"` and
+/// `"-2e10"` are accepted but `" 1"`, `"+1"`, `"01"` and `"1."` are errors.
+/// So are numbers beyond the `f64` range, rather than infinities.
+pub fn parse_double(buf: &[u8]) -> Result<f64> {
+    parse_number(buf, ffi::SJ_parse_double, |r| (r.error, r.value))
+}
+
+/// Parses `buf` as a JSON integer that fits an `i64`; see [`parse_double`].
+/// Fractions, exponents and out-of-range values are errors.
+pub fn parse_i64(buf: &[u8]) -> Result<i64> {
+    parse_number(buf, ffi::SJ_parse_int64, |r| (r.error, r.value))
+}
+
+/// Parses `buf` as a JSON integer that fits a `u64`; see [`parse_double`].
+pub fn parse_u64(buf: &[u8]) -> Result<u64> {
+    parse_number(buf, ffi::SJ_parse_uint64, |r| (r.error, r.value))
+}
+
+fn parse_number<R, T>(
+    buf: &[u8],
+    parse: unsafe extern "C" fn(*const std::os::raw::c_char, usize) -> R,
+    split: impl FnOnce(R) -> (i32, T),
+) -> Result<T> {
+    // simdjson stops at whitespace and structural characters as it would
+    // inside a document; only number characters may make up the input.
+    let number_chars = buf
+        .iter()
+        .all(|b| matches!(b, b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E'));
+    if !number_chars {
+        return Err(SimdJsonError::NumberError);
+    }
+    match split(unsafe { parse(buf.as_ptr().cast(), buf.len()) }) {
+        (0, value) => Ok(value),
+        (code, _) => Err(SimdJsonError::from(code)),
+    }
+}
+
+/// Appends `s` to `out` escaped for use inside a JSON string, without the
+/// surrounding quotes, using the SIMD escaping of
+/// [`StringBuilder`](crate::builder::StringBuilder) when the `builder`
+/// feature is on and a byte-at-a-time loop otherwise.
+///
+/// Meant for JSON produced by other means, such as templates or log
+/// formatters. Quotes, backslashes and control characters are escaped;
+/// everything else, non-ASCII text included, is copied as is. On error
+/// `out` is left as it was.
+pub fn escape_json_string(s: &str, out: &mut String) -> Result<()> {
+    #[cfg(feature = "builder")]
+    {
+        pool::with_builder(|builder| {
+            builder.append_escaped(s);
+            out.push_str(builder.view()?);
+            Ok(())
+        })
+    }
+    #[cfg(not(feature = "builder"))]
+    {
+        escape_bytewise(s, out);
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "builder"))]
+fn escape_bytewise(s: &str, out: &mut String) {
+    use std::fmt::Write as _;
+
+    let mut start = 0;
+    for (i, b) in s.bytes().enumerate() {
+        let escape = match b {
+            b'"' => "\\\"",
+            b'\\' => "\\\\",
+            b'\n' => "\\n",
+            b'\r' => "\\r",
+            b'\t' => "\\t",
+            0x08 => "\\b",
+            0x0c => "\\f",
+            0x00..=0x1f => "",
+            _ => continue,
+        };
+        out.push_str(&s[start..i]);
+        if escape.is_empty() {
+            let _ = write!(out, "\\u{b:04x}");
+        } else {
+            out.push_str(escape);
+        }
+        start = i + 1;
+    }
+    out.push_str(&s[start..]);
+}
+
+/// Whether `s` has a character [`escape_json_string`] would escape: a quote,
+/// a backslash or a control character. Strings without one can be written
+/// between quotes as they are.
+pub fn needs_escaping(s: &str) -> bool {
+    s.bytes().any(|b| b == b'"' || b == b'\\' || b < 0x20)
+}
+
+/// Name of the SIMD kernel simdjson picked for this CPU, e.g. `"haswell"`,
+/// `"icelake"`, `"westmere"`, `"arm64"` or `"fallback"`.
+pub fn active_implementation() -> &'static str {
+    unsafe { string_view_struct_to_str(ffi::SJ_active_implementation_name()) }
+}
+
+/// Human readable description of [`active_implementation`].
+pub fn active_implementation_description() -> &'static str {
+    unsafe { string_view_struct_to_str(ffi::SJ_active_implementation_description()) }
+}
+
+/// Forces the kernel used by parsers created from now on. Existing parsers
+/// keep the kernel they were created with.
+///
+/// Fails with [`SimdJsonError::UnsupportedArchitecture`] if `name` is unknown
+/// or the CPU cannot run it. `"fallback"` is only there when simdjson compiles
+/// it in, for example with the crate's `fallback` feature; otherwise asking
+/// for it fails the same way.
+pub fn set_implementation(name: &str) -> Result<()> {
+    match unsafe { ffi::SJ_set_active_implementation(name.as_ptr().cast(), name.len()) } {
+        0 => Ok(()),
+        code => Err(SimdJsonError::from(code)),
+    }
+}
+
+/// A SIMD kernel compiled into simdjson.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Implementation {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Whether this CPU can run the kernel.
+    pub supported: bool,
+    /// Instruction set extensions the kernel needs.
+    pub requires: CpuFeatures,
+}
+
+/// Every kernel compiled into this build, whether or not the CPU supports it.
+pub fn available_implementations() -> Vec<Implementation> {
+    let len = unsafe { ffi::SJ_available_implementations_size() };
+    (0..len)
+        .map(|i| {
+            let info = unsafe { ffi::SJ_available_implementation(i) };
+            Implementation {
+                name: string_view_struct_to_str(info.name),
+                description: string_view_struct_to_str(info.description),
+                supported: info.supported,
+                requires: CpuFeatures::from_instruction_sets(info.required_instruction_sets),
+            }
+        })
+        .collect()
+}
+
+/// SIMD extensions relevant to simdjson's kernels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CpuFeatures {
+    pub sse42: bool,
+    pub pclmulqdq: bool,
+    pub bmi1: bool,
+    pub bmi2: bool,
+    pub avx2: bool,
+    pub avx512f: bool,
+    pub avx512dq: bool,
+    pub avx512cd: bool,
+    pub avx512bw: bool,
+    pub avx512vl: bool,
+    pub avx512vbmi2: bool,
+    pub neon: bool,
+}
+
+impl CpuFeatures {
+    /// Whether every feature set in `other` is also set in `self`.
+    pub fn contains(&self, other: &CpuFeatures) -> bool {
+        let (a, b) = (self.bits(), other.bits());
+        a & b == b
+    }
+
+    fn bits(&self) -> u32 {
+        [
+            self.sse42,
+            self.pclmulqdq,
+            self.bmi1,
+            self.bmi2,
+            self.avx2,
+            self.avx512f,
+            self.avx512dq,
+            self.avx512cd,
+            self.avx512bw,
+            self.avx512vl,
+            self.avx512vbmi2,
+            self.neon,
+        ]
+        .iter()
+        .enumerate()
+        .fold(0, |bits, (i, &set)| bits | (u32::from(set) << i))
+    }
+
+    /// Decodes simdjson's `internal::instruction_set` bitmask.
+    fn from_instruction_sets(bits: u32) -> Self {
+        let has = |flag: u32| bits & flag != 0;
+        Self {
+            neon: has(0x1),
+            avx2: has(0x4),
+            sse42: has(0x8),
+            pclmulqdq: has(0x10),
+            bmi1: has(0x20),
+            bmi2: has(0x40),
+            avx512f: has(0x100),
+            avx512dq: has(0x200),
+            avx512cd: has(0x2000),
+            avx512bw: has(0x4000),
+            avx512vl: has(0x8000),
+            avx512vbmi2: has(0x10000),
+        }
+    }
+}
+
+/// SIMD extensions detected on the running CPU.
+pub fn cpu_features() -> CpuFeatures {
+    #[allow(unused_mut)]
+    let mut features = CpuFeatures::default();
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        features.sse42 = std::is_x86_feature_detected!("sse4.2");
+        features.pclmulqdq = std::is_x86_feature_detected!("pclmulqdq");
+        features.bmi1 = std::is_x86_feature_detected!("bmi1");
+        features.bmi2 = std::is_x86_feature_detected!("bmi2");
+        features.avx2 = std::is_x86_feature_detected!("avx2");
+        features.avx512f = std::is_x86_feature_detected!("avx512f");
+        features.avx512dq = std::is_x86_feature_detected!("avx512dq");
+        features.avx512cd = std::is_x86_feature_detected!("avx512cd");
+        features.avx512bw = std::is_x86_feature_detected!("avx512bw");
+        features.avx512vl = std::is_x86_feature_detected!("avx512vl");
+        features.avx512vbmi2 = std::is_x86_feature_detected!("avx512vbmi2");
+    }
+    #[cfg(target_arch = "aarch64")]
+    {
+        features.neon = std::arch::is_aarch64_feature_detected!("neon");
+    }
+    features
+}
+
+/// Splits newline-delimited JSON into its records without parsing them.
+///
+/// Records end at `\n` or `\r\n`; blank lines are skipped. Line ends are
+/// found with SIMD `memchr`, and no quote tracking is needed: a JSON string
+/// cannot contain a raw newline, so every newline in valid input ends a
+/// record. A record inside a padded buffer is followed by readable bytes and
+/// can be parsed in place.
+pub fn split_records(s: &str) -> SplitRecords<'_> {
+    SplitRecords { rest: s }
+}
+
+/// Iterator over the records of a buffer; see [`split_records`].
+#[derive(Debug, Clone)]
+pub struct SplitRecords<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for SplitRecords<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        while !self.rest.is_empty() {
+            let (line, rest) = match memchr::memchr(b'\n', self.rest.as_bytes()) {
+                Some(i) => (&self.rest[..i], &self.rest[i + 1..]),
+                None => (self.rest, ""),
+            };
+            self.rest = rest;
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if !line.bytes().all(|b| b.is_ascii_whitespace()) {
+                return Some(line);
+            }
+        }
+        None
+    }
+}
+
+/// Splits `s` into chunks of at least `target` bytes that each end right
+/// after a newline, or at the end of `s`.
+#[cfg(any(feature = "rayon", feature = "ondemand"))]
+pub(crate) fn split_lines_at(s: &str, target: usize) -> Vec<&str> {
+    let target = target.max(1);
+    let mut chunks = Vec::new();
+    let mut rest = s;
+    while rest.len() > target {
+        match memchr::memchr(b'\n', &rest.as_bytes()[target..]) {
+            Some(i) => {
+                let (chunk, tail) = rest.split_at(target + i + 1);
+                chunks.push(chunk);
+                rest = tail;
+            }
+            None => break,
+        }
+    }
+    if !rest.is_empty() {
+        chunks.push(rest);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf8() {
+        assert!(validate_utf8(b""));
+        assert!(validate_utf8("héllo, 世界".as_bytes()));
+        assert!(!validate_utf8(b"\xff\xfe"));
+        assert!(!validate_utf8(b"abc\xc3"));
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(parse_double(b"-0.25e2").unwrap(), -25.0);
+        assert_eq!(parse_double(b"12").unwrap(), 12.0);
+        assert_eq!(parse_i64(b"-9223372036854775808").unwrap(), i64::MIN);
+        assert_eq!(parse_u64(b"18446744073709551615").unwrap(), u64::MAX);
+        for bad in [&b""[..], b"1.", b"01", b"12,3", b" 1", b"1e400", b"abc"] {
+            assert!(parse_double(bad).is_err(), "{bad:?}");
+        }
+        assert!(parse_i64(b"1.5").is_err());
+        assert!(parse_u64(b"-1").is_err());
+    }
+
+    #[test]
+    fn escaping_needed() {
+        assert!(!needs_escaping("plain text, é 世界 /"));
+        assert!(needs_escaping("a\"b"));
+        assert!(needs_escaping("a\\b"));
+        assert!(needs_escaping("tab\t"));
+        assert!(!needs_escaping(""));
+    }
+
+    #[test]
+    fn escape() {
+        let mut out = String::from("\"");
+        escape_json_string("a\"b\\c\n\u{1}é", &mut out).unwrap();
+        assert_eq!(out, r#""a\"b\\c\n\u0001é"#);
+        escape_json_string("", &mut out).unwrap();
+        escape_json_string(&"x".repeat(100), &mut out).unwrap();
+        assert_eq!(out.len(), 18 + 100);
+    }
+
+    #[test]
+    fn implementation() {
+        assert!(!active_implementation().is_empty());
+        assert!(matches!(
+            set_implementation("no-such-kernel"),
+            Err(SimdJsonError::UnsupportedArchitecture)
+        ));
+    }
+
+    #[test]
+    fn implementations() {
+        let features = cpu_features();
+        let implementations = available_implementations();
+        let active = active_implementation();
+        assert!(
+            implementations
+                .iter()
+                .any(|i| i.name == active && i.supported)
+        );
+        for implementation in implementations {
+            if implementation.supported {
+                assert!(
+                    features.contains(&implementation.requires),
+                    "{implementation:?}"
+                );
+            }
+        }
+    }
+}
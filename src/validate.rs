@@ -0,0 +1,85 @@
+use std::ptr::NonNull;
+
+use simdjson_sys as ffi;
+
+use crate::{Result, SimdJsonError, macros::impl_drop, pool};
+
+/// Checks that `padded_string` is well-formed JSON and valid UTF-8.
+///
+/// This is a one-shot helper backed by a parser kept per thread (see
+/// [`crate::release_thread_local_parsers`]). Use
+/// [`dom::Parser::validate`](crate::dom::Parser::validate) to manage the
+/// parser yourself.
+pub fn validate(padded_string: &str) -> Result<()> {
+    pool::with_parser(|parser| parser.validate(padded_string))
+}
+
+/// Whether `json` is a well-formed JSON document, for hot paths such as
+/// input sanitization that only need a yes or no. `json` does not need to
+/// be padded: it is copied into a buffer kept per thread.
+pub fn is_valid_json(json: &str) -> bool {
+    pool::with_padded(json, |json| {
+        pool::with_parser(|parser| parser.validate(json).is_ok())
+    })
+}
+
+/// An ondemand parser that reads documents through without keeping
+/// anything, for [`dom::Parser::validate`](crate::dom::Parser::validate).
+pub(crate) struct Validator {
+    ptr: NonNull<ffi::SJ_validator>,
+}
+
+// Like the parsers, it only must not be used from two threads at once.
+unsafe impl Send for Validator {}
+
+impl Validator {
+    pub(crate) fn new(max_capacity: usize, max_depth: usize) -> Self {
+        let ptr = unsafe { NonNull::new_unchecked(ffi::SJ_validator_new(max_capacity, max_depth)) };
+        Self { ptr }
+    }
+
+    pub(crate) fn validate(&mut self, padded_string: &str) -> Result<()> {
+        match unsafe {
+            ffi::SJ_validator_validate(
+                self.ptr.as_ptr(),
+                padded_string.as_ptr().cast(),
+                padded_string.len(),
+            )
+        } {
+            0 => Ok(()),
+            code => Err(SimdJsonError::from(code)),
+        }
+    }
+}
+
+impl_drop!(Validator, ffi::SJ_validator_free);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SimdJsonError, prelude::*};
+
+    #[test]
+    fn validate_documents() {
+        assert!(validate(&r#"{"a": [true, null]}"#.to_padded_string()).is_ok());
+        assert!(matches!(
+            validate(&"\"abc".to_padded_string()),
+            Err(SimdJsonError::UnclosedString)
+        ));
+        assert!(matches!(
+            validate(&"1 2".to_padded_string()),
+            Err(SimdJsonError::TailingContent)
+        ));
+        for invalid in ["[1 2]", r#"{"a" 1}"#, "01", "1.", r#""\x""#, "nul", "[", ""] {
+            assert!(validate(&invalid.to_padded_string()).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn quick_check() {
+        assert!(is_valid_json(r#" {"a": [1, "b"]} "#));
+        assert!(is_valid_json("\u{FEFF}1"));
+        assert!(!is_valid_json("[1,]"));
+        assert!(!is_valid_json(""));
+    }
+}
@@ -0,0 +1,304 @@
+//! `SimdJson<T>`, a drop-in replacement for the `Json<T>` extractor and
+//! response of axum and actix-web.
+//!
+//! Request bodies go through the frameworks' own body extractors, so their
+//! body size limits (`DefaultBodyLimit`, `PayloadConfig`) still apply. The
+//! body is copied once into a padded buffer, parsed with a per-thread DOM
+//! parser and deserialized; responses are serialized with the SIMD string builder.
+
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{SimdJsonError, pool, serde::de::from_element, utils::validate_utf8};
+
+/// JSON extractor and response backed by simdjson.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SimdJson<T>(pub T);
+
+impl<T> Deref for SimdJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for SimdJson<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T> From<T> for SimdJson<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+/// Why a request body was rejected by [`SimdJson`].
+#[derive(Debug)]
+pub enum SimdJsonRejection {
+    /// The `Content-Type` header is missing or not JSON. Responds with
+    /// `415 Unsupported Media Type`.
+    MissingJsonContentType,
+    /// The body is not valid JSON. Responds with `400 Bad Request`.
+    Syntax(SimdJsonError),
+    /// The body is valid JSON but does not match `T`. Responds with
+    /// `422 Unprocessable Entity`.
+    Data(SimdJsonError),
+    /// The body could not be read, e.g. because it exceeds the body limit.
+    #[cfg(feature = "axum")]
+    Body(axum::extract::rejection::BytesRejection),
+}
+
+impl SimdJsonRejection {
+    fn status(&self) -> u16 {
+        match self {
+            SimdJsonRejection::MissingJsonContentType => 415,
+            SimdJsonRejection::Syntax(_) => 400,
+            SimdJsonRejection::Data(_) => 422,
+            #[cfg(feature = "axum")]
+            SimdJsonRejection::Body(rejection) => rejection.status().as_u16(),
+        }
+    }
+}
+
+impl fmt::Display for SimdJsonRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SimdJsonRejection::MissingJsonContentType => {
+                f.write_str("Expected request with `Content-Type: application/json`")
+            }
+            SimdJsonRejection::Syntax(e) => {
+                write!(f, "Failed to parse the request body as JSON: {e}")
+            }
+            SimdJsonRejection::Data(e) => {
+                write!(
+                    f,
+                    "Failed to deserialize the JSON body into the target type: {e}"
+                )
+            }
+            #[cfg(feature = "axum")]
+            SimdJsonRejection::Body(rejection) => write!(f, "{}", rejection.body_text()),
+        }
+    }
+}
+
+impl std::error::Error for SimdJsonRejection {}
+
+fn is_json_content_type(content_type: Option<&[u8]>) -> bool {
+    let Some(content_type) = content_type.and_then(|v| std::str::from_utf8(v).ok()) else {
+        return false;
+    };
+    let essence = content_type.split(';').next().unwrap_or("").trim();
+    let Some((kind, subtype)) = essence.split_once('/') else {
+        return false;
+    };
+    kind.eq_ignore_ascii_case("application")
+        && (subtype.eq_ignore_ascii_case("json")
+            || subtype.len() > 5 && subtype[subtype.len() - 5..].eq_ignore_ascii_case("+json"))
+}
+
+fn decode<T: DeserializeOwned>(body: &[u8]) -> Result<T, SimdJsonRejection> {
+    if !validate_utf8(body) {
+        return Err(SimdJsonRejection::Syntax(SimdJsonError::Utf8Error));
+    }
+    // Checked just above.
+    let body = unsafe { std::str::from_utf8_unchecked(body) };
+    pool::with_padded(body, |body| {
+        pool::with_parser(|parser| {
+            let element = parser.parse(body).map_err(SimdJsonRejection::Syntax)?;
+            from_element(&element).map_err(SimdJsonRejection::Data)
+        })
+    })
+}
+
+fn encode<T: Serialize>(value: &T) -> Result<String, SimdJsonError> {
+    crate::serde::ser::to_string(value)
+}
+
+#[cfg(feature = "axum")]
+mod axum_impl {
+    use axum::{
+        body::Bytes,
+        extract::{FromRequest, Request},
+        http::{StatusCode, header},
+        response::{IntoResponse, Response},
+    };
+
+    use super::*;
+
+    impl<T, S> FromRequest<S> for SimdJson<T>
+    where
+        T: DeserializeOwned,
+        S: Send + Sync,
+    {
+        type Rejection = SimdJsonRejection;
+
+        async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+            let content_type = req.headers().get(header::CONTENT_TYPE);
+            if !is_json_content_type(content_type.map(|v| v.as_bytes())) {
+                return Err(SimdJsonRejection::MissingJsonContentType);
+            }
+            let body = Bytes::from_request(req, state)
+                .await
+                .map_err(SimdJsonRejection::Body)?;
+            decode(&body).map(SimdJson)
+        }
+    }
+
+    impl IntoResponse for SimdJsonRejection {
+        fn into_response(self) -> Response {
+            let status =
+                StatusCode::from_u16(self.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+            (status, self.to_string()).into_response()
+        }
+    }
+
+    impl<T: Serialize> IntoResponse for SimdJson<T> {
+        fn into_response(self) -> Response {
+            match encode(&self.0) {
+                Ok(body) => ([(header::CONTENT_TYPE, "application/json")], body).into_response(),
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "actix-web")]
+mod actix_impl {
+    use std::{future::Future, pin::Pin};
+
+    use actix_web::{
+        FromRequest, HttpRequest, HttpResponse, Responder, ResponseError,
+        body::BoxBody,
+        dev::Payload,
+        http::{StatusCode, header},
+        web::Bytes,
+    };
+
+    use super::*;
+
+    impl<T: DeserializeOwned + 'static> FromRequest for SimdJson<T> {
+        type Error = actix_web::Error;
+        type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+        fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+            let content_type = req.headers().get(header::CONTENT_TYPE);
+            let is_json = is_json_content_type(content_type.map(|v| v.as_bytes()));
+            let body = Bytes::from_request(req, payload);
+            Box::pin(async move {
+                if !is_json {
+                    return Err(SimdJsonRejection::MissingJsonContentType.into());
+                }
+                let body = body.await?;
+                Ok(decode(&body).map(SimdJson)?)
+            })
+        }
+    }
+
+    impl ResponseError for SimdJsonRejection {
+        fn status_code(&self) -> StatusCode {
+            StatusCode::from_u16(self.status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+
+    impl<T: Serialize> Responder for SimdJson<T> {
+        type Body = BoxBody;
+
+        fn respond_to(self, _req: &HttpRequest) -> HttpResponse {
+            match encode(&self.0) {
+                Ok(body) => HttpResponse::Ok()
+                    .content_type("application/json")
+                    .body(body),
+                Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Input {
+        name: String,
+    }
+
+    #[test]
+    fn content_type() {
+        assert!(is_json_content_type(Some(b"application/json")));
+        assert!(is_json_content_type(Some(
+            b"Application/JSON; charset=utf-8"
+        )));
+        assert!(is_json_content_type(Some(b"application/problem+json")));
+        assert!(!is_json_content_type(Some(b"text/json")));
+        assert!(!is_json_content_type(Some(b"application/+json")));
+        assert!(!is_json_content_type(None));
+    }
+
+    #[test]
+    fn decode_body() {
+        let input: Input = decode(br#"{"name": "a"}"#).unwrap();
+        assert_eq!(input.name, "a");
+        assert!(matches!(
+            decode::<Input>(b"{"),
+            Err(SimdJsonRejection::Syntax(_))
+        ));
+        assert!(matches!(
+            decode::<Input>(b"{}"),
+            Err(SimdJsonRejection::Data(_))
+        ));
+        assert!(matches!(
+            decode::<Input>(b"\"\xff\""),
+            Err(SimdJsonRejection::Syntax(_))
+        ));
+    }
+
+    #[cfg(feature = "axum")]
+    #[tokio::test]
+    async fn axum_extract() {
+        use axum::{
+            extract::{FromRequest, Request},
+            http::header,
+            response::IntoResponse,
+        };
+
+        let request = Request::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(axum::body::Body::from(r#"{"name": "b"}"#))
+            .unwrap();
+        let SimdJson(input) = SimdJson::<Input>::from_request(request, &()).await.unwrap();
+        assert_eq!(input.name, "b");
+
+        let request = Request::builder()
+            .body(axum::body::Body::from("{}"))
+            .unwrap();
+        let rejection = SimdJson::<Input>::from_request(request, &())
+            .await
+            .unwrap_err();
+        assert_eq!(rejection.into_response().status(), 415);
+    }
+
+    #[cfg(feature = "actix-web")]
+    #[tokio::test]
+    async fn actix_extract() {
+        use actix_web::{FromRequest, http::header, test::TestRequest};
+
+        let (request, mut payload) = TestRequest::default()
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .set_payload(r#"{"name": "c"}"#)
+            .to_http_parts();
+        let SimdJson(input) = SimdJson::<Input>::from_request(&request, &mut payload)
+            .await
+            .unwrap();
+        assert_eq!(input.name, "c");
+    }
+}
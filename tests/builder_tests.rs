@@ -1,11 +1,13 @@
 #![cfg(feature = "serde_impl")]
 
 use serde::{Deserialize, Serialize};
-use simdjson_rust::builder::StringBuilder;
+use simdjson_rust::builder::{JsoncOptions, StringBuilder};
 use simdjson_rust::dom::Parser;
 use simdjson_rust::prelude::*;
 use simdjson_rust::serde::de::from_element;
-use simdjson_rust::serde::ser::{to_string, to_string_with_capacity};
+use simdjson_rust::serde::ser::{
+    StreamingArrayWriter, to_string, to_string_with_capacity, to_writer_array,
+};
 
 // ---------------------------------------------------------------------------
 // Basic StringBuilder usage
@@ -47,6 +49,20 @@ fn builder_object() {
     assert_eq!(json, r#"{"name":"Alice","age":30}"#);
 }
 
+#[test]
+fn builder_field_header() {
+    let mut builder = StringBuilder::new();
+    builder.start_object();
+    builder.append_field_header("name", true);
+    builder.append_string("Alice");
+    builder.append_field_header("a\"b", false);
+    builder.append_i64(30);
+    builder.end_object();
+
+    let json = builder.view().unwrap();
+    assert_eq!(json, r#"{"name":"Alice","a\"b":30}"#);
+}
+
 #[test]
 fn builder_nested() {
     let mut builder = StringBuilder::new();
@@ -77,6 +93,17 @@ fn builder_clear_reuse() {
     assert_eq!(builder.view().unwrap(), r#""reset""#);
 }
 
+#[test]
+fn builder_allocated_bytes() {
+    let mut builder = StringBuilder::with_capacity(16);
+    assert_eq!(builder.allocated_bytes(), 16);
+    builder.append_string(&"x".repeat(100));
+    assert!(builder.allocated_bytes() >= 102);
+    builder.clear();
+    assert_eq!(builder.size(), 0);
+    assert!(builder.allocated_bytes() >= 102);
+}
+
 #[test]
 fn builder_display() {
     let mut builder = StringBuilder::new();
@@ -84,6 +111,99 @@ fn builder_display() {
     assert_eq!(format!("{builder}"), "true");
 }
 
+#[test]
+fn builder_checked_mode() {
+    use simdjson_rust::SimdJsonError;
+
+    let mut builder = StringBuilder::checked();
+    builder.start_object();
+    builder.append_field_header("a", true);
+    builder.start_array();
+    builder.append_i64(1);
+    builder.append_comma();
+    builder.append_string("x");
+    builder.end_array();
+    builder.append_comma();
+    builder.append_string("b");
+    builder.append_colon();
+    builder.append_null();
+    assert!(builder.view().is_ok());
+    assert!(matches!(
+        builder.check(),
+        Err(SimdJsonError::BuilderMisuse("unfinished document"))
+    ));
+    builder.end_object();
+    assert_eq!(builder.into_string().unwrap(), r#"{"a":[1,"x"],"b":null}"#);
+
+    let misuse = |build: fn(&mut StringBuilder)| {
+        let mut builder = StringBuilder::checked();
+        build(&mut builder);
+        match builder.view() {
+            Err(SimdJsonError::BuilderMisuse(misuse)) => misuse,
+            other => panic!("{other:?}"),
+        }
+    };
+    assert_eq!(
+        misuse(|b| {
+            b.start_object();
+            b.append_string("k");
+            b.append_i64(1);
+        }),
+        "missing colon after an object key"
+    );
+    assert_eq!(
+        misuse(|b| {
+            b.start_array();
+            b.append_i64(1);
+            b.append_i64(2);
+        }),
+        "missing comma between values"
+    );
+    assert_eq!(
+        misuse(|b| {
+            b.start_object();
+            b.append_i64(1);
+        }),
+        "value where an object key is expected"
+    );
+    assert_eq!(
+        misuse(|b| {
+            b.start_array();
+            b.end_object();
+        }),
+        "end_object without an open object"
+    );
+    assert_eq!(
+        misuse(|b| {
+            b.start_object();
+            b.append_field_header("k", true);
+            b.end_object();
+        }),
+        "object key without a value"
+    );
+    assert_eq!(
+        misuse(|b| {
+            b.start_array();
+            b.append_null();
+            b.append_comma();
+            b.end_array();
+        }),
+        "comma before the end of a container"
+    );
+
+    // The first misuse is kept until `clear`; unchecked builders never fail.
+    let mut builder = StringBuilder::checked();
+    builder.append_comma();
+    builder.append_null();
+    assert!(builder.view().is_err());
+    builder.clear();
+    builder.append_null();
+    assert_eq!(builder.into_string().unwrap(), "null");
+    let mut builder = StringBuilder::new();
+    builder.end_array();
+    assert!(builder.check().is_ok());
+}
+
 // ---------------------------------------------------------------------------
 // Serde serialization
 // ---------------------------------------------------------------------------
@@ -408,3 +528,242 @@ fn serialize_with_capacity() {
     let json = to_string_with_capacity(&person, 128).unwrap();
     assert_eq!(json, r#"{"name":"Dave","age":40,"active":true}"#);
 }
+
+// ---------------------------------------------------------------------------
+// Streaming arrays
+// ---------------------------------------------------------------------------
+
+#[test]
+fn streaming_array_chunks() {
+    /// Records the size of every write it receives.
+    #[derive(Default)]
+    struct Chunks(Vec<u8>, Vec<usize>);
+
+    impl std::io::Write for Chunks {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.extend_from_slice(buf);
+            self.1.push(buf.len());
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut writer = StreamingArrayWriter::with_chunk_len(Chunks::default(), 16);
+    for i in 0..10u32 {
+        let person = Person {
+            name: format!("p{i}"),
+            age: i,
+            active: true,
+        };
+        writer.write(&person).unwrap();
+    }
+    assert_eq!(writer.len(), 10);
+    let out = writer.finish().unwrap();
+    assert!(out.1.len() > 1);
+    // Each chunk is flushed as soon as it reaches 16 bytes.
+    assert!(out.1.iter().all(|&len| len < 16 + 48));
+
+    let json = String::from_utf8(out.0).unwrap();
+    let mut parser = Parser::default();
+    let ps = json.into_padded_string();
+    let people: Vec<Person> = from_element(&parser.parse(&ps).unwrap()).unwrap();
+    assert_eq!(people.len(), 10);
+    assert_eq!(people[9].name, "p9");
+}
+
+#[test]
+fn streaming_array_failed_element() {
+    struct Unserializable;
+
+    impl Serialize for Unserializable {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::{Error, SerializeSeq};
+            let mut seq = serializer.serialize_seq(None)?;
+            seq.serialize_element(&1)?;
+            Err(S::Error::custom("unserializable"))
+        }
+    }
+
+    let mut writer = StreamingArrayWriter::new(Vec::new());
+    writer.write(&1).unwrap();
+    assert!(writer.write(&Unserializable).is_err());
+    writer.write(&2).unwrap();
+    assert_eq!(writer.len(), 2);
+    assert_eq!(writer.finish().unwrap(), b"[1,2]");
+}
+
+#[test]
+fn streaming_array_empty() {
+    let out = to_writer_array(Vec::new(), std::iter::empty::<u8>()).unwrap();
+    assert_eq!(out, b"[]");
+    let out = to_writer_array(Vec::new(), [1, 2, 3]).unwrap();
+    assert_eq!(out, b"[1,2,3]");
+}
+
+// ---------------------------------------------------------------------------
+// json_builder! macro
+// ---------------------------------------------------------------------------
+
+#[test]
+fn json_builder_literals_and_nesting() {
+    let json = simdjson_rust::json_builder!({
+        "a": [1, -2, 2.5, true, null, "x\"y"],
+        "b": {},
+        "c": [],
+        "d": {"e": [{"f": false}],},
+    })
+    .unwrap();
+    assert_eq!(
+        json,
+        r#"{"a":[1,-2,2.5,true,null,"x\"y"],"b":{},"c":[],"d":{"e":[{"f":false}]}}"#
+    );
+    assert_eq!(simdjson_rust::json_builder!(null).unwrap(), "null");
+}
+
+#[test]
+fn json_builder_interpolation() {
+    let person = Person {
+        name: "Alice".to_string(),
+        age: 30,
+        active: true,
+    };
+    let key = String::from("dynamic");
+    let n = 4;
+    let json = simdjson_rust::json_builder!({
+        "person": person,
+        (key): [n, n * 2, -n],
+        "len": person.name.len(),
+    })
+    .unwrap();
+    assert_eq!(
+        json,
+        r#"{"person":{"name":"Alice","age":30,"active":true},"dynamic":[4,8,-4],"len":5}"#
+    );
+
+    assert!(simdjson_rust::json_builder!({"nan": f64::NAN}).is_err());
+
+    let mut builder = StringBuilder::new();
+    simdjson_rust::json_builder!(in &mut builder; [n, "x"]).unwrap();
+    assert_eq!(builder.view().unwrap(), r#"[4,"x"]"#);
+}
+
+// ---------------------------------------------------------------------------
+// JSONC output
+// ---------------------------------------------------------------------------
+
+#[test]
+fn jsonc_output() {
+    let mut builder = StringBuilder::jsonc(JsoncOptions::default());
+    builder.append_comment("Generated file");
+    builder.start_object();
+    builder.append_comment("Port to listen on.\nChanging it needs a restart.");
+    builder.append_field_header("port", true);
+    builder.append_u64(8080);
+    builder.append_field_header("hosts", false);
+    builder.start_array();
+    builder.append_string("a");
+    builder.append_comma();
+    builder.append_string("b");
+    builder.append_comment("More hosts go here.");
+    builder.end_array();
+    builder.append_field_header("empty", false);
+    builder.start_object();
+    builder.end_object();
+    builder.end_object();
+    assert_eq!(
+        builder.into_string().unwrap(),
+        "// Generated file
+{
+  // Port to listen on.
+  // Changing it needs a restart.
+  \"port\": 8080,
+  \"hosts\": [
+    \"a\",
+    \"b\"
+    // More hosts go here.
+  ],
+  \"empty\": {}
+}
+"
+    );
+
+    #[derive(Serialize)]
+    struct Config {
+        name: &'static str,
+        ids: Vec<u32>,
+    }
+    let mut builder = StringBuilder::jsonc(JsoncOptions {
+        indent: 4,
+        trailing_newline: false,
+    });
+    let mut serializer = simdjson_rust::serde::ser::BuilderSerializer::new(&mut builder);
+    Config {
+        name: "x",
+        ids: vec![1, 2],
+    }
+    .serialize(&mut serializer)
+    .unwrap();
+    assert_eq!(
+        builder.into_string().unwrap(),
+        "{\n    \"name\": \"x\",\n    \"ids\": [\n        1,\n        2\n    ]\n}"
+    );
+
+    // A comment between a key and its value does not swallow the value.
+    let mut builder = StringBuilder::jsonc(JsoncOptions::default());
+    builder.start_object();
+    builder.append_field_header("port", true);
+    builder.append_comment("c");
+    builder.append_u64(8080);
+    builder.end_object();
+    assert_eq!(
+        builder.into_string().unwrap(),
+        "{\n  \"port\": \n  // c\n  8080\n}\n"
+    );
+
+    // Strict mode ignores comments.
+    let mut builder = StringBuilder::new();
+    builder.append_comment("dropped");
+    builder.start_array();
+    builder.end_array();
+    assert_eq!(builder.into_string().unwrap(), "[]");
+}
+
+// ---------------------------------------------------------------------------
+// Base64
+// ---------------------------------------------------------------------------
+
+#[cfg(feature = "base64")]
+#[test]
+fn builder_base64() {
+    let data: Vec<u8> = (0..=255).cycle().take(2000).collect();
+    let mut builder = StringBuilder::new();
+    builder.start_object();
+    builder.append_field_header("data", true);
+    builder.append_base64(&data);
+    builder.end_object();
+    let json = builder.into_string().unwrap();
+    assert_eq!(
+        json,
+        format!(r#"{{"data":"{}"}}"#, simdjson_rust::base64::encode(&data))
+    );
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Blob {
+        #[serde(with = "simdjson_rust::serde::helpers::base64")]
+        data: Vec<u8>,
+    }
+    let blob = Blob { data };
+    assert_eq!(to_string(&blob).unwrap(), json);
+    let mut parser = Parser::default();
+    let element = parser.parse(&json.as_str().to_padded_string()).unwrap();
+    assert_eq!(from_element::<Blob>(&element).unwrap(), blob);
+
+    let mut parser = Parser::default();
+    let element = parser
+        .parse(&r#"{"data":"Zm9v!"}"#.to_padded_string())
+        .unwrap();
+    assert!(from_element::<Blob>(&element).is_err());
+}
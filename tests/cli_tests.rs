@@ -0,0 +1,77 @@
+#![cfg(feature = "cli")]
+
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+fn run(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_simdjson-cli"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> &str {
+    assert!(output.status.success(), "{output:?}");
+    std::str::from_utf8(&output.stdout).unwrap()
+}
+
+const INPUT: &str = r#"{"users": [{"name": "a\"b", "age": 1.5}, {"name": "c", "tags": []}]}"#;
+
+#[test]
+fn validate() {
+    assert!(run(&["validate", "-"], INPUT).status.success());
+    let output = run(&["validate", "-"], "[1,");
+    assert!(!output.status.success());
+    assert!(output.stderr.starts_with(b"-: "));
+}
+
+#[test]
+fn minify() {
+    let output = run(&["minify"], "[ 1 , { \"a\" : null } ]");
+    assert_eq!(stdout(&output), "[1,{\"a\":null}]\n");
+    assert!(!run(&["minify"], "[1 2]").status.success());
+}
+
+#[test]
+fn pretty() {
+    let output = run(&["pretty"], INPUT);
+    assert_eq!(
+        stdout(&output),
+        r#"{
+  "users": [
+    {
+      "name": "a\"b",
+      "age": 1.5
+    },
+    {
+      "name": "c",
+      "tags": []
+    }
+  ]
+}
+"#
+    );
+}
+
+#[test]
+fn query() {
+    let output = run(&["query", "-", "/users/*/name", "--paths"], INPUT);
+    assert_eq!(
+        stdout(&output),
+        "/users/0/name\t\"a\\\"b\"\n/users/1/name\t\"c\"\n"
+    );
+    let output = run(&["query", "-", "/users/1"], INPUT);
+    assert_eq!(stdout(&output), "{\"name\":\"c\",\"tags\":[]}\n");
+}
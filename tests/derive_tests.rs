@@ -0,0 +1,121 @@
+#![cfg(feature = "derive")]
+
+use simdjson_rust::SimdJsonError;
+use simdjson_rust::dom::{FromJsonPointers, Parser};
+use simdjson_rust::prelude::*;
+
+#[derive(FromJsonPointers)]
+struct Request<'a> {
+    #[simdjson(pointer = "/meta/request_id")]
+    id: &'a str,
+    #[simdjson(pointer = "/payload/items/1/price")]
+    price: f64,
+    #[simdjson(pointer = "/payload/coupon")]
+    coupon: Option<String>,
+    status: u64,
+}
+
+#[derive(FromJsonPointers)]
+struct Owned {
+    #[simdjson(pointer = "/meta/request_id")]
+    id: String,
+    #[simdjson(pointer = "/a~1b")]
+    escaped: bool,
+}
+
+const JSON: &str = r#"{
+    "meta": {"request_id": "r-1"},
+    "payload": {"items": [{"price": 1.5}, {"price": 2.5}]},
+    "status": 200,
+    "a/b": true
+}"#;
+
+#[test]
+fn derive_borrowed() {
+    let mut parser = Parser::default();
+    let root = parser.parse(&JSON.to_padded_string()).unwrap();
+    let request = Request::from_pointers(&root).unwrap();
+    assert_eq!(request.id, "r-1");
+    assert_eq!(request.price, 2.5);
+    assert_eq!(request.coupon, None);
+    assert_eq!(request.status, 200);
+}
+
+#[test]
+fn derive_owned() {
+    let mut parser = Parser::default();
+    let owned = {
+        let root = parser.parse(&JSON.to_padded_string()).unwrap();
+        Owned::from_pointers(&root).unwrap()
+    };
+    assert_eq!(owned.id, "r-1");
+    assert!(owned.escaped);
+}
+
+#[test]
+fn derive_reports_pointer() {
+    let mut parser = Parser::default();
+    let root = parser
+        .parse(&r#"{"meta": {"request_id": 7}}"#.to_padded_string())
+        .unwrap();
+    match Owned::from_pointers(&root) {
+        Err(SimdJsonError::AtPointer { pointer, .. }) => assert_eq!(pointer, "/meta/request_id"),
+        _ => panic!("expected an AtPointer error"),
+    }
+}
+
+#[cfg(feature = "builder")]
+mod to_json_builder {
+    use simdjson_rust::builder::{JsoncOptions, StringBuilder, ToJsonBuilder};
+
+    #[derive(ToJsonBuilder)]
+    struct Item<'a> {
+        id: u64,
+        #[simdjson(rename = "display \"name\"")]
+        name: &'a str,
+        price: Option<f64>,
+    }
+
+    #[derive(ToJsonBuilder)]
+    struct Order<'a, T> {
+        r#type: &'static str,
+        items: Vec<Item<'a>>,
+        extra: T,
+    }
+
+    #[test]
+    fn derive_writes_object() {
+        let order = Order {
+            r#type: "order",
+            items: vec![
+                Item {
+                    id: 1,
+                    name: "a\nb",
+                    price: Some(2.5),
+                },
+                Item {
+                    id: 2,
+                    name: "c",
+                    price: None,
+                },
+            ],
+            extra: [true, false].to_vec(),
+        };
+        let json = r#"{"type":"order","items":[{"id":1,"display \"name\"":"a\nb","price":2.5},{"id":2,"display \"name\"":"c","price":null}],"extra":[true,false]}"#;
+        assert_eq!(order.to_json_string().unwrap(), json);
+
+        let mut builder = StringBuilder::checked();
+        order.write_json(&mut builder);
+        assert_eq!(builder.into_string().unwrap(), json);
+
+        let mut builder = StringBuilder::jsonc(JsoncOptions {
+            indent: 1,
+            trailing_newline: false,
+        });
+        order.items[1].write_json(&mut builder);
+        assert_eq!(
+            builder.into_string().unwrap(),
+            "{\n \"id\": 2,\n \"display \\\"name\\\"\": \"c\",\n \"price\": null\n}"
+        );
+    }
+}
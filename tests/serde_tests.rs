@@ -3,8 +3,11 @@
 use serde::{Deserialize, Serialize};
 use simdjson_rust::dom::Parser;
 use simdjson_rust::prelude::*;
-use simdjson_rust::serde::de::from_element;
+use simdjson_rust::serde::de::{
+    DeserializerOptions, from_element, from_pointer, from_slice, from_str, from_str_with,
+};
 use simdjson_rust::serde::value::element_to_value;
+use simdjson_rust::{BomPolicy, SimdJsonError};
 
 // ---------------------------------------------------------------------------
 // Basic type deserialization
@@ -55,6 +58,100 @@ fn deserialize_string_array() {
     assert_eq!(v, vec!["hello".to_string(), "world".to_string()]);
 }
 
+#[test]
+fn deserialize_number_array_types() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Meters(f32);
+    let mut parser = Parser::default();
+    let ps = "[1, -2, 2.5, 18446744073709551615]".to_padded_string();
+    let elm = parser.parse(&ps).unwrap();
+    let v: Vec<Option<f64>> = from_element(&elm).unwrap();
+    assert_eq!(v, [Some(1.0), Some(-2.0), Some(2.5), Some(u64::MAX as f64)]);
+    let v: Vec<Meters> = from_element(&elm).unwrap();
+    assert_eq!(v[2], Meters(2.5));
+    // Integers stay integers.
+    let v: Vec<serde_json::Value> = from_element(&elm).unwrap();
+    assert_eq!(
+        v,
+        serde_json::json!([1, -2, 2.5, u64::MAX])
+            .as_array()
+            .unwrap()[..]
+    );
+    assert!(from_element::<Vec<i64>>(&elm).is_err());
+    assert!(from_element::<Vec<u64>>(&elm).is_err());
+    assert!(from_element::<Vec<String>>(&elm).is_err());
+}
+
+#[test]
+fn deserialize_number_array_narrowing() {
+    let mut parser = Parser::default();
+    let ps = "[1, 300]".to_padded_string();
+    let elm = parser.parse(&ps).unwrap();
+    assert_eq!(from_element::<Vec<u16>>(&elm).unwrap(), [1, 300]);
+    assert!(from_element::<Vec<u8>>(&elm).is_err());
+}
+
+#[test]
+fn deserialize_numbers_helper() {
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Series {
+        #[serde(with = "simdjson_rust::serde::helpers::numbers")]
+        a: Vec<f64>,
+        #[serde(with = "simdjson_rust::serde::helpers::numbers")]
+        b: Vec<u16>,
+    }
+    let mut parser = Parser::default();
+    let ps = r#"{"a": [1, -2, 2.5, 18446744073709551615], "b": [1, 300]}"#.to_padded_string();
+    let elm = parser.parse(&ps).unwrap();
+    let series: Series = from_element(&elm).unwrap();
+    assert_eq!(series.a, [1.0, -2.0, 2.5, u64::MAX as f64]);
+    assert_eq!(series.b, [1, 300]);
+    assert_eq!(
+        serde_json::from_str::<Series>(&serde_json::to_string(&series).unwrap()).unwrap(),
+        series
+    );
+
+    // Anything but numbers goes element by element, and fails as it would.
+    for json in [
+        r#"{"a": [1, "2"], "b": []}"#,
+        r#"{"a": [], "b": [1, 70000]}"#,
+    ] {
+        assert!(from_str::<Series>(json).is_err());
+    }
+    let options = DeserializerOptions {
+        integral_floats: true,
+        ..DeserializerOptions::default()
+    };
+    let series: Series = from_str_with(r#"{"a": [], "b": [1, 2.0]}"#, options).unwrap();
+    assert_eq!(series.b, [1, 2]);
+}
+
+#[test]
+fn sequence_size_hint() {
+    struct Hint(Option<usize>);
+    impl<'de> Deserialize<'de> for Hint {
+        fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            struct V;
+            impl<'de> serde::de::Visitor<'de> for V {
+                type Value = Hint;
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    f.write_str("a sequence")
+                }
+                fn visit_seq<A: serde::de::SeqAccess<'de>>(self, seq: A) -> Result<Hint, A::Error> {
+                    Ok(Hint(seq.size_hint()))
+                }
+            }
+            d.deserialize_seq(V)
+        }
+    }
+    let mut parser = Parser::default();
+    for json in ["[1, 2, 3]", r#"[1, "a", null]"#] {
+        let ps = json.to_padded_string();
+        let elm = parser.parse(&ps).unwrap();
+        assert_eq!(from_element::<Hint>(&elm).unwrap().0, Some(3));
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Struct deserialization
 // ---------------------------------------------------------------------------
@@ -240,6 +337,123 @@ fn deserialize_hashmap() {
     assert_eq!(m.len(), 2);
 }
 
+#[test]
+fn deserialize_borrowed_keys_and_strings() {
+    use std::collections::BTreeMap;
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Tagged<'a> {
+        name: &'a str,
+        tags: BTreeMap<&'a str, &'a str>,
+    }
+    let mut parser = Parser::default();
+    let ps = r#"{"name": "x", "tags": {"k1": "v1", "k2": "v2"}}"#.to_padded_string();
+    let elm = parser.parse(&ps).unwrap();
+    let t: Tagged = from_element(&elm).unwrap();
+    assert_eq!(t.name, "x");
+    assert_eq!(
+        t.tags.into_iter().collect::<Vec<_>>(),
+        [("k1", "v1"), ("k2", "v2")]
+    );
+}
+
+#[test]
+fn deserialize_borrowed_bytes() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Blob<'a> {
+        data: &'a [u8],
+        raw: Vec<u8>,
+    }
+    let mut parser = Parser::default();
+    let ps = r#"{"data": "a\u00e9", "raw": [1, 2]}"#.to_padded_string();
+    let elm = parser.parse(&ps).unwrap();
+    let b: Blob = from_element(&elm).unwrap();
+    assert_eq!(b.data, "a\u{e9}".as_bytes());
+    assert_eq!(b.raw, [1, 2]);
+}
+
+#[test]
+fn deserialize_smart_pointers_and_cow() {
+    use std::borrow::Cow;
+
+    use simdjson_rust::{ondemand, serde::ondemand::from_document};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Node {
+        name: Box<str>,
+        next: Option<Box<Node>>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Shared<'a> {
+        #[serde(borrow)]
+        label: Cow<'a, str>,
+        copied: Cow<'a, str>,
+        #[serde(borrow)]
+        bytes: Cow<'a, [u8]>,
+        boxed: Box<[u32]>,
+        node: Box<Node>,
+    }
+
+    let json = r#"{"label": "l\u00e9", "copied": "c", "bytes": "b",
+                   "boxed": [1, 2], "node": {"name": "a", "next": {"name": "b", "next": null}}}"#;
+    let ps = json.to_padded_string();
+    let check = |shared: &Shared<'_>| {
+        // The DOM and ondemand parsers both hand out unescaped strings from
+        // their own buffers, so escapes do not force a copy.
+        assert!(matches!(shared.label, Cow::Borrowed("l\u{e9}")));
+        assert!(matches!(shared.copied, Cow::Owned(_)));
+        assert!(matches!(shared.bytes, Cow::Borrowed(b"b")));
+        assert_eq!(&*shared.boxed, [1, 2]);
+        assert_eq!(shared.node.next.as_ref().unwrap().name.as_ref(), "b");
+    };
+
+    let mut parser = Parser::default();
+    let elm = parser.parse(&ps).unwrap();
+    let shared: Shared = from_element(&elm).unwrap();
+    check(&shared);
+    // Owned parts outlive the document; borrowed ones end with it.
+    let node = shared.node;
+    drop(elm);
+    assert_eq!(node.name.as_ref(), "a");
+
+    let mut parser = ondemand::Parser::default();
+    let mut doc = parser.iterate(&ps).unwrap();
+    check(&from_document(&mut doc).unwrap());
+
+    let owned: Cow<'static, str> = from_str(r#""o""#).unwrap();
+    assert!(matches!(owned, Cow::Owned(_)));
+}
+
+#[cfg(feature = "serde_rc")]
+#[test]
+fn deserialize_rc_and_arc() {
+    use std::{rc::Rc, sync::Arc};
+
+    use simdjson_rust::{ondemand, serde::ondemand::from_document};
+
+    #[derive(Debug, Deserialize)]
+    struct Shared {
+        rc: Rc<str>,
+        arc: Arc<[u32]>,
+    }
+
+    let ps = r#"{"rc": "r", "arc": [1, 2]}"#.to_padded_string();
+    let mut parser = Parser::default();
+    let elm = parser.parse(&ps).unwrap();
+    let shared: Shared = from_element(&elm).unwrap();
+    drop(elm);
+    assert_eq!(&*shared.rc, "r");
+    assert_eq!(&*shared.arc, [1, 2]);
+
+    let mut parser = ondemand::Parser::default();
+    let mut doc = parser.iterate(&ps).unwrap();
+    let shared: Shared = from_document(&mut doc).unwrap();
+    assert_eq!(&*shared.rc, "r");
+
+    let name: Arc<String> = from_str(r#""x""#).unwrap();
+    assert_eq!(name.as_str(), "x");
+}
+
 // ---------------------------------------------------------------------------
 // element_to_value conversion
 // ---------------------------------------------------------------------------
@@ -285,6 +499,53 @@ fn element_to_value_nested() {
     assert_eq!(v, serde_json::json!({"key": [1, 2, {"nested": true}]}));
 }
 
+#[test]
+fn element_to_value_skipping_keys() {
+    use simdjson_rust::serde::value::element_to_value_skipping;
+
+    let mut parser = Parser::default();
+    let ps = r#"{"id": 1, "debug": {"trace": [1, 2]}, "items": [{"raw_payload": "x", "n": 2}]}"#
+        .to_padded_string();
+    let elm = parser.parse(&ps).unwrap();
+    let v = element_to_value_skipping(&elm, &["debug", "raw_payload"]).unwrap();
+    assert_eq!(v, serde_json::json!({"id": 1, "items": [{"n": 2}]}));
+}
+
+#[test]
+fn ondemand_value_to_value_exact_numbers() {
+    use simdjson_rust::ondemand;
+    use simdjson_rust::serde::value::ondemand_value_to_value_exact;
+
+    let mut parser = ondemand::Parser::default();
+    let ps = r#"[12345678901234567890123, -5, 18446744073709551615, 0.1,
+        0.10000000000000000555, 1e400, 1e-400, 0.0e5, 2.5E3, 0.30000000000000004 ]"#
+        .to_padded_string();
+    let mut doc = parser.iterate(&ps).unwrap();
+    let v = ondemand_value_to_value_exact(&mut doc.get_value().unwrap()).unwrap();
+    assert_eq!(
+        v,
+        serde_json::json!([
+            "12345678901234567890123",
+            -5,
+            18446744073709551615u64,
+            0.1,
+            "0.10000000000000000555",
+            "1e400",
+            "1e-400",
+            0.0,
+            2500.0,
+            0.30000000000000004
+        ])
+    );
+
+    for bad in ["[01]", "[1.]", "[-]"] {
+        let mut parser = ondemand::Parser::default();
+        let ps = bad.to_padded_string();
+        let mut doc = parser.iterate(&ps).unwrap();
+        assert!(ondemand_value_to_value_exact(&mut doc.get_value().unwrap()).is_err());
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Round-trip: parse → serde_json::Value → to_string → parse again
 // ---------------------------------------------------------------------------
@@ -455,6 +716,85 @@ fn type_mismatch_array_as_struct() {
     assert!(result.is_err());
 }
 
+// ---------------------------------------------------------------------------
+// Lenient coercions
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Sloppy {
+    id: u32,
+    price: f64,
+    active: bool,
+    flags: Vec<bool>,
+    nested: Option<Box<Sloppy>>,
+}
+
+#[test]
+fn lenient_coercions() {
+    let json = r#"{"id": "42", "price": " 1.5", "active": "true", "flags": [0, 1],
+        "nested": {"id": 7, "price": "-2", "active": 0, "flags": ["false"], "nested": null}}"#;
+    let strict: Result<Sloppy, _> = from_str(json);
+    assert!(strict.is_err());
+
+    let sloppy: Sloppy = from_str_with(json, DeserializerOptions::lenient()).unwrap();
+    assert_eq!(sloppy.id, 42);
+    assert_eq!(sloppy.price, 1.5);
+    assert!(sloppy.active);
+    assert_eq!(sloppy.flags, [false, true]);
+    let nested = sloppy.nested.unwrap();
+    assert_eq!((nested.id, nested.price, nested.active), (7, -2.0, false));
+
+    let numbers_only = DeserializerOptions {
+        coerce_numbers: true,
+        ..Default::default()
+    };
+    assert!(from_str_with::<Sloppy>(json, numbers_only).is_err());
+    assert!(from_str_with::<bool>("2", DeserializerOptions::lenient()).is_err());
+    assert!(from_str_with::<u64>(r#""4x""#, DeserializerOptions::lenient()).is_err());
+}
+
+#[test]
+fn integral_floats() {
+    let options = DeserializerOptions {
+        integral_floats: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        from_str_with::<Vec<u32>>("[3.0, 1e3, -0.0, 7]", options).unwrap(),
+        [3, 1000, 0, 7]
+    );
+    assert_eq!(from_str_with::<i64>("-2.0", options).unwrap(), -2);
+    assert!(from_str_with::<u64>("3.5", options).is_err());
+    assert!(from_str_with::<u64>("-1.0", options).is_err());
+    assert!(from_str_with::<i64>("9223372036854775808.0", options).is_err());
+    assert!(from_str::<u64>("3.0").is_err());
+
+    // Numeric strings only go through `f64` when written as floats.
+    let lenient = DeserializerOptions::lenient();
+    assert_eq!(from_str_with::<u64>(r#""2.0""#, lenient).unwrap(), 2);
+    assert_eq!(
+        from_str_with::<u64>(r#""18446744073709551615""#, lenient).unwrap(),
+        u64::MAX
+    );
+}
+
+#[test]
+fn byte_order_mark() {
+    let json =
+        "\u{FEFF}{\"id\": 1, \"price\": 2.5, \"active\": true, \"flags\": [], \"nested\": null}";
+    let sloppy: Sloppy = from_str(json).unwrap();
+    assert_eq!(sloppy.id, 1);
+    assert_eq!(from_slice::<Vec<u8>>(b"\xEF\xBB\xBF [1]").unwrap(), [1]);
+    let strict = DeserializerOptions {
+        bom: BomPolicy::Reject,
+        ..Default::default()
+    };
+    assert!(matches!(
+        from_str_with::<Sloppy>(json, strict),
+        Err(SimdJsonError::ByteOrderMark)
+    ));
+}
+
 // ---------------------------------------------------------------------------
 // Security: empty inputs
 // ---------------------------------------------------------------------------
@@ -643,3 +983,549 @@ fn at_pointer_extensive_usage() {
     let deepest = root.at_pointer(&(value_path + "/level_9")).unwrap();
     assert_eq!(deepest.get_string().unwrap(), "deepest_value");
 }
+
+#[test]
+fn from_pointer_subtree() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Item<'a> {
+        name: &'a str,
+        qty: u32,
+    }
+
+    let json = r#"{"data": {"items": [{"name": "a", "qty": 1}, {"name": "b", "qty": "x"}]}}"#
+        .to_padded_string();
+    let mut parser = Parser::default();
+    let root = parser.parse(&json).unwrap();
+    let first: Item = from_pointer(&root, "/data/items/0").unwrap();
+    assert_eq!(first, Item { name: "a", qty: 1 });
+
+    let err = from_pointer::<Vec<Item>>(&root, "/data/items").unwrap_err();
+    assert!(matches!(&err, SimdJsonError::AtPointer { pointer, .. } if pointer == "/data/items"));
+    assert!(err.to_string().starts_with("At /data/items: "));
+    assert!(matches!(
+        from_pointer::<u32>(&root, "/data/missing"),
+        Err(SimdJsonError::AtPointer { .. })
+    ));
+}
+
+// ---------------------------------------------------------------------------
+// NDJSON
+// ---------------------------------------------------------------------------
+
+#[test]
+fn ndjson_round_trip() {
+    use simdjson_rust::ndjson::{self, InvalidRecord, Reader};
+
+    let people = vec![
+        Simple {
+            name: "Alice".to_string(),
+            age: 30,
+            active: true,
+        },
+        Simple {
+            name: "Bob".to_string(),
+            age: 25,
+            active: false,
+        },
+    ];
+    let mut out = Vec::new();
+    let written = ndjson::write_many(&mut out, &people).unwrap();
+    assert_eq!(written.records, 2);
+    assert_eq!(written.bytes, out.len());
+
+    out.extend_from_slice(b"{\"name\": \"Carol\"}\n");
+    let mut records = Reader::new(out.as_slice())
+        .on_invalid(InvalidRecord::Skip)
+        .records::<Simple>();
+    let read: Vec<Simple> = records.by_ref().map(Result::unwrap).collect();
+    assert_eq!(read, people);
+    assert_eq!(records.counters().skipped, 1);
+}
+
+#[test]
+fn ndjson_load_many() {
+    use simdjson_rust::ndjson;
+
+    let input = concat!(
+        "{\"name\": \"Alice\", \"age\": 30, \"active\": true}\n",
+        "\n",
+        "{\"name\": \"Bob\"}\n",
+        "[1,\r\n",
+        "{\"name\": \"Carol\", \"age\": 41, \"active\": false}",
+    );
+    let path = std::env::temp_dir().join(format!("load_many_{}.ndjson", std::process::id()));
+    std::fs::write(&path, input).unwrap();
+    let (people, report) = ndjson::load_many::<Simple, _>(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(
+        people.iter().map(|p| p.name.as_str()).collect::<Vec<_>>(),
+        ["Alice", "Carol"]
+    );
+    assert!(!report.is_clean());
+    assert_eq!(report.lines, 5);
+    assert_eq!(report.bytes, input.len());
+    let failed: Vec<_> = report.failed.iter().map(|f| (f.line, f.offset)).collect();
+    assert_eq!(failed, [(3, 46), (4, 62)]);
+    assert!(matches!(report.failed[0].error, SimdJsonError::Serde(_)));
+    assert!(ndjson::load_many::<Simple, _>("/nonexistent/file.ndjson").is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Ondemand projection
+// ---------------------------------------------------------------------------
+
+#[test]
+fn projection_into_struct() {
+    use simdjson_rust::ondemand::{self, Projection};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Item {
+        id: u64,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Order {
+        user: Simple,
+        items: Vec<Item>,
+    }
+
+    let ps = r#"{
+        "user": {"name": "Alice", "age": 30, "active": true, "tags": ["a", "b"]},
+        "notes": "ignored",
+        "items": [{"id": 1, "qty": 2}, {"id": 2, "qty": 5}]
+    }"#
+    .to_padded_string();
+    let projection =
+        Projection::new(["/user/name", "/user/age", "/user/active", "/items/*/id"]).unwrap();
+    let mut parser = ondemand::Parser::default();
+    let mut doc = parser.iterate(&ps).unwrap();
+    let order: Order = projection.deserialize(&mut doc).unwrap();
+    assert_eq!(
+        order,
+        Order {
+            user: Simple {
+                name: "Alice".to_string(),
+                age: 30,
+                active: true,
+            },
+            items: vec![Item { id: 1 }, Item { id: 2 }],
+        }
+    );
+}
+
+#[test]
+fn projection_keeps_array_indices() {
+    use simdjson_rust::ondemand::{self, Projection};
+
+    let ps = r#"{"items": [{"id": 1}, {"id": 2}, {"id": 3}]}"#.to_padded_string();
+    let projection = Projection::new(["/items/2/id", "/items/1/id"]).unwrap();
+    let mut parser = ondemand::Parser::default();
+    let mut doc = parser.iterate(&ps).unwrap();
+    let value: serde_json::Value = projection.deserialize(&mut doc).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!({"items": [null, {"id": 2}, {"id": 3}]})
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Ondemand deserializer
+// ---------------------------------------------------------------------------
+
+#[test]
+fn ondemand_from_document() {
+    use simdjson_rust::{ondemand, serde::ondemand::from_document};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    enum Kind {
+        Plain,
+        Tagged { level: u8 },
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Record<'a> {
+        name: &'a str,
+        missing: Option<u32>,
+        nothing: Option<u32>,
+        kinds: Vec<Kind>,
+        scores: (i64, f64),
+    }
+
+    let ps = r#"{
+        "name": "Alice",
+        "ignored": {"deep": [1, 2, {"x": null}]},
+        "nothing": null,
+        "kinds": ["Plain", {"Tagged": {"level": 3}}],
+        "scores": [-1, 2.5]
+    }"#
+    .to_padded_string();
+    let mut parser = ondemand::Parser::default();
+    let mut doc = parser.iterate(&ps).unwrap();
+    let record: Record<'_> = from_document(&mut doc).unwrap();
+    assert_eq!(
+        record,
+        Record {
+            name: "Alice",
+            missing: None,
+            nothing: None,
+            kinds: vec![Kind::Plain, Kind::Tagged { level: 3 }],
+            scores: (-1, 2.5),
+        }
+    );
+
+    let ps = "42".to_padded_string();
+    let mut parser = ondemand::Parser::default();
+    let mut doc = parser.iterate(&ps).unwrap();
+    assert_eq!(from_document::<u8>(&mut doc).unwrap(), 42);
+}
+
+#[test]
+fn ondemand_tagged_and_untagged_enums() {
+    use simdjson_rust::{ondemand, serde::ondemand::from_document};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(tag = "type", rename_all = "snake_case")]
+    enum Event<'a> {
+        Click { x: i32, y: i32 },
+        Key { key: &'a str },
+        Close,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(untagged)]
+    enum Id<'a> {
+        Number(u64),
+        Name(&'a str),
+        Pair { a: u8, b: u8 },
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Batch<'a> {
+        #[serde(borrow)]
+        events: Vec<Event<'a>>,
+        #[serde(borrow)]
+        ids: Vec<Id<'a>>,
+    }
+
+    // The tag comes after the fields it selects, so the object is buffered.
+    let ps = r#"{
+        "events": [{"x": 1, "y": -2, "type": "click"}, {"type": "key", "key": "q"},
+                   {"type": "close"}],
+        "ids": [7, "seven", {"b": 2, "a": 1}]
+    }"#
+    .to_padded_string();
+    let mut parser = ondemand::Parser::default();
+    let mut doc = parser.iterate(&ps).unwrap();
+    assert_eq!(
+        from_document::<Batch<'_>>(&mut doc).unwrap(),
+        Batch {
+            events: vec![
+                Event::Click { x: 1, y: -2 },
+                Event::Key { key: "q" },
+                Event::Close,
+            ],
+            ids: vec![Id::Number(7), Id::Name("seven"), Id::Pair { a: 1, b: 2 }],
+        }
+    );
+    drop(doc);
+
+    for json in [r#"{"type": "scroll"}"#, r#"{"x": 1}"#] {
+        let ps = json.to_padded_string();
+        let mut doc = parser.iterate(&ps).unwrap();
+        assert!(from_document::<Event<'_>>(&mut doc).is_err());
+    }
+    let ps = "[true]".to_padded_string();
+    let mut doc = parser.iterate(&ps).unwrap();
+    assert!(from_document::<Vec<Id<'_>>>(&mut doc).is_err());
+}
+
+#[test]
+fn ondemand_transcode() {
+    use simdjson_rust::{ondemand, serde::ondemand::OndemandDeserializer};
+
+    let json = r#"{"a": [1, -2, 3.5, "x\ny"], "b": {"c": null, "d": true}}"#;
+    let ps = json.to_padded_string();
+    let mut parser = ondemand::Parser::default();
+    let mut doc = parser.iterate(&ps).unwrap();
+
+    let mut out = Vec::new();
+    let mut serializer = serde_json::Serializer::new(&mut out);
+    serde_transcode::transcode(OndemandDeserializer::new(&mut doc), &mut serializer).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        r#"{"a":[1,-2,3.5,"x\ny"],"b":{"c":null,"d":true}}"#
+    );
+}
+
+// ---------------------------------------------------------------------------
+// One-shot helpers
+// ---------------------------------------------------------------------------
+
+#[test]
+fn one_shot_from_str() {
+    use simdjson_rust::serde::de::{from_slice, from_str};
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Point {
+        x: i64,
+        y: i64,
+    }
+
+    for i in 0..3 {
+        let json = format!(r#"{{"x": {i}, "y": -1}}"#);
+        assert_eq!(from_str::<Point>(&json).unwrap(), Point { x: i, y: -1 });
+    }
+    assert_eq!(from_slice::<Vec<u8>>(b"[1, 2]").unwrap(), [1, 2]);
+    assert!(from_slice::<Vec<u8>>(b"[\"\xff\"]").is_err());
+    assert!(from_str::<Point>("{").is_err());
+    simdjson_rust::release_thread_local_parsers();
+    assert!(from_str::<Point>(r#"{"x": 1, "y": 2}"#).is_ok());
+}
+
+// ---------------------------------------------------------------------------
+// Numbers as strings
+// ---------------------------------------------------------------------------
+
+#[test]
+fn numbers_as_strings() {
+    use simdjson_rust::{
+        ondemand,
+        serde::{helpers, ondemand::from_document, ser::to_string},
+    };
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Order {
+        #[serde(with = "helpers::u64_as_string")]
+        id: u64,
+        #[serde(with = "helpers::i64_as_string")]
+        delta: i64,
+        #[serde(with = "helpers::f64_as_string")]
+        price: f64,
+    }
+
+    let expected = Order {
+        id: u64::MAX,
+        delta: -3,
+        price: 0.1,
+    };
+    let json = r#"{"id": "18446744073709551615", "delta": "-3", "price": "0.1"}"#;
+    assert_eq!(from_str::<Order>(json).unwrap(), expected);
+    let ps = json.to_padded_string();
+    let mut parser = ondemand::Parser::default();
+    let mut doc = parser.iterate(&ps).unwrap();
+    assert_eq!(from_document::<Order>(&mut doc).unwrap(), expected);
+
+    // Plain numbers are accepted too.
+    let order: Order = from_str(r#"{"id": 7, "delta": 2, "price": 1}"#).unwrap();
+    assert_eq!((order.id, order.delta, order.price), (7, 2, 1.0));
+
+    assert_eq!(
+        to_string(&expected).unwrap(),
+        r#"{"id":"18446744073709551615","delta":"-3","price":"0.1"}"#
+    );
+
+    assert!(from_str::<Order>(r#"{"id": "-1", "delta": "0", "price": "0"}"#).is_err());
+    assert!(from_str::<Order>(r#"{"id": "1", "delta": " 0", "price": "0"}"#).is_err());
+    assert!(from_str::<Order>(r#"{"id": "1", "delta": "0", "price": "x"}"#).is_err());
+    assert!(from_str::<Order>(r#"{"id": 1.5, "delta": "0", "price": "0"}"#).is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Flatten
+// ---------------------------------------------------------------------------
+
+#[test]
+fn flattened_fields() {
+    use std::collections::BTreeMap;
+
+    use simdjson_rust::{ondemand, serde::ondemand::from_document};
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Page<'a> {
+        offset: u64,
+        #[serde(borrow)]
+        cursor: Option<&'a str>,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Response<'a> {
+        id: u64,
+        #[serde(flatten, borrow)]
+        page: Page<'a>,
+        #[serde(flatten)]
+        extra: BTreeMap<String, serde_json::Value>,
+    }
+
+    let json = r#"{"offset": 10, "id": 1, "cursor": "cé", "tags": [1, "a"], "more": null}"#;
+    let ps = json.to_padded_string();
+    let check = |response: Response<'_>| {
+        assert_eq!(response.id, 1);
+        assert_eq!(
+            response.page,
+            Page {
+                offset: 10,
+                cursor: Some("c\u{e9}"),
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&response.extra).unwrap(),
+            r#"{"more":null,"tags":[1,"a"]}"#
+        );
+    };
+
+    let mut parser = Parser::default();
+    let elm = parser.parse(&ps).unwrap();
+    check(from_element(&elm).unwrap());
+    drop(elm);
+    let missing = r#"{"id": 1, "cursor": null}"#.to_padded_string();
+    let elm = parser.parse(&missing).unwrap();
+    assert!(from_element::<Response<'_>>(&elm).is_err());
+
+    let mut parser = ondemand::Parser::default();
+    let mut doc = parser.iterate(&ps).unwrap();
+    check(from_document(&mut doc).unwrap());
+}
+
+// ---------------------------------------------------------------------------
+// String interning
+// ---------------------------------------------------------------------------
+
+#[test]
+fn interned_strings() {
+    use std::{collections::HashMap, sync::Arc};
+
+    use simdjson_rust::serde::intern::{Interned, StringCache, interned, with_cache};
+
+    #[derive(Deserialize, Debug)]
+    struct Row {
+        country: Interned,
+        #[serde(deserialize_with = "interned")]
+        status: Arc<str>,
+        tags: HashMap<Interned, u8>,
+    }
+
+    let json = r#"[
+        {"country": "NZ", "status": "ok", "tags": {"a": 1}},
+        {"country": "AU", "status": "ok", "tags": {"a": 2}},
+        {"country": "NZ", "status": "failed", "tags": {}}
+    ]"#;
+    let mut cache = StringCache::new();
+    let rows: Vec<Row> = with_cache(&mut cache, || from_str(json)).unwrap();
+    assert_eq!(cache.len(), 5);
+    assert_eq!(&*rows[0].country, "NZ");
+    assert!(Arc::ptr_eq(&rows[0].country.0, &rows[2].country.0));
+    assert!(Arc::ptr_eq(&rows[0].status, &rows[1].status));
+    assert_eq!(rows[1].tags.get("a"), Some(&2));
+
+    // The cache carries over to later calls; without one nothing is shared.
+    let again: Row = with_cache(&mut cache, || {
+        from_str(r#"{"country": "NZ", "status": "ok", "tags": {}}"#)
+    })
+    .unwrap();
+    assert!(Arc::ptr_eq(&again.country.0, &rows[0].country.0));
+    let plain: Vec<Row> = from_str(json).unwrap();
+    assert!(!Arc::ptr_eq(&plain[0].country.0, &plain[2].country.0));
+    assert!(from_str::<Row>(r#"{"country": 1, "status": "ok", "tags": {}}"#).is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Exact numbers
+// ---------------------------------------------------------------------------
+
+#[test]
+fn exact_number_round_trip() {
+    use simdjson_rust::{
+        Number, builder::StringBuilder, ondemand, serde::ondemand::from_document,
+        serde::ser::to_string,
+    };
+
+    #[derive(Serialize, Deserialize)]
+    struct Prices {
+        a: Number,
+        b: Vec<Number>,
+    }
+
+    let json = r#"{"a":1.50,"b":[1e2,-3,18446744073709551615,123456789012345678901234567890,0.1]}"#;
+    let ps = json.to_padded_string();
+    let mut parser = ondemand::Parser::default();
+    let mut doc = parser.iterate(&ps).unwrap();
+    let prices: Prices = from_document(&mut doc).unwrap();
+    assert_eq!(prices.a, Number::from_raw("1.50").unwrap());
+    assert_eq!(prices.b[1], Number::I64(-3));
+    assert_eq!(prices.b[2], Number::U64(u64::MAX));
+    assert_eq!(to_string(&prices).unwrap(), json);
+    drop(doc);
+
+    let ps = "2.50 ".to_padded_string();
+    let mut doc = parser.iterate(&ps).unwrap();
+    assert_eq!(
+        from_document::<Number>(&mut doc).unwrap(),
+        Number::from_raw("2.50").unwrap()
+    );
+    drop(doc);
+    let ps = r#"["1.5"]"#.to_padded_string();
+    let mut doc = parser.iterate(&ps).unwrap();
+    assert!(from_document::<Vec<Number>>(&mut doc).is_err());
+
+    // The DOM keeps parsed values only.
+    let prices: Prices = from_str(r#"{"a": 1.50, "b": [7]}"#).unwrap();
+    assert_eq!(prices.a, Number::F64(1.5));
+    assert_eq!(prices.b, [Number::I64(7)]);
+
+    let mut builder = StringBuilder::new();
+    builder.start_array();
+    builder.append_number(&Number::from_raw("1.0e-7").unwrap());
+    builder.append_comma();
+    builder.append_number(&Number::U64(5));
+    builder.end_array();
+    assert_eq!(builder.into_string().unwrap(), "[1.0e-7,5]");
+    // JSON strings are not numbers, whichever deserializer reads them.
+    assert!(from_str::<Number>(r#""1.5""#).is_err());
+}
+
+// ---------------------------------------------------------------------------
+// Field presence
+// ---------------------------------------------------------------------------
+
+#[test]
+fn tracked_fields() {
+    use simdjson_rust::{ondemand, serde::ondemand::from_document, serde::tracked::Tracked};
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct UserPatch {
+        #[serde(default, skip_serializing_if = "Tracked::is_absent")]
+        nickname: Tracked<String>,
+        #[serde(default, skip_serializing_if = "Tracked::is_absent")]
+        age: Tracked<u8>,
+        #[serde(default, skip_serializing_if = "Tracked::is_absent")]
+        email: Tracked<String>,
+    }
+
+    let json = r#"{"nickname": null, "age": 30}"#;
+    let expected = UserPatch {
+        nickname: Tracked::Null,
+        age: Tracked::Value(30),
+        email: Tracked::Absent,
+    };
+    assert_eq!(from_str::<UserPatch>(json).unwrap(), expected);
+    let ps = json.to_padded_string();
+    let mut parser = ondemand::Parser::default();
+    let mut doc = parser.iterate(&ps).unwrap();
+    assert_eq!(from_document::<UserPatch>(&mut doc).unwrap(), expected);
+
+    let (mut nickname, mut age, mut email) = (Some("a".to_owned()), Some(1), Some("e".to_owned()));
+    expected.nickname.clone().apply(&mut nickname);
+    expected.age.clone().apply(&mut age);
+    expected.email.clone().apply(&mut email);
+    assert_eq!(
+        (nickname, age, email.as_deref()),
+        (None, Some(30), Some("e"))
+    );
+
+    assert_eq!(
+        simdjson_rust::serde::ser::to_string(&expected).unwrap(),
+        r#"{"nickname":null,"age":30}"#
+    );
+    assert!(from_str::<UserPatch>(r#"{"age": "x"}"#).is_err());
+}